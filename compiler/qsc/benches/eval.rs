@@ -5,7 +5,7 @@ allocator::assign_global!();
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use indoc::indoc;
-use qsc::{interpret::Interpreter, PackageType, TargetCapabilityFlags};
+use qsc::{interpret::Interpreter, Backend, PackageType, SparseSim, TargetCapabilityFlags};
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_eval::output::GenericReceiver;
 use qsc_frontend::compile::SourceMap;
@@ -208,6 +208,18 @@ pub fn large_nested_iteration(c: &mut Criterion) {
     });
 }
 
+pub fn large_state_capture(c: &mut Criterion) {
+    c.bench_function("Large state capture", |b| {
+        let mut sim = SparseSim::new();
+        for _ in 0..16 {
+            let q = sim.qubit_allocate();
+            sim.h(q);
+        }
+
+        b.iter(|| sim.capture_quantum_state());
+    });
+}
+
 criterion_group!(
     benches,
     teleport,
@@ -217,5 +229,6 @@ criterion_group!(
     array_update,
     array_literal,
     large_nested_iteration,
+    large_state_capture,
 );
 criterion_main!(benches);
@@ -10,7 +10,9 @@ pub mod qsharp {
 }
 
 pub mod qir {
-    use qsc_codegen::qir::{fir_to_qir, fir_to_rir};
+    use qsc_codegen::qir::{fir_to_program, fir_to_qir, fir_to_rir};
+    pub use qsc_codegen::qir::{set_output_labels, write_qir, OutputLabelError, ToQir};
+    pub use qsc_rir::rir::Program;
 
     use qsc_data_structures::{language_features::LanguageFeatures, target::TargetCapabilityFlags};
     use qsc_frontend::{
@@ -28,6 +30,18 @@ pub mod qir {
         sources: SourceMap,
         capabilities: TargetCapabilityFlags,
     ) -> Result<String, Vec<Error>> {
+        get_program_from_ast(store, dependencies, ast_package, sources, capabilities)
+            .map(|program| program.to_qir(&program))
+    }
+
+    /// Compiles the given AST package to the RIR program that QIR is generated from.
+    pub fn get_program_from_ast(
+        store: &mut PackageStore,
+        dependencies: &Dependencies,
+        ast_package: qsc_ast::ast::Package,
+        sources: SourceMap,
+        capabilities: TargetCapabilityFlags,
+    ) -> Result<Program, Vec<Error>> {
         if capabilities == TargetCapabilityFlags::all() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
@@ -73,7 +87,7 @@ pub mod qir {
                 .collect::<Vec<_>>()
         })?;
 
-        fir_to_qir(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|e| {
+        fir_to_program(&fir_store, capabilities, Some(compute_properties), &entry).map_err(|e| {
             let source_package_id = match e.span() {
                 Some(span) => span.package,
                 None => package_id,
@@ -14,6 +14,7 @@ use qsc_frontend::{
 };
 use qsc_hir::hir::PackageId;
 use qsc_passes::{PackageType, PassContext};
+use std::sync::Arc;
 
 /// An incremental Q# compiler.
 pub struct Compiler {
@@ -27,6 +28,10 @@ pub struct Compiler {
     passes: PassContext,
     /// The frontend incremental compiler.
     frontend: qsc_frontend::incremental::Compiler,
+    /// The dependencies of the source package, excluding the source package itself.
+    dependencies: Vec<(PackageId, Option<Arc<str>>)>,
+    /// The language features used for compilation.
+    language_features: LanguageFeatures,
 }
 
 /// An incremental compiler error.
@@ -59,6 +64,7 @@ impl Compiler {
         // make the user code fully public, so increments on top of this can access them
         unit.expose();
 
+        let source_dependencies = dependencies.to_vec();
         let mut dependencies = dependencies.iter().map(Clone::clone).collect::<Vec<_>>();
 
         let source_package_id = store.insert(unit);
@@ -77,6 +83,8 @@ impl Compiler {
             source_package_id,
             frontend,
             passes: PassContext::default(),
+            dependencies: source_dependencies,
+            language_features,
         })
     }
 
@@ -87,6 +95,7 @@ impl Compiler {
         language_features: LanguageFeatures,
         dependencies: &Dependencies,
     ) -> Result<Self, Errors> {
+        let source_dependencies = dependencies.to_vec();
         let mut dependencies = dependencies.iter().map(Clone::clone).collect::<Vec<_>>();
 
         dependencies.push((source_package_id, None));
@@ -104,9 +113,26 @@ impl Compiler {
             source_package_id,
             frontend,
             passes: PassContext::default(),
+            dependencies: source_dependencies,
+            language_features,
         })
     }
 
+    /// Creates a compiler that shares the closed packages of this compiler, including
+    /// the source package, and compiles further increments into a new open package.
+    /// Increments compiled by this compiler are not visible to the fork.
+    /// # Errors
+    /// Returns a vector of errors if the fork cannot be set up.
+    pub fn fork(&self, capabilities: TargetCapabilityFlags) -> Result<Self, Errors> {
+        Self::from(
+            self.store.share_closed(),
+            self.source_package_id,
+            capabilities,
+            self.language_features,
+            &self.dependencies,
+        )
+    }
+
     /// Compiles Q# fragments. Fragments are Q# code that can contain
     /// top-level statements as well as namespaces. A notebook cell
     /// or an interpreter entry is an example of fragments.
@@ -292,6 +318,12 @@ impl Compiler {
         self.source_package_id
     }
 
+    /// Returns the language features used for compilation.
+    #[must_use]
+    pub fn language_features(&self) -> LanguageFeatures {
+        self.language_features
+    }
+
     /// Consumes the incremental compiler and returns an immutable package store.
     /// This method can be used to finalize the compilation.
     #[must_use]
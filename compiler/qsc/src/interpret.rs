@@ -11,13 +11,18 @@ mod package_tests;
 #[cfg(test)]
 mod tests;
 
-use std::rc::Rc;
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
 pub use qsc_eval::{
     debug::Frame,
     noise::PauliNoise,
     output::{self, GenericReceiver},
     val::Closure,
+    val::IntoResultValue,
     val::Range as ValueRange,
     val::Result,
     val::Value,
@@ -28,11 +33,13 @@ use qsc_linter::{HirLint, Lint, LintKind, LintLevel};
 use qsc_lowerer::{map_fir_package_to_hir, map_hir_package_to_fir};
 use qsc_partial_eval::ProgramEntry;
 use qsc_rca::PackageStoreComputeProperties;
+use qsc_rir::rir::Program;
 
 use crate::{
     error::{self, WithStack},
     incremental::Compiler,
     location::Location,
+    target::Profile,
 };
 use debug::format_call_stack;
 use miette::Diagnostic;
@@ -42,7 +49,7 @@ use qsc_circuit::{
     operations::entry_expr_for_qubit_operation, Builder as CircuitBuilder, Circuit,
     Config as CircuitConfig,
 };
-use qsc_codegen::qir::{fir_to_qir, fir_to_qir_from_callable};
+use qsc_codegen::qir::{fir_to_program, fir_to_program_from_callable, ToQir};
 use qsc_data_structures::{
     functors::FunctorApp,
     language_features::LanguageFeatures,
@@ -51,7 +58,9 @@ use qsc_data_structures::{
     target::TargetCapabilityFlags,
 };
 use qsc_eval::{
-    backend::{Backend, Chain as BackendChain, SparseSim},
+    backend::{
+        Backend, Chain as BackendChain, CustomIntrinsicHandler, SparseSim, StateSizeSampler,
+    },
     output::Receiver,
     val, Env, State, VariableInfo,
 };
@@ -66,7 +75,7 @@ use qsc_frontend::{
     incremental::Increment,
 };
 use qsc_passes::{PackageType, PassContext};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use thiserror::Error;
 
 impl Error {
@@ -127,6 +136,8 @@ pub struct Interpreter {
     lowerer: qsc_lowerer::Lowerer,
     /// The execution graph for the last expression evaluated.
     expr_graph: Option<ExecGraph>,
+    /// The source of the entry expression that `expr_graph` was compiled from, if any.
+    entry_expr: Option<String>,
     /// The ID of the current package.
     /// This ID is valid both for the FIR store and the `PackageStore`.
     package: PackageId,
@@ -139,9 +150,24 @@ pub struct Interpreter {
     /// The quantum seed, if any. This is cached here so that it can be used in calls to
     /// `run_internal` which use a passed instance of the simulator instead of the one above.
     quantum_seed: Option<u64>,
+    /// The handler for custom intrinsics unknown to the simulator, if any. Like the quantum seed,
+    /// this is cached so that it can be applied to fresh simulator instances.
+    custom_intrinsic_handler: Option<CustomIntrinsicHandler>,
+    /// The declared output types of the body intrinsics in the compiled packages, by name.
+    /// This is shared so that custom intrinsic handlers can convert their results.
+    intrinsic_output_tys: Rc<RefCell<FxHashMap<Rc<str>, ty::Ty>>>,
     /// The classical seed, if any. This needs to be passed to the evaluator for use in intrinsic
     /// calls that produce classical random numbers.
     classical_seed: Option<u64>,
+    /// The measurement outcomes of the most recent run on a fresh simulator, in order.
+    measurement_log: Vec<bool>,
+    /// The custom intrinsic calls answered by the handler in each shot of the most
+    /// recent run on a fresh simulator, in order.
+    intrinsic_logs: Vec<Vec<(String, Value)>>,
+    /// Whether runs on a fresh simulator record the custom intrinsic calls answered by the handler.
+    capture_intrinsics: bool,
+    /// The sizes the simulator state went through, if state size tracking is enabled.
+    state_size_history: Option<Rc<RefCell<Vec<usize>>>>,
     /// The evaluator environment.
     env: Env,
 }
@@ -243,6 +269,7 @@ impl Interpreter {
             })?;
         }
 
+        let intrinsic_output_tys = collect_intrinsic_output_tys(&compiler);
         Ok(Self {
             compiler,
             lines: 0,
@@ -250,10 +277,17 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new().with_debug(dbg),
             expr_graph: None,
+            entry_expr: None,
             env: Env::default(),
             sim: sim_circuit_backend(),
             quantum_seed: None,
+            custom_intrinsic_handler: None,
+            intrinsic_output_tys,
             classical_seed: None,
+            measurement_log: Vec::new(),
+            intrinsic_logs: Vec::new(),
+            capture_intrinsics: false,
+            state_size_history: None,
             package,
             source_package: map_hir_package_to_fir(source_package_id),
         })
@@ -306,6 +340,7 @@ impl Interpreter {
             })?;
         }
 
+        let intrinsic_output_tys = collect_intrinsic_output_tys(&compiler);
         Ok(Self {
             compiler,
             lines: 0,
@@ -313,15 +348,76 @@ impl Interpreter {
             fir_store,
             lowerer: qsc_lowerer::Lowerer::new().with_debug(dbg),
             expr_graph: None,
+            entry_expr: None,
             env: Env::default(),
             sim: sim_circuit_backend(),
             quantum_seed: None,
+            custom_intrinsic_handler: None,
+            intrinsic_output_tys,
             classical_seed: None,
+            measurement_log: Vec::new(),
+            intrinsic_logs: Vec::new(),
+            capture_intrinsics: false,
+            state_size_history: None,
             package,
             source_package: map_hir_package_to_fir(source_package_id),
         })
     }
 
+    /// Creates an independent copy of this interpreter that shares the compiled source package
+    /// and its dependencies, but starts with a fresh simulator state and classical scope.
+    /// The compiled packages are shared rather than copied, so only the new fragment package
+    /// is lowered. Items and bindings introduced by previously evaluated fragments are not
+    /// carried over. Seeds configured on this interpreter are applied to the fork.
+    /// # Errors
+    /// If setting up the forked compilation fails, compiler errors are returned.
+    pub fn fork(&self) -> std::result::Result<Self, Vec<Error>> {
+        let compiler = self
+            .compiler
+            .fork(self.capabilities)
+            .map_err(into_errors)?;
+
+        let mut fir_store = self.fir_store.share_except(self.package);
+        let package_id = compiler.package_id();
+        let unit = compiler
+            .package_store()
+            .get(package_id)
+            .expect("open package should exist in the package store");
+        let package = map_hir_package_to_fir(package_id);
+        let dbg = self.lowerer.debug_enabled();
+        let pkg = qsc_lowerer::Lowerer::new()
+            .with_debug(dbg)
+            .lower_package(&unit.package, &fir_store);
+        fir_store.insert(package, pkg);
+
+        let intrinsic_output_tys = collect_intrinsic_output_tys(&compiler);
+        let mut forked = Self {
+            compiler,
+            lines: 0,
+            capabilities: self.capabilities,
+            fir_store,
+            lowerer: qsc_lowerer::Lowerer::new().with_debug(dbg),
+            expr_graph: None,
+            entry_expr: None,
+            env: Env::default(),
+            sim: sim_circuit_backend(),
+            quantum_seed: None,
+            custom_intrinsic_handler: None,
+            intrinsic_output_tys,
+            classical_seed: None,
+            measurement_log: Vec::new(),
+            intrinsic_logs: Vec::new(),
+            capture_intrinsics: false,
+            state_size_history: None,
+            package,
+            source_package: self.source_package,
+        };
+        forked.set_quantum_seed(self.quantum_seed);
+        forked.set_classical_seed(self.classical_seed);
+        forked.set_custom_intrinsic_handler(self.custom_intrinsic_handler.clone());
+        Ok(forked)
+    }
+
     /// Given a package ID, returns all the global items in the package.
     /// Note this does not currently include re-exports.
     fn package_globals(&self, package_id: PackageId) -> Vec<(Vec<Rc<str>>, Rc<str>, Value)> {
@@ -359,6 +455,30 @@ impl Interpreter {
         self.package_globals(self.package)
     }
 
+    /// Returns whether the given value representing a global item is an operation.
+    #[must_use]
+    pub fn is_global_operation(&self, item_id: &Value) -> bool {
+        let Value::Global(item_id, _) = item_id else {
+            return false;
+        };
+        let package_id = map_fir_package_to_hir(item_id.package);
+        self.compiler
+            .package_store()
+            .get(package_id)
+            .and_then(|unit| {
+                unit.package
+                    .items
+                    .get(qsc_hir::hir::LocalItemId::from(usize::from(item_id.item)))
+            })
+            .is_some_and(|item| {
+                matches!(
+                    &item.kind,
+                    qsc_hir::hir::ItemKind::Callable(decl)
+                        if decl.kind == qsc_hir::hir::CallableKind::Operation
+                )
+            })
+    }
+
     /// Get the input and output types of a given value representing a global item.
     /// # Panics
     /// Panics if the item is not callable or a type that can be invoked as a callable.
@@ -397,6 +517,82 @@ impl Interpreter {
         self.classical_seed = seed;
     }
 
+    /// Sets the Pauli noise applied by the simulator used for evaluating fragments and
+    /// invoking callables, from now on. Passing `None` makes it noiseless again.
+    pub fn set_noise(&mut self, noise: Option<PauliNoise>) {
+        let noise = noise.unwrap_or_default();
+        self.sim.main.set_noise(&noise, &noise);
+    }
+
+    /// Sets the handler invoked when evaluation reaches a custom intrinsic that the
+    /// simulator does not recognize. Passing `None` removes any existing handler.
+    pub fn set_custom_intrinsic_handler(&mut self, handler: Option<CustomIntrinsicHandler>) {
+        self.sim.main.set_custom_intrinsic_handler(handler.clone());
+        self.custom_intrinsic_handler = handler;
+    }
+
+    /// Returns the declared output types of the body intrinsics in the compiled packages,
+    /// by name. The map is shared and stays current as more code is evaluated, so custom
+    /// intrinsic handlers can hold on to it to convert their results.
+    #[must_use]
+    pub fn intrinsic_output_tys(&self) -> Rc<RefCell<FxHashMap<Rc<str>, ty::Ty>>> {
+        self.intrinsic_output_tys.clone()
+    }
+
+    /// Enables or disables recording the custom intrinsic calls answered by the handler
+    /// in later runs on a fresh simulator, as returned by `intrinsic_logs`.
+    pub fn set_capture_intrinsics(&mut self, capture: bool) {
+        self.capture_intrinsics = capture;
+    }
+
+    /// Enables or disables recording the number of nonzero amplitudes in the simulator
+    /// state after each gate, which slows down simulation.
+    pub fn set_state_size_tracking(&mut self, enabled: bool) {
+        self.state_size_history = enabled.then(Rc::default);
+        let sampler = self.state_size_sampler();
+        self.sim.main.set_state_size_sampler(sampler);
+    }
+
+    /// Gets the sizes the simulator state went through while state size tracking was
+    /// enabled, since the last run on a fresh simulator. Consecutive gates that leave
+    /// the size unchanged are summarized as a single entry.
+    #[must_use]
+    pub fn state_size_history(&self) -> Vec<usize> {
+        self.state_size_history
+            .as_ref()
+            .map(|history| history.borrow().clone())
+            .unwrap_or_default()
+    }
+
+    fn state_size_sampler(&self) -> Option<StateSizeSampler> {
+        let history = self.state_size_history.clone()?;
+        Some(Rc::new(move |size| {
+            let mut history = history.borrow_mut();
+            if history.last() != Some(&size) {
+                history.push(size);
+            }
+        }))
+    }
+
+    /// Creates a fresh simulator configured with the given noise, measurement outcomes
+    /// to replay and the custom intrinsic handler, if any.
+    fn new_sparse_sim(&self, noise: Option<PauliNoise>, replay: Option<Vec<bool>>) -> SparseSim {
+        let mut sim = match noise {
+            Some(noise) => SparseSim::new_with_noise(&noise),
+            None => SparseSim::new(),
+        };
+        sim.set_custom_intrinsic_handler(self.custom_intrinsic_handler.clone());
+        sim.set_capture_intrinsics(self.capture_intrinsics);
+        if let Some(history) = &self.state_size_history {
+            history.borrow_mut().clear();
+        }
+        sim.set_state_size_sampler(self.state_size_sampler());
+        if let Some(replay) = replay {
+            sim.set_replay(replay);
+        }
+        sim
+    }
+
     pub fn check_source_lints(&self) -> Vec<Lint> {
         if let Some(compile_unit) = self
             .compiler
@@ -425,8 +621,8 @@ impl Interpreter {
     /// Returns a vector of errors if evaluating the entry point fails.
     pub fn eval_entry(&mut self, receiver: &mut impl Receiver) -> InterpretResult {
         let graph = self.get_entry_exec_graph()?;
-        self.expr_graph = Some(graph.clone());
-        eval(
+        self.set_expr_graph(graph.clone(), None);
+        let result = eval(
             self.source_package,
             self.classical_seed,
             graph,
@@ -435,18 +631,20 @@ impl Interpreter {
             &mut Env::default(),
             &mut self.sim,
             receiver,
-        )
+        );
+        self.measurement_log = self.sim.main.take_measurement_log();
+        result
     }
 
     /// Executes the entry expression until the end of execution, using the given simulator backend
     /// and a new instance of the environment.
     pub fn eval_entry_with_sim(
         &mut self,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         receiver: &mut impl Receiver,
     ) -> InterpretResult {
         let graph = self.get_entry_exec_graph()?;
-        self.expr_graph = Some(graph.clone());
+        self.set_expr_graph(graph.clone(), None);
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
         }
@@ -492,6 +690,44 @@ impl Interpreter {
         self.eval_increment(receiver, increment)
     }
 
+    /// Evaluates each top-level statement or namespace in `fragments` in turn and
+    /// returns the value produced by each, where the value of an expression statement
+    /// ending in a semicolon is the value of its expression.
+    /// Since each fragment is compiled on its own, a fragment can only refer to
+    /// items declared by the fragments before it.
+    /// # Errors
+    /// If the parsing of the fragments fails, an error is returned.
+    /// If the compilation or evaluation of a fragment fails, its errors are returned
+    /// and the fragments after it are not evaluated.
+    pub fn eval_fragments_each(
+        &mut self,
+        receiver: &mut impl Receiver,
+        fragments: &str,
+    ) -> std::result::Result<Vec<Value>, Vec<Error>> {
+        let (nodes, errors) =
+            qsc_parse::top_level_nodes(fragments, self.compiler.language_features());
+        if !errors.is_empty() {
+            // Evaluate the fragments as a whole so the parse errors are reported with their sources.
+            return self
+                .eval_fragments(receiver, fragments)
+                .map(|value| vec![value]);
+        }
+
+        let mut values = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let span = match &node {
+                qsc_ast::ast::TopLevelNode::Namespace(namespace) => namespace.span,
+                qsc_ast::ast::TopLevelNode::Stmt(stmt) => match stmt.kind.as_ref() {
+                    qsc_ast::ast::StmtKind::Semi(expr) => expr.span,
+                    _ => stmt.span,
+                },
+            };
+            let fragment = &fragments[span.lo as usize..span.hi as usize];
+            values.push(self.eval_fragments(receiver, fragment)?);
+        }
+        Ok(values)
+    }
+
     /// It is assumed that if there were any parse errors on the fragments, the caller would have
     /// already handled them. This function is intended to be used in cases where the caller wants
     /// to handle the parse errors themselves.
@@ -520,7 +756,7 @@ impl Interpreter {
         increment: Increment,
     ) -> InterpretResult {
         let (graph, _) = self.lower(&increment)?;
-        self.expr_graph = Some(graph.clone());
+        self.set_expr_graph(graph.clone(), None);
 
         // Updating the compiler state with the new AST/HIR nodes
         // is not necessary for the interpreter to function, as all
@@ -530,7 +766,7 @@ impl Interpreter {
         // here to keep the package stores consistent.
         self.compiler.update(increment);
 
-        eval(
+        let result = eval(
             self.package,
             self.classical_seed,
             graph,
@@ -539,7 +775,9 @@ impl Interpreter {
             &mut self.env,
             &mut self.sim,
             receiver,
-        )
+        );
+        self.measurement_log = self.sim.main.take_measurement_log();
+        result
     }
 
     /// Invokes the given callable with the given arguments using the current environment, simlator, and compilation.
@@ -549,7 +787,7 @@ impl Interpreter {
         callable: Value,
         args: Value,
     ) -> InterpretResult {
-        qsc_eval::invoke(
+        let result = qsc_eval::invoke(
             self.package,
             self.classical_seed,
             &self.fir_store,
@@ -566,38 +804,108 @@ impl Interpreter {
                 call_stack,
                 error,
             )
-        })
+        });
+        self.measurement_log = self.sim.main.take_measurement_log();
+        result
     }
 
     // Invokes the given callable with the given arguments using the current compilation but with a fresh
-    // environment and simulator configured with the given noise, if any.
+    // environment and simulator configured with the given noise and measurement outcomes to replay, if any.
     pub fn invoke_with_noise(
         &mut self,
         receiver: &mut impl Receiver,
         callable: Value,
         args: Value,
         noise: Option<PauliNoise>,
+        replay: Option<Vec<bool>>,
     ) -> InterpretResult {
-        let mut sim = match noise {
-            Some(noise) => SparseSim::new_with_noise(&noise),
-            None => SparseSim::new(),
-        };
-        self.invoke_with_sim(&mut sim, receiver, callable, args)
+        let mut sim = self.new_sparse_sim(noise, replay);
+        let result = self.invoke_with_sim(&mut sim, receiver, callable, args);
+        self.measurement_log = sim.measurement_log().to_vec();
+        self.intrinsic_logs = vec![sim.intrinsic_log().to_vec()];
+        result
     }
 
     /// Runs the given entry expression on a new instance of the environment and simulator,
-    /// but using the current compilation.
+    /// but using the current compilation. Measurements are forced to the outcomes in `replay`,
+    /// in order, if given.
     pub fn run(
         &mut self,
         receiver: &mut impl Receiver,
         expr: Option<&str>,
         noise: Option<PauliNoise>,
+        replay: Option<Vec<bool>>,
     ) -> InterpretResult {
-        let mut sim = match noise {
-            Some(noise) => SparseSim::new_with_noise(&noise),
-            None => SparseSim::new(),
-        };
-        self.run_with_sim(&mut sim, receiver, expr)
+        let mut sim = self.new_sparse_sim(noise, replay);
+        let result = self.run_with_sim(&mut sim, receiver, expr);
+        self.measurement_log = sim.measurement_log().to_vec();
+        self.intrinsic_logs = vec![sim.intrinsic_log().to_vec()];
+        result
+    }
+
+    /// Runs the given entry expression `shots` times, each on a new instance of the environment
+    /// and simulator, but using the current compilation. If a quantum seed is set, it is
+    /// incremented for each shot so that the shots differ but remain deterministic.
+    /// Returns the result of each shot, including any runtime errors, with its wall-clock
+    /// duration, so that a failing shot doesn't discard the others.
+    /// # Errors
+    /// Returns errors if the entry expression fails to compile, or if the receiver aborts
+    /// evaluation, which also stops the remaining shots.
+    pub fn run_shots(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: Option<&str>,
+        noise: Option<PauliNoise>,
+        shots: usize,
+    ) -> std::result::Result<Vec<(InterpretResult, Duration)>, Vec<Error>> {
+        if let Some(expr) = expr {
+            let (graph, _) = self.compile_entry_expr(expr)?;
+            self.set_expr_graph(graph, Some(expr));
+        } else if self.expr_graph.is_none() {
+            return Err(vec![Error::NoEntryPoint]);
+        }
+
+        let base_seed = self.quantum_seed;
+        let mut results = Vec::with_capacity(shots);
+        self.intrinsic_logs.clear();
+        for shot in 0..shots {
+            let start = Instant::now();
+            let mut sim = self.new_sparse_sim(noise, None);
+            self.quantum_seed = base_seed.map(|seed| seed.wrapping_add(shot as u64));
+            let result = self.run_with_sim(&mut sim, receiver, None);
+            let duration = start.elapsed();
+            self.quantum_seed = base_seed;
+            self.measurement_log = sim.measurement_log().to_vec();
+            self.intrinsic_logs.push(sim.intrinsic_log().to_vec());
+            match result {
+                Err(errors) if is_aborted(&errors) => return Err(errors),
+                result => results.push((result, duration)),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Gets the measurement outcomes of the most recent evaluation, such as a call to `run`,
+    /// `invoke_with_noise`, `eval_fragments` or `invoke`, in order, which can be passed back
+    /// to `run` or `invoke_with_noise` to replay that run.
+    #[must_use]
+    pub fn measurement_log(&self) -> &[bool] {
+        &self.measurement_log
+    }
+
+    /// Gets the custom intrinsic calls answered by the handler in each shot of the most
+    /// recent call to `run`, `run_shots` or `invoke_with_noise`, as names and arguments in
+    /// the order they were made. Calls are only recorded while capturing is enabled with
+    /// `set_capture_intrinsics`.
+    #[must_use]
+    pub fn intrinsic_logs(&self) -> &[Vec<(String, Value)>] {
+        &self.intrinsic_logs
+    }
+
+    /// Gets the number of qubits currently allocated, without capturing the state.
+    #[must_use]
+    pub fn qubit_count(&self) -> usize {
+        self.sim.qubit_count()
     }
 
     /// Gets the current quantum state of the simulator.
@@ -605,6 +913,69 @@ impl Interpreter {
         self.sim.capture_quantum_state()
     }
 
+    /// Gets the current quantum state of the simulator as a `.npy` file containing
+    /// a dense complex128 array, indexed as in `get_quantum_state`.
+    /// # Errors
+    /// Returns an error message if the state has too many qubits to be made dense.
+    pub fn get_quantum_state_npy(&mut self) -> std::result::Result<Vec<u8>, String> {
+        self.sim.main.to_npy_bytes()
+    }
+
+    /// Computes the fidelity between the current simulator state and `reference`,
+    /// a state in the format returned by `get_quantum_state`.
+    pub fn fidelity(&mut self, reference: &[(BigUint, Complex<f64>)]) -> f64 {
+        self.sim.main.fidelity_with(reference)
+    }
+
+    /// Computes the reduced density matrix of `qubits` in the current simulator state,
+    /// with qubits indexed as in `get_quantum_state`.
+    /// # Errors
+    /// Returns an error message if the qubits are not distinct or out of range.
+    pub fn density_matrix(
+        &mut self,
+        qubits: &[usize],
+    ) -> std::result::Result<Vec<Vec<Complex<f64>>>, String> {
+        self.sim.main.reduced_density_matrix(qubits)
+    }
+
+    /// Computes the probability that the given qubits would measure to the paired
+    /// outcomes in the current simulator state, without collapsing it,
+    /// with qubits indexed as in `get_quantum_state`.
+    /// # Errors
+    /// Returns an error message if a qubit is out of range.
+    pub fn outcome_probability(
+        &mut self,
+        bits: &[(usize, bool)],
+    ) -> std::result::Result<f64, String> {
+        self.sim.main.outcome_probability(bits)
+    }
+
+    /// Checks whether `q0` and `q1` are entangled in the current simulator state,
+    /// within `tol`, with qubits indexed as in `get_quantum_state`.
+    /// # Errors
+    /// Returns an error message if the qubits are the same or out of range.
+    pub fn are_entangled(
+        &mut self,
+        q0: usize,
+        q1: usize,
+        tol: f64,
+    ) -> std::result::Result<bool, String> {
+        self.sim.main.are_entangled(q0, q1, tol)
+    }
+
+    /// Resets and releases all qubits in the simulator, so that the next allocation
+    /// starts from a fresh `|0...0⟩` register. Qubits allocated by earlier input
+    /// must not be used afterwards.
+    pub fn reset_all_qubits(&mut self) {
+        self.sim.reset_all_qubits();
+    }
+
+    /// Gets the accumulated angle of the global phases applied in the simulator.
+    #[must_use]
+    pub fn global_phase(&self) -> f64 {
+        self.sim.main.global_phase_value()
+    }
+
     /// Get the current circuit representation of the program.
     pub fn get_circuit(&self) -> Circuit {
         self.sim.chained.snapshot()
@@ -613,6 +984,12 @@ impl Interpreter {
     /// Performs QIR codegen using the given entry expression on a new instance of the environment
     /// and simulator but using the current compilation.
     pub fn qirgen(&mut self, expr: &str) -> std::result::Result<String, Vec<Error>> {
+        self.rirgen(expr).map(|program| program.to_qir(&program))
+    }
+
+    /// Generates the RIR program that QIR codegen for the given entry expression is based on,
+    /// so that it can be adjusted, for example with a shot count, before QIR is written.
+    pub fn rirgen(&mut self, expr: &str) -> std::result::Result<Program, Vec<Error>> {
         if self.capabilities == TargetCapabilityFlags::all() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
@@ -637,8 +1014,8 @@ impl Interpreter {
             )
                 .into(),
         };
-        // Generate QIR
-        fir_to_qir(
+        // Generate RIR
+        fir_to_program(
             &self.fir_store,
             self.capabilities,
             Some(compute_properties),
@@ -668,6 +1045,17 @@ impl Interpreter {
         callable: &Value,
         args: Value,
     ) -> std::result::Result<String, Vec<Error>> {
+        self.rirgen_from_callable(callable, args)
+            .map(|program| program.to_qir(&program))
+    }
+
+    /// Generates the RIR program that QIR codegen for the given callable with the given
+    /// arguments is based on. See [`Self::rirgen`].
+    pub fn rirgen_from_callable(
+        &mut self,
+        callable: &Value,
+        args: Value,
+    ) -> std::result::Result<Program, Vec<Error>> {
         if self.capabilities == TargetCapabilityFlags::all() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
@@ -676,7 +1064,7 @@ impl Interpreter {
             return Err(vec![Error::NotACallable]);
         };
 
-        fir_to_qir_from_callable(
+        fir_to_program_from_callable(
             &self.fir_store,
             self.capabilities,
             None,
@@ -700,6 +1088,69 @@ impl Interpreter {
         })
     }
 
+    /// Performs QIR codegen like [`Self::qirgen`], but treats violations of the target profile
+    /// as warnings rather than errors. If codegen fails for the configured capabilities, it is
+    /// retried with the widest capabilities supported by QIR codegen, and the original errors
+    /// are returned as warnings alongside the resulting best-effort QIR.
+    pub fn qirgen_lenient(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<(String, Vec<Error>), Vec<Error>> {
+        self.rirgen_lenient(expr)
+            .map(|(program, warnings)| (program.to_qir(&program), warnings))
+    }
+
+    /// Performs QIR codegen like [`Self::qirgen_from_callable`], but treats violations of the
+    /// target profile as warnings rather than errors. See [`Self::qirgen_lenient`].
+    pub fn qirgen_from_callable_lenient(
+        &mut self,
+        callable: &Value,
+        args: &Value,
+    ) -> std::result::Result<(String, Vec<Error>), Vec<Error>> {
+        self.rirgen_from_callable_lenient(callable, args)
+            .map(|(program, warnings)| (program.to_qir(&program), warnings))
+    }
+
+    /// Generates RIR like [`Self::rirgen`], but leniently. See [`Self::qirgen_lenient`].
+    pub fn rirgen_lenient(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<(Program, Vec<Error>), Vec<Error>> {
+        self.lenient_rirgen(|interpreter| interpreter.rirgen(expr))
+    }
+
+    /// Generates RIR like [`Self::rirgen_from_callable`], but leniently. See
+    /// [`Self::qirgen_lenient`].
+    pub fn rirgen_from_callable_lenient(
+        &mut self,
+        callable: &Value,
+        args: &Value,
+    ) -> std::result::Result<(Program, Vec<Error>), Vec<Error>> {
+        self.lenient_rirgen(|interpreter| interpreter.rirgen_from_callable(callable, args.clone()))
+    }
+
+    fn lenient_rirgen(
+        &mut self,
+        mut rirgen: impl FnMut(&mut Self) -> std::result::Result<Program, Vec<Error>>,
+    ) -> std::result::Result<(Program, Vec<Error>), Vec<Error>> {
+        let errors = match rirgen(self) {
+            Ok(program) => return Ok((program, Vec::new())),
+            Err(errors) => errors,
+        };
+
+        let capabilities = self.capabilities;
+        self.capabilities |= TargetCapabilityFlags::from(Profile::AdaptiveRIF);
+        let result = rirgen(self);
+        self.capabilities = capabilities;
+
+        // If the program can't be generated even with the widened capabilities,
+        // the original errors are the most relevant ones to report.
+        match result {
+            Ok(program) => Ok((program, errors)),
+            Err(_) => Err(errors),
+        }
+    }
+
     /// Generates a circuit representation for the program.
     ///
     /// `entry` can be the current entrypoint, an entry expression, or any operation
@@ -712,10 +1163,14 @@ impl Interpreter {
     /// circuit is returned (a.k.a. trace mode). Otherwise, the circuit is generated without
     /// simulation. In this case circuit generation may fail if the program contains dynamic
     /// behavior (quantum operations that are dependent on measurement results).
+    ///
+    /// If `basis_gates` is specified, the circuit is decomposed into only those gates,
+    /// e.g. `["rz", "sx", "cx"]`, and generation fails if a gate can't be decomposed.
     pub fn circuit(
         &mut self,
         entry: CircuitEntryPoint,
         simulate: bool,
+        basis_gates: Option<Vec<String>>,
     ) -> std::result::Result<Circuit, Vec<Error>> {
         let (entry_expr, invoke_params) = match entry {
             CircuitEntryPoint::Operation(operation_expr) => {
@@ -739,7 +1194,7 @@ impl Interpreter {
 
                     self.invoke_with_sim(&mut sim, &mut out, callable, args)?
                 }
-                None => self.run_with_sim_no_output(entry_expr, &mut sim)?,
+                None => self.run_with_sim_no_output(entry_expr.as_deref(), &mut sim)?,
             };
 
             sim.chained.finish()
@@ -755,33 +1210,57 @@ impl Interpreter {
 
                     self.invoke_with_sim(&mut sim, &mut out, callable, args)?
                 }
-                None => self.run_with_sim_no_output(entry_expr, &mut sim)?,
+                None => self.run_with_sim_no_output(entry_expr.as_deref(), &mut sim)?,
             };
 
             sim.finish()
         };
 
-        Ok(circuit)
+        match basis_gates {
+            Some(basis_gates) => {
+                qsc_circuit::decompose(&circuit, &basis_gates).map_err(|e| vec![e.into()])
+            }
+            None => Ok(circuit),
+        }
     }
 
     /// Sets the entry expression for the interpreter.
     pub fn set_entry_expr(&mut self, entry_expr: &str) -> std::result::Result<(), Vec<Error>> {
         let (graph, _) = self.compile_entry_expr(entry_expr)?;
-        self.expr_graph = Some(graph);
+        self.set_expr_graph(graph, Some(entry_expr));
         Ok(())
     }
 
+    /// Gets the source of the entry expression set with `set_entry_expr`, or of the last
+    /// one run, if the last expression evaluated was compiled from one.
+    #[must_use]
+    pub fn get_entry_expr(&self) -> Option<&str> {
+        self.entry_expr.as_deref()
+    }
+
+    /// Clears the entry expression, so that running without one fails until a new one is set.
+    pub fn clear_entry_expr(&mut self) {
+        self.expr_graph = None;
+        self.entry_expr = None;
+    }
+
+    /// Records `graph` as the last expression evaluated, compiled from `entry_expr` if given.
+    fn set_expr_graph(&mut self, graph: ExecGraph, entry_expr: Option<&str>) {
+        self.expr_graph = Some(graph);
+        self.entry_expr = entry_expr.map(ToString::to_string);
+    }
+
     /// Runs the given entry expression on the given simulator with a new instance of the environment
     /// but using the current compilation.
     pub fn run_with_sim(
         &mut self,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         receiver: &mut impl Receiver,
         expr: Option<&str>,
     ) -> InterpretResult {
         let graph = if let Some(expr) = expr {
             let (graph, _) = self.compile_entry_expr(expr)?;
-            self.expr_graph = Some(graph.clone());
+            self.set_expr_graph(graph.clone(), Some(expr));
             graph
         } else {
             self.expr_graph.clone().ok_or(vec![Error::NoEntryPoint])?
@@ -805,20 +1284,20 @@ impl Interpreter {
 
     fn run_with_sim_no_output(
         &mut self,
-        entry_expr: Option<String>,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        entry_expr: Option<&str>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
     ) -> InterpretResult {
         let mut sink = std::io::sink();
         let mut out = GenericReceiver::new(&mut sink);
 
         let (package_id, graph) = if let Some(entry_expr) = entry_expr {
             // entry expression is provided
-            (self.package, self.compile_entry_expr(&entry_expr)?.0)
+            (self.package, self.compile_entry_expr(entry_expr)?.0)
         } else {
             // no entry expression, use the entrypoint in the package
             (self.source_package, self.get_entry_exec_graph()?)
         };
-        self.expr_graph = Some(graph.clone());
+        self.set_expr_graph(graph.clone(), entry_expr);
 
         if self.quantum_seed.is_some() {
             sim.set_seed(self.quantum_seed);
@@ -840,7 +1319,7 @@ impl Interpreter {
     /// but using the current compilation.
     pub fn invoke_with_sim(
         &mut self,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         receiver: &mut impl Receiver,
         callable: Value,
         args: Value,
@@ -908,6 +1387,7 @@ impl Interpreter {
     }
 
     fn lower_and_update_package(&mut self, unit: &qsc_frontend::incremental::Increment) {
+        record_intrinsic_output_tys(&mut self.intrinsic_output_tys.borrow_mut(), &unit.hir);
         {
             let fir_package = self.fir_store.get_mut(self.package);
             self.lowerer
@@ -985,6 +1465,44 @@ impl Interpreter {
     }
 }
 
+/// Returns whether evaluation stopped because the receiver aborted it, rather than
+/// because the program failed.
+fn is_aborted(errors: &[Error]) -> bool {
+    errors.iter().any(|error| {
+        matches!(
+            error,
+            Error::Eval(error) if matches!(error.error().error(), qsc_eval::Error::Aborted(_))
+        )
+    })
+}
+
+/// Collects the declared output types of the body intrinsics in every compiled package.
+fn collect_intrinsic_output_tys(compiler: &Compiler) -> Rc<RefCell<FxHashMap<Rc<str>, ty::Ty>>> {
+    let mut tys = FxHashMap::default();
+    for (_, unit) in compiler.package_store() {
+        record_intrinsic_output_tys(&mut tys, &unit.package);
+    }
+    Rc::new(RefCell::new(tys))
+}
+
+/// Records the declared output type of every body intrinsic in `package`, replacing the
+/// types of any earlier intrinsics with the same name.
+fn record_intrinsic_output_tys(
+    tys: &mut FxHashMap<Rc<str>, ty::Ty>,
+    package: &qsc_hir::hir::Package,
+) {
+    for item in package.items.values() {
+        if let qsc_hir::hir::ItemKind::Callable(decl) = &item.kind {
+            if matches!(
+                decl.body.body,
+                qsc_hir::hir::SpecBody::Gen(qsc_hir::hir::SpecGen::Intrinsic)
+            ) {
+                tys.insert(decl.name.name.clone(), decl.output.clone());
+            }
+        }
+    }
+}
+
 fn sim_circuit_backend() -> BackendChain<SparseSim, CircuitBuilder> {
     BackendChain::new(
         SparseSim::new(),
@@ -1179,7 +1697,7 @@ fn eval(
     package_store: &PackageStore,
     fir_store: &fir::PackageStore,
     env: &mut Env,
-    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
     receiver: &mut impl Receiver,
 ) -> InterpretResult {
     qsc_eval::eval(
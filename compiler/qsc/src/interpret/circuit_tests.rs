@@ -7,6 +7,7 @@ use super::{CircuitEntryPoint, Debugger, Interpreter};
 use crate::target::Profile;
 use expect_test::expect;
 use miette::Diagnostic;
+use qsc_circuit::Operation;
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_eval::output::GenericReceiver;
 use qsc_frontend::compile::SourceMap;
@@ -41,7 +42,7 @@ fn empty() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![].assert_eq(&circ.to_string());
@@ -63,7 +64,7 @@ fn one_gate() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -91,7 +92,7 @@ fn measure_same_qubit_twice() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![["
@@ -118,7 +119,7 @@ fn toffoli() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -145,7 +146,7 @@ fn rotation_gate() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -172,7 +173,7 @@ fn classical_for_loop() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -199,7 +200,7 @@ fn m_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -227,7 +228,7 @@ fn m_unrestricted_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -255,7 +256,7 @@ fn mresetz_unrestricted_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -265,6 +266,46 @@ fn mresetz_unrestricted_profile() {
     .assert_eq(&circ.to_string());
 }
 
+#[test]
+fn reset_is_distinct_from_measurement() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                    Reset(q);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
+        .expect("circuit generation should succeed");
+
+    expect![[r#"
+        q_0    ── H ──── |0〉 ──
+    "#]]
+    .assert_eq(&circ.to_string());
+
+    let ops = circ
+        .component_grid
+        .iter()
+        .flat_map(|col| col.components.iter())
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(ops.last(), Some(Operation::Ket(ket)) if ket.gate == "0"),
+        "reset should be rendered as a ket element"
+    );
+    assert!(
+        !ops.iter().any(|op| matches!(op, Operation::Measurement(_))),
+        "reset should not be rendered as a measurement"
+    );
+}
+
 #[test]
 fn mresetz_base_profile() {
     let mut interpreter = interpreter(
@@ -283,7 +324,7 @@ fn mresetz_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -321,7 +362,7 @@ fn unrestricted_profile_result_comparison() {
     interpreter.set_quantum_seed(Some(2));
 
     let circuit_err = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect_err("circuit should return error")
         .pop()
         .expect("error should exist");
@@ -342,7 +383,7 @@ fn unrestricted_profile_result_comparison() {
     // Result comparisons are okay when tracing
     // circuit with the simulator.
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, true)
+        .circuit(CircuitEntryPoint::EntryPoint, true, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -390,7 +431,7 @@ fn custom_intrinsic() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -419,7 +460,7 @@ fn custom_intrinsic_classical_arg() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     // A custom intrinsic that doesn't take qubits just doesn't
@@ -450,7 +491,7 @@ fn custom_intrinsic_one_classical_arg() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -486,7 +527,7 @@ fn custom_intrinsic_mixed_args() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -533,7 +574,7 @@ fn custom_intrinsic_apply_idle_noise() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     // ConfigurePauliNoise has no qubit arguments so it shouldn't show up.
@@ -563,7 +604,11 @@ fn operation_with_qubits() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            false,
+            None,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -594,7 +639,11 @@ fn operation_with_qubits_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            false,
+            None,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -639,7 +688,11 @@ fn operation_with_qubit_arrays() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            false,
+            None,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -695,6 +748,7 @@ fn adjoint_operation() {
         .circuit(
             CircuitEntryPoint::Operation("Adjoint Test.Foo".into()),
             false,
+            None,
         )
         .expect("circuit generation should succeed");
 
@@ -716,7 +770,11 @@ fn lambda() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("q => H(q)".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("q => H(q)".into()),
+            false,
+            None,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -725,6 +783,66 @@ fn lambda() {
     .assert_eq(&circ.to_string());
 }
 
+#[test]
+fn decompose_to_basis_gates() {
+    let mut interpreter = interpreter(
+        r"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] { [] }
+        }",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(
+            CircuitEntryPoint::Operation("qs => { H(qs[0]); CNOT(qs[0], qs[1]); }".into()),
+            false,
+            Some(vec!["rz".into(), "sx".into(), "cx".into()]),
+        )
+        .expect("circuit generation should succeed");
+
+    let circ = circ.to_string();
+    assert!(!circ.contains('H'), "no H should remain:\n{circ}");
+    expect![[r#"
+        q_0    ─ Rz(1.5708) ─── SX ─── Rz(1.5708) ──── ● ──
+        q_1    ─────────────────────────────────────── X ──
+    "#]]
+    .assert_eq(&circ);
+}
+
+#[test]
+fn decompose_to_basis_gates_that_cannot_express_circuit_fails() {
+    let mut interpreter = interpreter(
+        r"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Result[] { [] }
+        }",
+        Profile::Unrestricted,
+    );
+
+    let errors = interpreter
+        .circuit(
+            CircuitEntryPoint::Operation("q => H(q)".into()),
+            false,
+            Some(vec!["cx".into()]),
+        )
+        .expect_err("circuit generation should fail");
+
+    expect![[r#"
+        [
+            Circuit(
+                CannotDecompose(
+                    "h",
+                    "cx",
+                ),
+            ),
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
+
 #[test]
 fn controlled_operation() {
     let mut interpreter = interpreter(
@@ -761,6 +879,7 @@ fn controlled_operation() {
         .circuit(
             CircuitEntryPoint::Operation("Controlled Test.SWAP".into()),
             false,
+            None,
         )
         .expect_err("circuit generation should fail");
 
@@ -794,7 +913,11 @@ fn internal_operation() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            false,
+            None,
+        )
         .expect("circuit generation should not fail");
 
     expect![[r#"
@@ -822,7 +945,11 @@ fn operation_with_non_qubit_args() {
     );
 
     let circ_err = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()), false)
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            false,
+            None,
+        )
         .expect_err("circuit generation should fail");
 
     expect![[r"
@@ -877,7 +1004,7 @@ fn operation_with_long_gates_properly_aligned() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -915,7 +1042,7 @@ fn operation_with_subsequent_qubits_gets_horizontal_lines() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -949,7 +1076,7 @@ fn operation_with_subsequent_qubits_no_double_rows() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
@@ -985,7 +1112,7 @@ fn operation_with_subsequent_qubits_no_added_rows() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint, false)
+        .circuit(CircuitEntryPoint::EntryPoint, false, None)
         .expect("circuit generation should succeed");
 
     expect![[r#"
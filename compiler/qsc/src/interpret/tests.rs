@@ -24,7 +24,7 @@ mod given_interpreter {
         let mut cursor = Cursor::new(Vec::<u8>::new());
         let mut receiver = CursorReceiver::new(&mut cursor);
         (
-            interpreter.run(&mut receiver, Some(expr), None),
+            interpreter.run(&mut receiver, Some(expr), None, None),
             receiver.dump(),
         )
     }
@@ -1064,6 +1064,203 @@ mod given_interpreter {
             );
         }
 
+        #[test]
+        fn lenient_qirgen_reports_profile_errors_as_warnings() {
+            let mut interpreter = get_interpreter_with_capabilities(TargetCapabilityFlags::empty());
+            let expr = "{ use q = Qubit(); if M(q) == One { X(q); } MResetZ(q) }";
+            interpreter.qirgen(expr).expect_err("expected error");
+            let (qir, warnings) = interpreter.qirgen_lenient(expr).expect("expected success");
+            is_error(
+                &warnings,
+                &expect![[r#"
+                    cannot use a dynamic bool value
+                       [<entry>] [M(q) == One]
+                "#]],
+            );
+            expect![[r#"
+                %Result = type opaque
+                %Qubit = type opaque
+
+                define void @ENTRYPOINT__main() #0 {
+                block_0:
+                  call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+                  %var_0 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+                  br i1 %var_0, label %block_1, label %block_2
+                block_1:
+                  call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+                  br label %block_2
+                block_2:
+                  call void @__quantum__qis__mresetz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+                  call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+                  ret void
+                }
+
+                declare void @__quantum__qis__m__body(%Qubit*, %Result*) #1
+
+                declare i1 @__quantum__qis__read_result__body(%Result*)
+
+                declare void @__quantum__qis__x__body(%Qubit*)
+
+                declare void @__quantum__qis__mresetz__body(%Qubit*, %Result*) #1
+
+                declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+                attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="1" "required_num_results"="2" }
+                attributes #1 = { "irreversible" }
+
+                ; module flags
+
+                !llvm.module.flags = !{!0, !1, !2, !3, !4, !5}
+
+                !0 = !{i32 1, !"qir_major_version", i32 1}
+                !1 = !{i32 7, !"qir_minor_version", i32 0}
+                !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+                !3 = !{i32 1, !"dynamic_result_management", i1 false}
+                !4 = !{i32 1, !"int_computations", !"i64"}
+                !5 = !{i32 1, !"float_computations", !"f64"}
+            "#]].assert_eq(&qir);
+        }
+
+        #[test]
+        fn lenient_qirgen_without_profile_errors_has_no_warnings() {
+            let mut interpreter = get_interpreter_with_capabilities(TargetCapabilityFlags::empty());
+            let (qir, warnings) = interpreter
+                .qirgen_lenient("{ use q = Qubit(); MResetZ(q) }")
+                .expect("expected success");
+            assert!(warnings.is_empty());
+            assert!(qir.contains(r#""qir_profiles"="base_profile""#));
+        }
+
+        #[test]
+        fn custom_intrinsic_handler_is_invoked_for_unknown_intrinsic() {
+            let mut interpreter = get_interpreter();
+            let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+            let recorded = calls.clone();
+            interpreter.set_custom_intrinsic_handler(Some(std::rc::Rc::new(
+                move |name: &str, arg: &Value| {
+                    if name != "LogValue" {
+                        return None;
+                    }
+                    recorded.borrow_mut().push(arg.clone());
+                    Some(Ok(Value::Int(arg.clone().unwrap_int() * 2)))
+                },
+            )));
+            let (result, output) = line(
+                &mut interpreter,
+                "function LogValue(x : Int) : Int { body intrinsic; }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = line(&mut interpreter, "LogValue(21)");
+            is_only_value(&result, &output, &Value::Int(42));
+            assert_eq!(*calls.borrow(), vec![Value::Int(21)]);
+
+            let (result, output) = run(&mut interpreter, "LogValue(1)");
+            is_only_value(&result, &output, &Value::Int(2));
+            assert_eq!(*calls.borrow(), vec![Value::Int(21), Value::Int(1)]);
+        }
+
+        #[test]
+        fn run_shots_keeps_results_of_other_shots_when_a_shot_fails() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_quantum_seed(Some(42));
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let results = interpreter
+                .run_shots(
+                    &mut receiver,
+                    Some(r#"{ use q = Qubit(); H(q); if MResetZ(q) == One { fail "one"; } 7 }"#),
+                    None,
+                    20,
+                )
+                .expect("entry expression should compile");
+            assert_eq!(results.len(), 20);
+            assert!(results
+                .iter()
+                .any(|(result, _)| result.as_ref().is_ok_and(|value| *value == Value::Int(7))));
+            assert!(results.iter().any(|(result, _)| result.is_err()));
+        }
+
+        #[test]
+        fn eval_fragments_each_returns_value_of_every_fragment() {
+            let mut interpreter = get_interpreter();
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let values = interpreter
+                .eval_fragments_each(&mut receiver, "1+1; 2*3;")
+                .expect("fragments should evaluate");
+            assert_eq!(values, [Value::Int(2), Value::Int(6)]);
+        }
+
+        #[test]
+        fn eval_fragments_each_returns_unit_for_declarations() {
+            let mut interpreter = get_interpreter();
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let values = interpreter
+                .eval_fragments_each(
+                    &mut receiver,
+                    "function Square(x : Int) : Int { x * x } let y = Square(3); y",
+                )
+                .expect("fragments should evaluate");
+            assert_eq!(values, [Value::unit(), Value::unit(), Value::Int(9)]);
+        }
+
+        #[test]
+        fn receiver_progress_can_abort_long_loops() {
+            struct AbortingReceiver(Vec<u64>);
+            impl qsc_eval::output::Receiver for AbortingReceiver {
+                fn state(
+                    &mut self,
+                    _: Vec<(num_bigint::BigUint, num_complex::Complex64)>,
+                    _: usize,
+                ) -> Result<(), qsc_eval::output::Error> {
+                    Ok(())
+                }
+                fn matrix(
+                    &mut self,
+                    _: Vec<Vec<num_complex::Complex64>>,
+                ) -> Result<(), qsc_eval::output::Error> {
+                    Ok(())
+                }
+                fn message(&mut self, _: &str) -> Result<(), qsc_eval::output::Error> {
+                    Ok(())
+                }
+                fn progress(&mut self, iterations: u64) -> Result<(), qsc_eval::output::Error> {
+                    self.0.push(iterations);
+                    if self.0.len() < 3 {
+                        Ok(())
+                    } else {
+                        Err(qsc_eval::output::Error)
+                    }
+                }
+            }
+
+            let mut interpreter = get_interpreter();
+            let mut receiver = AbortingReceiver(Vec::new());
+            let result = interpreter.eval_fragments(&mut receiver, "while true {}");
+            is_only_error(
+                &result,
+                "",
+                &expect![[r#"
+                    runtime error: evaluation aborted
+                      aborted while evaluating this loop [line_0] [true]
+                "#]],
+            );
+            assert_eq!(receiver.0, [10_000, 20_000, 30_000]);
+        }
+
+        #[test]
+        fn state_size_history_records_growth_of_state() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_state_size_tracking(true);
+            let (result, output) = run(
+                &mut interpreter,
+                "{ use qs = Qubit[3]; for q in qs { H(q); } ResetAll(qs); }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            assert_eq!(interpreter.state_size_history()[..3], [2, 4, 8]);
+        }
+
         #[test]
         fn qirgen_entry_expr_in_block() {
             let mut interpreter = get_interpreter_with_capabilities(TargetCapabilityFlags::empty());
@@ -1367,6 +1564,21 @@ mod given_interpreter {
             }
         }
 
+        #[test]
+        fn global_phase_accumulates_across_applications() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    use q = Qubit();
+                    R(PauliI, -Std.Math.PI(), q);
+                    R(PauliI, -Std.Math.PI(), q);
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+            assert!((interpreter.global_phase() - std::f64::consts::PI).abs() < 1e-12);
+        }
+
         #[test]
         fn base_prof_non_result_return() {
             let mut interpreter = get_interpreter_with_capabilities(TargetCapabilityFlags::empty());
@@ -1522,6 +1734,85 @@ mod given_interpreter {
             is_unit_with_output_eval_entry(&result, &output, "hello there...");
         }
 
+        #[test]
+        fn forked_interpreters_do_not_share_state() {
+            let source = indoc! { r#"
+            namespace Test {
+                operation Flip(q : Qubit) : Unit {
+                    X(q);
+                }
+            }"#};
+
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let (std_id, store) =
+                crate::compile::package_store_with_stdlib(TargetCapabilityFlags::all());
+            let mut interpreter = Interpreter::new(
+                sources,
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                store,
+                &[(std_id, None)],
+            )
+            .expect("interpreter should be created");
+            line(
+                &mut interpreter,
+                "let x = 1; use q = Qubit(); Test.Flip(q);",
+            )
+            .0
+            .expect("line should succeed");
+
+            let mut forked = interpreter.fork().expect("fork should succeed");
+            let (result, output) = line(&mut forked, "use r = Qubit(); M(r)");
+            is_only_value(
+                &result,
+                &output,
+                &Value::Result(qsc_eval::val::Result::Val(false)),
+            );
+            let (result, output) = line(&mut forked, "Test.Flip(r); M(r)");
+            is_only_value(
+                &result,
+                &output,
+                &Value::Result(qsc_eval::val::Result::Val(true)),
+            );
+            let (result, output) = line(&mut forked, "x");
+            is_only_error(
+                &result,
+                &output,
+                &expect![[r#"
+                    name error: `x` not found
+                       [line_2] [x]
+                "#]],
+            );
+
+            let (result, output) = line(&mut interpreter, "Reset(q); M(q)");
+            is_only_value(
+                &result,
+                &output,
+                &Value::Result(qsc_eval::val::Result::Val(false)),
+            );
+            let (result, output) = line(&mut interpreter, "x");
+            is_only_value(&result, &output, &Value::Int(1));
+        }
+
+        #[test]
+        fn forked_interpreter_keeps_debug_lowering() {
+            let (std_id, store) =
+                crate::compile::package_store_with_stdlib(TargetCapabilityFlags::all());
+            let interpreter = Interpreter::new_with_debug(
+                SourceMap::default(),
+                PackageType::Lib,
+                TargetCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                store,
+                &[(std_id, None)],
+            )
+            .expect("interpreter should be created");
+
+            let forked = interpreter.fork().expect("fork should succeed");
+            assert!(forked.lowerer.debug_enabled());
+        }
+
         #[test]
         fn errors_returned_if_sources_do_not_match_profile() {
             let source = indoc! { r#"
@@ -49,11 +49,13 @@ pub mod line_column {
 }
 
 pub use qsc_eval::{
-    backend::{Backend, SparseSim},
-    noise::PauliNoise,
+    backend::{Backend, CustomIntrinsicHandler, MeasurementOutcome, SparseSim},
+    noise::{PauliChannel, PauliNoise},
     state::{
-        fmt_basis_state_label, fmt_complex, format_state_id, get_matrix_latex, get_phase,
-        get_state_latex,
+        dense_state_len, fmt_basis_state_label, fmt_complex, fmt_complex_with_precision,
+        format_state_id, get_matrix_latex, get_phase, get_state_latex,
+        get_state_latex_with_precision, write_dense_state_bytes, DEFAULT_DISPLAY_PRECISION,
+        MAX_DENSE_STATE_QUBITS,
     },
 };
 
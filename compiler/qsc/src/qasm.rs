@@ -6,7 +6,7 @@ use std::path::Path;
 use qsc_qasm::io::SourceResolver;
 pub use qsc_qasm::{
     CompilerConfig, OperationSignature, OutputSemantics, ProgramType, QasmCompileUnit,
-    QubitSemantics,
+    QubitSemantics, Seconds,
 };
 pub mod io {
     pub use qsc_qasm::io::*;
@@ -14,6 +14,9 @@ pub mod io {
 pub mod parser {
     pub use qsc_qasm::parser::*;
 }
+pub mod types {
+    pub use qsc_qasm::types::*;
+}
 pub mod error {
     pub use qsc_qasm::Error;
     pub use qsc_qasm::ErrorKind;
@@ -20,6 +20,7 @@ pub struct Builder {
     operations: Vec<Operation>,
     config: Config,
     remapper: Remapper,
+    negative_controls: Vec<(usize, Vec<WireId>)>,
 }
 
 impl Backend for Builder {
@@ -173,6 +174,10 @@ impl Backend for Builder {
         self.remapper.swap(q0, q1);
     }
 
+    fn reset_all_qubits(&mut self) {
+        self.remapper.reset_all();
+    }
+
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
         (Vec::new(), 0)
     }
@@ -183,6 +188,28 @@ impl Backend for Builder {
         true
     }
 
+    fn barrier(&mut self, qs: &[usize]) {
+        let targets = if qs.is_empty() {
+            (0..self.remapper.num_qubits())
+                .map(WireId)
+                .collect::<Vec<_>>()
+        } else {
+            qs.iter().map(|&q| self.map(q)).collect()
+        };
+        self.push_gate(custom_gate("barrier", &targets, vec![]));
+    }
+
+    fn begin_negative_controls(&mut self, ctls: &[usize]) {
+        let ctls = ctls.iter().map(|&q| self.map(q)).collect();
+        self.negative_controls.push((self.operations.len(), ctls));
+    }
+
+    fn end_negative_controls(&mut self) {
+        if let Some((start, ctls)) = self.negative_controls.pop() {
+            self.fold_negative_controls(start, &ctls);
+        }
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         // The qubit arguments are treated as the targets for custom gates.
         // Any remaining arguments will be kept in the display_args field
@@ -216,6 +243,7 @@ impl Builder {
             operations: vec![],
             config,
             remapper: Remapper::default(),
+            negative_controls: vec![],
         }
     }
 
@@ -244,6 +272,55 @@ impl Builder {
         self.operations.push(gate);
     }
 
+    /// Folds the X gates that flip `ctls` around the operations recorded since `start`
+    /// into negative controls on those operations.
+    /// The operations are left as recorded if the circuit was truncated or if a flipped
+    /// qubit is used as anything other than a control in between.
+    fn fold_negative_controls(&mut self, start: usize, ctls: &[WireId]) {
+        let n = ctls.len();
+        if self.max_ops_exceeded || self.operations.len() < start + 2 * n {
+            return;
+        }
+        let end = self.operations.len() - n;
+        let controls = ctls
+            .iter()
+            .map(|q| Register::quantum(q.0))
+            .collect::<Vec<_>>();
+
+        let is_flip = |op: &Operation| match op {
+            Operation::Unitary(u) => {
+                u.gate == "X"
+                    && u.controls.is_empty()
+                    && u.targets.len() == 1
+                    && controls.contains(&u.targets[0])
+            }
+            _ => false,
+        };
+        let only_controlled = |op: &Operation| match op {
+            Operation::Unitary(u) => !u.targets.iter().any(|t| controls.contains(t)),
+            _ => !operation_registers(op).any(|reg| controls.contains(reg)),
+        };
+        let ops = &self.operations;
+        if !ops[start..start + n].iter().all(is_flip)
+            || !ops[end..].iter().all(is_flip)
+            || !ops[start + n..end].iter().all(only_controlled)
+        {
+            return;
+        }
+
+        for op in &mut self.operations[start + n..end] {
+            if let Operation::Unitary(u) = op {
+                for control in &controls {
+                    if u.controls.contains(control) && !u.negative_controls.contains(control) {
+                        u.negative_controls.push(control.clone());
+                    }
+                }
+            }
+        }
+        self.operations.truncate(end);
+        self.operations.drain(start..start + n);
+    }
+
     fn num_measurements_for_qubit(&self, qubit: WireId) -> usize {
         self.remapper
             .qubit_measurement_counts
@@ -398,6 +475,12 @@ impl Remapper {
         self.next_qubit_id -= 1;
     }
 
+    /// Releases every qubit, so that the next allocations are mapped to fresh wires.
+    fn reset_all(&mut self) {
+        self.next_qubit_id = 0;
+        self.qubit_map = IndexMap::default();
+    }
+
     fn swap(&mut self, q0: usize, q1: usize) {
         let q0_mapped = self.map(q0);
         let q1_mapped = self.map(q1);
@@ -433,12 +516,23 @@ impl From<WireId> for usize {
     }
 }
 
+/// Returns all the registers an operation acts on.
+fn operation_registers(op: &Operation) -> impl Iterator<Item = &Register> {
+    let (first, second): (&[Register], &[Register]) = match op {
+        Operation::Measurement(m) => (&m.qubits, &m.results),
+        Operation::Unitary(u) => (&u.targets, &u.controls),
+        Operation::Ket(k) => (&k.targets, &[]),
+    };
+    first.iter().chain(second)
+}
+
 fn gate<const N: usize>(name: &str, targets: [WireId; N]) -> Operation {
     Operation::Unitary(Unitary {
         gate: name.into(),
         args: vec![],
         is_adjoint: false,
         controls: vec![],
+        negative_controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     })
@@ -450,6 +544,7 @@ fn adjoint_gate<const N: usize>(name: &str, targets: [WireId; N]) -> Operation {
         args: vec![],
         is_adjoint: true,
         controls: vec![],
+        negative_controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     })
@@ -465,6 +560,7 @@ fn controlled_gate<const M: usize, const N: usize>(
         args: vec![],
         is_adjoint: false,
         controls: controls.iter().map(|q| Register::quantum(q.0)).collect(),
+        negative_controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     })
@@ -495,6 +591,7 @@ fn rotation_gate<const N: usize>(name: &str, theta: f64, targets: [WireId; N]) -
         args: vec![format!("{theta:.4}")],
         is_adjoint: false,
         controls: vec![],
+        negative_controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     })
@@ -506,6 +603,7 @@ fn custom_gate(name: &str, targets: &[WireId], args: Vec<String>) -> Operation {
         args,
         is_adjoint: false,
         controls: vec![],
+        negative_controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
     })
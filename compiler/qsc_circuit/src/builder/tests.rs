@@ -49,3 +49,56 @@ fn exceed_max_operations_deferred_measurements() {
     "#]]
     .assert_eq(&circuit.to_string());
 }
+
+#[test]
+fn flipped_control_keeps_x_gates() {
+    let mut builder = Builder::new(Config {
+        max_operations: Config::DEFAULT_MAX_OPERATIONS,
+    });
+
+    let c = builder.qubit_allocate();
+    let q = builder.qubit_allocate();
+
+    builder.x(c);
+    builder.cx(c, q);
+    builder.x(c);
+
+    builder.qubit_release(c);
+    builder.qubit_release(q);
+
+    let circuit = builder.finish();
+
+    expect![[r#"
+        q_0    ── X ──── ● ──── X ──
+        q_1    ───────── X ─────────
+    "#]]
+    .assert_eq(&circuit.to_string());
+}
+
+#[test]
+fn marked_negative_control_is_open_control() {
+    let mut builder = Builder::new(Config {
+        max_operations: Config::DEFAULT_MAX_OPERATIONS,
+    });
+
+    let c = builder.qubit_allocate();
+    let q = builder.qubit_allocate();
+
+    builder.begin_negative_controls(&[c]);
+    builder.x(c);
+    builder.cx(c, q);
+    builder.x(c);
+    builder.end_negative_controls();
+    builder.x(c);
+
+    builder.qubit_release(c);
+    builder.qubit_release(q);
+
+    let circuit = builder.finish();
+
+    expect![[r#"
+        q_0    ── ○ ──── X ──
+        q_1    ── X ─────────
+    "#]]
+    .assert_eq(&circuit.to_string());
+}
@@ -35,6 +35,196 @@ pub struct Circuit {
     pub component_grid: ComponentGrid,
 }
 
+impl Circuit {
+    /// Returns the depth of the circuit, the number of layers of operations when each
+    /// operation is applied as soon as all the qubits it acts on are free.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let mut qubit_depths = FxHashMap::default();
+        for op in leaf_operations(&self.component_grid) {
+            let registers: Vec<&Register> = match op {
+                Operation::Measurement(m) => m.qubits.iter().chain(&m.results).collect(),
+                Operation::Unitary(u) => u.targets.iter().chain(&u.controls).collect(),
+                Operation::Ket(k) => k.targets.iter().collect(),
+            };
+            let depth = 1 + registers
+                .iter()
+                .map(|register| qubit_depths.get(&register.qubit).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0);
+            for register in registers {
+                qubit_depths.insert(register.qubit, depth);
+            }
+        }
+        qubit_depths.into_values().max().unwrap_or(0)
+    }
+
+    /// Returns the number of each kind of operation in the circuit. Unitaries are
+    /// named as in basis gate lists, such as `cx` or `sdg`, while measurements
+    /// are counted as `measure` and resets as `reset`.
+    #[must_use]
+    pub fn gate_counts(&self) -> FxHashMap<String, usize> {
+        let mut counts = FxHashMap::default();
+        for op in leaf_operations(&self.component_grid) {
+            let name = match op {
+                Operation::Measurement(_) => "measure".to_string(),
+                Operation::Unitary(u) => crate::decompose::gate_name(u),
+                Operation::Ket(_) => "reset".to_string(),
+            };
+            *counts.entry(name).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Returns the circuit as a `quantikz` diagram, for use in LaTeX documents.
+    /// Each qubit is drawn as a wire and each column of the circuit as a column of
+    /// the diagram. Grouped operations are drawn as a single box over their qubits.
+    #[must_use]
+    pub fn to_latex(&self) -> String {
+        let qubit_rows: FxHashMap<usize, usize> = self
+            .qubits
+            .iter()
+            .enumerate()
+            .map(|(row, qubit)| (qubit.id, row))
+            .collect();
+        let mut rows: Vec<Vec<String>> = self
+            .qubits
+            .iter()
+            .map(|qubit| vec![format!("\\lstick{{$q_{{{}}}$}}", qubit.id)])
+            .collect();
+
+        for column in &self.component_grid {
+            let mut cells = vec![Some("\\qw".to_string()); rows.len()];
+            for op in &column.components {
+                latex_operation_cells(op, &qubit_rows, &mut cells);
+            }
+            for (row, cell) in rows.iter_mut().zip(cells) {
+                row.push(cell.unwrap_or_default());
+            }
+        }
+
+        let mut latex = "\\begin{quantikz}\n".to_string();
+        let rows = rows
+            .into_iter()
+            .map(|row| format!("{} & \\qw", row.join(" & ")))
+            .collect::<Vec<_>>();
+        latex.push_str(&rows.join(" \\\\\n"));
+        latex.push_str("\n\\end{quantikz}\n");
+        latex
+    }
+}
+
+/// Fills in the `quantikz` cells drawn by `op`, one per row. Rows covered by a
+/// multi-qubit box are set to `None`, since `quantikz` expects them to be empty.
+fn latex_operation_cells(
+    op: &Operation,
+    qubit_rows: &FxHashMap<usize, usize>,
+    cells: &mut [Option<String>],
+) {
+    let rows_of = |registers: &[Register]| -> Vec<usize> {
+        let mut rows: Vec<usize> = registers
+            .iter()
+            .filter_map(|register| qubit_rows.get(&register.qubit).copied())
+            .collect();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    };
+
+    match op {
+        Operation::Measurement(m) => {
+            for row in rows_of(&m.qubits) {
+                cells[row] = Some("\\meter{}".to_string());
+            }
+        }
+        Operation::Ket(k) => {
+            for row in rows_of(&k.targets) {
+                cells[row] = Some(format!("\\gate{{\\ket{{{}}}}}", latex_escape(&k.gate)));
+            }
+        }
+        Operation::Unitary(u) => {
+            let targets = rows_of(&u.targets);
+            let (Some(&top), Some(&bottom)) = (targets.first(), targets.last()) else {
+                return;
+            };
+            let negative_controls = rows_of(&u.negative_controls);
+            for control in rows_of(&u.controls) {
+                let offset = if top >= control {
+                    format!("{}", top - control)
+                } else {
+                    format!("-{}", control - top)
+                };
+                let command = if negative_controls.contains(&control) {
+                    "octrl"
+                } else {
+                    "ctrl"
+                };
+                cells[control] = Some(format!("\\{command}{{{offset}}}"));
+            }
+
+            if u.gate == "X" && !u.controls.is_empty() && targets.len() == 1 && !u.is_adjoint {
+                cells[top] = Some("\\targ{}".to_string());
+            } else if u.gate == "SWAP" && targets.len() == 2 && u.controls.is_empty() {
+                cells[top] = Some(format!("\\swap{{{}}}", bottom - top));
+                cells[bottom] = Some("\\targX{}".to_string());
+            } else {
+                let mut label = latex_escape(&u.gate);
+                if u.is_adjoint {
+                    label.push_str("^\\dagger");
+                }
+                if !u.args.is_empty() {
+                    let args = u.args.iter().map(|arg| latex_escape(arg));
+                    write!(label, "({})", args.collect::<Vec<_>>().join(", "))
+                        .expect("writing to string should succeed");
+                }
+                let wires = bottom - top + 1;
+                cells[top] = Some(if wires == 1 {
+                    format!("\\gate{{{label}}}")
+                } else {
+                    format!("\\gate[{wires}]{{{label}}}")
+                });
+                for cell in &mut cells[top + 1..=bottom] {
+                    *cell = None;
+                }
+            }
+        }
+    }
+}
+
+/// Escapes the characters that have special meaning in LaTeX. Gate labels are typeset
+/// in math mode, so characters without a math-mode escape use a math symbol instead.
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '_' | '&' | '%' | '#' | '$' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\\' => escaped.push_str("\\backslash{}"),
+            '^' => escaped.push_str("\\hat{}"),
+            '~' => escaped.push_str("\\sim{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the operations in `grid` in order, with grouped operations replaced by their children.
+fn leaf_operations(grid: &ComponentGrid) -> Vec<&Operation> {
+    let mut ops = vec![];
+    for column in grid {
+        for op in &column.components {
+            if op.children().is_empty() {
+                ops.push(op);
+            } else {
+                ops.extend(leaf_operations(op.children()));
+            }
+        }
+    }
+    ops
+}
+
 /// Type alias for a grid of components.
 pub type ComponentGrid = Vec<ComponentColumn>;
 
@@ -147,6 +337,12 @@ pub struct Unitary {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     pub controls: Vec<Register>,
+    /// The controls that are active when the control qubit is |0〉 rather than |1〉.
+    /// Every negative control is also listed in `controls`.
+    #[serde(rename = "negativeControls")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub negative_controls: Vec<Register>,
     #[serde(rename = "isAdjoint")]
     #[serde(skip_serializing_if = "Not::not")]
     #[serde(default)]
@@ -544,6 +740,7 @@ impl Circuit {
             for op in &col.components {
                 let targets = get_row_indexes(op, register_to_row, true);
                 let controls = get_row_indexes(op, register_to_row, false);
+                let negative_controls = get_negative_control_row_indexes(op, register_to_row);
 
                 let mut all_rows = targets.clone();
                 all_rows.extend(controls.iter());
@@ -558,7 +755,16 @@ impl Circuit {
 
                 let column = col_index + 1;
 
-                add_operation_to_rows(op, rows, &targets, &controls, column, begin, end);
+                add_operation_to_rows(
+                    op,
+                    rows,
+                    &targets,
+                    &controls,
+                    &negative_controls,
+                    column,
+                    begin,
+                    end,
+                );
             }
         }
     }
@@ -571,6 +777,7 @@ fn add_operation_to_rows(
     rows: &mut [Row],
     targets: &[usize],
     controls: &[usize],
+    negative_controls: &[usize],
     column: usize,
     begin: usize,
     end: usize,
@@ -596,6 +803,8 @@ fn add_operation_to_rows(
             let row = &mut rows[*i];
             if matches!(row.wire, Wire::Qubit { .. }) && operation.is_measurement() {
                 row.add_object(column, "M");
+            } else if negative_controls.contains(i) {
+                row.add_object(column, "○");
             } else {
                 row.add_object(column, "●");
             }
@@ -684,6 +893,21 @@ fn get_row_indexes(
         .collect()
 }
 
+/// Gets the row indexes for the negative controls of an operation.
+fn get_negative_control_row_indexes(
+    operation: &Operation,
+    register_to_row: &FxHashMap<(usize, Option<usize>), usize>,
+) -> Vec<usize> {
+    match operation {
+        Operation::Unitary(u) => u
+            .negative_controls
+            .iter()
+            .filter_map(|reg| register_to_row.get(&(reg.qubit, reg.result)).copied())
+            .collect(),
+        Operation::Measurement(_) | Operation::Ket(_) => vec![],
+    }
+}
+
 /// Converts a list of operations into a 2D grid of operations in col-row format.
 /// Operations will be left-justified as much as possible in the resulting grid.
 /// Children operations are recursively converted into a grid.
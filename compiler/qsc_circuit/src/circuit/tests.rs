@@ -50,6 +50,7 @@ fn unitary(gate: &str, targets: Vec<Register>) -> Operation {
         args: vec![],
         is_adjoint: false,
         controls: vec![],
+        negative_controls: vec![],
         targets,
         children: vec![],
     })
@@ -61,6 +62,7 @@ fn ctl_unitary(gate: &str, targets: Vec<Register>, controls: Vec<Register>) -> O
         args: vec![],
         is_adjoint: false,
         controls,
+        negative_controls: vec![],
         targets,
         children: vec![],
     })
@@ -210,6 +212,7 @@ fn with_args() {
             args: vec!["1.5708".to_string()],
             is_adjoint: false,
             controls: vec![],
+            negative_controls: vec![],
             targets: vec![Register::quantum(0)],
             children: vec![],
         })]]),
@@ -230,6 +233,7 @@ fn two_targets() {
             args: vec!["1.0000".to_string()],
             is_adjoint: false,
             controls: vec![],
+            negative_controls: vec![],
             targets: vec![Register::quantum(0), Register::quantum(2)],
             children: vec![],
         })]]),
@@ -260,3 +264,75 @@ fn respect_column_info() {
     "#]]
     .assert_eq(&c.to_string());
 }
+
+#[test]
+fn depth_and_gate_counts() {
+    let operations = vec![
+        unitary("H", vec![q_reg(0)]),
+        ctl_unitary("X", vec![q_reg(1)], vec![q_reg(0)]),
+        unitary("H", vec![q_reg(2)]),
+        measurement(0, 0),
+        measurement(1, 0),
+    ];
+    let c = Circuit {
+        qubits: (0..3).map(qubit).collect(),
+        component_grid: operation_list_to_grid(operations, 3),
+    };
+
+    assert_eq!(c.depth(), 3);
+    let mut counts: Vec<_> = c.gate_counts().into_iter().collect();
+    counts.sort();
+    assert_eq!(
+        counts,
+        vec![
+            ("cx".to_string(), 1),
+            ("h".to_string(), 2),
+            ("measure".to_string(), 2)
+        ]
+    );
+}
+
+#[test]
+fn latex() {
+    let operations = vec![
+        unitary("H", vec![q_reg(0)]),
+        ctl_unitary("X", vec![q_reg(2)], vec![q_reg(0)]),
+        unitary("SWAP", vec![q_reg(1), q_reg(2)]),
+        unitary("Rzz", vec![q_reg(0), q_reg(1)]),
+        measurement(0, 0),
+    ];
+    let c = Circuit {
+        qubits: (0..3).map(qubit).collect(),
+        component_grid: operation_list_to_grid(operations, 3),
+    };
+
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \gate{H} & \ctrl{2} & \qw & \gate[2]{Rzz} & \meter{} & \qw \\
+        \lstick{$q_{1}$} & \qw & \qw & \swap{1} &  & \qw & \qw \\
+        \lstick{$q_{2}$} & \qw & \targ{} & \targX{} & \qw & \qw & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&c.to_latex());
+}
+
+#[test]
+fn latex_escapes_special_characters_in_labels() {
+    let mut op = unitary("a_b^c", vec![q_reg(1)]);
+    if let Operation::Unitary(u) = &mut op {
+        u.args = vec!["~1\\2".to_string()];
+    }
+    let operations = vec![op, ctl_unitary("Z", vec![q_reg(0)], vec![q_reg(1)])];
+    let c = Circuit {
+        qubits: (0..2).map(qubit).collect(),
+        component_grid: operation_list_to_grid(operations, 2),
+    };
+
+    expect![[r#"
+        \begin{quantikz}
+        \lstick{$q_{0}$} & \qw & \gate{Z} & \qw \\
+        \lstick{$q_{1}$} & \gate{a\_b\hat{}c(\sim{}1\backslash{}2)} & \ctrl{-1} & \qw
+        \end{quantikz}
+    "#]]
+    .assert_eq(&c.to_latex());
+}
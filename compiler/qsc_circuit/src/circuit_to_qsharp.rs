@@ -203,8 +203,15 @@ fn generate_unitary_call(
     qubits: &FxHashMap<usize, String>,
     indent: &str,
 ) -> String {
+    // Negative controls are generated by flipping the control qubits around the call
+    let mut flips = String::new();
+    for control in &unitary.negative_controls {
+        let name = get_qubit_name(qubits, control.qubit);
+        writeln!(flips, "{indent}X({name});").expect("could not write to flips");
+    }
+
     // "SX" will generate three operations: H, X and H
-    if unitary.gate == "SX" {
+    let call = if unitary.gate == "SX" {
         let h_str = operation_call(
             &Unitary {
                 gate: "H".to_string(),
@@ -212,6 +219,7 @@ fn generate_unitary_call(
                 children: vec![],
                 targets: unitary.targets.clone(),
                 controls: unitary.controls.clone(),
+                negative_controls: unitary.negative_controls.clone(),
                 is_adjoint: false,
             },
             qubits,
@@ -223,6 +231,7 @@ fn generate_unitary_call(
                 children: vec![],
                 targets: unitary.targets.clone(),
                 controls: unitary.controls.clone(),
+                negative_controls: unitary.negative_controls.clone(),
                 is_adjoint: unitary.is_adjoint,
             },
             qubits,
@@ -231,7 +240,8 @@ fn generate_unitary_call(
     } else {
         let operation_str = operation_call(unitary, qubits);
         format!("{indent}{operation_str};\n")
-    }
+    };
+    format!("{flips}{call}{flips}")
 }
 
 fn generate_ket_call(ket: &Ket, qubits: &FxHashMap<usize, String>, indent: &str) -> String {
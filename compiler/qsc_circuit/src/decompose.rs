@@ -0,0 +1,323 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    circuit::{operation_list_to_grid, Circuit, Operation, Register, Unitary},
+    Error,
+};
+use rustc_hash::FxHashSet;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+/// How many levels of rules are tried before giving up on a gate.
+/// This also stops rules that decompose gates into each other from looping.
+const MAX_DEPTH: usize = 6;
+
+/// The gates known to the decomposition rules, as the name used in basis gate
+/// lists, the name shown in circuits, the number of controls, and whether the
+/// gate is adjoint.
+const GATES: &[(&str, &str, usize, bool)] = &[
+    ("x", "X", 0, false),
+    ("y", "Y", 0, false),
+    ("z", "Z", 0, false),
+    ("h", "H", 0, false),
+    ("s", "S", 0, false),
+    ("sdg", "S", 0, true),
+    ("t", "T", 0, false),
+    ("tdg", "T", 0, true),
+    ("sx", "SX", 0, false),
+    ("rx", "Rx", 0, false),
+    ("ry", "Ry", 0, false),
+    ("rz", "Rz", 0, false),
+    ("cx", "X", 1, false),
+    ("cy", "Y", 1, false),
+    ("cz", "Z", 1, false),
+    ("ccx", "X", 2, false),
+    ("swap", "SWAP", 0, false),
+    ("rxx", "Rxx", 0, false),
+    ("ryy", "Ryy", 0, false),
+    ("rzz", "Rzz", 0, false),
+];
+
+/// Rewrites the unitaries in `circuit` using only the gates in `basis_gates`,
+/// such as `["rz", "sx", "cx"]`. Gate names are matched case-insensitively, with
+/// a `c` prefix for each control and a `dg` suffix for adjoints.
+/// Uncontrolled gates are decomposed up to a global phase.
+/// Measurements and resets are kept as they are.
+///
+/// # Errors
+///
+/// Returns an error if a gate can't be decomposed into the basis gates.
+pub fn decompose(circuit: &Circuit, basis_gates: &[String]) -> Result<Circuit, Error> {
+    let mut decomposer = Decomposer {
+        basis: basis_gates.iter().map(|gate| gate.to_lowercase()).collect(),
+        failed: FxHashSet::default(),
+    };
+
+    let mut operations = vec![];
+    for column in &circuit.component_grid {
+        for op in &column.components {
+            match op {
+                Operation::Unitary(unitary) => {
+                    let gates = decomposer.expand(unitary, MAX_DEPTH).ok_or_else(|| {
+                        Error::CannotDecompose(gate_name(unitary), basis_gates.join(", "))
+                    })?;
+                    operations.extend(gates.into_iter().map(Operation::Unitary));
+                }
+                Operation::Measurement(_) | Operation::Ket(_) => operations.push(op.clone()),
+            }
+        }
+    }
+
+    Ok(Circuit {
+        qubits: circuit.qubits.clone(),
+        component_grid: operation_list_to_grid(operations, circuit.qubits.len()),
+    })
+}
+
+struct Decomposer {
+    basis: Vec<String>,
+    /// The gates, with the depth they were tried at, that couldn't be decomposed.
+    failed: FxHashSet<(String, usize)>,
+}
+
+impl Decomposer {
+    /// Returns the basis gates equivalent to `gate`, trying each of its rules in turn.
+    fn expand(&mut self, gate: &Unitary, depth: usize) -> Option<Vec<Unitary>> {
+        let name = gate_name(gate);
+        if gate.negative_controls.is_empty() && self.basis.contains(&name) {
+            return Some(vec![gate.clone()]);
+        }
+        if depth == 0 || self.failed.contains(&(name.clone(), depth)) {
+            return None;
+        }
+
+        for rule in rules(gate) {
+            let expanded: Option<Vec<_>> = rule
+                .iter()
+                .map(|gate| self.expand(gate, depth - 1))
+                .collect();
+            if let Some(expanded) = expanded {
+                return Some(expanded.into_iter().flatten().collect());
+            }
+        }
+        self.failed.insert((name, depth));
+        None
+    }
+}
+
+/// Returns the name of the gate as used in basis gate lists.
+pub(crate) fn gate_name(gate: &Unitary) -> String {
+    GATES
+        .iter()
+        .find(|(_, display, controls, adjoint)| {
+            *display == gate.gate && *controls == gate.controls.len() && *adjoint == gate.is_adjoint
+        })
+        .map_or_else(
+            || {
+                let controls = "c".repeat(gate.controls.len());
+                let adjoint = if gate.is_adjoint { "dg" } else { "" };
+                format!("{controls}{}{adjoint}", gate.gate.to_lowercase())
+            },
+            |(name, ..)| (*name).to_string(),
+        )
+}
+
+/// Builds the gate with the given basis name, where the first qubits are the controls.
+fn unitary(name: &str, theta: Option<f64>, qubits: &[usize]) -> Unitary {
+    let (_, display, controls, adjoint) = GATES
+        .iter()
+        .find(|(gate, ..)| *gate == name)
+        .expect("rules should only use known gates");
+    Unitary {
+        gate: (*display).to_string(),
+        args: theta
+            .map(|theta| format!("{theta:.4}"))
+            .into_iter()
+            .collect(),
+        children: vec![],
+        targets: qubits[*controls..]
+            .iter()
+            .copied()
+            .map(Register::quantum)
+            .collect(),
+        controls: qubits[..*controls]
+            .iter()
+            .copied()
+            .map(Register::quantum)
+            .collect(),
+        negative_controls: vec![],
+        is_adjoint: *adjoint,
+    }
+}
+
+/// Flips the negative controls of `gate` with X gates before and after
+/// applying it with positive controls.
+fn negative_controls_rule(gate: &Unitary) -> Vec<Unitary> {
+    let flips: Vec<_> = gate
+        .negative_controls
+        .iter()
+        .map(|control| unitary("x", None, &[control.qubit]))
+        .collect();
+    let mut positive = gate.clone();
+    positive.negative_controls.clear();
+    let mut rule = flips.clone();
+    rule.push(positive);
+    rule.extend(flips);
+    rule
+}
+
+/// Returns the alternative sequences of gates that `gate` can be rewritten as.
+fn rules(gate: &Unitary) -> Vec<Vec<Unitary>> {
+    if !gate.negative_controls.is_empty() {
+        return vec![negative_controls_rule(gate)];
+    }
+
+    let qubits: Vec<_> = gate
+        .controls
+        .iter()
+        .chain(&gate.targets)
+        .map(|register| register.qubit)
+        .collect();
+    let theta = gate.args.first().and_then(|arg| arg.parse::<f64>().ok());
+    match qubits.as_slice() {
+        &[a] => single_qubit_rules(&gate_name(gate), theta, a),
+        qubits => multi_qubit_rules(&gate_name(gate), theta, qubits),
+    }
+}
+
+fn single_qubit_rules(name: &str, theta: Option<f64>, a: usize) -> Vec<Vec<Unitary>> {
+    let fixed = |name, qubits: &[usize]| unitary(name, None, qubits);
+    let rot = |name, theta, qubits: &[usize]| unitary(name, Some(theta), qubits);
+    match (name, theta) {
+        ("x", _) => vec![
+            vec![rot("rx", PI, &[a])],
+            vec![fixed("sx", &[a]), fixed("sx", &[a])],
+            vec![fixed("h", &[a]), fixed("z", &[a]), fixed("h", &[a])],
+        ],
+        ("y", _) => vec![
+            vec![rot("ry", PI, &[a])],
+            vec![fixed("z", &[a]), fixed("x", &[a])],
+        ],
+        ("z", _) => vec![
+            vec![rot("rz", PI, &[a])],
+            vec![fixed("s", &[a]), fixed("s", &[a])],
+            vec![fixed("h", &[a]), fixed("x", &[a]), fixed("h", &[a])],
+        ],
+        ("h", _) => vec![
+            vec![
+                rot("rz", FRAC_PI_2, &[a]),
+                fixed("sx", &[a]),
+                rot("rz", FRAC_PI_2, &[a]),
+            ],
+            vec![fixed("z", &[a]), rot("ry", FRAC_PI_2, &[a])],
+            vec![
+                rot("rz", FRAC_PI_2, &[a]),
+                rot("rx", FRAC_PI_2, &[a]),
+                rot("rz", FRAC_PI_2, &[a]),
+            ],
+        ],
+        ("s", _) => vec![
+            vec![rot("rz", FRAC_PI_2, &[a])],
+            vec![fixed("t", &[a]), fixed("t", &[a])],
+        ],
+        ("sdg", _) => vec![
+            vec![rot("rz", -FRAC_PI_2, &[a])],
+            vec![fixed("tdg", &[a]), fixed("tdg", &[a])],
+            vec![fixed("s", &[a]), fixed("s", &[a]), fixed("s", &[a])],
+        ],
+        ("t", _) => vec![vec![rot("rz", FRAC_PI_4, &[a])]],
+        ("tdg", _) => vec![vec![rot("rz", -FRAC_PI_4, &[a])]],
+        ("sx", _) => vec![
+            vec![rot("rx", FRAC_PI_2, &[a])],
+            vec![fixed("h", &[a]), fixed("s", &[a]), fixed("h", &[a])],
+            vec![fixed("sdg", &[a]), fixed("h", &[a]), fixed("sdg", &[a])],
+        ],
+        ("rx", Some(theta)) => vec![vec![
+            fixed("h", &[a]),
+            rot("rz", theta, &[a]),
+            fixed("h", &[a]),
+        ]],
+        ("ry", Some(theta)) => {
+            vec![vec![
+                fixed("sdg", &[a]),
+                rot("rx", theta, &[a]),
+                fixed("s", &[a]),
+            ]]
+        }
+        ("rz", Some(theta)) => vec![vec![
+            fixed("h", &[a]),
+            rot("rx", theta, &[a]),
+            fixed("h", &[a]),
+        ]],
+        _ => vec![],
+    }
+}
+
+fn multi_qubit_rules(name: &str, theta: Option<f64>, qubits: &[usize]) -> Vec<Vec<Unitary>> {
+    let fixed = |name, qubits: &[usize]| unitary(name, None, qubits);
+    let rot = |name, theta, qubits: &[usize]| unitary(name, Some(theta), qubits);
+    match (name, theta, qubits) {
+        ("cx", _, &[c, t]) => vec![vec![
+            fixed("h", &[t]),
+            fixed("cz", &[c, t]),
+            fixed("h", &[t]),
+        ]],
+        ("cy", _, &[c, t]) => vec![vec![
+            fixed("sdg", &[t]),
+            fixed("cx", &[c, t]),
+            fixed("s", &[t]),
+        ]],
+        ("cz", _, &[c, t]) => vec![vec![
+            fixed("h", &[t]),
+            fixed("cx", &[c, t]),
+            fixed("h", &[t]),
+        ]],
+        ("swap", _, &[a, b]) => vec![vec![
+            fixed("cx", &[a, b]),
+            fixed("cx", &[b, a]),
+            fixed("cx", &[a, b]),
+        ]],
+        ("rzz", Some(theta), &[a, b]) => {
+            vec![vec![
+                fixed("cx", &[a, b]),
+                rot("rz", theta, &[b]),
+                fixed("cx", &[a, b]),
+            ]]
+        }
+        ("rxx", Some(theta), &[a, b]) => vec![vec![
+            fixed("h", &[a]),
+            fixed("h", &[b]),
+            rot("rzz", theta, &[a, b]),
+            fixed("h", &[a]),
+            fixed("h", &[b]),
+        ]],
+        ("ryy", Some(theta), &[a, b]) => vec![vec![
+            rot("rx", FRAC_PI_2, &[a]),
+            rot("rx", FRAC_PI_2, &[b]),
+            rot("rzz", theta, &[a, b]),
+            rot("rx", -FRAC_PI_2, &[a]),
+            rot("rx", -FRAC_PI_2, &[b]),
+        ]],
+        ("ccx", _, &[a, b, c]) => vec![vec![
+            fixed("h", &[c]),
+            fixed("cx", &[b, c]),
+            fixed("tdg", &[c]),
+            fixed("cx", &[a, c]),
+            fixed("t", &[c]),
+            fixed("cx", &[b, c]),
+            fixed("tdg", &[c]),
+            fixed("cx", &[a, c]),
+            fixed("t", &[b]),
+            fixed("t", &[c]),
+            fixed("h", &[c]),
+            fixed("cx", &[a, b]),
+            fixed("t", &[a]),
+            fixed("tdg", &[b]),
+            fixed("cx", &[a, b]),
+        ]],
+        _ => vec![],
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::*;
+use crate::{Builder, Config};
+use expect_test::expect;
+use qsc_eval::backend::Backend;
+
+fn basis(gates: &[&str]) -> Vec<String> {
+    gates.iter().map(ToString::to_string).collect()
+}
+
+fn builder() -> Builder {
+    Builder::new(Config {
+        max_operations: Config::DEFAULT_MAX_OPERATIONS,
+    })
+}
+
+#[test]
+fn hadamard_decomposes_into_rz_and_sx() {
+    let mut builder = builder();
+    let q = builder.qubit_allocate();
+    builder.h(q);
+    let circuit = builder.finish();
+
+    let circuit = decompose(&circuit, &basis(&["rz", "sx", "cx"])).expect("should decompose");
+    expect![[r#"
+        q_0    ─ Rz(1.5708) ─── SX ─── Rz(1.5708) ──
+    "#]]
+    .assert_eq(&circuit.to_string());
+}
+
+#[test]
+fn gates_already_in_basis_are_kept() {
+    let mut builder = builder();
+    let q0 = builder.qubit_allocate();
+    let q1 = builder.qubit_allocate();
+    builder.cx(q0, q1);
+    builder.m(q1);
+    let circuit = builder.finish();
+
+    let decomposed = decompose(&circuit, &basis(&["CX"])).expect("should decompose");
+    assert_eq!(decomposed, circuit);
+}
+
+#[test]
+fn gate_outside_basis_reach_fails() {
+    let mut builder = builder();
+    let q = builder.qubit_allocate();
+    builder.h(q);
+    let circuit = builder.finish();
+
+    let error = decompose(&circuit, &basis(&["cx"])).expect_err("should fail");
+    expect!["cannot decompose h into the basis gates cx"].assert_eq(&error.to_string());
+}
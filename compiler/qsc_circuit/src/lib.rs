@@ -3,10 +3,12 @@
 
 mod builder;
 mod circuit;
+mod decompose;
 pub mod operations;
 
 pub use builder::Builder;
 pub use circuit::{Circuit, CircuitGroup, Config, Operation, CURRENT_VERSION};
+pub use decompose::decompose;
 pub use operations::Error;
 pub mod circuit_to_qsharp;
 pub mod json_to_circuit;
@@ -25,6 +25,10 @@ pub enum Error {
         "controlled invocations are not currently supported. consider wrapping the invocation in a lambda expression"
     ))]
     ControlledUnsupported,
+    #[error("cannot decompose {0} into the basis gates {1}")]
+    #[diagnostic(code("Qsc.Circuit.CannotDecompose"))]
+    #[diagnostic(help("add a gate that {0} can be decomposed into to the basis gates"))]
+    CannotDecompose(String, String),
 }
 
 /// If the item is a callable, returns the information that would
@@ -17,7 +17,8 @@ use qsc_rir::{
     rir::{self, ConditionCode, FcmpConditionCode, Program},
     utils::get_all_block_successors,
 };
-use std::fmt::Write;
+use std::fmt::{self, Write};
+use thiserror::Error;
 
 fn lower_store(package_store: &qsc_frontend::compile::PackageStore) -> qsc_fir::fir::PackageStore {
     let mut fir_store = qsc_fir::fir::PackageStore::new();
@@ -59,9 +60,21 @@ pub fn fir_to_qir(
     compute_properties: Option<PackageStoreComputeProperties>,
     entry: &ProgramEntry,
 ) -> Result<String, qsc_partial_eval::Error> {
+    let program = fir_to_program(fir_store, capabilities, compute_properties, entry)?;
+    Ok(ToQir::<String>::to_qir(&program, &program))
+}
+
+/// converts the given sources to the RIR program that QIR is generated from,
+/// using the given language features.
+pub fn fir_to_program(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    entry: &ProgramEntry,
+) -> Result<Program, qsc_partial_eval::Error> {
     let mut program = get_rir_from_compilation(fir_store, compute_properties, entry, capabilities)?;
     check_and_transform(&mut program);
-    Ok(ToQir::<String>::to_qir(&program, &program))
+    Ok(program)
 }
 
 /// converts the given callable to QIR using the given arguments and language features.
@@ -72,6 +85,20 @@ pub fn fir_to_qir_from_callable(
     callable: qsc_fir::fir::StoreItemId,
     args: Value,
 ) -> Result<String, qsc_partial_eval::Error> {
+    let program =
+        fir_to_program_from_callable(fir_store, capabilities, compute_properties, callable, args)?;
+    Ok(ToQir::<String>::to_qir(&program, &program))
+}
+
+/// converts the given callable to the RIR program that QIR is generated from,
+/// using the given arguments and language features.
+pub fn fir_to_program_from_callable(
+    fir_store: &qsc_fir::fir::PackageStore,
+    capabilities: TargetCapabilityFlags,
+    compute_properties: Option<PackageStoreComputeProperties>,
+    callable: qsc_fir::fir::StoreItemId,
+    args: Value,
+) -> Result<Program, qsc_partial_eval::Error> {
     let compute_properties = compute_properties.unwrap_or_else(|| {
         let analyzer = qsc_rca::Analyzer::init(fir_store);
         analyzer.analyze_all()
@@ -80,7 +107,7 @@ pub fn fir_to_qir_from_callable(
     let mut program =
         partially_evaluate_call(fir_store, &compute_properties, callable, args, capabilities)?;
     check_and_transform(&mut program);
-    Ok(ToQir::<String>::to_qir(&program, &program))
+    Ok(program)
 }
 
 fn get_rir_from_compilation(
@@ -118,12 +145,18 @@ impl ToQir<String> for rir::Literal {
             }
             rir::Literal::Integer(i) => format!("i64 {i}"),
             rir::Literal::Pointer => "i8* null".to_string(),
+            rir::Literal::Tag(index, len) => format!("i8* {}", tag_to_qir(*index, *len)),
             rir::Literal::Qubit(q) => format!("%Qubit* inttoptr (i64 {q} to %Qubit*)"),
             rir::Literal::Result(r) => format!("%Result* inttoptr (i64 {r} to %Result*)"),
         }
     }
 }
 
+/// Returns a pointer to the start of the tag with the given index and length in bytes.
+fn tag_to_qir(index: usize, len: usize) -> String {
+    format!("getelementptr inbounds ([{len} x i8], [{len} x i8]* @{index}, i64 0, i64 0)")
+}
+
 impl ToQir<String> for rir::Ty {
     fn to_qir(&self, _program: &rir::Program) -> String {
         match self {
@@ -569,6 +602,7 @@ fn get_value_as_str(value: &rir::Operand, program: &rir::Program) -> String {
             }
             rir::Literal::Integer(i) => format!("{i}"),
             rir::Literal::Pointer => "null".to_string(),
+            rir::Literal::Tag(index, len) => tag_to_qir(*index, *len),
             rir::Literal::Qubit(q) => format!("{q}"),
             rir::Literal::Result(r) => format!("{r}"),
         },
@@ -584,7 +618,7 @@ fn get_value_ty(lhs: &rir::Operand) -> &str {
             rir::Literal::Double(_) => get_f64_ty(),
             rir::Literal::Qubit(_) => "%Qubit*",
             rir::Literal::Result(_) => "%Result*",
-            rir::Literal::Pointer => "i8*",
+            rir::Literal::Pointer | rir::Literal::Tag(..) => "i8*",
         },
         rir::Operand::Variable(var) => get_variable_ty(*var),
     }
@@ -634,69 +668,233 @@ impl ToQir<String> for rir::Block {
 
 impl ToQir<String> for rir::Callable {
     fn to_qir(&self, program: &rir::Program) -> String {
-        let input_type = self
-            .input_type
-            .iter()
-            .map(|t| ToQir::<String>::to_qir(t, program))
-            .collect::<Vec<_>>()
-            .join(", ");
-        let output_type = ToQir::<String>::to_qir(&self.output_type, program);
-        let Some(entry_id) = self.body else {
-            return format!(
-                "declare {output_type} @{}({input_type}){}",
-                self.name,
-                if matches!(
-                    self.call_type,
-                    rir::CallableType::Measurement | rir::CallableType::Reset
-                ) {
-                    // These callables are a special case that need the irreversable attribute.
-                    " #1"
-                } else {
-                    ""
-                }
-            );
-        };
-        let mut body = String::new();
-        let mut all_blocks = vec![entry_id];
-        all_blocks.extend(get_all_block_successors(entry_id, program));
-        for block_id in all_blocks {
-            let block = program.get_block(block_id);
-            write!(
-                body,
-                "{}:\n{}\n",
-                ToQir::<String>::to_qir(&block_id, program),
-                ToQir::<String>::to_qir(block, program)
-            )
-            .expect("writing to string should succeed");
-        }
-        assert!(
-            input_type.is_empty(),
-            "entry point should not have an input"
-        );
-        format!("define {output_type} @ENTRYPOINT__main() #0 {{\n{body}}}",)
+        let mut qir = String::new();
+        write_callable(self, program, &mut qir).expect("writing to string should succeed");
+        qir
     }
 }
 
 impl ToQir<String> for rir::Program {
     fn to_qir(&self, _program: &rir::Program) -> String {
-        let callables = self
-            .callables
-            .iter()
-            .map(|(_, callable)| ToQir::<String>::to_qir(callable, self))
-            .collect::<Vec<_>>()
-            .join("\n\n");
-        let profile = if self.config.is_base() {
-            "base_profile"
+        let mut qir = String::new();
+        write_qir(self, &mut qir).expect("writing to string should succeed");
+        qir
+    }
+}
+
+/// Writes the QIR for `program` to `w`, one instruction at a time, so that the
+/// generated program never has to be held in memory as a whole.
+pub fn write_qir(program: &rir::Program, w: &mut impl Write) -> fmt::Result {
+    w.write_str("%Result = type opaque\n%Qubit = type opaque\n\n")?;
+    for (index, tag) in program.tags.iter().enumerate() {
+        writeln!(
+            w,
+            "@{index} = internal constant [{} x i8] c\"{}\\00\"",
+            tag.len() + 1,
+            escape_tag(tag)
+        )?;
+    }
+    if !program.tags.is_empty() {
+        w.write_str("\n")?;
+    }
+    for (index, (_, callable)) in program.callables.iter().enumerate() {
+        if index > 0 {
+            w.write_str("\n\n")?;
+        }
+        write_callable(callable, program, w)?;
+    }
+    let profile = if program.config.is_base() {
+        "base_profile"
+    } else {
+        "adaptive_profile"
+    };
+    write!(
+        w,
+        "\n\nattributes #0 = {{ \"entry_point\" \"output_labeling_schema\" \"qir_profiles\"=\"{profile}\" \"required_num_qubits\"=\"{}\" \"required_num_results\"=\"{}\" }}\n",
+        program.num_qubits, program.num_results
+    )?;
+    w.write_str("attributes #1 = { \"irreversible\" }\n\n; module flags\n\n")?;
+    w.write_str(&get_module_metadata(program))
+}
+
+fn write_callable(
+    callable: &rir::Callable,
+    program: &rir::Program,
+    w: &mut impl Write,
+) -> fmt::Result {
+    let input_type = callable
+        .input_type
+        .iter()
+        .map(|t| ToQir::<String>::to_qir(t, program))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let output_type = ToQir::<String>::to_qir(&callable.output_type, program);
+    let Some(entry_id) = callable.body else {
+        return write!(
+            w,
+            "declare {output_type} @{}({input_type}){}",
+            callable.name,
+            if matches!(
+                callable.call_type,
+                rir::CallableType::Measurement | rir::CallableType::Reset
+            ) {
+                // These callables are a special case that need the irreversable attribute.
+                " #1"
+            } else {
+                ""
+            }
+        );
+    };
+    assert!(
+        input_type.is_empty(),
+        "entry point should not have an input"
+    );
+    writeln!(w, "define {output_type} @ENTRYPOINT__main() #0 {{")?;
+    let mut all_blocks = vec![entry_id];
+    all_blocks.extend(get_all_block_successors(entry_id, program));
+    for block_id in all_blocks {
+        writeln!(w, "{}:", ToQir::<String>::to_qir(&block_id, program))?;
+        for instr in &program.get_block(block_id).0 {
+            writeln!(w, "{}", ToQir::<String>::to_qir(instr, program))?;
+        }
+    }
+    w.write_str("}")
+}
+
+/// Escapes `tag` for use in an LLVM string constant, keeping its length in bytes.
+fn escape_tag(tag: &str) -> String {
+    let mut escaped = String::with_capacity(tag.len());
+    for byte in tag.bytes() {
+        if (byte.is_ascii_graphic() || byte == b' ') && byte != b'"' && byte != b'\\' {
+            escaped.push(char::from(byte));
         } else {
-            "adaptive_profile"
+            write!(escaped, "\\{byte:02X}").expect("writing to string should succeed");
+        }
+    }
+    escaped
+}
+
+/// An error labeling the outputs recorded by a program.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum OutputLabelError {
+    #[error("expected {labels} output labels, but the program records {outputs} outputs")]
+    CountMismatch { labels: usize, outputs: usize },
+    #[error("the output recorded by the program is malformed")]
+    MalformedOutput,
+}
+
+/// Labels the top-level outputs recorded by `program` with `labels` in order. A single
+/// label names the whole output; otherwise the output must be a tuple with an item for
+/// each label. The labels are added to the program's tags and referenced by the output
+/// recording calls, so that codegen emits them in place of the `null` label.
+pub fn set_output_labels(
+    program: &mut rir::Program,
+    labels: &[String],
+) -> Result<(), OutputLabelError> {
+    if labels.is_empty() {
+        return Ok(());
+    }
+
+    let records = output_records(program);
+    let labeled = if labels.len() == 1 && !records.is_empty() {
+        vec![0]
+    } else {
+        let outputs = match records.first() {
+            Some(record) if record.is_tuple => record.children,
+            Some(_) => 1,
+            None => 0,
         };
-        let body = format!(
-            include_str!("./qir/template.ll"),
-            callables, profile, self.num_qubits, self.num_results
-        );
-        let flags = get_module_metadata(self);
-        body + "\n" + &flags
+        if outputs != labels.len() {
+            return Err(OutputLabelError::CountMismatch {
+                labels: labels.len(),
+                outputs,
+            });
+        }
+        let mut items = Vec::with_capacity(outputs);
+        let mut next = 1;
+        for _ in 0..outputs {
+            items.push(next);
+            next += record_tree_len(&records, next).ok_or(OutputLabelError::MalformedOutput)?;
+        }
+        items
+    };
+
+    for (record_index, label) in labeled.into_iter().zip(labels) {
+        let record = &records[record_index];
+        let tag = rir::Literal::Tag(program.tags.len(), label.len() + 1);
+        let block = program
+            .blocks
+            .get_mut(record.block)
+            .expect("block should be present");
+        let rir::Instruction::Call(_, args, _) = &mut block.0[record.instr] else {
+            return Err(OutputLabelError::MalformedOutput);
+        };
+        match args.last_mut() {
+            Some(arg @ rir::Operand::Literal(rir::Literal::Pointer)) => {
+                *arg = rir::Operand::Literal(tag);
+            }
+            _ => return Err(OutputLabelError::MalformedOutput),
+        }
+        program.tags.push(label.clone());
+    }
+    Ok(())
+}
+
+/// An output recording call in a program.
+struct OutputRecord {
+    block: rir::BlockId,
+    instr: usize,
+    is_tuple: bool,
+    /// The number of records nested directly under this one.
+    children: usize,
+}
+
+/// Returns the output recording calls in the entry point of `program`, in the order
+/// they are emitted.
+fn output_records(program: &rir::Program) -> Vec<OutputRecord> {
+    let Some(entry_id) = program.get_callable(program.entry).body else {
+        return Vec::new();
+    };
+    let mut all_blocks = vec![entry_id];
+    all_blocks.extend(get_all_block_successors(entry_id, program));
+    let mut records = Vec::new();
+    for block_id in all_blocks {
+        for (instr, instruction) in program.get_block(block_id).0.iter().enumerate() {
+            let rir::Instruction::Call(callable_id, args, _) = instruction else {
+                continue;
+            };
+            let callable = program.get_callable(*callable_id);
+            if callable.call_type != rir::CallableType::OutputRecording {
+                continue;
+            }
+            let is_tuple = callable.name == "__quantum__rt__tuple_record_output";
+            let children = match args.first() {
+                Some(rir::Operand::Literal(rir::Literal::Integer(len)))
+                    if is_tuple || callable.name == "__quantum__rt__array_record_output" =>
+                {
+                    usize::try_from(*len).unwrap_or_default()
+                }
+                _ => 0,
+            };
+            records.push(OutputRecord {
+                block: block_id,
+                instr,
+                is_tuple,
+                children,
+            });
+        }
     }
+    records
+}
+
+/// Returns the number of records in the tree rooted at `records[start]`, or `None`
+/// if the tree extends past the end of `records`.
+fn record_tree_len(records: &[OutputRecord], start: usize) -> Option<usize> {
+    let mut len = 1;
+    for _ in 0..records.get(start)?.children {
+        len += record_tree_len(records, start + len)?;
+    }
+    Some(len)
 }
 
 /// Create the module metadata for the given program.
@@ -749,6 +947,12 @@ fn get_module_metadata(program: &rir::Program) -> String {
         }
     }
 
+    if let Some(shots) = program.shots {
+        writeln!(flags, "!{index} = !{{i32 1, !\"shots\", i64 {shots}}}")
+            .expect("writing to string should succeed");
+        index += 1;
+    }
+
     let mut metadata_def = String::new();
     metadata_def.push_str("!llvm.module.flags = !{");
     for i in 0..index - 1 {
@@ -1,9 +0,0 @@
-%Result = type opaque
-%Qubit = type opaque
-
-{}
-
-attributes #0 = {{ "entry_point" "output_labeling_schema" "qir_profiles"="{}" "required_num_qubits"="{}" "required_num_results"="{}" }}
-attributes #1 = {{ "irreversible" }}
-
-; module flags
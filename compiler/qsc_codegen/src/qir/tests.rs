@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use super::ToQir;
+use super::{set_output_labels, OutputLabelError, ToQir};
 use expect_test::expect;
 use qsc_rir::builder;
 use qsc_rir::rir;
@@ -167,6 +167,89 @@ fn bell_program() {
     "#]].assert_eq(&program.to_qir(&program));
 }
 
+#[test]
+fn shots_are_added_to_module_flags() {
+    let mut program = builder::bell_program();
+    program.shots = Some(1000);
+    let qir = program.to_qir(&program);
+    let flags = &qir[qir
+        .find("; module flags")
+        .expect("qir should have module flags")..];
+    expect![[r#"
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"shots", i64 1000}
+    "#]]
+    .assert_eq(flags);
+}
+
+#[test]
+fn single_output_label_names_the_whole_output() {
+    let mut program = builder::bell_program();
+    set_output_labels(&mut program, &["c".to_string()]).expect("labels should apply");
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        @0 = internal constant [2 x i8] c"c\00"
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__cx__body(%Qubit*, %Qubit*)
+
+        declare void @__quantum__qis__m__body(%Qubit*, %Result*) #1
+
+        declare void @__quantum__rt__array_record_output(i64, i8*)
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Qubit* inttoptr (i64 1 to %Qubit*))
+          call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          call void @__quantum__rt__array_record_output(i64 2, i8* getelementptr inbounds ([2 x i8], [2 x i8]* @0, i64 0, i64 0))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          ret void
+        }
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="2" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+    "#]].assert_eq(&program.to_qir(&program));
+}
+
+#[test]
+fn multiple_output_labels_for_an_array_output_fail() {
+    let mut program = builder::bell_program();
+    let error = set_output_labels(&mut program, &["a".to_string(), "b".to_string()])
+        .expect_err("labels should not apply");
+    assert_eq!(
+        error,
+        OutputLabelError::CountMismatch {
+            labels: 2,
+            outputs: 1
+        }
+    );
+    assert!(program.tags.is_empty());
+}
+
 #[test]
 fn teleport_program() {
     let program = builder::teleport_program();
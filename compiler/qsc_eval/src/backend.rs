@@ -1,16 +1,28 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::val::Value;
-use crate::{noise::PauliNoise, val::unwrap_tuple};
+use crate::val::{IntoResultValue, Value};
+use crate::{
+    noise::{PauliChannel, PauliNoise},
+    state::{dense_state_len, write_dense_state_bytes},
+    val::unwrap_tuple,
+};
 use ndarray::Array2;
 use num_bigint::BigUint;
 use num_complex::Complex;
+use qsc_fir::fir::Pauli;
 use quantum_sparse_sim::QuantumSim;
 use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    rc::Rc,
+};
 
 #[cfg(test)]
 mod noise_tests;
+#[cfg(test)]
+mod tests;
 
 /// The trait that must be implemented by a quantum backend, whose functions will be invoked when
 /// quantum intrinsics are called.
@@ -20,6 +32,15 @@ pub trait Backend {
     fn ccx(&mut self, _ctl0: usize, _ctl1: usize, _q: usize) {
         unimplemented!("ccx gate");
     }
+    /// Applies a phase of `theta` to the state where both `ctl` and `q` are `|1⟩`.
+    /// The default implementation decomposes it, up to a global phase, into `rz` and `cx` gates.
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.rz(theta / 2.0, ctl);
+        self.rz(theta / 2.0, q);
+        self.cx(ctl, q);
+        self.rz(-theta / 2.0, q);
+        self.cx(ctl, q);
+    }
     fn cx(&mut self, _ctl: usize, _q: usize) {
         unimplemented!("cx gate");
     }
@@ -29,6 +50,11 @@ pub trait Backend {
     fn cz(&mut self, _ctl: usize, _q: usize) {
         unimplemented!("cz gate");
     }
+    /// Applies the fermionic simulation gate, which swaps an excitation between the
+    /// qubits by `theta` and applies a conditional phase of `-phi` to `|11⟩`.
+    fn fsim(&mut self, _theta: f64, _phi: f64, _q0: usize, _q1: usize) {
+        unimplemented!("fsim gate");
+    }
     fn h(&mut self, _q: usize) {
         unimplemented!("h gate");
     }
@@ -38,9 +64,22 @@ pub trait Backend {
     fn mresetz(&mut self, _q: usize) -> Self::ResultType {
         unimplemented!("mresetz operation");
     }
+    /// Measures each of `qs` in the computational basis, returning the results in order.
+    /// The default implementation measures the qubits one at a time with `m`.
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        qs.iter().map(|&q| self.m(q)).collect()
+    }
     fn reset(&mut self, _q: usize) {
         unimplemented!("reset gate");
     }
+    /// Resets each of `qs` to `|0⟩`, with the same semantics as calling `reset`
+    /// on each qubit in order.
+    /// The default implementation resets the qubits one at a time with `reset`.
+    fn reset_all(&mut self, qs: &[usize]) {
+        for &q in qs {
+            self.reset(q);
+        }
+    }
     fn rx(&mut self, _theta: f64, _q: usize) {
         unimplemented!("rx gate");
     }
@@ -74,6 +113,14 @@ pub trait Backend {
     fn t(&mut self, _q: usize) {
         unimplemented!("t gate");
     }
+    /// Applies the general single-qubit gate `U(theta, phi, lambda)`, as defined for QASM.
+    /// The default implementation decomposes it, up to a global phase of
+    /// `(phi + lambda) / 2`, into `rz` and `ry` gates.
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        self.rz(lambda, q);
+        self.ry(theta, q);
+        self.rz(phi, q);
+    }
     fn x(&mut self, _q: usize) {
         unimplemented!("x gate");
     }
@@ -83,6 +130,72 @@ pub trait Backend {
     fn z(&mut self, _q: usize) {
         unimplemented!("z gate");
     }
+    /// Applies the tensor product of `paulis` to the corresponding `qubits` in one logical step.
+    /// The default implementation applies each non-identity Pauli individually.
+    fn apply_pauli(&mut self, paulis: &[Pauli], qubits: &[usize]) {
+        assert_eq!(
+            paulis.len(),
+            qubits.len(),
+            "number of Paulis should match number of qubits"
+        );
+        for (pauli, &q) in paulis.iter().zip(qubits) {
+            match pauli {
+                Pauli::I => {}
+                Pauli::X => self.x(q),
+                Pauli::Y => self.y(q),
+                Pauli::Z => self.z(q),
+            }
+        }
+    }
+    /// Applies a Pauli error sampled from `channel` to `qubits`, where `qubits[i]` gets
+    /// the `i`th Pauli of the sampled string.
+    /// The default implementation samples with the thread's random number generator.
+    fn apply_pauli_channel(&mut self, channel: &PauliChannel, qubits: &[usize]) {
+        let paulis = channel.sample(&mut rand::thread_rng());
+        self.apply_pauli(&paulis, qubits);
+    }
+    /// Applies the unitary `matrix` to `qubits`, where `qubits[0]` is the most
+    /// significant bit of the matrix index.
+    /// # Errors
+    /// Returns an error if the backend doesn't support arbitrary unitaries.
+    fn apply_unitary(
+        &mut self,
+        _matrix: &[Vec<Complex<f64>>],
+        _qubits: &[usize],
+    ) -> Result<(), String> {
+        Err("arbitrary unitaries are not supported by this backend".to_string())
+    }
+    /// Applies the unitary `matrix` to `qubits` when all of `ctls` are in the one state.
+    /// The default implementation builds the full controlled matrix and passes it,
+    /// with the controls as the most significant qubits, to `apply_unitary`.
+    /// # Errors
+    /// Returns an error if the matrix dimensions don't match the number of qubits,
+    /// or if the backend doesn't support arbitrary unitaries.
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        validate_unitary_dims(matrix, qubits)?;
+        let dim = 1 << (ctls.len() + qubits.len());
+        let offset = dim - matrix.len();
+        let controlled = (0..dim)
+            .map(|row| {
+                (0..dim)
+                    .map(
+                        |col| match (row.checked_sub(offset), col.checked_sub(offset)) {
+                            (Some(row), Some(col)) => matrix[row][col],
+                            _ if row == col => Complex::new(1.0, 0.0),
+                            _ => Complex::new(0.0, 0.0),
+                        },
+                    )
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let all_qubits = ctls.iter().chain(qubits).copied().collect::<Vec<_>>();
+        self.apply_unitary(&controlled, &all_qubits)
+    }
     fn qubit_allocate(&mut self) -> usize {
         unimplemented!("qubit_allocate operation");
     }
@@ -96,9 +209,29 @@ pub trait Backend {
     fn qubit_swap_id(&mut self, _q0: usize, _q1: usize) {
         unimplemented!("qubit_swap_id operation");
     }
+    /// Resets and releases every live qubit, so the next allocation starts
+    /// from a fresh `|0...0⟩` register.
+    /// The default relies on `qubit_count` and assumes the live qubits are the ids
+    /// below that count, so backends that reuse ids out of order or do not track
+    /// the count must override it.
+    fn reset_all_qubits(&mut self) {
+        for q in (0..self.qubit_count()).rev() {
+            self.reset(q);
+            self.qubit_release(q);
+        }
+    }
+    /// Returns the nonzero amplitudes of the state, sorted by index, and the number
+    /// of qubits. Indices are big-endian, with qubit 0 as the most significant bit,
+    /// matching the `|q0 q1 ...⟩` labels shown by `DumpMachine`. Backends whose
+    /// simulators order bits the other way must reverse the indices.
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
         unimplemented!("capture_quantum_state operation");
     }
+    /// Returns the number of qubits currently allocated, without the cost of
+    /// capturing the state.
+    fn qubit_count(&self) -> usize {
+        unimplemented!("qubit_count operation");
+    }
     fn qubit_is_zero(&mut self, _q: usize) -> bool {
         unimplemented!("qubit_is_zero operation");
     }
@@ -109,6 +242,76 @@ pub trait Backend {
         None
     }
     fn set_seed(&mut self, _seed: Option<u64>) {}
+    /// Sets a systematic over-rotation error that scales the angle of every
+    /// `rx`, `ry` and `rz` gate by `1 + epsilon`, modeling calibration drift.
+    /// Backends that don't model control errors ignore it.
+    fn set_rotation_error(&mut self, _epsilon: f64) {}
+    /// Applies depolarizing noise to `q` at an explicit point, independently of any
+    /// per-gate noise: one of the X, Y and Z errors, chosen uniformly, with probability
+    /// `p`, and the identity otherwise.
+    /// Backends that don't model noise ignore it.
+    fn apply_depolarizing(&mut self, _p: f64, _q: usize) {}
+    /// Marks a barrier across `qs`, or across all qubits if `qs` is empty, which
+    /// prevents gates from being reordered across it. Simulators ignore it, but
+    /// backends that record circuits can show it.
+    fn barrier(&mut self, _qs: &[usize]) {}
+    /// Marks the start of gates that are applied only when the classical `condition`
+    /// holds, such as the operation passed to `ApplyIfOne`. The evaluator still skips
+    /// those gates when `condition` is false, but backends that render circuits or
+    /// model noise on conditional execution can use it to see the condition.
+    fn begin_conditional(&mut self, _condition: bool) {}
+    /// Marks the end of the gates started by the matching `begin_conditional`.
+    fn end_conditional(&mut self) {}
+    /// Marks the start of gates that are controlled on the qubits `ctls` being in the
+    /// zero state, as lowered from an OpenQASM `negctrl` modifier. The controls are
+    /// flipped with X gates around the controlled gates, and circuit backends can use
+    /// it to show those controls as negative instead of as the X gates.
+    fn begin_negative_controls(&mut self, _ctls: &[usize]) {}
+    /// Marks the end of the gates started by the matching `begin_negative_controls`.
+    fn end_negative_controls(&mut self) {}
+}
+
+/// A measurement result that also carries the probability of the observed outcome,
+/// for backends that report richer information than a single bit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeasurementOutcome {
+    /// The measured bit, where `true` corresponds to `One`.
+    pub bit: bool,
+    /// The probability with which the measured bit was observed.
+    pub prob: f64,
+}
+
+impl IntoResultValue for MeasurementOutcome {
+    fn into_result_value(self) -> Value {
+        self.bit.into_result_value()
+    }
+}
+
+/// A handler for custom intrinsics that a backend does not recognize, such as calls used
+/// for logging or to mock hardware. It receives the intrinsic name and argument and returns
+/// `None` when it does not handle the intrinsic either.
+pub type CustomIntrinsicHandler = Rc<dyn Fn(&str, &Value) -> Option<Result<Value, String>>>;
+
+/// Receives the number of nonzero amplitudes in the simulator state after each gate.
+pub type StateSizeSampler = Rc<dyn Fn(usize)>;
+
+/// Reverses the order of the lowest `width` bits of `idx`.
+/// Works a 32-bit digit at a time: the digits are reversed in order and each digit has its
+/// bits reversed, after which the padding introduced by rounding `width` up to a whole
+/// number of digits is shifted out.
+fn reverse_index_bits(idx: &BigUint, width: usize) -> BigUint {
+    if width == 0 {
+        return BigUint::default();
+    }
+    let digit_count = width.div_ceil(32);
+    let mut digits = idx.to_u32_digits();
+    digits.resize(digit_count, 0);
+    let reversed = digits
+        .iter()
+        .rev()
+        .map(|digit| digit.reverse_bits())
+        .collect::<Vec<_>>();
+    BigUint::new(reversed) >> (digit_count * 32 - width)
 }
 
 /// Default backend used when targeting sparse simulation.
@@ -118,9 +321,39 @@ pub struct SparseSim {
     /// Pauli noise that is applied after a gate or before a measurement is executed.
     /// Service functions aren't subject to noise.
     pub noise: PauliNoise,
+    /// Pauli noise that is applied to each qubit after a gate on two or more qubits,
+    /// which is usually far noisier on hardware than a single-qubit gate.
+    pub two_qubit_noise: PauliNoise,
     /// Random number generator to sample Pauli noise.
     /// Noise is not applied when rng is None.
     pub rng: Option<StdRng>,
+    /// Sum of the angles of all unconditional global phases applied so far.
+    /// Controlled global phases are relative phases and are not included.
+    global_phase: f64,
+    /// Fallback for custom intrinsics not handled by the simulator itself.
+    custom_intrinsic_handler: Option<CustomIntrinsicHandler>,
+    /// Relative over-rotation applied to single-qubit rotation angles.
+    rotation_error: f64,
+    /// Outcomes of all measurements performed so far, in order.
+    measurement_log: Vec<bool>,
+    /// Names and arguments of the custom intrinsic calls answered by the handler, in order,
+    /// recorded only while `capture_intrinsics` is set.
+    intrinsic_log: Vec<(String, Value)>,
+    /// Whether to record the custom intrinsic calls answered by the handler.
+    capture_intrinsics: bool,
+    /// Recorded outcomes to force on the next measurements, in order.
+    replay: VecDeque<bool>,
+    /// Called with the state size after each gate, if set.
+    state_size_sampler: Option<StateSizeSampler>,
+    /// Random number generator to sample the outcomes of `measure_all` and the
+    /// errors of `apply_depolarizing`, seeded along with the simulator.
+    measure_rng: StdRng,
+    /// The seed set with `set_seed`, used to seed the noise generator when
+    /// noise is configured afterwards.
+    seed: Option<u64>,
+    /// Ids of the qubits currently allocated. The simulator orders the bits of the
+    /// indices returned by `get_state` by qubit id, so a qubit's bit is at its rank here.
+    qubits: BTreeSet<usize>,
 }
 
 impl Default for SparseSim {
@@ -135,24 +368,285 @@ impl SparseSim {
         Self {
             sim: QuantumSim::new(None),
             noise: PauliNoise::default(),
+            two_qubit_noise: PauliNoise::default(),
             rng: None,
+            global_phase: 0.0,
+            custom_intrinsic_handler: None,
+            rotation_error: 0.0,
+            measurement_log: Vec::new(),
+            intrinsic_log: Vec::new(),
+            capture_intrinsics: false,
+            replay: VecDeque::new(),
+            state_size_sampler: None,
+            measure_rng: StdRng::from_entropy(),
+            seed: None,
+            qubits: BTreeSet::new(),
         }
     }
 
     #[must_use]
     pub fn new_with_noise(noise: &PauliNoise) -> Self {
+        Self::new_with_gate_noise(noise, noise)
+    }
+
+    /// Creates a simulator that applies `noise` after single-qubit gates and
+    /// `two_qubit_noise` after gates on two or more qubits.
+    #[must_use]
+    pub fn new_with_gate_noise(noise: &PauliNoise, two_qubit_noise: &PauliNoise) -> Self {
         let mut sim = SparseSim::new();
-        sim.set_noise(noise);
+        sim.set_noise(noise, two_qubit_noise);
         sim
     }
 
-    fn set_noise(&mut self, noise: &PauliNoise) {
+    /// Sets the noise applied after single-qubit gates and after gates on two or
+    /// more qubits from now on. Noiseless values turn noise off.
+    pub fn set_noise(&mut self, noise: &PauliNoise, two_qubit_noise: &PauliNoise) {
         self.noise = *noise;
-        if noise.is_noiseless() {
+        self.two_qubit_noise = *two_qubit_noise;
+        if noise.is_noiseless() && two_qubit_noise.is_noiseless() {
             self.rng = None;
         } else {
-            self.rng = Some(StdRng::from_entropy());
+            self.rng = Some(
+                self.seed
+                    .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            );
+        }
+    }
+
+    /// Forces the next measurements to have the given outcomes, in order, so that
+    /// a run recorded with `measurement_log` can be reproduced exactly.
+    /// Once the outcomes are exhausted, measurements are random again.
+    pub fn set_replay(&mut self, outcomes: Vec<bool>) {
+        self.replay = outcomes.into();
+    }
+
+    /// Returns the outcomes of all measurements performed so far, in order.
+    #[must_use]
+    pub fn measurement_log(&self) -> &[bool] {
+        &self.measurement_log
+    }
+
+    /// Returns the outcomes of the measurements performed since the last call, in order,
+    /// and clears them.
+    pub fn take_measurement_log(&mut self) -> Vec<bool> {
+        std::mem::take(&mut self.measurement_log)
+    }
+
+    /// Enables or disables recording the custom intrinsic calls answered by the
+    /// custom intrinsic handler from now on.
+    pub fn set_capture_intrinsics(&mut self, capture: bool) {
+        self.capture_intrinsics = capture;
+    }
+
+    /// Returns the names and arguments of the custom intrinsic calls answered by the
+    /// custom intrinsic handler so far while capturing was enabled, in order.
+    #[must_use]
+    pub fn intrinsic_log(&self) -> &[(String, Value)] {
+        &self.intrinsic_log
+    }
+
+    /// Measures `q`, forcing the next replayed outcome if there is one,
+    /// and records the outcome.
+    fn measure(&mut self, q: usize) -> bool {
+        if let Some(outcome) = self.replay.pop_front() {
+            self.project(q, outcome);
+        }
+        let res = self.sim.measure(q);
+        self.measurement_log.push(res);
+        res
+    }
+
+    /// Measures `q` using `r`, a uniform value in `[0, 1)`, in place of a value drawn
+    /// from the random number generator, so measurement-dependent logic can be tested
+    /// deterministically. The outcome is `One` when `r` is below the probability of `One`.
+    pub fn measure_with_rng_value(&mut self, q: usize, r: f64) -> bool {
+        let outcome = r < self.sim.joint_probability(&[q]);
+        self.project(q, outcome);
+        let res = self.sim.measure(q);
+        self.measurement_log.push(res);
+        res
+    }
+
+    /// Returns the number of nonzero amplitudes in the current state.
+    /// This needs mutable access because pending operations are applied first.
+    pub fn state_size(&mut self) -> usize {
+        self.sim.get_state().0.len()
+    }
+
+    /// Sets the sampler called with the state size after each gate, e.g. to find where
+    /// a program's state grows. Sampling is slow, so pass `None` to stop it.
+    pub fn set_state_size_sampler(&mut self, sampler: Option<StateSizeSampler>) {
+        self.state_size_sampler = sampler;
+    }
+
+    fn sample_state_size(&mut self) {
+        if let Some(sampler) = self.state_size_sampler.clone() {
+            sampler(self.state_size());
+        }
+    }
+
+    /// Projects `q` onto the given outcome and renormalizes the state.
+    /// Outcomes that are impossible in the current state are left to chance,
+    /// since the run has already diverged from the one being replayed.
+    fn project(&mut self, q: usize, outcome: bool) {
+        let prob_one = self.sim.joint_probability(&[q]);
+        let prob = if outcome { prob_one } else { 1.0 - prob_one };
+        if prob < 1e-12 {
+            return;
+        }
+        let scale = Complex::new(1.0 / prob.sqrt(), 0.0);
+        let mut matrix = Array2::<Complex<f64>>::zeros((2, 2));
+        let idx = usize::from(outcome);
+        matrix[[idx, idx]] = scale;
+        self.sim.apply(&matrix, &[q], None);
+    }
+
+    /// Returns the accumulated angle, in radians, of the global phases applied to the state.
+    #[must_use]
+    pub fn global_phase_value(&self) -> f64 {
+        self.global_phase
+    }
+
+    /// Overrides the accumulated global phase angle, e.g. to clear it between experiments.
+    /// This only updates the tracked value and does not change the simulator state.
+    pub fn set_global_phase_value(&mut self, theta: f64) {
+        self.global_phase = theta;
+    }
+
+    /// Sets the handler invoked for custom intrinsics that the simulator does not recognize.
+    pub fn set_custom_intrinsic_handler(&mut self, handler: Option<CustomIntrinsicHandler>) {
+        self.custom_intrinsic_handler = handler;
+    }
+
+    /// Returns the fidelity |⟨ref|ψ⟩|² between the current state and `reference`,
+    /// which uses the same index ordering as `capture_quantum_state`.
+    /// Both states are assumed to be normalized.
+    pub fn fidelity_with(&mut self, reference: &[(BigUint, Complex<f64>)]) -> f64 {
+        let (state, _) = self.capture_quantum_state();
+        let amplitudes = state.into_iter().collect::<FxHashMap<_, _>>();
+        let overlap = reference
+            .iter()
+            .filter_map(|(idx, val)| amplitudes.get(idx).map(|amp| val.conj() * amp))
+            .sum::<Complex<f64>>();
+        overlap.norm_sqr()
+    }
+
+    /// Returns the reduced density matrix of `qubits`, tracing out all other qubits.
+    /// Qubits are indexed as in `capture_quantum_state`, and the first qubit in
+    /// `qubits` is the most significant bit of the matrix row and column indices.
+    /// # Errors
+    /// Returns an error message if the qubits are not distinct or not all less than
+    /// the number of allocated qubits.
+    pub fn reduced_density_matrix(
+        &mut self,
+        qubits: &[usize],
+    ) -> Result<Vec<Vec<Complex<f64>>>, String> {
+        let (state, count) = self.capture_quantum_state();
+        if let Some(q) = qubits.iter().find(|q| **q >= count) {
+            return Err(format!("qubit {q} is out of range for {count} qubits"));
+        }
+        if qubits.iter().collect::<FxHashSet<_>>().len() != qubits.len() {
+            return Err("qubits must be distinct".to_string());
+        }
+        let bits = qubits
+            .iter()
+            .map(|q| (count - 1 - q) as u64)
+            .collect::<Vec<_>>();
+
+        // Group the amplitudes by the state of the traced out qubits,
+        // keeping the index of the kept qubits for each amplitude.
+        let mut environments: FxHashMap<BigUint, Vec<(usize, Complex<f64>)>> = FxHashMap::default();
+        for (mut idx, amp) in state {
+            let mut row = 0;
+            for &bit in &bits {
+                row = (row << 1) | usize::from(idx.bit(bit));
+                idx.set_bit(bit, false);
+            }
+            environments.entry(idx).or_default().push((row, amp));
+        }
+
+        let dim = 1 << qubits.len();
+        let mut matrix = vec![vec![Complex::new(0.0, 0.0); dim]; dim];
+        for amps in environments.values() {
+            for (row, row_amp) in amps {
+                for (col, col_amp) in amps {
+                    matrix[*row][*col] += row_amp * col_amp.conj();
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Returns the probability that measuring each qubit in `bits` gives its paired
+    /// outcome, where `true` is `One`, summing over the outcomes of all other qubits.
+    /// The state is not collapsed. Qubits are indexed as in `capture_quantum_state`.
+    /// # Errors
+    /// Returns an error message if a qubit is not less than the number of allocated qubits.
+    pub fn outcome_probability(&mut self, bits: &[(usize, bool)]) -> Result<f64, String> {
+        let (state, count) = self.capture_quantum_state();
+        if let Some((q, _)) = bits.iter().find(|(q, _)| *q >= count) {
+            return Err(format!("qubit {q} is out of range for {count} qubits"));
         }
+        Ok(state
+            .iter()
+            .filter(|(idx, _)| {
+                bits.iter()
+                    .all(|&(q, value)| idx.bit((count - 1 - q) as u64) == value)
+            })
+            .map(|(_, amp)| amp.norm_sqr())
+            .sum())
+    }
+
+    /// Returns the state as a `.npy` file with a dense complex128 array of the
+    /// amplitudes, indexed as in `capture_quantum_state`, for loading with `numpy.load`.
+    /// # Errors
+    /// Returns an error message if the state has too many qubits to be made dense.
+    pub fn to_npy_bytes(&mut self) -> Result<Vec<u8>, String> {
+        let (state, count) = self.capture_quantum_state();
+        let len = dense_state_len(count)?;
+
+        // The header is padded with spaces so that the data starts at a multiple of 64 bytes,
+        // after the magic string, the version and the header length.
+        let mut header =
+            format!("{{'descr': '<c16', 'fortran_order': False, 'shape': ({len},), }}");
+        let preamble_len = 10;
+        let padding = (64 - (preamble_len + header.len() + 1) % 64) % 64;
+        header.push_str(&" ".repeat(padding));
+        header.push('\n');
+
+        let data_start = preamble_len + header.len();
+        let mut bytes = vec![0; data_start + len * 16];
+        bytes[..8].copy_from_slice(b"\x93NUMPY\x01\x00");
+        bytes[8..preamble_len].copy_from_slice(
+            &u16::try_from(header.len())
+                .expect("header length should fit in u16")
+                .to_le_bytes(),
+        );
+        bytes[preamble_len..data_start].copy_from_slice(header.as_bytes());
+        write_dense_state_bytes(&state, &mut bytes[data_start..]);
+        Ok(bytes)
+    }
+
+    /// Returns whether `q0` and `q1` are entangled, meaning their two-qubit reduced
+    /// state differs from the product of their single-qubit reduced states by more
+    /// than `tol` in some entry. Qubits are indexed as in `capture_quantum_state`.
+    /// # Errors
+    /// Returns an error message if the qubits are the same or out of range.
+    pub fn are_entangled(&mut self, q0: usize, q1: usize, tol: f64) -> Result<bool, String> {
+        let pair = self.reduced_density_matrix(&[q0, q1])?;
+        let first = self.reduced_density_matrix(&[q0])?;
+        let second = self.reduced_density_matrix(&[q1])?;
+        Ok((0..4).any(|row| {
+            (0..4).any(|col| {
+                let product = first[row >> 1][col >> 1] * second[row & 1][col & 1];
+                (pair[row][col] - product).norm() > tol
+            })
+        }))
+    }
+
+    /// Returns `theta` scaled by the configured rotation error.
+    fn over_rotate(&self, theta: f64) -> f64 {
+        theta * (1.0 + self.rotation_error)
     }
 
     #[must_use]
@@ -161,13 +655,21 @@ impl SparseSim {
     }
 
     fn apply_noise(&mut self, q: usize) {
+        self.apply_pauli_noise(self.noise, q);
+    }
+
+    fn apply_two_qubit_noise(&mut self, q: usize) {
+        self.apply_pauli_noise(self.two_qubit_noise, q);
+    }
+
+    fn apply_pauli_noise(&mut self, noise: PauliNoise, q: usize) {
         if let Some(rng) = &mut self.rng {
             let p = rng.gen_range(0.0..1.0);
-            if p >= self.noise.distribution[2] {
+            if p >= noise.distribution[2] {
                 // In the most common case we don't apply noise
-            } else if p < self.noise.distribution[0] {
+            } else if p < noise.distribution[0] {
                 self.sim.x(q);
-            } else if p < self.noise.distribution[1] {
+            } else if p < noise.distribution[1] {
                 self.sim.y(q);
             } else {
                 self.sim.z(q);
@@ -182,42 +684,85 @@ impl Backend for SparseSim {
 
     fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
         self.sim.mcx(&[ctl0, ctl1], q);
-        self.apply_noise(ctl0);
-        self.apply_noise(ctl1);
-        self.apply_noise(q);
+        self.apply_two_qubit_noise(ctl0);
+        self.apply_two_qubit_noise(ctl1);
+        self.apply_two_qubit_noise(q);
+        self.sample_state_size();
+    }
+
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.sim.mcphase(&[ctl], Complex::from_polar(1.0, theta), q);
+        self.apply_two_qubit_noise(ctl);
+        self.apply_two_qubit_noise(q);
+        self.sample_state_size();
     }
 
     fn cx(&mut self, ctl: usize, q: usize) {
         self.sim.mcx(&[ctl], q);
-        self.apply_noise(ctl);
-        self.apply_noise(q);
+        self.apply_two_qubit_noise(ctl);
+        self.apply_two_qubit_noise(q);
+        self.sample_state_size();
     }
 
     fn cy(&mut self, ctl: usize, q: usize) {
         self.sim.mcy(&[ctl], q);
-        self.apply_noise(ctl);
-        self.apply_noise(q);
+        self.apply_two_qubit_noise(ctl);
+        self.apply_two_qubit_noise(q);
+        self.sample_state_size();
     }
 
     fn cz(&mut self, ctl: usize, q: usize) {
         self.sim.mcz(&[ctl], q);
-        self.apply_noise(ctl);
-        self.apply_noise(q);
+        self.apply_two_qubit_noise(ctl);
+        self.apply_two_qubit_noise(q);
+        self.sample_state_size();
+    }
+
+    fn fsim(&mut self, theta: f64, phi: f64, q0: usize, q1: usize) {
+        // The excitation swap is exp(-iθ(XX + YY)/2), applied as Rxx(θ) followed by
+        // Ryy(θ) since XX and YY commute.
+        self.sim.h(q0);
+        self.sim.h(q1);
+        self.sim.mcx(&[q1], q0);
+        self.sim.rz(theta, q0);
+        self.sim.mcx(&[q1], q0);
+        self.sim.h(q1);
+        self.sim.h(q0);
+        self.sim.h(q0);
+        self.sim.s(q0);
+        self.sim.h(q0);
+        self.sim.h(q1);
+        self.sim.s(q1);
+        self.sim.h(q1);
+        self.sim.mcx(&[q1], q0);
+        self.sim.rz(theta, q0);
+        self.sim.mcx(&[q1], q0);
+        self.sim.h(q1);
+        self.sim.sadj(q1);
+        self.sim.h(q1);
+        self.sim.h(q0);
+        self.sim.sadj(q0);
+        self.sim.h(q0);
+        self.sim.mcphase(&[q0], Complex::from_polar(1.0, -phi), q1);
+        self.apply_two_qubit_noise(q0);
+        self.apply_two_qubit_noise(q1);
+        self.sample_state_size();
     }
 
     fn h(&mut self, q: usize) {
         self.sim.h(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn m(&mut self, q: usize) -> Self::ResultType {
         self.apply_noise(q);
-        self.sim.measure(q)
+        self.measure(q)
     }
 
     fn mresetz(&mut self, q: usize) -> Self::ResultType {
         self.apply_noise(q); // Applying noise before measurement
-        let res = self.sim.measure(q);
+        let res = self.measure(q);
         if res {
             self.sim.x(q);
         }
@@ -225,14 +770,74 @@ impl Backend for SparseSim {
         res
     }
 
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        if !self.replay.is_empty() {
+            return qs.iter().map(|&q| self.m(q)).collect();
+        }
+        if qs.is_empty() {
+            return Vec::new();
+        }
+        for &q in qs {
+            self.apply_noise(q);
+        }
+
+        // Sample a basis state once, which gives the joint outcome of all the qubits.
+        let (state, _) = self.sim.get_state();
+        let r = self.measure_rng.gen_range(0.0..1.0);
+        let mut cumulative = 0.0;
+        let sampled = state
+            .iter()
+            .find(|(_, amp)| {
+                cumulative += amp.norm_sqr();
+                r < cumulative
+            })
+            .or(state.last())
+            .map(|(idx, _)| idx.clone())
+            .expect("state should have a nonzero amplitude");
+        let ranks = qs
+            .iter()
+            .map(|q| self.qubits.range(..q).count() as u64)
+            .collect::<Vec<_>>();
+        let results = ranks
+            .iter()
+            .map(|&rank| sampled.bit(rank))
+            .collect::<Vec<_>>();
+
+        // Collapse onto that outcome with an unnormalized projector per qubit, then
+        // renormalize once by the probability of the joint outcome.
+        let prob = state
+            .iter()
+            .filter(|(idx, _)| {
+                ranks
+                    .iter()
+                    .zip(&results)
+                    .all(|(&rank, &res)| idx.bit(rank) == res)
+            })
+            .map(|(_, amp)| amp.norm_sqr())
+            .sum::<f64>();
+        for (&q, &res) in qs.iter().zip(&results) {
+            let mut projector = Array2::<Complex<f64>>::zeros((2, 2));
+            let idx = usize::from(res);
+            projector[[idx, idx]] = Complex::new(1.0, 0.0);
+            self.sim.apply(&projector, &[q], None);
+        }
+        let scale = Complex::new(1.0 / prob.sqrt(), 0.0);
+        let normalizer = Array2::from_diag(&ndarray::arr1(&[scale, scale]));
+        self.sim.apply(&normalizer, &[qs[0]], None);
+
+        self.measurement_log.extend(&results);
+        results
+    }
+
     fn reset(&mut self, q: usize) {
         self.mresetz(q);
         // Noise applied in mresetz.
     }
 
     fn rx(&mut self, theta: f64, q: usize) {
-        self.sim.rx(theta, q);
+        self.sim.rx(self.over_rotate(theta), q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
@@ -243,13 +848,15 @@ impl Backend for SparseSim {
         self.sim.mcx(&[q1], q0);
         self.sim.h(q1);
         self.sim.h(q0);
-        self.apply_noise(q0);
-        self.apply_noise(q1);
+        self.apply_two_qubit_noise(q0);
+        self.apply_two_qubit_noise(q1);
+        self.sample_state_size();
     }
 
     fn ry(&mut self, theta: f64, q: usize) {
-        self.sim.ry(theta, q);
+        self.sim.ry(self.over_rotate(theta), q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
@@ -268,70 +875,170 @@ impl Backend for SparseSim {
         self.sim.h(q0);
         self.sim.sadj(q0);
         self.sim.h(q0);
-        self.apply_noise(q0);
-        self.apply_noise(q1);
+        self.apply_two_qubit_noise(q0);
+        self.apply_two_qubit_noise(q1);
+        self.sample_state_size();
     }
 
     fn rz(&mut self, theta: f64, q: usize) {
-        self.sim.rz(theta, q);
+        self.sim.rz(self.over_rotate(theta), q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
         self.sim.mcx(&[q1], q0);
         self.sim.rz(theta, q0);
         self.sim.mcx(&[q1], q0);
-        self.apply_noise(q0);
-        self.apply_noise(q1);
+        self.apply_two_qubit_noise(q0);
+        self.apply_two_qubit_noise(q1);
+        self.sample_state_size();
     }
 
     fn sadj(&mut self, q: usize) {
         self.sim.sadj(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn s(&mut self, q: usize) {
         self.sim.s(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn swap(&mut self, q0: usize, q1: usize) {
         self.sim.swap_qubit_ids(q0, q1);
-        self.apply_noise(q0);
-        self.apply_noise(q1);
+        self.apply_two_qubit_noise(q0);
+        self.apply_two_qubit_noise(q1);
+        self.sample_state_size();
     }
 
     fn tadj(&mut self, q: usize) {
         self.sim.tadj(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn t(&mut self, q: usize) {
         self.sim.t(q);
         self.apply_noise(q);
+        self.sample_state_size();
+    }
+
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        // Applied as a single exact unitary, global phase included, so that it
+        // incurs the noise of one gate rather than that of its decomposition.
+        let (sin, cos) = (theta / 2.0).sin_cos();
+        let matrix = Array2::from_shape_vec(
+            (2, 2),
+            vec![
+                Complex::new(cos, 0.0),
+                -Complex::from_polar(sin, lambda),
+                Complex::from_polar(sin, phi),
+                Complex::from_polar(cos, phi + lambda),
+            ],
+        )
+        .expect("matrix should be 2x2");
+        self.sim.apply(&matrix, &[q], None);
+        self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn x(&mut self, q: usize) {
         self.sim.x(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn y(&mut self, q: usize) {
         self.sim.y(q);
         self.apply_noise(q);
+        self.sample_state_size();
     }
 
     fn z(&mut self, q: usize) {
         self.sim.z(q);
         self.apply_noise(q);
+        self.sample_state_size();
+    }
+
+    fn apply_pauli(&mut self, paulis: &[Pauli], qubits: &[usize]) {
+        assert_eq!(
+            paulis.len(),
+            qubits.len(),
+            "number of Paulis should match number of qubits"
+        );
+        for (pauli, &q) in paulis.iter().zip(qubits) {
+            match pauli {
+                Pauli::I => {}
+                Pauli::X => self.sim.x(q),
+                Pauli::Y => self.sim.y(q),
+                Pauli::Z => self.sim.z(q),
+            }
+        }
+        // Noise is applied once the whole Pauli string has been applied.
+        for (pauli, &q) in paulis.iter().zip(qubits) {
+            if *pauli != Pauli::I {
+                self.apply_noise(q);
+            }
+        }
+        self.sample_state_size();
+    }
+
+    fn apply_pauli_channel(&mut self, channel: &PauliChannel, qubits: &[usize]) {
+        // The configured noise generator is used when there is one, so that seeded runs
+        // are reproducible. The sampled error is itself noise, so no further noise is applied.
+        let paulis = match &mut self.rng {
+            Some(rng) => channel.sample(rng),
+            None => channel.sample(&mut rand::thread_rng()),
+        };
+        assert_eq!(
+            paulis.len(),
+            qubits.len(),
+            "number of channel qubits should match number of qubits"
+        );
+        for (pauli, &q) in paulis.iter().zip(qubits) {
+            match pauli {
+                Pauli::I => {}
+                Pauli::X => self.sim.x(q),
+                Pauli::Y => self.sim.y(q),
+                Pauli::Z => self.sim.z(q),
+            }
+        }
+        self.sample_state_size();
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.apply_controlled_unitary(&[], matrix, qubits)
+    }
+
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        validate_unitary_dims(matrix, qubits)?;
+        let matrix = Array2::from_shape_fn((matrix.len(), matrix.len()), |(i, j)| matrix[i][j]);
+        self.sim.apply(&matrix, qubits, Some(ctls));
+        self.sample_state_size();
+        Ok(())
     }
 
     fn qubit_allocate(&mut self) -> usize {
         // Fresh qubit start in ground state even with noise.
-        self.sim.allocate()
+        let q = self.sim.allocate();
+        self.qubits.insert(q);
+        q
     }
 
     fn qubit_release(&mut self, q: usize) -> bool {
+        self.qubits.remove(&q);
         if self.is_noiseless() {
             let was_zero = self.sim.qubit_is_zero(q);
             self.sim.release(q);
@@ -347,26 +1054,32 @@ impl Backend for SparseSim {
         self.sim.swap_qubit_ids(q0, q1);
     }
 
+    fn reset_all_qubits(&mut self) {
+        // Replacing the simulator drops the state and its free qubit ids at once,
+        // which is cheaper than resetting each qubit. The seed is applied again so
+        // that seeded runs after a reset are reproducible.
+        self.sim = QuantumSim::new(None);
+        self.global_phase = 0.0;
+        self.qubits.clear();
+        self.set_seed(self.seed);
+    }
+
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
         let (state, count) = self.sim.get_state();
         // Because the simulator returns the state indices with opposite endianness from the
         // expected one, we need to reverse the bit order of the indices.
         let mut new_state = state
             .into_iter()
-            .map(|(idx, val)| {
-                let mut new_idx = BigUint::default();
-                for i in 0..(count as u64) {
-                    if idx.bit((count as u64) - 1 - i) {
-                        new_idx.set_bit(i, true);
-                    }
-                }
-                (new_idx, val)
-            })
+            .map(|(idx, val)| (reverse_index_bits(&idx, count), val))
             .collect::<Vec<_>>();
         new_state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
         (new_state, count)
     }
 
+    fn qubit_count(&self) -> usize {
+        self.qubits.len()
+    }
+
     fn qubit_is_zero(&mut self, q: usize) -> bool {
         // This is a service function rather than a measurement so it doesn't incur noise.
         self.sim.qubit_is_zero(q)
@@ -387,17 +1100,21 @@ impl Backend for SparseSim {
                     .iter()
                     .map(|q| q.clone().unwrap_qubit().deref().0)
                     .collect::<Vec<_>>();
+                let theta = theta.clone().unwrap_double();
+                if ctls.is_empty() {
+                    self.global_phase += theta;
+                }
                 let q = self.sim.allocate();
                 // The new qubit is by-definition in the |0⟩ state, so by reversing the sign of the
                 // angle we can apply the phase to the entire state without increasing its size in memory.
-                self.sim
-                    .mcrz(&ctls, -2.0 * theta.clone().unwrap_double(), q);
+                self.sim.mcrz(&ctls, -2.0 * theta, q);
                 self.sim.release(q);
                 Some(Ok(Value::unit()))
             }
             "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
             "EndEstimateCaching"
             | "AccountForEstimatesInternal"
+            | "AccountForRuntimeInternal"
             | "BeginRepeatEstimatesInternal"
             | "EndRepeatEstimatesInternal" => Some(Ok(Value::unit())),
             "ConfigurePauliNoise" => {
@@ -409,7 +1126,7 @@ impl Backend for SparseSim {
                 let pz = zv.get_double();
                 match PauliNoise::from_probabilities(px, py, pz) {
                     Ok(noise) => {
-                        self.set_noise(&noise);
+                        self.set_noise(&noise, &noise);
                         Some(Ok(Value::unit()))
                     }
                     Err(message) => Some(Err(message)),
@@ -443,23 +1160,67 @@ impl Backend for SparseSim {
 
                 Some(Ok(Value::unit()))
             }
-            _ => None,
+            _ => {
+                let result = self
+                    .custom_intrinsic_handler
+                    .as_ref()
+                    .and_then(|handler| handler(name, &arg));
+                if self.capture_intrinsics && result.is_some() {
+                    self.intrinsic_log.push((name.to_string(), arg));
+                }
+                result
+            }
         }
     }
 
     fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
         if let Some(seed) = seed {
             if !self.is_noiseless() {
                 self.rng = Some(StdRng::seed_from_u64(seed));
             }
             self.sim.set_rng_seed(seed);
+            self.measure_rng = StdRng::seed_from_u64(seed);
         } else {
             if !self.is_noiseless() {
                 self.rng = Some(StdRng::from_entropy());
             }
             self.sim.set_rng_seed(rand::thread_rng().next_u64());
+            self.measure_rng = StdRng::from_entropy();
+        }
+    }
+
+    fn set_rotation_error(&mut self, epsilon: f64) {
+        self.rotation_error = epsilon;
+    }
+
+    fn apply_depolarizing(&mut self, p: f64, q: usize) {
+        // The noise rng only exists when per-gate noise is configured, so this is
+        // sampled with the measurement rng, which is always seeded.
+        let r = self.measure_rng.gen_range(0.0..1.0);
+        if r >= p {
+            return;
         }
+        if r < p / 3.0 {
+            self.sim.x(q);
+        } else if r < 2.0 * p / 3.0 {
+            self.sim.y(q);
+        } else {
+            self.sim.z(q);
+        }
+    }
+}
+
+/// Checks that `matrix` is square with one row for each basis state of `qubits`.
+fn validate_unitary_dims(matrix: &[Vec<Complex<f64>>], qubits: &[usize]) -> Result<(), String> {
+    let dim = 1 << qubits.len();
+    if matrix.len() != dim || matrix.iter().any(|row| row.len() != dim) {
+        return Err(format!(
+            "matrix should be {dim}x{dim} to apply to {} qubits",
+            qubits.len()
+        ));
     }
+    Ok(())
 }
 
 fn unwrap_matrix_as_array2(matrix: Value, qubits: &[usize]) -> Array2<Complex<f64>> {
@@ -518,6 +1279,11 @@ where
         self.main.ccx(ctl0, ctl1, q);
     }
 
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.chained.cp(theta, ctl, q);
+        self.main.cp(theta, ctl, q);
+    }
+
     fn cx(&mut self, ctl: usize, q: usize) {
         self.chained.cx(ctl, q);
         self.main.cx(ctl, q);
@@ -533,6 +1299,11 @@ where
         self.main.cz(ctl, q);
     }
 
+    fn fsim(&mut self, theta: f64, phi: f64, q0: usize, q1: usize) {
+        self.chained.fsim(theta, phi, q0, q1);
+        self.main.fsim(theta, phi, q0, q1);
+    }
+
     fn h(&mut self, q: usize) {
         self.chained.h(q);
         self.main.h(q);
@@ -543,6 +1314,11 @@ where
         self.main.m(q)
     }
 
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        let _ = self.chained.measure_all(qs);
+        self.main.measure_all(qs)
+    }
+
     fn mresetz(&mut self, q: usize) -> Self::ResultType {
         let _ = self.chained.mresetz(q);
         self.main.mresetz(q)
@@ -553,6 +1329,11 @@ where
         self.main.reset(q);
     }
 
+    fn reset_all(&mut self, qs: &[usize]) {
+        self.chained.reset_all(qs);
+        self.main.reset_all(qs);
+    }
+
     fn rx(&mut self, theta: f64, q: usize) {
         self.chained.rx(theta, q);
         self.main.rx(theta, q);
@@ -608,6 +1389,11 @@ where
         self.main.t(q);
     }
 
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        self.chained.u(theta, phi, lambda, q);
+        self.main.u(theta, phi, lambda, q);
+    }
+
     fn x(&mut self, q: usize) {
         self.chained.x(q);
         self.main.x(q);
@@ -623,6 +1409,30 @@ where
         self.main.z(q);
     }
 
+    fn apply_pauli(&mut self, paulis: &[Pauli], qubits: &[usize]) {
+        self.chained.apply_pauli(paulis, qubits);
+        self.main.apply_pauli(paulis, qubits);
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        let _ = self.chained.apply_unitary(matrix, qubits);
+        self.main.apply_unitary(matrix, qubits)
+    }
+
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        let _ = self.chained.apply_controlled_unitary(ctls, matrix, qubits);
+        self.main.apply_controlled_unitary(ctls, matrix, qubits)
+    }
+
     fn qubit_allocate(&mut self) -> usize {
         // Warning: we use the qubit id allocated by the
         // main backend, even for later calls into the chained
@@ -643,6 +1453,11 @@ where
         self.main.qubit_swap_id(q0, q1);
     }
 
+    fn reset_all_qubits(&mut self) {
+        self.chained.reset_all_qubits();
+        self.main.reset_all_qubits();
+    }
+
     fn capture_quantum_state(
         &mut self,
     ) -> (Vec<(num_bigint::BigUint, num_complex::Complex<f64>)>, usize) {
@@ -650,6 +1465,10 @@ where
         self.main.capture_quantum_state()
     }
 
+    fn qubit_count(&self) -> usize {
+        self.main.qubit_count()
+    }
+
     fn qubit_is_zero(&mut self, q: usize) -> bool {
         let _ = self.chained.qubit_is_zero(q);
         self.main.qubit_is_zero(q)
@@ -664,4 +1483,917 @@ where
         self.chained.set_seed(seed);
         self.main.set_seed(seed);
     }
+
+    fn set_rotation_error(&mut self, epsilon: f64) {
+        self.chained.set_rotation_error(epsilon);
+        self.main.set_rotation_error(epsilon);
+    }
+
+    fn apply_depolarizing(&mut self, p: f64, q: usize) {
+        self.chained.apply_depolarizing(p, q);
+        self.main.apply_depolarizing(p, q);
+    }
+
+    fn barrier(&mut self, qs: &[usize]) {
+        self.chained.barrier(qs);
+        self.main.barrier(qs);
+    }
+
+    fn begin_negative_controls(&mut self, ctls: &[usize]) {
+        self.chained.begin_negative_controls(ctls);
+        self.main.begin_negative_controls(ctls);
+    }
+
+    fn begin_conditional(&mut self, condition: bool) {
+        self.chained.begin_conditional(condition);
+        self.main.begin_conditional(condition);
+    }
+
+    fn end_conditional(&mut self) {
+        self.chained.end_conditional();
+        self.main.end_conditional();
+    }
+
+    fn end_negative_controls(&mut self) {
+        self.chained.end_negative_controls();
+        self.main.end_negative_controls();
+    }
+}
+
+/// A gate recorded by a [`TracingSim`], with its arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TracedGate {
+    Ccx(usize, usize, usize),
+    Cp(f64, usize, usize),
+    Cx(usize, usize),
+    Cy(usize, usize),
+    Cz(usize, usize),
+    Fsim(f64, f64, usize, usize),
+    H(usize),
+    Rx(f64, usize),
+    Rxx(f64, usize, usize),
+    Ry(f64, usize),
+    Ryy(f64, usize, usize),
+    Rz(f64, usize),
+    Rzz(f64, usize, usize),
+    S(usize),
+    SAdj(usize),
+    Swap(usize, usize),
+    T(usize),
+    TAdj(usize),
+    U(f64, f64, f64, usize),
+    X(usize),
+    Y(usize),
+    Z(usize),
+}
+
+impl TracedGate {
+    /// Returns the gate that undoes this one.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Cp(theta, ctl, q) => Self::Cp(-theta, ctl, q),
+            Self::Fsim(theta, phi, q0, q1) => Self::Fsim(-theta, -phi, q0, q1),
+            Self::Rx(theta, q) => Self::Rx(-theta, q),
+            Self::Rxx(theta, q0, q1) => Self::Rxx(-theta, q0, q1),
+            Self::Ry(theta, q) => Self::Ry(-theta, q),
+            Self::Ryy(theta, q0, q1) => Self::Ryy(-theta, q0, q1),
+            Self::Rz(theta, q) => Self::Rz(-theta, q),
+            Self::Rzz(theta, q0, q1) => Self::Rzz(-theta, q0, q1),
+            Self::S(q) => Self::SAdj(q),
+            Self::SAdj(q) => Self::S(q),
+            Self::T(q) => Self::TAdj(q),
+            Self::TAdj(q) => Self::T(q),
+            Self::U(theta, phi, lambda, q) => Self::U(-theta, -lambda, -phi, q),
+            gate => gate,
+        }
+    }
+
+    fn apply(self, backend: &mut impl Backend) {
+        match self {
+            Self::Ccx(ctl0, ctl1, q) => backend.ccx(ctl0, ctl1, q),
+            Self::Cp(theta, ctl, q) => backend.cp(theta, ctl, q),
+            Self::Cx(ctl, q) => backend.cx(ctl, q),
+            Self::Cy(ctl, q) => backend.cy(ctl, q),
+            Self::Cz(ctl, q) => backend.cz(ctl, q),
+            Self::Fsim(theta, phi, q0, q1) => backend.fsim(theta, phi, q0, q1),
+            Self::H(q) => backend.h(q),
+            Self::Rx(theta, q) => backend.rx(theta, q),
+            Self::Rxx(theta, q0, q1) => backend.rxx(theta, q0, q1),
+            Self::Ry(theta, q) => backend.ry(theta, q),
+            Self::Ryy(theta, q0, q1) => backend.ryy(theta, q0, q1),
+            Self::Rz(theta, q) => backend.rz(theta, q),
+            Self::Rzz(theta, q0, q1) => backend.rzz(theta, q0, q1),
+            Self::S(q) => backend.s(q),
+            Self::SAdj(q) => backend.sadj(q),
+            Self::Swap(q0, q1) => backend.swap(q0, q1),
+            Self::T(q) => backend.t(q),
+            Self::TAdj(q) => backend.tadj(q),
+            Self::U(theta, phi, lambda, q) => backend.u(theta, phi, lambda, q),
+            Self::X(q) => backend.x(q),
+            Self::Y(q) => backend.y(q),
+            Self::Z(q) => backend.z(q),
+        }
+    }
+}
+
+/// Backend that records the gates applied to an inner backend, so that
+/// they can be inspected or undone, for example to test adjoint correctness.
+/// Measurements, resets and arbitrary unitaries are passed through without
+/// being recorded.
+pub struct TracingSim<B> {
+    pub inner: B,
+    trace: Vec<TracedGate>,
+}
+
+impl<B: Backend> TracingSim<B> {
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            trace: Vec::new(),
+        }
+    }
+
+    /// Returns the gates applied so far, in order.
+    #[must_use]
+    pub fn trace(&self) -> &[TracedGate] {
+        &self.trace
+    }
+
+    /// Applies the inverse of the most recently applied gate to the inner
+    /// backend and removes it from the trace. Does nothing if the trace is empty.
+    pub fn undo_last(&mut self) {
+        if let Some(gate) = self.trace.pop() {
+            gate.inverse().apply(&mut self.inner);
+        }
+    }
+
+    fn record(&mut self, gate: TracedGate) {
+        gate.apply(&mut self.inner);
+        self.trace.push(gate);
+    }
+}
+
+impl<B: Backend> Backend for TracingSim<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.record(TracedGate::Ccx(ctl0, ctl1, q));
+    }
+
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.record(TracedGate::Cp(theta, ctl, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.record(TracedGate::Cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.record(TracedGate::Cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.record(TracedGate::Cz(ctl, q));
+    }
+
+    fn fsim(&mut self, theta: f64, phi: f64, q0: usize, q1: usize) {
+        self.record(TracedGate::Fsim(theta, phi, q0, q1));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.record(TracedGate::H(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.inner.mresetz(q)
+    }
+
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        self.inner.measure_all(qs)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.inner.reset(q);
+    }
+
+    fn reset_all(&mut self, qs: &[usize]) {
+        self.inner.reset_all(qs);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.record(TracedGate::Rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.record(TracedGate::Rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.record(TracedGate::Ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.record(TracedGate::Ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.record(TracedGate::Rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.record(TracedGate::Rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.record(TracedGate::SAdj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.record(TracedGate::S(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.record(TracedGate::Swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.record(TracedGate::TAdj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.record(TracedGate::T(q));
+    }
+
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        self.record(TracedGate::U(theta, phi, lambda, q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.record(TracedGate::X(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.record(TracedGate::Y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.record(TracedGate::Z(q));
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.inner.apply_controlled_unitary(ctls, matrix, qubits)
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) -> bool {
+        self.inner.qubit_release(q)
+    }
+
+    fn qubit_swap_id(&mut self, q0: usize, q1: usize) {
+        self.inner.qubit_swap_id(q0, q1);
+    }
+
+    fn reset_all_qubits(&mut self) {
+        self.inner.reset_all_qubits();
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn qubit_count(&self) -> usize {
+        self.inner.qubit_count()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn set_rotation_error(&mut self, epsilon: f64) {
+        self.inner.set_rotation_error(epsilon);
+    }
+
+    fn apply_depolarizing(&mut self, p: f64, q: usize) {
+        self.inner.apply_depolarizing(p, q);
+    }
+
+    fn barrier(&mut self, qs: &[usize]) {
+        self.inner.barrier(qs);
+    }
+
+    fn begin_conditional(&mut self, condition: bool) {
+        self.inner.begin_conditional(condition);
+    }
+
+    fn end_conditional(&mut self) {
+        self.inner.end_conditional();
+    }
+
+    fn begin_negative_controls(&mut self, ctls: &[usize]) {
+        self.inner.begin_negative_controls(ctls);
+    }
+
+    fn end_negative_controls(&mut self) {
+        self.inner.end_negative_controls();
+    }
+}
+
+/// The payload of the panic raised by a [`BoundedSim`] once its limit is exceeded.
+/// Hosts can catch it with [`std::panic::catch_unwind`] to abort a runaway program
+/// without tearing down the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperationLimitExceeded {
+    pub limit: usize,
+}
+
+/// Backend that forwards to an inner backend, counting the gates, measurements and
+/// resets applied, and aborts by panicking with [`OperationLimitExceeded`] as soon as
+/// more than `limit` have been applied. Qubit management and state queries are not counted.
+pub struct BoundedSim<B> {
+    pub inner: B,
+    limit: usize,
+    count: usize,
+}
+
+impl<B: Backend> BoundedSim<B> {
+    pub fn new(inner: B, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            count: 0,
+        }
+    }
+
+    /// Returns the number of operations applied so far.
+    #[must_use]
+    pub fn operation_count(&self) -> usize {
+        self.count
+    }
+
+    fn count(&mut self, operations: usize) {
+        self.count += operations;
+        if self.count > self.limit {
+            std::panic::panic_any(OperationLimitExceeded { limit: self.limit });
+        }
+    }
+}
+
+impl<B: Backend> Backend for BoundedSim<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.count(1);
+        self.inner.ccx(ctl0, ctl1, q);
+    }
+
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.count(1);
+        self.inner.cp(theta, ctl, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.count(1);
+        self.inner.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.count(1);
+        self.inner.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.count(1);
+        self.inner.cz(ctl, q);
+    }
+
+    fn fsim(&mut self, theta: f64, phi: f64, q0: usize, q1: usize) {
+        self.count(1);
+        self.inner.fsim(theta, phi, q0, q1);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.count(1);
+        self.inner.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.count(1);
+        self.inner.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.count(1);
+        self.inner.mresetz(q)
+    }
+
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        self.count(qs.len());
+        self.inner.measure_all(qs)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.count(1);
+        self.inner.reset(q);
+    }
+
+    fn reset_all(&mut self, qs: &[usize]) {
+        self.count(qs.len());
+        self.inner.reset_all(qs);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.count(1);
+        self.inner.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.count(1);
+        self.inner.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.count(1);
+        self.inner.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.count(1);
+        self.inner.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.count(1);
+        self.inner.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.count(1);
+        self.inner.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.count(1);
+        self.inner.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.count(1);
+        self.inner.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.count(1);
+        self.inner.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.count(1);
+        self.inner.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.count(1);
+        self.inner.t(q);
+    }
+
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        self.count(1);
+        self.inner.u(theta, phi, lambda, q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.count(1);
+        self.inner.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.count(1);
+        self.inner.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.count(1);
+        self.inner.z(q);
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.count(1);
+        self.inner.apply_unitary(matrix, qubits)
+    }
+
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.count(1);
+        self.inner.apply_controlled_unitary(ctls, matrix, qubits)
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.inner.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) -> bool {
+        self.inner.qubit_release(q)
+    }
+
+    fn qubit_swap_id(&mut self, q0: usize, q1: usize) {
+        self.inner.qubit_swap_id(q0, q1);
+    }
+
+    fn reset_all_qubits(&mut self) {
+        self.inner.reset_all_qubits();
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.inner.capture_quantum_state()
+    }
+
+    fn qubit_count(&self) -> usize {
+        self.inner.qubit_count()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.inner.qubit_is_zero(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.inner.custom_intrinsic(name, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.inner.set_seed(seed);
+    }
+
+    fn set_rotation_error(&mut self, epsilon: f64) {
+        self.inner.set_rotation_error(epsilon);
+    }
+
+    fn apply_depolarizing(&mut self, p: f64, q: usize) {
+        self.inner.apply_depolarizing(p, q);
+    }
+
+    fn barrier(&mut self, qs: &[usize]) {
+        self.inner.barrier(qs);
+    }
+
+    fn begin_conditional(&mut self, condition: bool) {
+        self.inner.begin_conditional(condition);
+    }
+
+    fn end_conditional(&mut self) {
+        self.inner.end_conditional();
+    }
+
+    fn begin_negative_controls(&mut self, ctls: &[usize]) {
+        self.inner.begin_negative_controls(ctls);
+    }
+
+    fn end_negative_controls(&mut self) {
+        self.inner.end_negative_controls();
+    }
+}
+
+/// Backend for classical shadow protocols, which measures qubits in random Pauli bases.
+/// Gates are applied noiselessly, and each measurement made with `measure_random_basis`
+/// is recorded along with its basis so that a shot's shadow can be read with `shadow`.
+pub struct ShadowSim {
+    pub sim: QuantumSim,
+    /// Random number generator used to choose measurement bases.
+    rng: StdRng,
+    /// Bases and outcomes of the random-basis measurements made in this shot, in order.
+    shadow: Vec<(Pauli, bool)>,
+    /// Number of qubits currently allocated.
+    qubit_count: usize,
+}
+
+impl Default for ShadowSim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShadowSim {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            sim: QuantumSim::new(None),
+            rng: StdRng::from_entropy(),
+            shadow: Vec::new(),
+            qubit_count: 0,
+        }
+    }
+
+    /// Measures `q` in the X, Y or Z basis, chosen uniformly at random, by applying
+    /// the Clifford that maps that basis to Z before measuring. Returns the basis
+    /// and the outcome, which are also recorded in the shadow.
+    pub fn measure_random_basis(&mut self, q: usize) -> (Pauli, bool) {
+        let basis = match self.rng.gen_range(0..3) {
+            0 => Pauli::X,
+            1 => Pauli::Y,
+            _ => Pauli::Z,
+        };
+        match basis {
+            Pauli::X => self.sim.h(q),
+            Pauli::Y => {
+                self.sim.sadj(q);
+                self.sim.h(q);
+            }
+            _ => {}
+        }
+        let outcome = self.sim.measure(q);
+        self.shadow.push((basis, outcome));
+        (basis, outcome)
+    }
+
+    /// Returns the bases and outcomes of the random-basis measurements made since
+    /// the qubits were last reset with `reset_all_qubits`, in order.
+    #[must_use]
+    pub fn shadow(&self) -> &[(Pauli, bool)] {
+        &self.shadow
+    }
+}
+
+impl Backend for ShadowSim {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.sim.mcx(&[ctl0, ctl1], q);
+    }
+
+    fn cp(&mut self, theta: f64, ctl: usize, q: usize) {
+        self.sim.mcphase(&[ctl], Complex::from_polar(1.0, theta), q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.sim.mcx(&[ctl], q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.sim.mcy(&[ctl], q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.sim.mcz(&[ctl], q);
+    }
+
+    fn fsim(&mut self, theta: f64, phi: f64, q0: usize, q1: usize) {
+        // The excitation swap is exp(-iθ(XX + YY)/2), applied as Rxx(θ) followed by
+        // Ryy(θ) since XX and YY commute.
+        self.rxx(theta, q0, q1);
+        self.ryy(theta, q0, q1);
+        self.cp(-phi, q0, q1);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.sim.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.sim.measure(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let res = self.sim.measure(q);
+        if res {
+            self.sim.x(q);
+        }
+        res
+    }
+
+    fn measure_all(&mut self, qs: &[usize]) -> Vec<Self::ResultType> {
+        // Without noise, measuring the qubits one at a time samples the same joint
+        // distribution as measuring them together.
+        qs.iter().map(|&q| self.sim.measure(q)).collect()
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.mresetz(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.sim.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.sim.h(q0);
+        self.sim.h(q1);
+        self.sim.mcx(&[q1], q0);
+        self.sim.rz(theta, q0);
+        self.sim.mcx(&[q1], q0);
+        self.sim.h(q1);
+        self.sim.h(q0);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.sim.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.sim.h(q0);
+        self.sim.s(q0);
+        self.sim.h(q0);
+        self.sim.h(q1);
+        self.sim.s(q1);
+        self.sim.h(q1);
+        self.sim.mcx(&[q1], q0);
+        self.sim.rz(theta, q0);
+        self.sim.mcx(&[q1], q0);
+        self.sim.h(q1);
+        self.sim.sadj(q1);
+        self.sim.h(q1);
+        self.sim.h(q0);
+        self.sim.sadj(q0);
+        self.sim.h(q0);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.sim.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.sim.mcx(&[q1], q0);
+        self.sim.rz(theta, q0);
+        self.sim.mcx(&[q1], q0);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.sim.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.sim.s(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.sim.swap_qubit_ids(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.sim.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.sim.t(q);
+    }
+
+    fn u(&mut self, theta: f64, phi: f64, lambda: f64, q: usize) {
+        let (sin, cos) = (theta / 2.0).sin_cos();
+        let matrix = Array2::from_shape_vec(
+            (2, 2),
+            vec![
+                Complex::new(cos, 0.0),
+                -Complex::from_polar(sin, lambda),
+                Complex::from_polar(sin, phi),
+                Complex::from_polar(cos, phi + lambda),
+            ],
+        )
+        .expect("matrix should be 2x2");
+        self.sim.apply(&matrix, &[q], None);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.sim.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.sim.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.sim.z(q);
+    }
+
+    fn apply_unitary(
+        &mut self,
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        self.apply_controlled_unitary(&[], matrix, qubits)
+    }
+
+    fn apply_controlled_unitary(
+        &mut self,
+        ctls: &[usize],
+        matrix: &[Vec<Complex<f64>>],
+        qubits: &[usize],
+    ) -> Result<(), String> {
+        validate_unitary_dims(matrix, qubits)?;
+        let matrix = Array2::from_shape_fn((matrix.len(), matrix.len()), |(i, j)| matrix[i][j]);
+        self.sim.apply(&matrix, qubits, Some(ctls));
+        Ok(())
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.qubit_count += 1;
+        self.sim.allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) -> bool {
+        self.qubit_count -= 1;
+        let was_zero = self.sim.qubit_is_zero(q);
+        self.sim.release(q);
+        was_zero
+    }
+
+    fn qubit_swap_id(&mut self, q0: usize, q1: usize) {
+        self.sim.swap_qubit_ids(q0, q1);
+    }
+
+    fn reset_all_qubits(&mut self) {
+        // Replacing the simulator is cheaper than resetting each qubit, which matters
+        // when a shadow protocol runs many short shots. The new simulator is seeded from
+        // the basis generator, so seeded runs stay reproducible without every shot
+        // repeating the outcomes of the first.
+        self.sim = QuantumSim::new(None);
+        self.sim.set_rng_seed(self.rng.next_u64());
+        self.shadow.clear();
+        self.qubit_count = 0;
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        let (state, count) = self.sim.get_state();
+        let mut new_state = state
+            .into_iter()
+            .map(|(idx, val)| (reverse_index_bits(&idx, count), val))
+            .collect::<Vec<_>>();
+        new_state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        (new_state, count)
+    }
+
+    fn qubit_count(&self) -> usize {
+        self.qubit_count
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.sim.qubit_is_zero(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        match name {
+            "GlobalPhase" => {
+                // As in `SparseSim`, the phase is applied with an Rz on a fresh qubit.
+                let [ctls_val, theta] = &*arg.unwrap_tuple() else {
+                    panic!("tuple arity for GlobalPhase intrinsic should be 2");
+                };
+                let ctls = ctls_val
+                    .clone()
+                    .unwrap_array()
+                    .iter()
+                    .map(|q| q.clone().unwrap_qubit().deref().0)
+                    .collect::<Vec<_>>();
+                let theta = theta.clone().unwrap_double();
+                let q = self.sim.allocate();
+                self.sim.mcrz(&ctls, -2.0 * theta, q);
+                self.sim.release(q);
+                Some(Ok(Value::unit()))
+            }
+            _ => None,
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        if let Some(seed) = seed {
+            self.sim.set_rng_seed(seed);
+            self.rng = StdRng::seed_from_u64(seed);
+        } else {
+            self.sim.set_rng_seed(rand::thread_rng().next_u64());
+            self.rng = StdRng::from_entropy();
+        }
+    }
 }
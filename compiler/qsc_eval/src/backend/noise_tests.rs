@@ -3,12 +3,13 @@
 
 use crate::{
     backend::{Backend, SparseSim},
-    noise::PauliNoise,
+    noise::{PauliChannel, PauliNoise},
     state::{fmt_complex, format_state_id},
 };
 use expect_test::{expect, Expect};
 use num_bigint::BigUint;
 use num_complex::Complex;
+use qsc_fir::fir::Pauli;
 use std::fmt::Write;
 
 #[test]
@@ -93,6 +94,27 @@ fn noiseless_gate() {
     );
 }
 
+#[test]
+fn two_qubit_gate_noise_is_separate_from_single_qubit_noise() {
+    let noise = PauliNoise::from_probabilities(0.0, 0.0, 0.0)
+        .expect("noiseless Pauli noise should be constructable.");
+    let two_qubit_noise = PauliNoise::from_probabilities(1.0, 0.0, 0.0)
+        .expect("bit flip noise with probability 100% should be constructable.");
+    let mut sim = SparseSim::new_with_gate_noise(&noise, &two_qubit_noise);
+    assert!(!sim.is_noiseless(), "Expected noisy simulator.");
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+
+    sim.x(q0);
+    assert!(sim.m(q0), "Expected True without single-qubit noise.");
+    assert!(!sim.m(q1), "Expected False without single-qubit noise.");
+
+    // The CNOT flips q1, then noise flips both qubits.
+    sim.cx(q0, q1);
+    assert!(!sim.m(q0), "Expected False after two-qubit bit flip noise.");
+    assert!(!sim.m(q1), "Expected False after two-qubit bit flip noise.");
+}
+
 #[test]
 fn bitflip_measurement() {
     let noise = PauliNoise::from_probabilities(1.0, 0.0, 0.0)
@@ -134,6 +156,28 @@ fn noisy_measurement() {
     );
 }
 
+#[test]
+fn noise_configured_after_seeding_is_reproducible() {
+    let noise = PauliNoise::from_probabilities(0.5, 0.0, 0.0)
+        .expect("bit flip noise with probability 50% should be constructable.");
+    let run = || {
+        let mut sim = SparseSim::new();
+        sim.set_seed(Some(42));
+        sim.set_noise(&noise, &noise);
+        let q = sim.qubit_allocate();
+        for _ in 0..100 {
+            sim.m(q);
+        }
+        sim.measurement_log().to_vec()
+    };
+    let log = run();
+    assert!(
+        log.contains(&true) && log.contains(&false),
+        "Expected noisy measurements."
+    );
+    assert_eq!(log, run(), "Expected seeded noise to be reproducible.");
+}
+
 pub fn state_to_string(input: &(Vec<(BigUint, Complex<f64>)>, usize)) -> String {
     input
         .0
@@ -202,3 +246,60 @@ fn noisy_via_z() {
     sim.z(q); // Followed by Z. So, no op.
     check_state(&mut sim, &expect!["|0⟩: 0.0000+1.0000𝑖 "]);
 }
+
+#[test]
+fn pauli_channel() {
+    let _ = PauliChannel::from_probabilities(2, &[(vec![Pauli::X, Pauli::X], 0.5)])
+        .expect("two-qubit channel with probability 0.5 should be constructable.");
+    let _ = PauliChannel::from_probabilities(2, &[(vec![Pauli::X], 0.5)])
+        .expect_err("channel with a one-qubit string on two qubits should result in error.");
+    let _ = PauliChannel::from_probabilities(1, &[(vec![Pauli::X], -0.1)])
+        .expect_err("channel with a negative probability should result in error.");
+    let _ = PauliChannel::from_probabilities(1, &[(vec![Pauli::X], 0.6), (vec![Pauli::Z], 0.6)])
+        .expect_err("channel with probabilities over 1.0 should result in error.");
+
+    let noise = PauliNoise::from_probabilities(0.1, 0.0, 0.0)
+        .expect("bit flip noise with probability 0.1 should be constructable.");
+    let channel = PauliChannel::tensor([noise.into(), PauliChannel::default(), noise.into()]);
+    assert_eq!(
+        channel.num_qubits(),
+        2,
+        "Expected one qubit per noise factor."
+    );
+}
+
+#[test]
+fn correlated_pauli_channel() {
+    // XX and YI flip the measured qubits together and alone, while ZZ is invisible to them.
+    let channel = PauliChannel::from_probabilities(
+        2,
+        &[
+            (vec![Pauli::X, Pauli::X], 0.3),
+            (vec![Pauli::Y, Pauli::I], 0.1),
+            (vec![Pauli::Z, Pauli::Z], 0.2),
+        ],
+    )
+    .expect("correlated two-qubit channel should be constructable.");
+    let mut sim = SparseSim::new();
+    let (q0, q1) = (sim.qubit_allocate(), sim.qubit_allocate());
+    let mut counts = [0; 4];
+    for _ in 0..2000 {
+        sim.apply_pauli_channel(&channel, &[q0, q1]);
+        let outcome = usize::from(sim.mresetz(q0)) << 1 | usize::from(sim.mresetz(q1));
+        counts[outcome] += 1;
+    }
+    // Outcomes are q0 q1: 00 with 0.6, 01 never, 10 with 0.1 and 11 with 0.3.
+    assert!(
+        (1100..1300).contains(&counts[0b00]),
+        "Expected about 60% no flips, got {counts:?}."
+    );
+    assert_eq!(counts[0b01], 0, "Expected q1 to never flip alone.");
+    assert!(
+        (130..270).contains(&counts[0b10]),
+        "Expected about 10% single flips, got {counts:?}."
+    );
+    assert!(
+        (500..700).contains(&counts[0b11]),
+        "Expected about 30% correlated flips, got {counts:?}."
+    );
+}
@@ -0,0 +1,901 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::backend::{
+    reverse_index_bits, Backend, BoundedSim, OperationLimitExceeded, ShadowSim, SparseSim,
+    TracedGate, TracingSim,
+};
+use crate::noise::PauliNoise;
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::Pauli;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    cell::RefCell,
+    f64::consts::PI,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+};
+
+#[test]
+fn apply_pauli_matches_individual_gates() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q1);
+    sim.apply_pauli(&[Pauli::X, Pauli::Z], &[q0, q1]);
+
+    let mut expected_sim = SparseSim::new();
+    let e0 = expected_sim.qubit_allocate();
+    let e1 = expected_sim.qubit_allocate();
+    expected_sim.h(e1);
+    expected_sim.x(e0);
+    expected_sim.z(e1);
+
+    assert_eq!(
+        sim.capture_quantum_state(),
+        expected_sim.capture_quantum_state()
+    );
+}
+
+#[test]
+fn apply_pauli_skips_identity() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.apply_pauli(&[Pauli::I, Pauli::Y], &[q0, q1]);
+
+    assert!(
+        sim.qubit_is_zero(q0),
+        "identity should leave qubit unchanged"
+    );
+    assert!(!sim.qubit_is_zero(q1), "Y should flip qubit");
+}
+
+#[test]
+#[should_panic(expected = "number of Paulis should match number of qubits")]
+fn apply_pauli_with_mismatched_lengths_panics() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    sim.apply_pauli(&[Pauli::X, Pauli::Z], &[q0]);
+}
+
+fn reverse_index_bits_bitwise(idx: &BigUint, width: usize) -> BigUint {
+    let mut new_idx = BigUint::default();
+    for i in 0..(width as u64) {
+        if idx.bit((width as u64) - 1 - i) {
+            new_idx.set_bit(i, true);
+        }
+    }
+    new_idx
+}
+
+#[test]
+fn reverse_index_bits_matches_bitwise_reversal() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for width in 0..=130_usize {
+        for _ in 0..20 {
+            let digits = (0..width.div_ceil(32))
+                .map(|_| rng.gen())
+                .collect::<Vec<u32>>();
+            let idx = BigUint::new(digits) >> (width.div_ceil(32) * 32 - width);
+            assert_eq!(
+                reverse_index_bits(&idx, width),
+                reverse_index_bits_bitwise(&idx, width),
+                "mismatch for index {idx} with width {width}"
+            );
+        }
+    }
+}
+
+#[test]
+fn capture_quantum_state_orders_entries_by_reversed_index() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let _q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.h(q2);
+
+    let (state, count) = sim.capture_quantum_state();
+    assert_eq!(count, 3);
+    let indices = state.into_iter().map(|(idx, _)| idx).collect::<Vec<_>>();
+    assert_eq!(indices, vec![BigUint::from(4_u32), BigUint::from(5_u32)]);
+}
+
+#[test]
+fn fidelity_with_own_state_is_one() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    let (reference, _) = sim.capture_quantum_state();
+    let fidelity = sim.fidelity_with(&reference);
+    assert!((fidelity - 1.0).abs() < 1e-12, "fidelity was {fidelity}");
+}
+
+#[test]
+fn fidelity_with_orthogonal_state_is_zero() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let _q1 = sim.qubit_allocate();
+    sim.x(q0);
+
+    let reference = vec![(BigUint::from(0_u32), Complex::new(1.0, 0.0))];
+    let fidelity = sim.fidelity_with(&reference);
+    assert!(fidelity.abs() < 1e-12, "fidelity was {fidelity}");
+}
+
+#[test]
+fn reduced_density_matrix_of_bell_pair_qubit_is_maximally_mixed() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    for q in [0, 1] {
+        let matrix = sim
+            .reduced_density_matrix(&[q])
+            .expect("qubit should be in range");
+        let expected = [[0.5, 0.0], [0.0, 0.5]];
+        for (row, expected_row) in matrix.iter().zip(expected) {
+            for (val, expected_val) in row.iter().zip(expected_row) {
+                assert!((val - expected_val).norm() < 1e-12, "{matrix:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn reduced_density_matrix_of_repeated_qubit_is_error() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+
+    assert_eq!(
+        sim.reduced_density_matrix(&[q0, q0]),
+        Err("qubits must be distinct".to_string())
+    );
+}
+
+#[test]
+fn bell_pair_qubits_are_entangled() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    assert_eq!(sim.are_entangled(q0, q1, 1e-9), Ok(true));
+}
+
+#[test]
+fn independently_prepared_qubits_are_not_entangled() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.ry(0.7, q1);
+
+    assert_eq!(sim.are_entangled(q0, q1, 1e-9), Ok(false));
+}
+
+#[test]
+fn outcome_probability_of_bell_pair_is_split_between_correlated_outcomes() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    let probability = |sim: &mut SparseSim, bits: &[(usize, bool)]| {
+        sim.outcome_probability(bits).expect("qubits should be in range")
+    };
+    assert!((probability(&mut sim, &[(q0, false), (q1, false)]) - 0.5).abs() < 1e-9);
+    assert!(probability(&mut sim, &[(q0, false), (q1, true)]).abs() < 1e-9);
+    assert!((probability(&mut sim, &[(q1, true)]) - 0.5).abs() < 1e-9);
+    assert_eq!(
+        sim.are_entangled(q0, q1, 1e-9),
+        Ok(true),
+        "computing probabilities should not collapse the state"
+    );
+}
+
+#[test]
+fn outcome_probability_of_unallocated_qubit_is_error() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+
+    assert_eq!(
+        sim.outcome_probability(&[(q0 + 1, true)]),
+        Err("qubit 1 is out of range for 1 qubits".to_string())
+    );
+}
+
+#[test]
+fn measure_all_of_ghz_state_gives_correlated_outcomes() {
+    let mut ones = 0;
+    for _ in 0..50 {
+        let mut sim = SparseSim::new();
+        let qs = [
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+        ];
+        sim.h(qs[0]);
+        sim.cx(qs[0], qs[1]);
+        sim.cx(qs[1], qs[2]);
+
+        let results = sim.measure_all(&qs);
+        assert!(
+            results == [false; 3] || results == [true; 3],
+            "{results:?} should be correlated"
+        );
+        assert_eq!(sim.measurement_log(), results);
+        assert_eq!(sim.measure_all(&qs), results, "qubits should be collapsed");
+        if results[0] {
+            ones += 1;
+        }
+    }
+    assert!(ones > 0 && ones < 50, "both outcomes should be sampled");
+}
+
+#[test]
+fn measure_all_matches_measuring_qubits_individually_on_basis_state() {
+    let mut sim = SparseSim::new();
+    let qs = [
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+    ];
+    sim.x(qs[0]);
+    sim.x(qs[2]);
+
+    assert_eq!(sim.measure_all(&[qs[2], qs[1]]), vec![true, false]);
+    assert!(sim.m(qs[0]));
+}
+
+#[test]
+fn measure_all_reads_outcomes_by_qubit_rank_after_release() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    assert!(sim.qubit_release(q1));
+    sim.x(q2);
+
+    assert_eq!(sim.measure_all(&[q2, q0]), vec![true, false]);
+    assert_eq!(sim.measure_all(&[q0, q2]), vec![false, true]);
+}
+
+#[test]
+fn measure_all_of_some_qubits_leaves_rest_normalized() {
+    for _ in 0..20 {
+        let mut sim = SparseSim::new();
+        let q0 = sim.qubit_allocate();
+        let q1 = sim.qubit_allocate();
+        let q2 = sim.qubit_allocate();
+        assert!(sim.qubit_release(q1));
+        sim.h(q0);
+        sim.cx(q0, q2);
+        sim.h(q2);
+
+        let results = sim.measure_all(&[q0]);
+        let (state, _) = sim.capture_quantum_state();
+        let norm = state.iter().map(|(_, amp)| amp.norm_sqr()).sum::<f64>();
+        assert!((norm - 1.0).abs() < 1e-9, "state should be normalized");
+        assert_eq!(
+            state.len(),
+            2,
+            "the unmeasured qubit should stay in superposition"
+        );
+        assert_eq!(sim.m(q0), results[0]);
+    }
+}
+
+#[test]
+fn npy_bytes_contain_header_and_dense_amplitudes() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    let bytes = sim.to_npy_bytes().expect("state should fit in a dense array");
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let header_len = usize::from(u16::from_le_bytes([bytes[8], bytes[9]]));
+    let data_start = 10 + header_len;
+    assert_eq!(data_start % 64, 0, "data should be aligned");
+    let header = std::str::from_utf8(&bytes[10..data_start]).expect("header should be ASCII");
+    assert_eq!(
+        header.trim_end(),
+        "{'descr': '<c16', 'fortran_order': False, 'shape': (4,), }"
+    );
+    assert!(header.ends_with('\n'));
+    assert_eq!(bytes.len(), data_start + 4 * 16);
+
+    let amplitude = |i: usize| {
+        let at = |offset: usize| {
+            let start = data_start + i * 16 + offset;
+            f64::from_le_bytes(
+                bytes[start..start + 8]
+                    .try_into()
+                    .expect("should be 8 bytes"),
+            )
+        };
+        Complex::new(at(0), at(8))
+    };
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    assert!((amplitude(0) - Complex::new(h, 0.0)).norm() < 1e-9);
+    assert!(amplitude(1).norm() < 1e-9);
+    assert!((amplitude(3) - Complex::new(h, 0.0)).norm() < 1e-9);
+}
+
+#[test]
+fn npy_bytes_fail_for_too_many_qubits() {
+    let mut sim = SparseSim::new();
+    for _ in 0..31 {
+        sim.qubit_allocate();
+    }
+
+    let error = sim
+        .to_npy_bytes()
+        .expect_err("31 qubits should exceed the dense state limit");
+    assert_eq!(
+        error,
+        "cannot build a dense state for 31 qubits, the limit is 30"
+    );
+}
+
+#[test]
+fn qubit_is_zero_is_false_for_small_one_amplitude() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.ry(0.01, q0);
+    sim.cx(q0, q1);
+
+    assert!(!sim.qubit_is_zero(q0));
+    assert!(!sim.qubit_is_zero(q1));
+    assert!(
+        !sim.qubit_is_zero(q0),
+        "checking a qubit should not collapse the state"
+    );
+    sim.cx(q0, q1);
+    sim.ry(-0.01, q0);
+    assert!(sim.qubit_is_zero(q0));
+    assert!(sim.qubit_is_zero(q1));
+}
+
+#[test]
+fn replayed_measurements_are_forced_and_logged() {
+    let mut sim = SparseSim::new();
+    sim.set_replay(vec![true, true, false]);
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+
+    assert!(sim.m(q0));
+    assert!(
+        sim.m(q1),
+        "entangled qubit should agree with the forced outcome"
+    );
+    sim.h(q0);
+    assert!(!sim.m(q0));
+    assert_eq!(sim.measurement_log(), [true, true, false]);
+}
+
+#[test]
+fn measure_with_rng_value_flips_outcome_at_probability_threshold() {
+    // Ry(2π/3) leaves the qubit in One with probability sin²(π/3) = 0.75.
+    let prob_one = 0.75;
+    for (r, expected) in [
+        (0.0, true),
+        (prob_one - 1e-6, true),
+        (prob_one + 1e-6, false),
+    ] {
+        let mut sim = SparseSim::new();
+        let q = sim.qubit_allocate();
+        sim.ry(2.0 * PI / 3.0, q);
+        assert_eq!(sim.measure_with_rng_value(q, r), expected, "r = {r}");
+        assert_eq!(
+            sim.qubit_is_zero(q),
+            !expected,
+            "state should collapse to the outcome"
+        );
+    }
+}
+
+#[test]
+fn state_size_doubles_with_each_hadamard() {
+    let sizes = Rc::new(RefCell::new(Vec::new()));
+    let recorded = sizes.clone();
+    let mut sim = SparseSim::new();
+    sim.set_state_size_sampler(Some(Rc::new(move |size| recorded.borrow_mut().push(size))));
+    let qubits: Vec<_> = (0..4).map(|_| sim.qubit_allocate()).collect();
+    assert_eq!(sim.state_size(), 1);
+
+    for q in qubits {
+        sim.h(q);
+    }
+    assert_eq!(*sizes.borrow(), [2, 4, 8, 16]);
+}
+
+#[test]
+fn rotation_error_accumulates_over_many_rotations() {
+    let mut sim = SparseSim::new();
+    sim.set_rotation_error(0.1);
+    let q = sim.qubit_allocate();
+    for _ in 0..100 {
+        sim.rx(PI / 200.0, q);
+    }
+
+    // The ideal total rotation is PI / 2, each gate over-rotates by 10%.
+    let total = PI / 2.0 * 1.1;
+    let (state, _) = sim.capture_quantum_state();
+    let one = state
+        .iter()
+        .find(|(idx, _)| *idx == BigUint::from(1_u32))
+        .map_or(0.0, |(_, amp)| amp.norm_sqr());
+    let expected = (total / 2.0).sin().powi(2);
+    assert!((one - expected).abs() < 1e-9, "{one} != {expected}");
+}
+
+#[test]
+fn apply_controlled_unitary_matches_ch_decomposition() {
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    let hadamard = vec![
+        vec![Complex::new(h, 0.0), Complex::new(h, 0.0)],
+        vec![Complex::new(h, 0.0), Complex::new(-h, 0.0)],
+    ];
+    let mut sim = SparseSim::new();
+    let ctl = sim.qubit_allocate();
+    let q = sim.qubit_allocate();
+    sim.h(ctl);
+    sim.apply_controlled_unitary(&[ctl], &hadamard, &[q])
+        .expect("controlled unitary should apply");
+
+    let mut expected_sim = SparseSim::new();
+    let ctl = expected_sim.qubit_allocate();
+    let q = expected_sim.qubit_allocate();
+    expected_sim.h(ctl);
+    expected_sim.s(q);
+    expected_sim.h(q);
+    expected_sim.t(q);
+    expected_sim.cx(ctl, q);
+    expected_sim.tadj(q);
+    expected_sim.h(q);
+    expected_sim.sadj(q);
+
+    let (expected, _) = expected_sim.capture_quantum_state();
+    assert!((sim.fidelity_with(&expected) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn fsim_matches_its_matrix_on_basis_states() {
+    let (theta, phi) = (PI / 3.0, PI / 5.0);
+    let zero = Complex::new(0.0, 0.0);
+    let one = Complex::new(1.0, 0.0);
+    let fsim = vec![
+        vec![one, zero, zero, zero],
+        vec![
+            zero,
+            Complex::new(theta.cos(), 0.0),
+            Complex::new(0.0, -theta.sin()),
+            zero,
+        ],
+        vec![
+            zero,
+            Complex::new(0.0, -theta.sin()),
+            Complex::new(theta.cos(), 0.0),
+            zero,
+        ],
+        vec![zero, zero, zero, Complex::from_polar(1.0, -phi)],
+    ];
+
+    for (flip0, flip1) in [(false, false), (false, true), (true, false), (true, true)] {
+        let prepare = |sim: &mut SparseSim| {
+            let q0 = sim.qubit_allocate();
+            let q1 = sim.qubit_allocate();
+            if flip0 {
+                sim.x(q0);
+            }
+            if flip1 {
+                sim.x(q1);
+            }
+            (q0, q1)
+        };
+        let mut sim = SparseSim::new();
+        let (q0, q1) = prepare(&mut sim);
+        sim.fsim(theta, phi, q0, q1);
+
+        let mut expected_sim = SparseSim::new();
+        let (q0, q1) = prepare(&mut expected_sim);
+        expected_sim
+            .apply_unitary(&fsim, &[q0, q1])
+            .expect("fsim matrix should apply");
+
+        let (state, _) = sim.capture_quantum_state();
+        let (expected, _) = expected_sim.capture_quantum_state();
+        assert_eq!(state.len(), expected.len(), "{state:?} != {expected:?}");
+        for ((idx, amp), (expected_idx, expected_amp)) in state.iter().zip(&expected) {
+            assert_eq!(idx, expected_idx);
+            assert!(
+                (amp - expected_amp).norm() < 1e-9,
+                "{state:?} != {expected:?}"
+            );
+        }
+    }
+}
+
+/// Backend that only implements the gates needed by the default `cp` and `u` decompositions.
+struct DecompositionSim(SparseSim);
+
+impl Backend for DecompositionSim {
+    type ResultType = bool;
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.0.cx(ctl, q);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.0.ry(theta, q);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.0.rz(theta, q);
+    }
+}
+
+#[test]
+fn cp_applies_phase_to_one_one_state() {
+    let theta = PI / 3.0;
+    let prepare = |sim: &mut SparseSim| {
+        let ctl = sim.qubit_allocate();
+        let q = sim.qubit_allocate();
+        sim.h(ctl);
+        sim.h(q);
+        (ctl, q)
+    };
+
+    let mut sim = SparseSim::new();
+    let (ctl, q) = prepare(&mut sim);
+    sim.cp(theta, ctl, q);
+    let (state, _) = sim.capture_quantum_state();
+    let phase = Complex::from_polar(0.5, theta);
+    assert!((state[3].1 - phase).norm() < 1e-9, "{state:?}");
+    for (_, amp) in &state[..3] {
+        assert!((amp - Complex::new(0.5, 0.0)).norm() < 1e-9, "{state:?}");
+    }
+
+    let mut decomposed = DecompositionSim(SparseSim::new());
+    let (ctl, q) = prepare(&mut decomposed.0);
+    decomposed.cp(theta, ctl, q);
+    assert!((decomposed.0.fidelity_with(&state) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn u_matches_hadamard_including_global_phase() {
+    for prepare_one in [false, true] {
+        let mut sim = SparseSim::new();
+        let q = sim.qubit_allocate();
+        let mut expected = SparseSim::new();
+        let e = expected.qubit_allocate();
+        if prepare_one {
+            sim.x(q);
+            expected.x(e);
+        }
+        sim.u(PI / 2.0, 0.0, PI, q);
+        expected.h(e);
+
+        let (state, _) = sim.capture_quantum_state();
+        let (expected_state, _) = expected.capture_quantum_state();
+        for ((index, amp), (expected_index, expected_amp)) in state.iter().zip(&expected_state) {
+            assert_eq!(index, expected_index);
+            assert!((amp - expected_amp).norm() < 1e-9, "{state:?}");
+        }
+    }
+}
+
+#[test]
+fn u_decomposition_matches_exact_unitary_up_to_global_phase() {
+    let (theta, phi, lambda) = (0.3, 1.1, -0.7);
+    let mut sim = SparseSim::new();
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    sim.u(theta, phi, lambda, q);
+    let (state, _) = sim.capture_quantum_state();
+
+    let mut decomposed = DecompositionSim(SparseSim::new());
+    let q = decomposed.0.qubit_allocate();
+    decomposed.0.h(q);
+    decomposed.u(theta, phi, lambda, q);
+    assert!((decomposed.0.fidelity_with(&state) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn apply_controlled_unitary_rejects_matrix_of_wrong_size() {
+    let mut sim = SparseSim::new();
+    let ctl = sim.qubit_allocate();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let identity = vec![
+        vec![Complex::new(1.0, 0.0), Complex::new(0.0, 0.0)],
+        vec![Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)],
+    ];
+    let error = sim
+        .apply_controlled_unitary(&[ctl], &identity, &[q0, q1])
+        .expect_err("matrix of wrong size should be rejected");
+    assert_eq!(error, "matrix should be 4x4 to apply to 2 qubits");
+}
+
+#[test]
+fn apply_depolarizing_flips_measurements_at_expected_rate() {
+    let p = 0.3;
+    let trials = 10_000;
+    let mut sim = SparseSim::new();
+    sim.set_seed(Some(42));
+    let q = sim.qubit_allocate();
+    let mut flips = 0;
+    for _ in 0..trials {
+        sim.apply_depolarizing(p, q);
+        if sim.mresetz(q) {
+            flips += 1;
+        }
+    }
+    // X and Y errors flip a computational basis measurement, Z errors don't.
+    let rate = f64::from(flips) / f64::from(trials);
+    assert!((rate - 2.0 * p / 3.0).abs() < 0.02, "flip rate was {rate}");
+}
+
+#[test]
+fn reset_all_returns_each_qubit_to_zero() {
+    let mut sim = SparseSim::new();
+    let qs = [
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+        sim.qubit_allocate(),
+    ];
+    sim.x(qs[0]);
+    sim.h(qs[1]);
+    sim.cx(qs[1], qs[2]);
+
+    sim.reset_all(&qs);
+    assert!(qs.iter().all(|&q| sim.qubit_is_zero(q)));
+    assert_eq!(sim.measurement_log().len(), 3);
+}
+
+#[test]
+fn reset_all_resets_repeated_qubits_in_order() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.x(q1);
+
+    sim.reset_all(&[q0, q1, q0]);
+    assert!(sim.qubit_is_zero(q0));
+    assert!(sim.qubit_is_zero(q1));
+    assert_eq!(sim.measurement_log(), [true, true, false]);
+}
+
+#[test]
+fn reset_all_with_noise_matches_resetting_each_qubit() {
+    let noise = PauliNoise::from_probabilities(0.1, 0.05, 0.1).expect("noise should be valid");
+    let run = |reset: &dyn Fn(&mut SparseSim, &[usize])| {
+        let mut sim = SparseSim::new_with_noise(&noise);
+        sim.set_seed(Some(42));
+        let qs = [
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+            sim.qubit_allocate(),
+        ];
+        let mut outcomes = vec![];
+        for _ in 0..20 {
+            sim.h(qs[0]);
+            sim.cx(qs[0], qs[1]);
+            sim.x(qs[2]);
+            reset(&mut sim, &qs);
+            outcomes.extend(qs.iter().map(|&q| sim.m(q)));
+        }
+        (sim.measurement_log().to_vec(), outcomes)
+    };
+
+    let with_reset_all = run(&|sim, qs| sim.reset_all(qs));
+    let with_reset = run(&|sim, qs| {
+        for &q in qs {
+            sim.reset(q);
+        }
+    });
+    assert_eq!(with_reset_all, with_reset);
+}
+
+#[test]
+fn reset_all_qubits_starts_from_fresh_register() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    sim.reset_all_qubits();
+
+    let (state, count) = sim.capture_quantum_state();
+    assert_eq!(count, 0);
+    assert_eq!(state.len(), 1);
+
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    assert_eq!((q0, q1), (0, 1), "qubit ids should be reused from zero");
+    let (state, count) = sim.capture_quantum_state();
+    assert_eq!(count, 2);
+    assert_eq!(state, vec![(BigUint::from(0_u32), Complex::new(1.0, 0.0))]);
+}
+
+#[test]
+fn reset_all_qubits_keeps_seeded_measurements_reproducible() {
+    let measure_flips = |sim: &mut SparseSim| {
+        (0..16)
+            .map(|_| {
+                let q = sim.qubit_allocate();
+                sim.h(q);
+                sim.mresetz(q)
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut sim = SparseSim::new();
+    sim.set_seed(Some(7));
+    let first = measure_flips(&mut sim);
+    sim.reset_all_qubits();
+    assert_eq!(measure_flips(&mut sim), first);
+}
+
+#[test]
+fn undo_last_restores_state_before_gate() {
+    let mut expected = SparseSim::new();
+    let q = expected.qubit_allocate();
+    expected.h(q);
+    let (expected, _) = expected.capture_quantum_state();
+
+    let mut sim = TracingSim::new(SparseSim::new());
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    sim.t(q);
+    assert_eq!(sim.trace(), [TracedGate::H(q), TracedGate::T(q)]);
+    sim.undo_last();
+
+    assert_eq!(sim.trace(), [TracedGate::H(q)]);
+    let fidelity = sim.inner.fidelity_with(&expected);
+    assert!((fidelity - 1.0).abs() < 1e-9, "fidelity was {fidelity}");
+}
+
+#[test]
+fn undo_last_inverts_rotations() {
+    let mut sim = TracingSim::new(SparseSim::new());
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.ry(0.3, q0);
+    sim.rzz(1.1, q0, q1);
+    sim.undo_last();
+    sim.undo_last();
+
+    assert!(sim.trace().is_empty());
+    assert!(sim.qubit_is_zero(q0));
+    assert!(sim.qubit_is_zero(q1));
+}
+
+#[test]
+fn bounded_sim_aborts_once_operation_limit_is_exceeded() {
+    let mut sim = BoundedSim::new(SparseSim::new(), 3);
+    let q = sim.qubit_allocate();
+    sim.h(q);
+    sim.x(q);
+    sim.h(q);
+    assert_eq!(sim.operation_count(), 3);
+    assert!(
+        sim.qubit_is_zero(q),
+        "gates within the limit should be applied"
+    );
+
+    let panic =
+        catch_unwind(AssertUnwindSafe(|| sim.x(q))).expect_err("exceeding the limit should abort");
+    assert_eq!(
+        panic.downcast_ref::<OperationLimitExceeded>(),
+        Some(&OperationLimitExceeded { limit: 3 })
+    );
+}
+
+#[test]
+fn shadow_sim_records_random_basis_per_shot() {
+    let mut sim = ShadowSim::new();
+    sim.set_seed(Some(0));
+    let mut chosen = Vec::new();
+    for _ in 0..30 {
+        // Prepare |+i⟩, which is certain to measure as `Zero` in the Y basis.
+        let q = sim.qubit_allocate();
+        sim.h(q);
+        sim.s(q);
+        let (basis, outcome) = sim.measure_random_basis(q);
+        assert_eq!(sim.shadow(), &[(basis, outcome)]);
+        if basis == Pauli::Y {
+            assert!(!outcome, "|+i⟩ should measure as Zero in the Y basis");
+        }
+        chosen.push(basis);
+        sim.reset_all_qubits();
+        assert!(sim.shadow().is_empty());
+    }
+    for basis in [Pauli::X, Pauli::Y, Pauli::Z] {
+        assert!(chosen.contains(&basis), "{basis:?} should be chosen");
+    }
+}
+
+#[test]
+fn shadow_sim_seeded_runs_are_reproducible_across_resets() {
+    let run = || {
+        let mut sim = ShadowSim::new();
+        sim.set_seed(Some(7));
+        (0..20)
+            .map(|_| {
+                let q = sim.qubit_allocate();
+                sim.h(q);
+                let res = sim.m(q);
+                sim.reset_all_qubits();
+                res
+            })
+            .collect::<Vec<_>>()
+    };
+    let first = run();
+    assert_eq!(first, run());
+    assert!(
+        first.contains(&true) && first.contains(&false),
+        "shots should not repeat the outcome of the first"
+    );
+}
+
+#[test]
+fn shadow_sim_supports_two_qubit_rotations_and_unitaries() {
+    let mut sim = ShadowSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+
+    // Rxx(π) is XX up to a global phase.
+    sim.rxx(PI, q0, q1);
+    assert!(!sim.qubit_is_zero(q0));
+    assert!(!sim.qubit_is_zero(q1));
+
+    let zero = Complex::new(0.0, 0.0);
+    let one = Complex::new(1.0, 0.0);
+    sim.apply_unitary(&[vec![zero, one], vec![one, zero]], &[q0])
+        .expect("unitary should be applied");
+    assert!(sim.qubit_is_zero(q0));
+
+    // U(π, 0, π) is X.
+    sim.u(PI, 0.0, PI, q1);
+    assert!(sim.qubit_is_zero(q1));
+
+    // Fsim(π/2, φ) swaps |10⟩ to |01⟩ up to a phase.
+    sim.x(q0);
+    sim.fsim(PI / 2.0, PI / 5.0, q0, q1);
+    assert!(sim.qubit_is_zero(q0));
+    assert!(!sim.qubit_is_zero(q1));
+}
+
+#[test]
+fn qubit_count_tracks_allocation_without_capturing_state() {
+    let mut sim = SparseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    assert_eq!(sim.qubit_count(), 3);
+
+    sim.qubit_release(q2);
+    assert_eq!(sim.qubit_count(), 2);
+    assert_eq!(sim.capture_quantum_state().1, 2);
+
+    sim.reset_all_qubits();
+    assert_eq!(sim.qubit_count(), 0);
+}
@@ -24,7 +24,7 @@ pub(crate) fn call(
     name_span: PackageSpan,
     arg: Value,
     arg_span: PackageSpan,
-    sim: &mut dyn Backend<ResultType = impl Into<val::Result>>,
+    sim: &mut dyn Backend<ResultType = impl val::IntoResultValue>,
     rng: &mut StdRng,
     out: &mut dyn Receiver,
 ) -> Result<Value, Error> {
@@ -102,6 +102,57 @@ pub(crate) fn call(
                 Err(_) => Err(Error::OutputFail(name_span)),
             }
         }
+        "RecordBarrier" => {
+            let qubits = arg.unwrap_array();
+            let qubits_len = qubits.len();
+            let qubits = qubits
+                .iter()
+                .filter_map(|q| q.clone().unwrap_qubit().try_deref().map(|q| q.0))
+                .collect::<Vec<_>>();
+            if qubits.len() != qubits_len {
+                return Err(Error::QubitUsedAfterRelease(arg_span));
+            }
+            sim.barrier(&qubits);
+            Ok(Value::unit())
+        }
+        "ResetAllInternal" => {
+            let qubits = arg.unwrap_array();
+            let qubits_len = qubits.len();
+            let qubits = qubits
+                .iter()
+                .filter_map(|q| q.clone().unwrap_qubit().try_deref().map(|q| q.0))
+                .collect::<Vec<_>>();
+            if qubits.len() != qubits_len {
+                return Err(Error::QubitUsedAfterRelease(arg_span));
+            }
+            sim.reset_all(&qubits);
+            Ok(Value::unit())
+        }
+        "BeginConditional" => {
+            sim.begin_conditional(arg.unwrap_bool());
+            Ok(Value::unit())
+        }
+        "EndConditional" => {
+            sim.end_conditional();
+            Ok(Value::unit())
+        }
+        "BeginNegativeControls" => {
+            let qubits = arg.unwrap_array();
+            let qubits_len = qubits.len();
+            let qubits = qubits
+                .iter()
+                .filter_map(|q| q.clone().unwrap_qubit().try_deref().map(|q| q.0))
+                .collect::<Vec<_>>();
+            if qubits.len() != qubits_len {
+                return Err(Error::QubitUsedAfterRelease(arg_span));
+            }
+            sim.begin_negative_controls(&qubits);
+            Ok(Value::unit())
+        }
+        "EndNegativeControls" => {
+            sim.end_negative_controls();
+            Ok(Value::unit())
+        }
         "PermuteLabels" => qubit_relabel(arg, arg_span, |q0, q1| sim.qubit_swap_id(q0, q1)),
         "Message" => match out.message(&arg.unwrap_string()) {
             Ok(()) => Ok(Value::unit()),
@@ -190,23 +241,21 @@ pub(crate) fn call(
         "__quantum__qis__z__body" => one_qubit_gate(|q| sim.z(q), arg, arg_span),
         "__quantum__qis__swap__body" => two_qubit_gate(|q0, q1| sim.swap(q0, q1), arg, arg_span),
         "__quantum__qis__reset__body" => one_qubit_gate(|q| sim.reset(q), arg, arg_span),
-        "__quantum__qis__m__body" => Ok(Value::Result(
-            sim.m(arg
+        "__quantum__qis__m__body" => Ok(sim
+            .m(arg
                 .unwrap_qubit()
                 .try_deref()
                 .ok_or(Error::QubitUsedAfterRelease(arg_span))?
                 .0)
-                .into(),
-        )),
-        "__quantum__qis__mresetz__body" => Ok(Value::Result(
-            sim.mresetz(
+            .into_result_value()),
+        "__quantum__qis__mresetz__body" => Ok(sim
+            .mresetz(
                 arg.unwrap_qubit()
                     .try_deref()
                     .ok_or(Error::QubitUsedAfterRelease(arg_span))?
                     .0,
             )
-            .into(),
-        )),
+            .into_result_value()),
         _ => {
             let qubits = arg.qubits();
             let qubits_len = qubits.len();
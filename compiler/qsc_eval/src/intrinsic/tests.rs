@@ -3,12 +3,13 @@
 
 use std::f64::consts;
 
-use crate::backend::{Backend, SparseSim};
+use crate::backend::{Backend, MeasurementOutcome, SparseSim};
+use crate::noise::PauliNoise;
 use crate::tests::eval_graph;
 use crate::Env;
 use crate::{
     output::{GenericReceiver, Receiver},
-    val::Value,
+    val::{IntoResultValue, Value},
     Error,
 };
 use expect_test::{expect, Expect};
@@ -148,7 +149,62 @@ impl Backend for CustomSim {
     }
 }
 
+/// A backend whose measurements report the probability of the observed outcome.
+#[derive(Default)]
+struct OutcomeSim {
+    sim: SparseSim,
+    outcomes: Vec<MeasurementOutcome>,
+}
+
+impl OutcomeSim {
+    fn measure(&mut self, bit: bool) -> MeasurementOutcome {
+        // Only deterministic states are prepared by the tests using this backend.
+        let outcome = MeasurementOutcome { bit, prob: 1.0 };
+        self.outcomes.push(outcome);
+        outcome
+    }
+}
+
+impl Backend for OutcomeSim {
+    type ResultType = MeasurementOutcome;
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        let bit = self.sim.m(q);
+        self.measure(bit)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let bit = self.sim.mresetz(q);
+        self.measure(bit)
+    }
+
+    fn x(&mut self, q: usize) {
+        self.sim.x(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.sim.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) -> bool {
+        self.sim.qubit_release(q)
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.sim.qubit_is_zero(q)
+    }
+}
+
 fn check_intrinsic(file: &str, expr: &str, out: &mut impl Receiver) -> Result<Value, Error> {
+    check_intrinsic_with_sim(file, expr, out, &mut CustomSim::default())
+}
+
+fn check_intrinsic_with_sim(
+    file: &str,
+    expr: &str,
+    out: &mut impl Receiver,
+    sim: &mut impl Backend<ResultType = impl IntoResultValue>,
+) -> Result<Value, Error> {
     let mut core = compile::core();
     run_core_passes(&mut core);
     let fir_store = fir::PackageStore::new();
@@ -186,7 +242,7 @@ fn check_intrinsic(file: &str, expr: &str, out: &mut impl Receiver) -> Result<Va
 
     eval_graph(
         entry,
-        &mut CustomSim::default(),
+        sim,
         &fir_store,
         map_hir_package_to_fir(id),
         &mut Env::default(),
@@ -1508,6 +1564,130 @@ fn m() {
     );
 }
 
+#[test]
+fn m_with_measurement_outcome_backend() {
+    let mut stdout = vec![];
+    let mut out = GenericReceiver::new(&mut stdout);
+    let mut sim = OutcomeSim::default();
+    let result = check_intrinsic_with_sim(
+        "",
+        indoc! {"{
+            use q = Qubit();
+            X(q);
+            (M(q), MResetZ(q), M(q))
+        }"},
+        &mut out,
+        &mut sim,
+    )
+    .expect("evaluation should succeed");
+    expect!["(One, One, Zero)"].assert_eq(&result.to_string());
+    assert_eq!(
+        sim.outcomes,
+        vec![
+            MeasurementOutcome {
+                bit: true,
+                prob: 1.0
+            },
+            MeasurementOutcome {
+                bit: true,
+                prob: 1.0
+            },
+            MeasurementOutcome {
+                bit: false,
+                prob: 1.0
+            },
+        ]
+    );
+}
+
+#[test]
+fn reset_all_uses_backend_reset_all_with_repeated_qubits() {
+    let mut stdout = vec![];
+    let mut out = GenericReceiver::new(&mut stdout);
+    let mut sim = SparseSim::new();
+    let result = check_intrinsic_with_sim(
+        "",
+        indoc! {"{
+            use qs = Qubit[2];
+            X(qs[0]);
+            X(qs[1]);
+            ResetAll([qs[0], qs[1], qs[0]]);
+            (M(qs[0]), M(qs[1]))
+        }"},
+        &mut out,
+        &mut sim,
+    )
+    .expect("evaluation should succeed");
+    expect!["(Zero, Zero)"].assert_eq(&result.to_string());
+    // The reset measures each listed qubit in order, then each qubit is measured again.
+    assert_eq!(sim.measurement_log(), [true, true, false, false, false]);
+}
+
+#[test]
+fn reset_all_with_seeded_noise_matches_resetting_each_qubit() {
+    let noise = PauliNoise::from_probabilities(0.1, 0.05, 0.1).expect("noise should be valid");
+    let run = |reset: &str| {
+        let mut stdout = vec![];
+        let mut out = GenericReceiver::new(&mut stdout);
+        let mut sim = SparseSim::new_with_noise(&noise);
+        sim.set_seed(Some(42));
+        let result = check_intrinsic_with_sim(
+            "",
+            &format!(
+                "{{
+                    use qs = Qubit[3];
+                    mutable results = [];
+                    for _ in 1..20 {{
+                        H(qs[0]);
+                        CNOT(qs[0], qs[1]);
+                        X(qs[2]);
+                        {reset}
+                        set results += Std.Measurement.MeasureEachZ(qs);
+                    }}
+                    results
+                }}"
+            ),
+            &mut out,
+            &mut sim,
+        )
+        .expect("evaluation should succeed");
+        (result.to_string(), sim.measurement_log().to_vec())
+    };
+
+    assert_eq!(run("ResetAll(qs);"), run("for q in qs { Reset(q); }"));
+}
+
+#[test]
+fn apply_if_one_applies_op_when_result_is_one() {
+    check_intrinsic_value(
+        "",
+        indoc! {"{
+            use (source, target) = (Qubit(), Qubit());
+            X(source);
+            Std.Canon.ApplyIfOne(M(source), (X, target));
+            let result = M(target);
+            ResetAll([source, target]);
+            result
+        }"},
+        &Value::RESULT_ONE,
+    );
+}
+
+#[test]
+fn apply_if_one_skips_op_when_result_is_zero() {
+    check_intrinsic_value(
+        "",
+        indoc! {"{
+            use (source, target) = (Qubit(), Qubit());
+            Std.Canon.ApplyIfOne(M(source), (X, target));
+            let result = M(target);
+            ResetAll([source, target]);
+            result
+        }"},
+        &Value::RESULT_ZERO,
+    );
+}
+
 #[test]
 fn m_qubit_already_released_fails() {
     check_intrinsic_result(
@@ -58,6 +58,10 @@ use val::{update_functor_app, Qubit};
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum Error {
+    #[error("evaluation aborted")]
+    #[diagnostic(code("Qsc.Eval.Aborted"))]
+    Aborted(#[label("aborted while evaluating this loop")] PackageSpan),
+
     #[error("array too large")]
     #[diagnostic(code("Qsc.Eval.ArrayTooLarge"))]
     ArrayTooLarge(#[label("this array has too many items")] PackageSpan),
@@ -186,7 +190,8 @@ impl Error {
     #[must_use]
     pub fn span(&self) -> &PackageSpan {
         match self {
-            Error::ArrayTooLarge(span)
+            Error::Aborted(span)
+            | Error::ArrayTooLarge(span)
             | Error::CallableAlreadyCounted(span)
             | Error::CallableNotCounted(span)
             | Error::DivZero(span)
@@ -271,7 +276,7 @@ pub fn eval(
     exec_graph: ExecGraph,
     globals: &impl PackageStoreLookup,
     env: &mut Env,
-    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
     receiver: &mut impl Receiver,
 ) -> Result<Value, (Error, Vec<Frame>)> {
     let mut state = State::new(package, exec_graph, seed);
@@ -293,7 +298,7 @@ pub fn invoke(
     seed: Option<u64>,
     globals: &impl PackageStoreLookup,
     env: &mut Env,
-    sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+    sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
     receiver: &mut impl Receiver,
     callable: Value,
     args: Value,
@@ -544,6 +549,9 @@ struct Scope {
 
 type CallableCountKey = (StoreItemId, bool, bool);
 
+/// The number of loop iterations evaluated between calls to [`Receiver::progress`].
+const PROGRESS_INTERVAL: u64 = 10_000;
+
 pub struct State {
     exec_graph_stack: Vec<ExecGraph>,
     idx: u32,
@@ -557,6 +565,7 @@ pub struct State {
     rng: RefCell<StdRng>,
     call_counts: FxHashMap<CallableCountKey, i64>,
     qubit_counter: Option<QubitCounter>,
+    loop_iterations: u64,
 }
 
 impl State {
@@ -579,6 +588,7 @@ impl State {
             rng,
             call_counts: FxHashMap::default(),
             qubit_counter: None,
+            loop_iterations: 0,
         }
     }
 
@@ -660,7 +670,7 @@ impl State {
         &mut self,
         globals: &impl PackageStoreLookup,
         env: &mut Env,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         out: &mut impl Receiver,
         breakpoints: &[StmtId],
         step: StepAction,
@@ -692,8 +702,12 @@ impl State {
                         None => continue,
                     }
                 }
-                Some(ExecGraphNode::Jump(idx)) => {
-                    self.idx = *idx;
+                Some(&ExecGraphNode::Jump(idx)) => {
+                    // Jumping backwards starts another iteration of a loop.
+                    if idx < self.idx {
+                        self.count_loop_iteration(out)?;
+                    }
+                    self.idx = idx;
                     continue;
                 }
                 Some(ExecGraphNode::JumpIf(idx)) => {
@@ -765,6 +779,19 @@ impl State {
         Ok(StepResult::Return(self.get_result()))
     }
 
+    fn count_loop_iteration(&mut self, out: &mut impl Receiver) -> Result<(), (Error, Vec<Frame>)> {
+        self.loop_iterations += 1;
+        if self.loop_iterations % PROGRESS_INTERVAL == 0
+            && out.progress(self.loop_iterations).is_err()
+        {
+            return Err((
+                Error::Aborted(self.to_global_span(self.current_span)),
+                self.get_stack_frames(),
+            ));
+        }
+        Ok(())
+    }
+
     fn check_for_break(
         &self,
         breakpoints: &[StmtId],
@@ -808,7 +835,7 @@ impl State {
     fn eval_expr(
         &mut self,
         env: &mut Env,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         globals: &impl PackageStoreLookup,
         out: &mut impl Receiver,
         expr: ExprId,
@@ -1047,7 +1074,7 @@ impl State {
     fn eval_call(
         &mut self,
         env: &mut Env,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         globals: &impl PackageStoreLookup,
         callable_span: Span,
         arg_span: Span,
@@ -1145,7 +1172,7 @@ impl State {
         callee_id: StoreItemId,
         functor: FunctorApp,
         callee: &fir::CallableDecl,
-        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        sim: &mut impl Backend<ResultType = impl val::IntoResultValue>,
         callee_span: PackageSpan,
         arg: Value,
         arg_span: PackageSpan,
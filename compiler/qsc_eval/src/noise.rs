@@ -1,6 +1,9 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use qsc_fir::fir::Pauli;
+use rand::Rng;
+
 #[derive(Copy, Clone, Debug)]
 pub struct PauliNoise {
     /// Pauli noise distribution for sampling.
@@ -38,3 +41,101 @@ impl PauliNoise {
         self.distribution[2] <= f64::EPSILON
     }
 }
+
+/// A Pauli error channel that applies each of a set of Pauli strings with a given
+/// probability, and the identity otherwise. Channels over many qubits can be built as
+/// the tensor product of smaller channels, which are sampled independently, instead of
+/// listing probabilities for the whole Pauli group.
+#[derive(Clone, Debug, Default)]
+pub struct PauliChannel {
+    /// The independently sampled factors of the channel, acting on consecutive qubits.
+    factors: Vec<PauliChannelFactor>,
+}
+
+#[derive(Clone, Debug)]
+struct PauliChannelFactor {
+    num_qubits: usize,
+    /// Pauli strings with the cumulative probability of all strings up to and
+    /// including them. The identity is applied when p is from [last probability, 1.0).
+    distribution: Vec<(Vec<Pauli>, f64)>,
+}
+
+impl PauliChannel {
+    /// Creates a channel over `num_qubits` qubits that applies each Pauli string in
+    /// `errors` with the given probability.
+    pub fn from_probabilities(
+        num_qubits: usize,
+        errors: &[(Vec<Pauli>, f64)],
+    ) -> Result<Self, String> {
+        let mut total = 0.0;
+        let mut distribution = Vec::with_capacity(errors.len());
+        for (paulis, p) in errors {
+            if paulis.len() != num_qubits {
+                return Err("Incorrect Pauli channel string length.".to_string());
+            }
+            if *p < 0.0 {
+                return Err("Incorrect Pauli channel probabilities.".to_string());
+            }
+            total += p;
+            distribution.push((paulis.clone(), total));
+        }
+        if total > 1.0 {
+            return Err("Incorrect Pauli channel probabilities.".to_string());
+        }
+        Ok(Self {
+            factors: vec![PauliChannelFactor {
+                num_qubits,
+                distribution,
+            }],
+        })
+    }
+
+    /// Creates the tensor product of `channels`, which act on consecutive qubits in order.
+    #[must_use]
+    pub fn tensor(channels: impl IntoIterator<Item = PauliChannel>) -> Self {
+        Self {
+            factors: channels
+                .into_iter()
+                .flat_map(|channel| channel.factors)
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn num_qubits(&self) -> usize {
+        self.factors.iter().map(|factor| factor.num_qubits).sum()
+    }
+
+    /// Samples a Pauli string from the channel, with one Pauli per qubit.
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<Pauli> {
+        let mut paulis = Vec::with_capacity(self.num_qubits());
+        for factor in &self.factors {
+            let p = rng.gen_range(0.0..1.0);
+            match factor
+                .distribution
+                .iter()
+                .find(|(_, cumulative)| p < *cumulative)
+            {
+                Some((error, _)) => paulis.extend_from_slice(error),
+                None => paulis.resize(paulis.len() + factor.num_qubits, Pauli::I),
+            }
+        }
+        paulis
+    }
+}
+
+impl From<PauliNoise> for PauliChannel {
+    fn from(noise: PauliNoise) -> Self {
+        let [px, px_py, px_py_pz] = noise.distribution;
+        Self {
+            factors: vec![PauliChannelFactor {
+                num_qubits: 1,
+                distribution: vec![
+                    (vec![Pauli::X], px),
+                    (vec![Pauli::Y], px_py),
+                    (vec![Pauli::Z], px_py_pz),
+                ],
+            }],
+        }
+    }
+}
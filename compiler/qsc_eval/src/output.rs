@@ -25,6 +25,14 @@ pub trait Receiver {
     /// # Errors
     /// This will return an error if handling the output fails.
     fn message(&mut self, msg: &str) -> Result<(), Error>;
+
+    /// Called periodically during evaluation with the number of loop iterations
+    /// evaluated so far, so long-running programs can report progress.
+    /// # Errors
+    /// Returning an error aborts evaluation.
+    fn progress(&mut self, _iterations: u64) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 pub struct GenericReceiver<'a> {
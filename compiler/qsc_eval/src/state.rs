@@ -8,6 +8,9 @@ use num_bigint::BigUint;
 use num_complex::{Complex, Complex64};
 use std::{f64::consts::FRAC_1_SQRT_2, fmt::Write};
 
+/// The default number of decimal digits used when displaying amplitudes.
+pub const DEFAULT_DISPLAY_PRECISION: usize = 4;
+
 #[must_use]
 pub fn format_state_id(id: &BigUint, qubit_count: usize) -> String {
     format!("|{}⟩", fmt_basis_state_label(id, qubit_count))
@@ -20,14 +23,20 @@ pub fn get_phase(c: &Complex<f64>) -> f64 {
 
 #[must_use]
 pub fn fmt_complex(c: &Complex<f64>) -> String {
+    fmt_complex_with_precision(c, DEFAULT_DISPLAY_PRECISION)
+}
+
+#[must_use]
+pub fn fmt_complex_with_precision(c: &Complex<f64>, precision: usize) -> String {
     // Format -0 as 0
     // Also using Unicode Minus Sign instead of ASCII Hyphen-Minus
     // and Unicode Mathematical Italic Small I instead of ASCII i.
+    let threshold = -0.5 * 10_f64.powi(-i32::try_from(precision).unwrap_or(i32::MAX));
     format!(
-        "{}{:.4}{}{:.4}𝑖",
-        if c.re <= -0.00005 { "−" } else { "" },
+        "{}{:.precision$}{}{:.precision$}𝑖",
+        if c.re <= threshold { "−" } else { "" },
         c.re.abs(),
-        if c.im <= -0.00005 { "−" } else { "+" },
+        if c.im <= threshold { "−" } else { "+" },
         c.im.abs()
     )
 }
@@ -39,6 +48,35 @@ pub fn fmt_basis_state_label(id: &BigUint, qubit_count: usize) -> String {
     format!("{:0>qubit_count$}", id.to_str_radix(2))
 }
 
+/// The largest number of qubits for which a state can be expanded into a dense array.
+pub const MAX_DENSE_STATE_QUBITS: usize = 30;
+
+/// Returns the number of amplitudes in the dense form of a state of `qubit_count` qubits.
+/// # Errors
+/// Returns an error message if the state has more than [`MAX_DENSE_STATE_QUBITS`] qubits.
+pub fn dense_state_len(qubit_count: usize) -> Result<usize, String> {
+    u32::try_from(qubit_count)
+        .ok()
+        .filter(|_| qubit_count <= MAX_DENSE_STATE_QUBITS)
+        .and_then(|count| 1_usize.checked_shl(count))
+        .ok_or_else(|| {
+            format!(
+                "cannot build a dense state for {qubit_count} qubits, the limit is {MAX_DENSE_STATE_QUBITS}"
+            )
+        })
+}
+
+/// Writes the amplitudes of a sparse state into `bytes` as a dense array of little-endian
+/// complex128 values. `bytes` should be zeroed and hold 16 bytes for every amplitude
+/// of the dense state, as given by [`dense_state_len`].
+pub fn write_dense_state_bytes(state: &[(BigUint, Complex64)], bytes: &mut [u8]) {
+    for (idx, amp) in state {
+        let start = usize::try_from(idx).expect("state index should fit in usize") * 16;
+        bytes[start..start + 8].copy_from_slice(&amp.re.to_le_bytes());
+        bytes[start + 8..start + 16].copy_from_slice(&amp.im.to_le_bytes());
+    }
+}
+
 #[must_use]
 fn is_significant(x: f64) -> bool {
     x.abs() > 1e-9
@@ -350,6 +388,16 @@ fn get_terms_for_state(state: &Vec<(BigUint, Complex64)>) -> Vec<Term> {
 /// if the formula consists of more than 16 terms or if more than two coefficients are not recognized.
 #[must_use]
 pub fn get_state_latex(state: &Vec<(BigUint, Complex64)>, qubit_count: usize) -> Option<String> {
+    get_state_latex_with_precision(state, qubit_count, DEFAULT_DISPLAY_PRECISION)
+}
+
+/// Same as [`get_state_latex`], but rounds decimal coefficients to `precision` digits.
+#[must_use]
+pub fn get_state_latex_with_precision(
+    state: &Vec<(BigUint, Complex64)>,
+    qubit_count: usize,
+    precision: usize,
+) -> Option<String> {
     if state.len() > 16 || qubit_count == 0 {
         return None;
     }
@@ -374,7 +422,7 @@ pub fn get_state_latex(state: &Vec<(BigUint, Complex64)>, qubit_count: usize) ->
     latex.push_str("$|\\psi\\rangle = ");
     let mut is_first: bool = true;
     for term in terms {
-        write_latex_for_term(&mut latex, &term, !is_first);
+        write_latex_for_term(&mut latex, &term, !is_first, precision);
         let basis_label = fmt_basis_state_label(&term.basis_vector, qubit_count);
         write!(latex, "|{basis_label}\\rangle").expect("Expected to write basis label.");
         is_first = false;
@@ -444,7 +492,7 @@ pub fn get_matrix_latex(matrix: &Vec<Vec<Complex64>>) -> String {
             }
 
             let cpl = ComplexNumber::recognize(element.re, element.im);
-            write_latex_for_complex_number(&mut latex, &cpl);
+            write_latex_for_complex_number(&mut latex, &cpl, DEFAULT_DISPLAY_PRECISION);
         }
         latex.push_str(" \\\\ ");
     }
@@ -455,10 +503,10 @@ pub fn get_matrix_latex(matrix: &Vec<Vec<Complex64>>) -> String {
 
 /// Write latex for a standalone complex number
 /// '-', 0 and 1 are always rendered, but '+' is not.
-fn write_latex_for_complex_number(latex: &mut String, number: &ComplexNumber) {
+fn write_latex_for_complex_number(latex: &mut String, number: &ComplexNumber, precision: usize) {
     match number {
         ComplexNumber::Cartesian(cartesian_form) => {
-            write_latex_for_cartesian_form(latex, cartesian_form, false, true);
+            write_latex_for_cartesian_form(latex, cartesian_form, false, true, precision);
         }
         ComplexNumber::Polar(polar_form) => {
             write_latex_for_polar_form(latex, polar_form, false);
@@ -469,10 +517,10 @@ fn write_latex_for_complex_number(latex: &mut String, number: &ComplexNumber) {
 /// Write latex for one term of quantum state.
 /// Latex is rendered for coefficient only (not for basis vector).
 /// + is rendered only if ``render_plus`` is true.
-fn write_latex_for_term(latex: &mut String, term: &Term, render_plus: bool) {
+fn write_latex_for_term(latex: &mut String, term: &Term, render_plus: bool, precision: usize) {
     match &term.coordinate {
         ComplexNumber::Cartesian(cartesian_form) => {
-            write_latex_for_cartesian_form(latex, cartesian_form, render_plus, false);
+            write_latex_for_cartesian_form(latex, cartesian_form, render_plus, false, precision);
         }
         ComplexNumber::Polar(polar_form) => {
             write_latex_for_polar_form(latex, polar_form, render_plus);
@@ -517,6 +565,7 @@ fn write_latex_for_cartesian_form(
     cartesian_form: &CartesianForm,
     render_plus: bool,
     render_one: bool,
+    precision: usize,
 ) {
     if cartesian_form.sign < 0 {
         latex.push('-');
@@ -528,35 +577,40 @@ fn write_latex_for_cartesian_form(
             latex.push('0');
         } else {
             // Only imaginary part present
-            write_latex_for_real_number(latex, &cartesian_form.imaginary_part, false);
+            write_latex_for_real_number(latex, &cartesian_form.imaginary_part, false, precision);
             latex.push('i');
         }
     } else if let RealNumber::Zero = cartesian_form.imaginary_part {
         // Only real part present
-        write_latex_for_real_number(latex, &cartesian_form.real_part, render_one);
+        write_latex_for_real_number(latex, &cartesian_form.real_part, render_one, precision);
     } else {
         // Both real and imaginary parts present
         latex.push_str("\\left( ");
-        write_latex_for_real_number(latex, &cartesian_form.real_part, true);
+        write_latex_for_real_number(latex, &cartesian_form.real_part, true, precision);
         latex.push(if cartesian_form.imaginary_part.sign() < 0 {
             '-'
         } else {
             '+'
         });
-        write_latex_for_real_number(latex, &cartesian_form.imaginary_part, false);
+        write_latex_for_real_number(latex, &cartesian_form.imaginary_part, false, precision);
         latex.push_str("i \\right)");
     }
 }
 
 /// Write latex for real number. Note that the sign is not rendered.
 /// 1 is only rendered if ``render_one`` is true.
-fn write_latex_for_real_number(latex: &mut String, number: &RealNumber, render_one: bool) {
+fn write_latex_for_real_number(
+    latex: &mut String,
+    number: &RealNumber,
+    render_one: bool,
+    precision: usize,
+) {
     match number {
         RealNumber::Algebraic(algebraic_number) => {
             write_latex_for_algebraic_number(latex, algebraic_number, render_one);
         }
         RealNumber::Decimal(decimal_number) => {
-            write_latex_for_decimal_number(latex, decimal_number, render_one);
+            write_latex_for_decimal_number(latex, decimal_number, render_one, precision);
         }
         RealNumber::Zero => {
             latex.push('0');
@@ -566,11 +620,17 @@ fn write_latex_for_real_number(latex: &mut String, number: &RealNumber, render_o
 
 /// Write latex for decimal number. Note that the sign is not rendered.
 /// 1 is only rendered if ``render_one`` is true.
-fn write_latex_for_decimal_number(latex: &mut String, number: &DecimalNumber, render_one: bool) {
+fn write_latex_for_decimal_number(
+    latex: &mut String,
+    number: &DecimalNumber,
+    render_one: bool,
+    precision: usize,
+) {
     if render_one || is_significant(number.value - 1.0) {
         // Using round() instead of neater string formatting({:.4})
         // because we do not want trailing zeros (we need 0.5 and not 0.5000)
-        write!(latex, "{}", (number.value * 10000.0).round() / 10000.0)
+        let scale = 10_f64.powi(i32::try_from(precision).unwrap_or(i32::MAX));
+        write!(latex, "{}", (number.value * scale).round() / scale)
             .expect("Expected to write decimal value.");
     }
 }
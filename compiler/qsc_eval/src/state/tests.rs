@@ -2,10 +2,12 @@
 // Licensed under the MIT License.
 
 use super::{
-    get_matrix_latex, get_state_latex, write_latex_for_algebraic_number,
+    fmt_complex, fmt_complex_with_precision, get_matrix_latex, get_state_latex,
+    get_state_latex_with_precision, write_latex_for_algebraic_number,
     write_latex_for_cartesian_form, write_latex_for_complex_number, write_latex_for_decimal_number,
     write_latex_for_polar_form, write_latex_for_real_number, write_latex_for_term, AlgebraicNumber,
     CartesianForm, ComplexNumber, DecimalNumber, PolarForm, RationalNumber, RealNumber, Term,
+    DEFAULT_DISPLAY_PRECISION,
 };
 use crate::state::{is_fractional_part_significant, is_significant};
 use expect_test::{expect, Expect};
@@ -479,7 +481,7 @@ fn check_get_latex_for_algebraic() {
 fn assert_latex_for_decimal(expected: &Expect, number: f64, render_one: bool) {
     let number = DecimalNumber::new(number);
     let mut latex = String::with_capacity(50);
-    write_latex_for_decimal_number(&mut latex, &number, render_one);
+    write_latex_for_decimal_number(&mut latex, &number, render_one, DEFAULT_DISPLAY_PRECISION);
     expected.assert_eq(&latex);
 }
 
@@ -495,7 +497,7 @@ fn check_get_latex_for_decimal() {
 fn assert_latex_for_real(expected: &Expect, x: f64, render_one: bool) {
     let number = RealNumber::recognize(x);
     let mut latex = String::with_capacity(50);
-    write_latex_for_real_number(&mut latex, &number, render_one);
+    write_latex_for_real_number(&mut latex, &number, render_one, DEFAULT_DISPLAY_PRECISION);
     expected.assert_eq(&latex);
 }
 
@@ -512,7 +514,13 @@ fn check_get_latex_for_real() {
 fn assert_latex_for_cartesian(expected: &Expect, re: f64, im: f64, render_plus: bool) {
     let number = CartesianForm::recognize(re, im);
     let mut latex = String::with_capacity(50);
-    write_latex_for_cartesian_form(&mut latex, &number, render_plus, false);
+    write_latex_for_cartesian_form(
+        &mut latex,
+        &number,
+        render_plus,
+        false,
+        DEFAULT_DISPLAY_PRECISION,
+    );
     expected.assert_eq(&latex);
 }
 
@@ -601,7 +609,7 @@ fn assert_latex_for_term(expected: &Expect, re: f64, im: f64, render_plus: bool)
         coordinate: ComplexNumber::recognize(re, im),
     };
     let mut latex = String::with_capacity(50);
-    write_latex_for_term(&mut latex, &t, render_plus);
+    write_latex_for_term(&mut latex, &t, render_plus, DEFAULT_DISPLAY_PRECISION);
     expected.assert_eq(&latex);
 }
 
@@ -642,7 +650,7 @@ fn check_get_latex_for_term() {
 fn assert_latex_for_complex_number(expected: &Expect, re: f64, im: f64) {
     let n: ComplexNumber = ComplexNumber::recognize(re, im);
     let mut latex = String::with_capacity(50);
-    write_latex_for_complex_number(&mut latex, &n);
+    write_latex_for_complex_number(&mut latex, &n, DEFAULT_DISPLAY_PRECISION);
     expected.assert_eq(&latex);
 }
 
@@ -818,3 +826,30 @@ fn check_get_latex_perf() {
         Instant::now().duration_since(start)
     );
 }
+
+#[test]
+fn check_fmt_complex_with_precision() {
+    let c = Complex64::new(FRAC_1_SQRT_2, -0.123_456_789);
+    assert_eq!(fmt_complex(&c), fmt_complex_with_precision(&c, 4));
+    expect!["0.71−0.12𝑖"].assert_eq(&fmt_complex_with_precision(&c, 2));
+    expect!["0.70710678−0.12345679𝑖"].assert_eq(&fmt_complex_with_precision(&c, 8));
+    // Values that round to zero at the given precision are displayed without a minus sign.
+    expect!["0.00+0.00𝑖"].assert_eq(&fmt_complex_with_precision(
+        &Complex64::new(-0.001, -0.001),
+        2,
+    ));
+    expect!["−0.001000+0.000000𝑖"]
+        .assert_eq(&fmt_complex_with_precision(&Complex64::new(-0.001, 0.0), 6));
+}
+
+#[test]
+fn check_get_latex_with_precision() {
+    let state = vec![
+        (0_u8.into(), Complex64::new(0.6, 0.0)),
+        (1_u8.into(), Complex64::new(0.123_456_789, 0.0)),
+    ];
+    expect!([r"$|\psi\rangle = \frac{3}{5}|0\rangle+0.12|1\rangle$"])
+        .assert_eq(&get_state_latex_with_precision(&state, 1, 2).expect("expected valid latex"));
+    expect!([r"$|\psi\rangle = \frac{3}{5}|0\rangle+0.1234568|1\rangle$"])
+        .assert_eq(&get_state_latex_with_precision(&state, 1, 7).expect("expected valid latex"));
+}
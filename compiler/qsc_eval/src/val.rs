@@ -88,6 +88,24 @@ impl From<usize> for Result {
     }
 }
 
+/// Converts the result type of a backend into a Q# `Result` value, so that the evaluator
+/// can support backends whose measurements return more than a bare result.
+pub trait IntoResultValue {
+    fn into_result_value(self) -> Value;
+}
+
+impl IntoResultValue for bool {
+    fn into_result_value(self) -> Value {
+        Value::Result(self.into())
+    }
+}
+
+impl IntoResultValue for usize {
+    fn into_result_value(self) -> Value {
+        Value::Result(self.into())
+    }
+}
+
 /// Tracks a reference to a qubit. This reference may be invalid if the qubit has been released.
 /// A `QubitRef` can only be created by converting a `Rc<Qubit>` to a `QubitRef`, which will maintain
 /// a weak reference to the `Rc<Qubit>`. This allows the `QubitRef` to be cloned and passed around
@@ -12,7 +12,7 @@ use crate::ty::{Arrow, FunctorSet, FunctorSetValue, GenericArg, Scheme, Ty, Type
 use indenter::{indented, Indented};
 use num_bigint::BigInt;
 use qsc_data_structures::{
-    index_map::{IndexMap, Iter},
+    index_map::{self, IndexMap},
     span::Span,
 };
 use std::{
@@ -459,9 +459,9 @@ pub trait PackageStoreLookup {
     fn get_item(&self, id: StoreItemId) -> &Item;
 }
 
-/// A FIR package store.
+/// A FIR package store. Packages are reference counted so that stores can share them.
 #[derive(Debug, Default)]
-pub struct PackageStore(IndexMap<PackageId, Package>);
+pub struct PackageStore(IndexMap<PackageId, Rc<Package>>);
 
 impl PackageStoreLookup for PackageStore {
     fn get_block(&self, id: StoreBlockId) -> &Block {
@@ -497,20 +497,34 @@ impl PackageStore {
     }
 
     /// Gets a mutable package from the store.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the package is shared with another store.
     #[must_use]
     pub fn get_mut(&mut self, id: PackageId) -> &mut Package {
-        self.0.get_mut(id).expect("store should have package")
+        Rc::get_mut(self.0.get_mut(id).expect("store should have package"))
+            .expect("package should not be shared with another store")
     }
 
     /// Inserts a package to the store.
     pub fn insert(&mut self, id: PackageId, package: Package) {
-        self.0.insert(id, package);
+        self.0.insert(id, Rc::new(package));
+    }
+
+    /// Creates a store that shares every package of this store except `excluded`,
+    /// without copying them. Shared packages can no longer be mutated.
+    #[must_use]
+    pub fn share_except(&self, excluded: PackageId) -> Self {
+        let mut store = Self(self.0.clone());
+        store.0.remove(excluded);
+        store
     }
 
     /// Gets a package store iterator.
     #[must_use]
-    pub fn iter(&self) -> Iter<PackageId, Package> {
-        self.0.iter()
+    pub fn iter(&self) -> PackageStoreIter {
+        PackageStoreIter(self.0.iter())
     }
 
     /// Creates a package store.
@@ -521,7 +535,7 @@ impl PackageStore {
 }
 
 impl<'a> IntoIterator for &'a PackageStore {
-    type IntoIter = qsc_data_structures::index_map::Iter<'a, PackageId, Package>;
+    type IntoIter = PackageStoreIter<'a>;
     type Item = (PackageId, &'a Package);
 
     fn into_iter(self) -> Self::IntoIter {
@@ -529,6 +543,17 @@ impl<'a> IntoIterator for &'a PackageStore {
     }
 }
 
+/// An iterator over the packages of a [`PackageStore`].
+pub struct PackageStoreIter<'a>(index_map::Iter<'a, PackageId, Rc<Package>>);
+
+impl<'a> Iterator for PackageStoreIter<'a> {
+    type Item = (PackageId, &'a Package);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(id, package)| (id, &**package))
+    }
+}
+
 /// A trait to find elements in a package.
 pub trait PackageLookup {
     /// Gets a block.
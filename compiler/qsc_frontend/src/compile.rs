@@ -35,7 +35,7 @@ use qsc_hir::{
     validate::Validator as HirValidator,
     visit::Visitor as _,
 };
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, rc::Rc, sync::Arc};
 use thiserror::Error;
 
 #[derive(Debug, Default)]
@@ -209,9 +209,11 @@ pub(super) enum ErrorKind {
     Lower(#[from] lower::Error),
 }
 
+/// A store of compiled packages. Packages are reference counted so that stores
+/// can share them.
 pub struct PackageStore {
-    core: global::Table,
-    units: IndexMap<PackageId, CompileUnit>,
+    core: Rc<global::Table>,
+    units: IndexMap<PackageId, Rc<CompileUnit>>,
     next_id: PackageId,
 }
 
@@ -226,9 +228,9 @@ impl PackageStore {
     pub fn new(core: CompileUnit) -> Self {
         let table = global::iter_package(Some(PackageId::CORE), &core.package).collect();
         let mut units = IndexMap::new();
-        units.insert(PackageId::CORE, core);
+        units.insert(PackageId::CORE, Rc::new(core));
         Self {
-            core: table,
+            core: Rc::new(table),
             units,
             next_id: PackageId::CORE.successor(),
         }
@@ -242,13 +244,13 @@ impl PackageStore {
     pub fn insert(&mut self, unit: CompileUnit) -> PackageId {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, unit);
+        self.units.insert(id, Rc::new(unit));
         id
     }
 
     #[must_use]
     pub fn get(&self, id: PackageId) -> Option<&CompileUnit> {
-        self.units.get(id)
+        self.units.get(id).map(|unit| &**unit)
     }
 
     #[must_use]
@@ -263,7 +265,7 @@ impl PackageStore {
     pub fn open(mut self) -> OpenPackageStore {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, CompileUnit::default());
+        self.units.insert(id, Rc::default());
 
         OpenPackageStore {
             store: self,
@@ -314,13 +316,30 @@ impl OpenPackageStore {
 
         (
             &self.store.core,
-            self.store
-                .units
-                .get_mut(id)
-                .expect("open package id should exist in store"),
+            Rc::get_mut(
+                self.store
+                    .units
+                    .get_mut(id)
+                    .expect("open package id should exist in store"),
+            )
+            .expect("open package should not be shared"),
         )
     }
 
+    /// Returns a package store that shares every package of this store except the
+    /// open one, without copying them. The returned store can be opened to start
+    /// an independent incremental compilation on top of the same packages.
+    #[must_use]
+    pub fn share_closed(&self) -> PackageStore {
+        let mut units = self.store.units.clone();
+        units.remove(self.open);
+        PackageStore {
+            core: self.store.core.clone(),
+            units,
+            next_id: self.store.next_id,
+        }
+    }
+
     /// Consumes the `OpenPackageStore` and returns a `PackageStore`
     /// along with the id of the formerly open package.
     #[must_use]
@@ -329,19 +348,19 @@ impl OpenPackageStore {
     }
 }
 
-pub struct Iter<'a>(index_map::Iter<'a, PackageId, CompileUnit>);
+pub struct Iter<'a>(index_map::Iter<'a, PackageId, Rc<CompileUnit>>);
 
 impl<'a> Iterator for Iter<'a> {
     type Item = (PackageId, &'a CompileUnit);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|(id, unit)| (id, &**unit))
     }
 }
 
 impl DoubleEndedIterator for Iter<'_> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.0.next_back()
+        self.0.next_back().map(|(id, unit)| (id, &**unit))
     }
 }
 
@@ -443,7 +462,7 @@ pub fn compile_ast(
 #[must_use]
 pub fn core() -> CompileUnit {
     let store = PackageStore {
-        core: global::Table::default(),
+        core: Rc::default(),
         units: IndexMap::new(),
         next_id: PackageId::CORE,
     };
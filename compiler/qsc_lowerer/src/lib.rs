@@ -80,6 +80,12 @@ impl Lowerer {
         self
     }
 
+    /// Returns whether debugging statements are lowered, as set with `with_debug`.
+    #[must_use]
+    pub fn debug_enabled(&self) -> bool {
+        self.enable_debug
+    }
+
     pub fn take_exec_graph(&mut self) -> Vec<ExecGraphNode> {
         self.exec_graph
             .drain(..)
@@ -1605,10 +1605,14 @@ impl<'a> PartialEvaluator<'a> {
             "DumpRegister"
             | "DumpOperation"
             | "AccountForEstimatesInternal"
+            | "AccountForRuntimeInternal"
             | "BeginRepeatEstimatesInternal"
             | "EndRepeatEstimatesInternal"
             | "ApplyIdleNoise"
-            | "GlobalPhase" => Ok(Value::unit()),
+            | "GlobalPhase"
+            | "RecordBarrier"
+            | "BeginNegativeControls"
+            | "EndNegativeControls" => Ok(Value::unit()),
             // The following intrinsic functions and operations should never make it past conditional compilation and
             // the capabilities check pass.
             "CheckZero" | "DrawRandomInt" | "DrawRandomDouble" | "DrawRandomBool" | "Length" => {
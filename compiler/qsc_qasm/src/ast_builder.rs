@@ -727,10 +727,35 @@ pub(crate) fn build_measure_call(
     )
 }
 
+/// Measures each qubit of a register, producing an array of results.
+pub(crate) fn build_measure_each_call(
+    expr: ast::Expr,
+    name_span: Span,
+    operand_span: Span,
+    stmt_span: Span,
+) -> ast::Expr {
+    build_call_with_param(
+        "MeasureEachZ",
+        &["Std", "Measurement"],
+        expr,
+        name_span,
+        operand_span,
+        stmt_span,
+    )
+}
+
 pub(crate) fn build_reset_call(expr: ast::Expr, name_span: Span, operand_span: Span) -> ast::Expr {
     build_global_call_with_one_param("Reset", expr, name_span, operand_span)
 }
 
+pub(crate) fn build_reset_all_call(
+    expr: ast::Expr,
+    name_span: Span,
+    operand_span: Span,
+) -> ast::Expr {
+    build_global_call_with_one_param("ResetAll", expr, name_span, operand_span)
+}
+
 pub(crate) fn build_global_call_with_one_param<S: AsRef<str>>(
     name: S,
     expr: ast::Expr,
@@ -1327,6 +1352,9 @@ pub(crate) fn map_qsharp_type_to_ast_ty(output_ty: &crate::types::Type) -> Ty {
         crate::types::Type::BigIntArray(dims, _) => build_array_type_name("BigInt", dims),
         crate::types::Type::IntArray(dims, _) => build_array_type_name("Int", dims),
         crate::types::Type::DoubleArray(dims) => build_array_type_name("Double", dims),
+        crate::types::Type::AngleArray(dims, _) => {
+            wrap_array_ty_by_dims(dims, build_angle_ty_ident())
+        }
         crate::types::Type::BoolArray(dims, _) => build_array_type_name("Bool", dims),
         crate::types::Type::Callable(_, _, _) => todo!(),
         crate::types::Type::Range => build_path_ident_ty("Range"),
@@ -1476,8 +1504,9 @@ pub(crate) fn build_index_expr(expr: Expr, index_expr: Expr, span: Span) -> Expr
     }
 }
 
-pub(crate) fn build_barrier_call(span: Span) -> Stmt {
-    let expr = build_call_no_params("__quantum__qis__barrier__body", &[], span, span);
+pub(crate) fn build_barrier_call(qubits: Expr, span: Span) -> Stmt {
+    let qubits_span = qubits.span;
+    let expr = build_call_with_param("barrier", &[], qubits, span, qubits_span, span);
     build_stmt_semi_from_expr(expr)
 }
 
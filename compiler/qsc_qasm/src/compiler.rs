@@ -2,6 +2,10 @@
 // Licensed under the MIT License.
 
 pub mod error;
+mod functors;
+mod recursion;
+
+pub use recursion::DEFAULT_MAX_DEF_CALL_DEPTH;
 
 use core::f64;
 use std::{path::Path, rc::Rc};
@@ -10,6 +14,7 @@ use error::CompilerErrorKind;
 use num_bigint::BigInt;
 use qsc_data_structures::span::Span;
 use qsc_frontend::{compile::SourceMap, error::WithSource};
+use rustc_hash::FxHashSet;
 
 use crate::{
     ast_builder::{
@@ -26,9 +31,10 @@ use crate::{
         build_lit_angle_expr, build_lit_bigint_expr, build_lit_bool_expr, build_lit_complex_expr,
         build_lit_double_expr, build_lit_int_expr, build_lit_result_array_expr_from_bitstring,
         build_lit_result_expr, build_managed_qubit_alloc, build_math_call_from_exprs,
-        build_math_call_no_params, build_measure_call, build_operation_with_stmts,
-        build_path_ident_expr, build_path_ident_ty, build_qasm_import_decl,
-        build_qasm_import_items, build_qasmstd_convert_call_with_two_params, build_range_expr,
+        build_math_call_no_params, build_measure_call, build_measure_each_call,
+        build_operation_with_stmts, build_path_ident_expr, build_path_ident_ty,
+        build_qasm_import_decl, build_qasm_import_items,
+        build_qasmstd_convert_call_with_two_params, build_range_expr, build_reset_all_call,
         build_reset_call, build_return_expr, build_return_unit, build_stmt_semi_from_expr,
         build_stmt_semi_from_expr_with_span, build_top_level_ns_with_items, build_tuple_expr,
         build_unary_op_expr, build_unmanaged_qubit_alloc, build_unmanaged_qubit_alloc_array,
@@ -46,12 +52,76 @@ use crate::{
         types::{promote_types, ArrayDimensions, Type},
     },
     CompilerConfig, OperationSignature, OutputSemantics, ProgramType, QasmCompileUnit,
-    QubitSemantics,
+    QubitSemantics, Seconds,
 };
 
 use crate::semantic::ast as semast;
 use qsc_ast::ast::{self as qsast, NodeId, Package};
 
+/// The loop variable used when broadcasting a gate over a register.
+const BROADCAST_QUBIT_NAME: &str = "__qubit__";
+const BROADCAST_INDEX_NAME: &str = "__index__";
+const HARDWARE_QUBITS_NAME: &str = "__hardware_qubits__";
+/// The default number of hardware qubits available when they are mapped to physical qubits.
+pub const DEFAULT_MAX_HARDWARE_QUBITS: u32 = 1024;
+/// The largest denominator tried when recognizing a float as a fraction of π.
+const MAX_PI_DENOMINATOR: u32 = 64;
+
+fn is_nonzero_float_lit(expr: &Expr) -> bool {
+    matches!(&*expr.kind, semast::ExprKind::Lit(LiteralKind::Float(value)) if *value != 0.0)
+}
+
+/// Qiskit semantics only output classical bit registers. Single bits are only
+/// output when they are explicitly declared as `output bit`.
+fn is_qiskit_output(symbol: &Symbol) -> bool {
+    match symbol.ty {
+        Type::BitArray(..) => true,
+        Type::Bit(..) => symbol.io_kind == IOKind::Output,
+        _ => false,
+    }
+}
+
+/// Returns the numerator and denominator, in lowest terms, of the fraction of π
+/// that `value` is exactly equal to, if any.
+fn as_fraction_of_pi(value: f64) -> Option<(i32, u32)> {
+    if value == 0.0 || !value.is_finite() {
+        return None;
+    }
+    (1..=MAX_PI_DENOMINATOR).find_map(|den| {
+        #[allow(clippy::cast_possible_truncation)]
+        let num = (value / std::f64::consts::PI * f64::from(den)).round() as i32;
+        // Only exact matches qualify, so that the emitted expression evaluates
+        // to the same value as the literal it replaces.
+        #[allow(clippy::float_cmp)]
+        let is_exact = f64::from(num) * std::f64::consts::PI / f64::from(den) == value;
+        (num != 0 && is_exact).then_some((num, den))
+    })
+}
+
+/// Builds `num * PI() / den`, leaving out a numerator or denominator of one.
+fn build_fraction_of_pi_expr(num: i32, den: u32, span: Span) -> qsast::Expr {
+    let mut expr = build_math_call_no_params("PI", span);
+    if num != 1 {
+        expr = build_binary_expr(
+            false,
+            qsast::BinOp::Mul,
+            build_lit_double_expr(f64::from(num), span),
+            expr,
+            span,
+        );
+    }
+    if den != 1 {
+        expr = build_binary_expr(
+            false,
+            qsast::BinOp::Div,
+            expr,
+            build_lit_double_expr(f64::from(den), span),
+            span,
+        );
+    }
+    expr
+}
+
 /// Helper to create an error expression. Used when we fail to
 /// compile an expression. It is assumed that an error was
 /// already reported.
@@ -84,8 +154,12 @@ where
         source_map: res.source_map,
         config,
         stmts: vec![],
+        num_hardware_qubits: 0,
+        in_callable_body: false,
         symbols: res.symbols,
         errors: res.errors,
+        warnings: res.warnings,
+        gates_without_functors: FxHashSet::default(),
     };
 
     compiler.compile(&program)
@@ -101,8 +175,16 @@ pub struct QasmCompiler {
     pub config: CompilerConfig,
     /// The compiled statments accumulated during compilation.
     pub stmts: Vec<qsast::Stmt>,
+    /// The number of qubits needed to cover every hardware qubit used,
+    /// when hardware qubits are mapped to physical qubits.
+    pub num_hardware_qubits: u32,
+    /// Whether the body of a def or gate is being compiled.
+    pub in_callable_body: bool,
     pub symbols: SymbolTable,
     pub errors: Vec<WithSource<crate::Error>>,
+    pub warnings: Vec<WithSource<crate::Error>>,
+    /// The gates compiled without `Adj + Ctl` functor support.
+    pub(crate) gates_without_functors: FxHashSet<SymbolId>,
 }
 
 impl QasmCompiler {
@@ -113,6 +195,24 @@ impl QasmCompiler {
         // in non-file mode we need the runtime imports in the body
         let program_ty = self.config.program_ty.clone();
 
+        // Old-style declarations are only deprecated, unless strict declarations
+        // were requested. Other warnings are never promoted to errors.
+        if self.config.strict_declarations {
+            let (old_style, warnings) =
+                std::mem::take(&mut self.warnings)
+                    .into_iter()
+                    .partition(|warning| {
+                        matches!(
+                            warning.error().0,
+                            crate::ErrorKind::Semantic(crate::semantic::Error(
+                                crate::semantic::SemanticErrorKind::OldStyleDeclaration(..)
+                            ))
+                        )
+                    });
+            self.warnings = warnings;
+            self.errors.extend::<Vec<_>>(old_style);
+        }
+
         // If we are compiling for operation/fragments, we need to
         // prepend to the list of statements.
         // In file mode we need to add top level imports which are
@@ -121,14 +221,25 @@ impl QasmCompiler {
             self.append_runtime_import_decls();
         }
 
+        self.check_def_call_depth(&program.statements);
+        self.gates_without_functors = functors::find_gates_without_functors(&program.statements);
+
+        let first_program_stmt = self.stmts.len();
         self.compile_stmts(&program.statements);
+        self.insert_hardware_qubits_alloc(first_program_stmt);
         let (package, signature) = match program_ty {
             ProgramType::File => self.build_file(),
             ProgramType::Operation => self.build_operation(),
             ProgramType::Fragments => (self.build_fragments(), None),
         };
 
-        QasmCompileUnit::new(self.source_map, self.errors, package, signature)
+        QasmCompileUnit::new(
+            self.source_map,
+            self.errors,
+            self.warnings,
+            package,
+            signature,
+        )
     }
 
     /// Build a package with namespace and an operation
@@ -245,9 +356,22 @@ impl QasmCompiler {
     ) -> (qsast::Item, OperationSignature) {
         let mut stmts = stmts;
         let is_qiskit = matches!(output_semantics, OutputSemantics::Qiskit);
+        let output_labels = match (&output, output_semantics) {
+            (None, _) | (_, OutputSemantics::ResourceEstimation) => vec![],
+            (Some(output), OutputSemantics::Qiskit) => output
+                .iter()
+                .rev()
+                .filter(|symbol| is_qiskit_output(symbol))
+                .map(|symbol| symbol.name.clone())
+                .collect(),
+            (Some(output), OutputSemantics::OpenQasm) => {
+                output.iter().map(|symbol| symbol.name.clone()).collect()
+            }
+        };
         let mut signature = OperationSignature {
             input: vec![],
             output: String::new(),
+            output_labels,
             name: name.as_ref().to_string(),
             ns: None,
         };
@@ -267,7 +391,7 @@ impl QasmCompiler {
             .iter()
             .flat_map(|s| {
                 s.iter()
-                    .map(|s| (s.name.to_string(), format!("{}", s.qsharp_ty)))
+                    .map(|s| (s.name.to_string(), s.qsharp_ty.clone()))
             })
             .collect::<Vec<_>>();
         signature.input = input_desc;
@@ -313,14 +437,17 @@ impl QasmCompiler {
                 output
                     .iter()
                     .rev()
-                    .filter(|symbol| {
-                        matches!(symbol.ty, crate::semantic::types::Type::BitArray(..))
-                    })
+                    .filter(|symbol| is_qiskit_output(symbol))
                     .map(|symbol| {
                         let ident =
                             build_path_ident_expr(symbol.name.as_str(), symbol.span, symbol.span);
-
-                        build_array_reverse_expr(ident)
+                        // A single bit is returned as a scalar `Result`, which has no
+                        // bit order to reverse.
+                        if matches!(symbol.ty, Type::BitArray(..)) {
+                            build_array_reverse_expr(ident)
+                        } else {
+                            ident
+                        }
                     })
                     .collect::<Vec<_>>()
             } else {
@@ -350,9 +477,7 @@ impl QasmCompiler {
                 output
                     .iter()
                     .rev()
-                    .filter(|symbol| {
-                        matches!(symbol.ty, crate::semantic::types::Type::BitArray(..))
-                    })
+                    .filter(|symbol| is_qiskit_output(symbol))
                     .map(|symbol| symbol.qsharp_ty.clone())
                     .collect::<Vec<_>>()
             } else {
@@ -424,7 +549,7 @@ impl QasmCompiler {
             semast::StmtKind::Assign(stmt) => self.compile_assign_stmt(stmt),
             semast::StmtKind::IndexedAssign(stmt) => self.compile_indexed_assign_stmt(stmt),
             semast::StmtKind::AssignOp(stmt) => self.compile_assign_op_stmt(stmt),
-            semast::StmtKind::Barrier(stmt) => Self::compile_barrier_stmt(stmt),
+            semast::StmtKind::Barrier(stmt) => self.compile_barrier_stmt(stmt),
             semast::StmtKind::Box(stmt) => self.compile_box_stmt(stmt),
             semast::StmtKind::Block(stmt) => self.compile_block_stmt(stmt),
             semast::StmtKind::Break(stmt) => self.compile_break_stmt(stmt),
@@ -465,6 +590,23 @@ impl QasmCompiler {
     }
 
     fn compile_alias_decl_stmt(&mut self, stmt: &semast::AliasDeclStmt) -> Option<qsast::Stmt> {
+        let symbol = self.symbols[stmt.symbol_id].clone();
+        // Quantum aliases of a single expression are bound directly. Gate
+        // operands naming the alias were already resolved during lowering.
+        if let [expr] = &*stmt.exprs {
+            if symbol.ty.is_quantum() {
+                let expr = self.compile_expr(expr);
+                return Some(build_classical_decl(
+                    &symbol.name,
+                    true,
+                    symbol.span,
+                    stmt.span,
+                    symbol.span,
+                    &symbol.qsharp_ty,
+                    expr,
+                ));
+            }
+        }
         self.push_unimplemented_error_message("alias statements", stmt.span);
         None
     }
@@ -519,8 +661,11 @@ impl QasmCompiler {
 
     fn compile_assign_op_stmt(&mut self, stmt: &semast::AssignOpStmt) -> Option<qsast::Stmt> {
         // If the lhs is of type Angle, we call compile_assign_stmt with the rhs = lhs + rhs.
-        // This will call compile_binary_expr which handles angle & complex correctly.
-        if matches!(&stmt.lhs.ty, Type::Angle(..) | Type::Complex(..)) {
+        // This will call compile_binary_expr which handles angle, complex, and
+        // float division correctly.
+        if matches!(&stmt.lhs.ty, Type::Angle(..) | Type::Complex(..))
+            || (matches!(&stmt.lhs.ty, Type::Float(..)) && matches!(stmt.op, semast::BinOp::Div))
+        {
             if stmt.indices.is_empty() {
                 let rhs = semast::Expr {
                     span: stmt.span,
@@ -589,8 +734,34 @@ impl QasmCompiler {
         Some(build_stmt_semi_from_expr(expr))
     }
 
-    fn compile_barrier_stmt(stmt: &semast::BarrierStmt) -> Option<qsast::Stmt> {
-        Some(build_barrier_call(stmt.span))
+    fn compile_barrier_stmt(&mut self, stmt: &semast::BarrierStmt) -> Option<qsast::Stmt> {
+        // The operands are passed as one array: runs of single qubits are gathered
+        // into array literals, which are concatenated with any registers.
+        let mut parts = Vec::new();
+        let mut singles = Vec::new();
+        for operand in &stmt.qubits {
+            let is_register = matches!(&operand.kind, GateOperandKind::Expr(expr) if matches!(expr.ty, Type::QubitArray(..)));
+            let expr = self.compile_gate_operand(operand);
+            if is_register {
+                if !singles.is_empty() {
+                    parts.push(build_expr_array_expr(
+                        std::mem::take(&mut singles),
+                        stmt.span,
+                    ));
+                }
+                parts.push(expr);
+            } else {
+                singles.push(expr);
+            }
+        }
+        if !singles.is_empty() || parts.is_empty() {
+            parts.push(build_expr_array_expr(singles, stmt.span));
+        }
+        let qubits = parts
+            .into_iter()
+            .reduce(|lhs, rhs| build_binary_expr(false, qsast::BinOp::Add, lhs, rhs, stmt.span))
+            .expect("there should be at least one part");
+        Some(build_barrier_call(qubits, stmt.span))
     }
 
     fn compile_box_stmt(&mut self, stmt: &semast::BoxStmt) -> Option<qsast::Stmt> {
@@ -635,7 +806,7 @@ impl QasmCompiler {
     ) -> Option<qsast::Stmt> {
         let symbol = &self.symbols[decl.symbol_id].clone();
         let name = &symbol.name;
-        let is_const = symbol.ty.is_const();
+        let is_const = symbol.ty.is_const() || symbol.qsharp_ty.is_const();
         let ty_span = decl.ty_span;
         let decl_span = decl.span;
         let name_span = symbol.span;
@@ -678,7 +849,9 @@ impl QasmCompiler {
             })
             .collect();
 
+        self.in_callable_body = true;
         let body = Some(self.compile_block(&stmt.body));
+        self.in_callable_body = false;
         let return_type = map_qsharp_type_to_ast_ty(&stmt.return_type);
         let kind = if stmt.has_qubit_params {
             qsast::CallableKind::Operation
@@ -798,12 +971,18 @@ impl QasmCompiler {
 
     fn compile_gate_call_stmt(&mut self, stmt: &semast::GateCall) -> Option<qsast::Stmt> {
         let symbol = self.symbols[stmt.symbol_id].clone();
+        if self.config.warn_on_legacy_u && symbol.name == "U" {
+            self.push_compiler_warning(CompilerErrorKind::DeprecatedUGate(stmt.gate_name_span));
+        }
         let mut qubits: Vec<_> = stmt
             .qubits
             .iter()
             .map(|q| self.compile_gate_operand(q))
             .collect();
-        let args: Vec<_> = stmt.args.iter().map(|arg| self.compile_expr(arg)).collect();
+        let mut args: Vec<_> = stmt.args.iter().map(|arg| self.compile_expr(arg)).collect();
+        self.apply_fractional_powers(stmt, &mut args);
+
+        let broadcast = self.take_broadcast_registers(stmt, &mut qubits);
 
         // Take the number of qubit args that the gates expects from the source qubits.
         let gate_qubits =
@@ -824,6 +1003,9 @@ impl QasmCompiler {
                         modifier.modifier_keyword_span,
                     );
                 }
+                semast::GateModifierKind::Pow(expr) if matches!(expr.ty, Type::Float(..)) => {
+                    // Already applied to the rotation angle.
+                }
                 semast::GateModifierKind::Pow(expr) => {
                     let exponent_expr = self.compile_expr(expr);
                     args = build_tuple_expr(vec![exponent_expr, callee, args]);
@@ -863,10 +1045,9 @@ impl QasmCompiler {
                     }
                     let ctrl = qubits.split_off(qubits.len().saturating_sub(*num_ctrls as usize));
                     let ctrls = build_expr_array_expr(ctrl, modifier.span);
-                    let lit_0 = build_lit_int_expr(0, Span::default());
-                    args = build_tuple_expr(vec![lit_0, callee, ctrls, args]);
+                    args = build_tuple_expr(vec![callee, ctrls, args]);
                     callee = build_path_ident_expr(
-                        "ApplyControlledOnInt",
+                        "ApplyNegCtrl",
                         modifier.modifier_keyword_span,
                         stmt.span,
                     );
@@ -875,7 +1056,23 @@ impl QasmCompiler {
         }
 
         let expr = build_gate_call_with_params_and_callee(args, callee, stmt.span);
-        Some(build_stmt_semi_from_expr(expr))
+        let stmt_semi = build_stmt_semi_from_expr(expr);
+        let Some((loop_var_name, loop_var_ty, iter, register_span)) = broadcast else {
+            return Some(stmt_semi);
+        };
+        let body = qsast::Block {
+            id: qsast::NodeId::default(),
+            stmts: list_from_iter([stmt_semi]),
+            span: stmt.span,
+        };
+        Some(build_for_stmt(
+            loop_var_name,
+            register_span,
+            &loop_var_ty,
+            iter,
+            body,
+            stmt.span,
+        ))
     }
 
     fn compile_include_stmt(&mut self, stmt: &semast::IncludeStmt) -> Option<qsast::Stmt> {
@@ -969,22 +1166,50 @@ impl QasmCompiler {
             })
             .collect();
 
-        let body = Some(self.compile_block(&stmt.body));
+        self.in_callable_body = true;
+        let mut body = self.compile_block(&stmt.body);
+        self.in_callable_body = false;
 
-        let attrs = annotations
+        // A `@duration` annotation gives the time the gate takes, which is accounted
+        // for in the runtime when estimating resources.
+        let (durations, annotations): (Vec<_>, Vec<_>) = annotations
             .iter()
-            .filter_map(|annotation| self.compile_annotation(annotation));
+            .partition(|annotation| annotation.identifier.as_ref() == "duration");
+        for annotation in durations {
+            let Some(nanoseconds) = self.compile_duration_annotation(annotation) else {
+                continue;
+            };
+            if matches!(
+                self.config.output_semantics,
+                OutputSemantics::ResourceEstimation
+            ) {
+                let account = build_call_with_param(
+                    "AccountForRuntime",
+                    &["QasmStd", "Intrinsic"],
+                    build_lit_int_expr(nanoseconds, annotation.span),
+                    annotation.span,
+                    annotation.span,
+                    annotation.span,
+                );
+                let mut stmts = vec![build_stmt_semi_from_expr(account)];
+                stmts.extend(body.stmts.iter().map(|stmt| (**stmt).clone()));
+                body.stmts = list_from_iter(stmts);
+            }
+        }
+        let body = Some(body);
 
-        // Do not compile functors if we have the @SimulatableIntrinsic annotation.
-        let functors = if annotations
-            .iter()
-            .any(|annotation| annotation.identifier.as_ref() == "SimulatableIntrinsic")
-        {
+        // Do not compile functors if we have the @SimulatableIntrinsic annotation,
+        // or if Q# can't generate the specializations from the gate body.
+        let functors = if self.gates_without_functors.contains(&stmt.symbol_id) {
             None
         } else {
             Some(build_adj_plus_ctl_functor())
         };
 
+        let attrs = annotations
+            .iter()
+            .filter_map(|annotation| self.compile_annotation(annotation));
+
         Some(build_function_or_operation(
             name,
             cargs,
@@ -1052,10 +1277,57 @@ impl QasmCompiler {
         Some(stmt)
     }
 
+    /// Allocates the register that hardware qubits index into, before the
+    /// program's statements so that it is in scope wherever they are used.
+    fn insert_hardware_qubits_alloc(&mut self, index: usize) {
+        if self.num_hardware_qubits == 0 {
+            return;
+        }
+        let size = self.num_hardware_qubits;
+        let span = Span::default();
+        let stmt = match self.config.qubit_semantics {
+            QubitSemantics::QSharp => {
+                managed_qubit_alloc_array(HARDWARE_QUBITS_NAME, size, span, span, span)
+            }
+            QubitSemantics::Qiskit => {
+                build_unmanaged_qubit_alloc_array(HARDWARE_QUBITS_NAME, size, span, span, span)
+            }
+        };
+        self.stmts.insert(index, stmt);
+    }
+
     fn compile_reset_stmt(&mut self, stmt: &semast::ResetStmt) -> Option<qsast::Stmt> {
         let operand = self.compile_gate_operand(&stmt.operand);
         let operand_span = operand.span;
-        let expr = build_reset_call(operand, stmt.reset_token_span, operand_span);
+        let is_register = matches!(
+            &stmt.operand.kind,
+            GateOperandKind::Expr(expr) if matches!(expr.ty, Type::QubitArray(..))
+        );
+        let expr = if matches!(
+            self.config.output_semantics,
+            OutputSemantics::ResourceEstimation
+        ) {
+            // Resetting a qubit on hardware takes a measurement, which the estimator
+            // would otherwise not count, so resets are estimated as measurements
+            // whose results are discarded.
+            let name = if is_register {
+                "MResetEachZ"
+            } else {
+                "MResetZ"
+            };
+            build_call_with_param(
+                name,
+                &["Std", "Measurement"],
+                operand,
+                stmt.reset_token_span,
+                operand_span,
+                stmt.span,
+            )
+        } else if is_register {
+            build_reset_all_call(operand, stmt.reset_token_span, operand_span)
+        } else {
+            build_reset_call(operand, stmt.reset_token_span, operand_span)
+        };
         Some(build_stmt_semi_from_expr(expr))
     }
 
@@ -1278,6 +1550,23 @@ impl QasmCompiler {
             return Self::compile_complex_binary_op(op, lhs, rhs);
         }
 
+        // Float division is checked at runtime unless the divisor is a known
+        // non-zero literal, so that dividing by zero fails instead of
+        // silently producing an infinite or NaN value.
+        if matches!(binary.op, semast::BinOp::Div)
+            && matches!(&binary.lhs.ty, Type::Float(..))
+            && !is_nonzero_float_lit(&binary.rhs)
+        {
+            let span = binary.span();
+            return build_call_with_params(
+                "DivideDoubles",
+                &["QasmStd", "Arithmetic"],
+                vec![lhs, rhs],
+                span,
+                span,
+            );
+        }
+
         let is_assignment = false;
         build_binary_expr(is_assignment, op, lhs, rhs, binary.span())
     }
@@ -1385,7 +1674,7 @@ impl QasmCompiler {
             LiteralKind::Duration(value, time_unit) => {
                 self.compile_duration_literal(*value, *time_unit, span)
             }
-            LiteralKind::Float(value) => Self::compile_float_literal(*value, span),
+            LiteralKind::Float(value) => self.compile_float_literal(*value, span),
             LiteralKind::Complex(real, imag) => Self::compile_complex_literal(*real, *imag, span),
             LiteralKind::Int(value) => Self::compile_int_literal(*value, span),
             LiteralKind::BigInt(value) => Self::compile_bigint_literal(value, span),
@@ -1452,16 +1741,155 @@ impl QasmCompiler {
         let name_span = expr.measure_token_span;
         let arg = self.compile_gate_operand(&expr.operand);
         let operand_span = expr.operand.span;
-        build_measure_call(arg, name_span, operand_span, call_span)
+        if matches!(&expr.operand.kind, GateOperandKind::Expr(operand) if matches!(operand.ty, Type::QubitArray(_)))
+        {
+            build_measure_each_call(arg, name_span, operand_span, call_span)
+        } else {
+            build_measure_call(arg, name_span, operand_span, call_span)
+        }
+    }
+
+    /// Non-integer powers are only allowed on rotation gates, and are
+    /// applied by multiplying the rotation angle by each exponent.
+    fn apply_fractional_powers(&mut self, stmt: &semast::GateCall, args: &mut [qsast::Expr]) {
+        let Some(angle) = args.first_mut() else {
+            return;
+        };
+        let powers = stmt
+            .modifiers
+            .iter()
+            .filter_map(|modifier| match &modifier.kind {
+                semast::GateModifierKind::Pow(expr) if matches!(expr.ty, Type::Float(..)) => {
+                    Some(expr)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if powers.is_empty() {
+            return;
+        }
+
+        let span = angle.span;
+        let mut scaled =
+            build_angle_cast_call_by_name("AngleAsDouble", std::mem::take(angle), span, span);
+        for power in powers {
+            let exponent = self.compile_expr(power);
+            scaled = build_binary_expr(false, qsast::BinOp::Mul, scaled, exponent, span);
+        }
+        let width = build_lit_int_expr(f64::MANTISSA_DIGITS.into(), span);
+        *angle = build_call_with_params(
+            "DoubleAsAngle",
+            &["QasmStd", "Angle"],
+            vec![scaled, width],
+            span,
+            span,
+        );
+    }
+
+    /// A register operand broadcasts the gate over each of its qubits.
+    /// Replaces the register operands with the broadcast loop variable, or with
+    /// the register indexed by it when there are several registers, and returns
+    /// the loop variable's name and type, what to iterate over, and its span.
+    /// The lowerer has already checked that the registers have the same size.
+    fn take_broadcast_registers(
+        &mut self,
+        stmt: &semast::GateCall,
+        qubits: &mut [qsast::Expr],
+    ) -> Option<(&'static str, crate::types::Type, qsast::Expr, Span)> {
+        let registers: Vec<_> = stmt
+            .qubits
+            .iter()
+            .enumerate()
+            .filter_map(|(i, q)| match &q.kind {
+                GateOperandKind::Expr(expr) => match &expr.ty {
+                    Type::QubitArray(dims) => Some((i, dims)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        match registers.as_slice() {
+            [] => None,
+            [(index, _)] => {
+                let span = stmt.qubits[*index].span;
+                let loop_var = build_path_ident_expr(BROADCAST_QUBIT_NAME, span, stmt.span);
+                let register = std::mem::replace(&mut qubits[*index], loop_var);
+                Some((
+                    BROADCAST_QUBIT_NAME,
+                    crate::types::Type::Qubit,
+                    register,
+                    span,
+                ))
+            }
+            [(first, ArrayDimensions::One(size)), ..] => {
+                let span = stmt.qubits[*first].span;
+                for (index, _) in &registers {
+                    let operand_span = stmt.qubits[*index].span;
+                    let loop_var =
+                        build_path_ident_expr(BROADCAST_INDEX_NAME, operand_span, operand_span);
+                    let register = std::mem::take(&mut qubits[*index]);
+                    qubits[*index] = build_index_expr(register, loop_var, operand_span);
+                }
+                let start = build_lit_int_expr(0, span);
+                let stop = build_lit_int_expr(i64::from(*size) - 1, span);
+                let range = build_range_expr(start, stop, None, span);
+                Some((
+                    BROADCAST_INDEX_NAME,
+                    crate::types::Type::Int(false),
+                    range,
+                    span,
+                ))
+            }
+            _ => {
+                self.push_unsupported_error_message(
+                    "broadcasting a gate over multidimensional registers",
+                    stmt.span,
+                );
+                None
+            }
+        }
     }
 
     fn compile_gate_operand(&mut self, op: &GateOperand) -> qsast::Expr {
         match &op.kind {
+            GateOperandKind::HardwareQubit(_)
+                if self.config.physical_qubits && self.in_callable_body =>
+            {
+                // The register of hardware qubits is local to the program body,
+                // so it is out of scope in the callables that defs and gates compile to.
+                self.push_unsupported_error_message(
+                    "hardware qubits in def and gate bodies",
+                    op.span,
+                );
+                err_expr(op.span)
+            }
+            GateOperandKind::HardwareQubit(hw) if self.config.physical_qubits => {
+                let max = self.config.max_hardware_qubits;
+                let Some(size) = hw
+                    .name
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|index| index.checked_add(1))
+                    .filter(|&size| size <= max)
+                else {
+                    self.push_compiler_error(CompilerErrorKind::HardwareQubitOutOfRange(
+                        hw.name.clone(),
+                        max,
+                        op.span,
+                    ));
+                    return err_expr(op.span);
+                };
+                self.num_hardware_qubits = self.num_hardware_qubits.max(size);
+                let index = size - 1;
+                let register = build_path_ident_expr(HARDWARE_QUBITS_NAME, hw.span, op.span);
+                let index = build_lit_int_expr(index.into(), hw.span);
+                build_index_expr(register, index, op.span)
+            }
             GateOperandKind::HardwareQubit(hw) => {
-                // We don't support hardware qubits, so we need to push an error
-                // but we can still create an identifier for the hardware qubit
-                // and let the rest of the containing expression compile to
-                // catch any other errors
+                // Outside of physical qubit mode we don't support hardware qubits,
+                // so we need to push an error but we can still create an identifier
+                // for the hardware qubit and let the rest of the containing
+                // expression compile to catch any other errors
                 let message = "hardware qubit operands";
                 self.push_unsupported_error_message(message, op.span);
                 build_path_ident_expr(hw.name.clone(), hw.span, op.span)
@@ -1490,10 +1918,14 @@ impl QasmCompiler {
 
     fn compile_index_set(&mut self, set: &IndexSet) -> qsast::Expr {
         // This is a temporary limitation. We can only handle
-        // single index expressions for now.
+        // single index expressions and ranges for now.
         if set.values.len() == 1 {
-            if let semast::IndexSetItem::Expr(expr) = &*set.values[0] {
-                return self.compile_expr(expr);
+            match &*set.values[0] {
+                semast::IndexSetItem::Expr(expr) => return self.compile_expr(expr),
+                semast::IndexSetItem::RangeDefinition(range) => {
+                    return self.compile_range_expr(range);
+                }
+                semast::IndexSetItem::Err => {}
             }
         }
 
@@ -1525,9 +1957,9 @@ impl QasmCompiler {
         build_range_expr(start, end, step, range.span)
     }
 
-    fn compile_array_literal(&mut self, _value: &List<Expr>, span: Span) -> qsast::Expr {
-        self.push_unimplemented_error_message("array literals", span);
-        err_expr(span)
+    fn compile_array_literal(&mut self, value: &List<Expr>, span: Span) -> qsast::Expr {
+        let values = value.iter().map(|expr| self.compile_expr(expr)).collect();
+        build_expr_array_expr(values, span)
     }
 
     fn compile_bit_literal(value: bool, span: Span) -> qsast::Expr {
@@ -1538,14 +1970,56 @@ impl QasmCompiler {
         build_lit_bool_expr(value, span)
     }
 
-    fn compile_duration_literal(
-        &mut self,
-        _value: f64,
-        _unit: TimeUnit,
-        span: Span,
-    ) -> qsast::Expr {
-        self.push_unsupported_error_message("timing literals", span);
-        err_expr(span)
+    /// Durations are normalized to seconds, using the configured cycle time for `dt` units.
+    fn compile_duration_literal(&mut self, value: f64, unit: TimeUnit, span: Span) -> qsast::Expr {
+        match self.duration_to_seconds(value, unit, span) {
+            Some(seconds) => build_lit_double_expr(seconds, span),
+            None => err_expr(span),
+        }
+    }
+
+    fn duration_to_seconds(&mut self, value: f64, unit: TimeUnit, span: Span) -> Option<f64> {
+        let seconds_per_unit = match unit {
+            TimeUnit::Dt => {
+                let Some(Seconds(dt_seconds)) = self.config.dt_seconds else {
+                    self.push_compiler_error(CompilerErrorKind::DtWithoutCycleTime(span));
+                    return None;
+                };
+                dt_seconds
+            }
+            TimeUnit::Ns => 1e-9,
+            TimeUnit::Us => 1e-6,
+            TimeUnit::Ms => 1e-3,
+            TimeUnit::S => 1.0,
+        };
+        Some(value * seconds_per_unit)
+    }
+
+    /// Parses the value of a `@duration` annotation, such as `50ns`, into nanoseconds.
+    fn compile_duration_annotation(&mut self, annotation: &semast::Annotation) -> Option<i64> {
+        let value = annotation.value.as_deref().unwrap_or_default().trim();
+        let unit_start = value
+            .find(|c: char| c.is_alphabetic() || c == 'µ')
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(unit_start);
+        let unit = match unit {
+            "dt" => Some(TimeUnit::Dt),
+            "ns" => Some(TimeUnit::Ns),
+            "us" | "µs" => Some(TimeUnit::Us),
+            "ms" => Some(TimeUnit::Ms),
+            "s" => Some(TimeUnit::S),
+            _ => None,
+        };
+        let (Ok(number), Some(unit)) = (number.trim().parse::<f64>(), unit) else {
+            self.push_compiler_error(CompilerErrorKind::InvalidDurationAnnotation(
+                value.to_string(),
+                annotation.span,
+            ));
+            return None;
+        };
+        let seconds = self.duration_to_seconds(number, unit, annotation.span)?;
+        #[allow(clippy::cast_possible_truncation)]
+        Some((seconds * 1e9).round() as i64)
     }
 
     fn compile_bitstring_literal(value: &BigInt, width: u32, span: Span) -> qsast::Expr {
@@ -1562,7 +2036,12 @@ impl QasmCompiler {
         build_lit_complex_expr(crate::types::Complex::new(real, imag), span)
     }
 
-    fn compile_float_literal(value: f64, span: Span) -> qsast::Expr {
+    fn compile_float_literal(&self, value: f64, span: Span) -> qsast::Expr {
+        if self.config.symbolic_pi {
+            if let Some((num, den)) = as_fraction_of_pi(value) {
+                return build_fraction_of_pi_expr(num, den, span);
+            }
+        }
         build_lit_double_expr(value, span)
     }
 
@@ -1579,6 +2058,18 @@ impl QasmCompiler {
         err_expr(span)
     }
 
+    /// Reports the `def` call chains that are statically known to be
+    /// deeper than the configured limit, including unbounded recursion.
+    fn check_def_call_depth(&mut self, stmts: &[Box<crate::semantic::ast::Stmt>]) {
+        let max_depth = self.config.max_def_call_depth;
+        for (def, span) in recursion::find_deep_def_calls(stmts, max_depth) {
+            let name = self.symbols[def].name.clone();
+            self.push_compiler_error(CompilerErrorKind::DefCallDepthExceeded(
+                name, max_depth, span,
+            ));
+        }
+    }
+
     /// Pushes an unsupported error with the supplied message.
     pub fn push_unsupported_error_message<S: AsRef<str>>(&mut self, message: S, span: Span) {
         let kind = CompilerErrorKind::NotSupported(message.as_ref().to_string(), span);
@@ -1599,13 +2090,22 @@ impl QasmCompiler {
         self.errors.push(error);
     }
 
+    /// Pushes a compiler warning with the given kind.
+    pub fn push_compiler_warning(&mut self, kind: CompilerErrorKind) {
+        let kind = crate::ErrorKind::Compiler(error::Error(kind));
+        let warning = WithSource::from_map(&self.source_map, crate::Error(kind));
+        self.warnings.push(warning);
+    }
+
     /// +----------------+-------------------------------------------------------------+
     /// | Allowed casts  | Casting To                                                  |
     /// +----------------+-------+-----+------+-------+-------+-----+----------+-------+
     /// | Casting From   | bool  | int | uint | float | angle | bit | duration | qubit |
     /// +----------------+-------+-----+------+-------+-------+-----+----------+-------+
-    /// | angle          | Yes   | No  | No   | No    | -     | Yes | No       | No    |
+    /// | angle          | Yes   | No  | No   | Yes   | -     | Yes | No       | No    |
     /// +----------------+-------+-----+------+-------+-------+-----+----------+-------+
+    ///
+    /// The cast to float is only allowed explicitly.
     fn cast_angle_expr_to_ty(
         expr: qsast::Expr,
         expr_ty: &crate::semantic::types::Type,
@@ -1637,6 +2137,7 @@ impl QasmCompiler {
                 build_angle_cast_call_by_name("AngleAsResultArray", expr, span, span)
             }
             Type::Bool(..) => build_angle_cast_call_by_name("AngleAsBool", expr, span, span),
+            Type::Float(..) => build_angle_cast_call_by_name("AngleAsDouble", expr, span, span),
             _ => err_expr(span),
         }
     }
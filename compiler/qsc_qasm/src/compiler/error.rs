@@ -14,9 +14,31 @@ pub struct Error(pub CompilerErrorKind);
 /// safety checks to ensure that the QASM code is valid.
 #[derive(Clone, Debug, Diagnostic, Eq, Error, PartialEq)]
 pub enum CompilerErrorKind {
+    #[error("the builtin U gate is the legacy spelling of the u3 gate")]
+    #[diagnostic(help("include \"stdgates.inc\" and use u3 instead"))]
+    #[diagnostic(code("Qasm.Compiler.DeprecatedUGate"))]
+    DeprecatedUGate(#[label] Span),
+    #[error("calls made by {0} exceed the maximum def call depth of {1}")]
+    #[diagnostic(help("unconditional recursive calls never terminate"))]
+    #[diagnostic(code("Qasm.Compiler.DefCallDepthExceeded"))]
+    DefCallDepthExceeded(String, usize, #[label] Span),
+    #[error("durations in dt units require the cycle time dt to be configured")]
+    #[diagnostic(help("set `dt_seconds` in the compiler configuration"))]
+    #[diagnostic(code("Qasm.Compiler.DtWithoutCycleTime"))]
+    DtWithoutCycleTime(#[label] Span),
+    #[error("hardware qubit ${0} is out of range for {1} hardware qubits")]
+    #[diagnostic(help(
+        "set `max_hardware_qubits` in the compiler configuration to allow more hardware qubits"
+    ))]
+    #[diagnostic(code("Qasm.Compiler.HardwareQubitOutOfRange"))]
+    HardwareQubitOutOfRange(String, u32, #[label] Span),
     #[error("annotations only valid on def and gate statements")]
     #[diagnostic(code("Qasm.Compiler.InvalidAnnotationTarget"))]
     InvalidAnnotationTarget(#[label] Span),
+    #[error("invalid gate duration: {0}")]
+    #[diagnostic(help("durations are a number followed by a unit, such as `@duration 50ns`"))]
+    #[diagnostic(code("Qasm.Compiler.InvalidDurationAnnotation"))]
+    InvalidDurationAnnotation(String, #[label] Span),
     #[error("gate expects {0} qubit arguments, but {1} were provided")]
     #[diagnostic(code("Qasm.Compiler.InvalidNumberOfQubitArgs"))]
     InvalidNumberOfQubitArgs(usize, usize, #[label] Span),
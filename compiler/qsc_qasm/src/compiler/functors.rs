@@ -0,0 +1,114 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Detection of the `gate` definitions that can't be compiled with
+//! `Adj + Ctl` functor support.
+//!
+//! Q# generates the adjoint and controlled specializations of a gate by
+//! inverting and controlling each statement of its body. Bodies that update
+//! variables or loop with `while` can't be inverted, and calls to operations
+//! without the functors, such as `def`s with qubit parameters and barriers,
+//! can be neither inverted nor controlled.
+
+use rustc_hash::FxHashSet;
+
+use crate::semantic::{
+    ast::{Expr, ExprKind, Stmt, StmtKind},
+    symbols::SymbolId,
+};
+
+/// Returns the gates defined in `stmts` whose bodies don't support the
+/// adjoint and controlled functors, including `@SimulatableIntrinsic` gates,
+/// which are compiled without them.
+pub(crate) fn find_gates_without_functors(stmts: &[Box<Stmt>]) -> FxHashSet<SymbolId> {
+    let mut operation_defs = FxHashSet::default();
+    let mut gates = FxHashSet::default();
+    for stmt in stmts {
+        match stmt.kind.as_ref() {
+            StmtKind::Def(def) if def.has_qubit_params => {
+                operation_defs.insert(def.symbol_id);
+            }
+            StmtKind::QuantumGateDefinition(gate) => {
+                let is_intrinsic = stmt
+                    .annotations
+                    .iter()
+                    .any(|annotation| annotation.identifier.as_ref() == "SimulatableIntrinsic");
+                let checker = Checker {
+                    operation_defs: &operation_defs,
+                    gates_without_functors: &gates,
+                };
+                if is_intrinsic || !checker.block_supports_functors(&gate.body.stmts) {
+                    gates.insert(gate.symbol_id);
+                }
+            }
+            _ => {}
+        }
+    }
+    gates
+}
+
+struct Checker<'a> {
+    /// The defs that take qubits, which are compiled to operations without functors.
+    operation_defs: &'a FxHashSet<SymbolId>,
+    /// The gates defined so far that don't support the functors.
+    gates_without_functors: &'a FxHashSet<SymbolId>,
+}
+
+impl Checker<'_> {
+    fn block_supports_functors(&self, stmts: &[Box<Stmt>]) -> bool {
+        stmts.iter().all(|stmt| self.stmt_supports_functors(stmt))
+    }
+
+    fn stmt_supports_functors(&self, stmt: &Stmt) -> bool {
+        match stmt.kind.as_ref() {
+            StmtKind::Assign(_)
+            | StmtKind::AssignOp(_)
+            | StmtKind::Barrier(_)
+            | StmtKind::IndexedAssign(_)
+            | StmtKind::WhileLoop(_) => false,
+            StmtKind::Block(block) => self.block_supports_functors(&block.stmts),
+            StmtKind::ClassicalDecl(decl) => self.expr_supports_functors(&decl.init_expr),
+            StmtKind::ExprStmt(stmt) => self.expr_supports_functors(&stmt.expr),
+            StmtKind::For(stmt) => self.stmt_supports_functors(&stmt.body),
+            StmtKind::GateCall(call) => {
+                !self.gates_without_functors.contains(&call.symbol_id)
+                    && call.args.iter().all(|arg| self.expr_supports_functors(arg))
+            }
+            StmtKind::If(stmt) => {
+                self.expr_supports_functors(&stmt.condition)
+                    && self.stmt_supports_functors(&stmt.if_body)
+                    && stmt
+                        .else_body
+                        .as_ref()
+                        .is_none_or(|body| self.stmt_supports_functors(body))
+            }
+            StmtKind::Switch(stmt) => {
+                self.expr_supports_functors(&stmt.target)
+                    && stmt
+                        .cases
+                        .iter()
+                        .map(|case| &case.block)
+                        .chain(&stmt.default)
+                        .all(|block| self.block_supports_functors(&block.stmts))
+            }
+            _ => true,
+        }
+    }
+
+    fn expr_supports_functors(&self, expr: &Expr) -> bool {
+        match expr.kind.as_ref() {
+            ExprKind::BinaryOp(expr) => {
+                self.expr_supports_functors(&expr.lhs) && self.expr_supports_functors(&expr.rhs)
+            }
+            ExprKind::Cast(expr) => self.expr_supports_functors(&expr.expr),
+            ExprKind::FunctionCall(call) => {
+                !self.operation_defs.contains(&call.symbol_id)
+                    && call.args.iter().all(|arg| self.expr_supports_functors(arg))
+            }
+            ExprKind::IndexExpr(expr) => self.expr_supports_functors(&expr.collection),
+            ExprKind::Paren(expr) => self.expr_supports_functors(expr),
+            ExprKind::UnaryOp(expr) => self.expr_supports_functors(&expr.expr),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Static detection of `def` call chains that are too deep to run.
+//!
+//! Only calls made unconditionally are followed. Calls on the right of a
+//! short-circuiting operator, or after a branch or loop that may return
+//! early, may not happen at runtime, so recursion through them is allowed.
+//! An unconditional recursive call never terminates and always exceeds the
+//! limit.
+
+use qsc_data_structures::span::Span;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::semantic::{
+    ast::{BinOp, Expr, ExprKind, Stmt, StmtKind},
+    symbols::SymbolId,
+};
+
+/// The default maximum depth of nested `def` calls.
+pub const DEFAULT_MAX_DEF_CALL_DEPTH: usize = 100;
+
+/// Returns, for each `def` whose unconditional call chain is deeper than
+/// `max_depth`, the def and the span of the call that starts the chain.
+pub(crate) fn find_deep_def_calls(stmts: &[Box<Stmt>], max_depth: usize) -> Vec<(SymbolId, Span)> {
+    let mut defs = Vec::new();
+    let mut calls = FxHashMap::default();
+    for stmt in stmts {
+        if let StmtKind::Def(def) = stmt.kind.as_ref() {
+            let mut def_calls = Vec::new();
+            collect_block_calls(&def.body.stmts, &mut def_calls);
+            defs.push(def.symbol_id);
+            calls.insert(def.symbol_id, def_calls);
+        }
+    }
+
+    let mut graph = CallGraph {
+        calls,
+        depths: FxHashMap::default(),
+        in_progress: FxHashSet::default(),
+    };
+    let mut deep_calls = Vec::new();
+    for def in defs {
+        let def_calls = graph.calls[&def].clone();
+        let deep_call = def_calls.into_iter().find(|(callee, _)| {
+            graph
+                .depth(*callee)
+                .is_none_or(|depth| depth + 1 > max_depth)
+        });
+        if let Some((_, span)) = deep_call {
+            deep_calls.push((def, span));
+        }
+    }
+    deep_calls
+}
+
+struct CallGraph {
+    /// The unconditional calls made by each def, with the span of the call.
+    calls: FxHashMap<SymbolId, Vec<(SymbolId, Span)>>,
+    /// The memoized call depth of each def, `None` if it is unbounded.
+    depths: FxHashMap<SymbolId, Option<usize>>,
+    /// The defs whose depth is being computed, used to detect recursion.
+    in_progress: FxHashSet<SymbolId>,
+}
+
+impl CallGraph {
+    /// Returns the number of nested def frames entered by calling `def`,
+    /// or `None` if the calls recurse without bound.
+    /// Calls to symbols that are not defs don't add to the depth.
+    fn depth(&mut self, def: SymbolId) -> Option<usize> {
+        if let Some(depth) = self.depths.get(&def) {
+            return *depth;
+        }
+        let Some(def_calls) = self.calls.get(&def).cloned() else {
+            return Some(0);
+        };
+        if !self.in_progress.insert(def) {
+            return None;
+        }
+        let mut depth = Some(1);
+        for (callee, _) in def_calls {
+            depth = match (depth, self.depth(callee)) {
+                (Some(depth), Some(callee_depth)) => Some(depth.max(callee_depth + 1)),
+                _ => None,
+            };
+        }
+        self.in_progress.remove(&def);
+        self.depths.insert(def, depth);
+        depth
+    }
+}
+
+/// Collects the calls made unconditionally by `stmts`.
+/// Returns `true` if the statements may stop executing before their end,
+/// after which no further calls are unconditional.
+fn collect_block_calls(stmts: &[Box<Stmt>], calls: &mut Vec<(SymbolId, Span)>) -> bool {
+    for stmt in stmts {
+        match stmt.kind.as_ref() {
+            StmtKind::Assign(stmt) => collect_expr_calls(&stmt.rhs, calls),
+            StmtKind::AssignOp(stmt) => collect_expr_calls(&stmt.rhs, calls),
+            StmtKind::Block(block) if collect_block_calls(&block.stmts, calls) => return true,
+            StmtKind::ClassicalDecl(stmt) => collect_expr_calls(&stmt.init_expr, calls),
+            StmtKind::ExprStmt(stmt) => collect_expr_calls(&stmt.expr, calls),
+            StmtKind::GateCall(stmt) => {
+                for arg in &stmt.args {
+                    collect_expr_calls(arg, calls);
+                }
+            }
+            StmtKind::End(_)
+            | StmtKind::For(_)
+            | StmtKind::If(_)
+            | StmtKind::Switch(_)
+            | StmtKind::WhileLoop(_) => return true,
+            StmtKind::IndexedAssign(stmt) => collect_expr_calls(&stmt.rhs, calls),
+            StmtKind::Return(stmt) => {
+                if let Some(expr) = &stmt.expr {
+                    collect_expr_calls(expr, calls);
+                }
+                return true;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Collects the calls made unconditionally when evaluating `expr`.
+fn collect_expr_calls(expr: &Expr, calls: &mut Vec<(SymbolId, Span)>) {
+    match expr.kind.as_ref() {
+        ExprKind::BinaryOp(expr) => {
+            collect_expr_calls(&expr.lhs, calls);
+            if !matches!(expr.op, BinOp::AndL | BinOp::OrL) {
+                collect_expr_calls(&expr.rhs, calls);
+            }
+        }
+        ExprKind::Cast(expr) => collect_expr_calls(&expr.expr, calls),
+        ExprKind::FunctionCall(call) => {
+            for arg in &call.args {
+                collect_expr_calls(arg, calls);
+            }
+            calls.push((call.symbol_id, call.span));
+        }
+        ExprKind::IndexExpr(expr) => collect_expr_calls(&expr.collection, calls),
+        ExprKind::Paren(expr) => collect_expr_calls(expr, calls),
+        ExprKind::UnaryOp(expr) => collect_expr_calls(&expr.expr, calls),
+        _ => {}
+    }
+}
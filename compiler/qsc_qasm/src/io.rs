@@ -6,11 +6,11 @@ pub use error::Error;
 pub use error::ErrorKind;
 
 use std::{
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 /// A trait for resolving include file paths to their contents.
 /// This is used by the parser to resolve `include` directives.
@@ -22,6 +22,29 @@ pub trait SourceResolver {
     fn resolve<P>(&mut self, path: P) -> miette::Result<(PathBuf, String), Error>
     where
         P: AsRef<Path>;
+
+    /// Returns the canonical form of a path returned by `resolve`, which is the same
+    /// for every path that names the file, so that each file is only included once.
+    /// The default normalizes the path lexically, which suits resolvers that do not
+    /// read from the file system. Resolvers that do can resolve links here.
+    fn canonicalize_path(&self, path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir
+                    if matches!(
+                        normalized.components().next_back(),
+                        Some(Component::Normal(_))
+                    ) =>
+                {
+                    normalized.pop();
+                }
+                _ => normalized.push(component),
+            }
+        }
+        normalized
+    }
 }
 
 pub struct IncludeGraphNode {
@@ -35,6 +58,8 @@ pub struct SourceResolverContext {
     include_graph: FxHashMap<PathBuf, IncludeGraphNode>,
     /// Path being resolved.
     current_file: Option<PathBuf>,
+    /// Canonicalized paths of the files included so far.
+    included_files: FxHashSet<PathBuf>,
 }
 
 impl SourceResolverContext {
@@ -48,6 +73,7 @@ impl SourceResolverContext {
         // If the new path doesn't make a cycle but it was already
         // included before, we return a `MultipleInclude`
         // error saying "<FILE> was already included in <FILE>".
+        // The parser treats this error as a request to skip the include.
         if let Some(parent_file) = self.path_was_already_included(path) {
             return Err(Error(ErrorKind::MultipleInclude(
                 path.display().to_string(),
@@ -60,6 +86,13 @@ impl SourceResolverContext {
         Ok(())
     }
 
+    /// Records the canonicalized `path` as included.
+    /// Returns `false` if the file was already included, possibly
+    /// through a different path.
+    pub fn mark_included(&mut self, path: PathBuf) -> bool {
+        self.included_files.insert(path)
+    }
+
     /// Changes `current_path` to its parent in the `include_graph`.
     pub fn pop_current_file(&mut self) {
         let parent = self
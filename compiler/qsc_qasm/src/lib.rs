@@ -4,7 +4,9 @@
 mod ast_builder;
 mod compiler;
 mod stdlib;
-pub use compiler::compile_to_qsharp_ast_with_config;
+pub use compiler::{
+    compile_to_qsharp_ast_with_config, DEFAULT_MAX_DEF_CALL_DEPTH, DEFAULT_MAX_HARDWARE_QUBITS,
+};
 pub use stdlib::package_store_with_qasm;
 mod convert;
 pub mod display_utils;
@@ -13,7 +15,7 @@ mod keyword;
 mod lex;
 pub mod parser;
 pub mod semantic;
-mod types;
+pub mod types;
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -91,11 +93,46 @@ pub enum QubitSemantics {
     Qiskit,
 }
 
+/// A duration in seconds. It is compared by the bits of its value, so that
+/// configurations holding it can be `Eq`.
+#[derive(Debug, Clone, Copy)]
+pub struct Seconds(pub f64);
+
+impl PartialEq for Seconds {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Seconds {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct CompilerConfig {
     pub qubit_semantics: QubitSemantics,
     pub output_semantics: OutputSemantics,
     pub program_ty: ProgramType,
+    /// Reject old-style `qreg` and `creg` declarations as errors. Otherwise
+    /// they are accepted and reported as deprecation warnings.
+    pub strict_declarations: bool,
+    /// Map hardware qubits such as `$0` to the qubit at that index of a
+    /// register allocated at the start of the program. Otherwise hardware
+    /// qubit operands are reported as unsupported.
+    pub physical_qubits: bool,
+    /// The number of hardware qubits available when they are mapped to physical
+    /// qubits. Hardware qubits with a larger index are reported as errors.
+    pub max_hardware_qubits: u32,
+    /// The cycle time, in seconds, that durations in `dt` units are multiplied by
+    /// to resolve them to absolute time. Durations are compiled to seconds.
+    pub dt_seconds: Option<Seconds>,
+    /// The maximum depth of nested `def` calls that can be statically
+    /// proven to happen. Unconditional recursion always exceeds it.
+    pub max_def_call_depth: usize,
+    /// Report calls to the builtin `U` gate as warnings suggesting `u3`.
+    pub warn_on_legacy_u: bool,
+    /// Emit float literals that are exactly a fraction of π, such as
+    /// `0.7853981633974483`, as multiples of `Std.Math.PI()` instead.
+    pub symbolic_pi: bool,
     operation_name: Option<Arc<str>>,
     namespace: Option<Arc<str>>,
 }
@@ -113,6 +150,13 @@ impl CompilerConfig {
             qubit_semantics,
             output_semantics,
             program_ty,
+            strict_declarations: false,
+            physical_qubits: false,
+            max_hardware_qubits: DEFAULT_MAX_HARDWARE_QUBITS,
+            dt_seconds: None,
+            max_def_call_depth: DEFAULT_MAX_DEF_CALL_DEPTH,
+            warn_on_legacy_u: true,
+            symbolic_pi: false,
             operation_name,
             namespace,
         }
@@ -137,6 +181,13 @@ impl Default for CompilerConfig {
             qubit_semantics: QubitSemantics::Qiskit,
             output_semantics: OutputSemantics::Qiskit,
             program_ty: ProgramType::Fragments,
+            strict_declarations: false,
+            physical_qubits: false,
+            max_hardware_qubits: DEFAULT_MAX_HARDWARE_QUBITS,
+            dt_seconds: None,
+            max_def_call_depth: DEFAULT_MAX_DEF_CALL_DEPTH,
+            warn_on_legacy_u: true,
+            symbolic_pi: false,
             operation_name: None,
             namespace: None,
         }
@@ -168,8 +219,11 @@ pub enum ProgramType {
 pub struct OperationSignature {
     pub name: String,
     pub ns: Option<String>,
-    pub input: Vec<(String, String)>,
+    pub input: Vec<(String, crate::types::Type)>,
     pub output: String,
+    /// The names of the output registers, in the order they are returned,
+    /// to label the outputs recorded in generated QIR.
+    pub output_labels: Vec<String>,
 }
 
 impl OperationSignature {
@@ -231,6 +285,8 @@ pub struct QasmCompileUnit {
     /// Semantic errors encountered during compilation.
     /// These are always fatal errors that prevent compilation.
     errors: Vec<WithSource<crate::Error>>,
+    /// Diagnostics that were reported but did not prevent compilation.
+    warnings: Vec<WithSource<crate::Error>>,
     /// The compiled AST package
     /// There is no guarantee that this package is valid unless
     /// there are no errors.
@@ -249,12 +305,14 @@ impl QasmCompileUnit {
     pub fn new(
         source_map: SourceMap,
         errors: Vec<WithSource<crate::Error>>,
+        warnings: Vec<WithSource<crate::Error>>,
         package: Package,
         signature: Option<OperationSignature>,
     ) -> Self {
         Self {
             source_map,
             errors,
+            warnings,
             package,
             signature,
         }
@@ -272,6 +330,12 @@ impl QasmCompileUnit {
         self.errors.clone()
     }
 
+    /// Returns a list of warnings in the compilation unit.
+    #[must_use]
+    pub fn warnings(&self) -> Vec<WithSource<crate::Error>> {
+        self.warnings.clone()
+    }
+
     /// Deconstructs the compilation unit into its owned parts.
     #[must_use]
     pub fn into_tuple(
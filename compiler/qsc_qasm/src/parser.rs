@@ -53,6 +53,18 @@ impl QasmParseResult {
         self.source.has_errors()
     }
 
+    /// Returns the paths of all files pulled in by `include` statements,
+    /// flattened in the order they were resolved. The root source is not
+    /// part of the list.
+    #[must_use]
+    pub fn resolved_includes(&self) -> Vec<Arc<str>> {
+        let mut files = Vec::new();
+        for include in self.source.includes() {
+            collect_source_files(include, &mut files);
+        }
+        files.into_iter().map(|(path, _)| path).collect()
+    }
+
     pub fn all_errors(&self) -> Vec<WithSource<crate::Error>> {
         let mut self_errors = self.errors();
         let include_errors = self
@@ -109,7 +121,6 @@ fn update_offsets(source_map: &SourceMap, source: &mut QasmSource) {
 /// Parse a QASM file and return the parse result.
 /// This function will resolve includes using the provided resolver.
 /// If an include file cannot be resolved, an error will be returned.
-/// If a file is included recursively, a stack overflow occurs.
 pub fn parse_source<S, P, R>(source: S, path: P, resolver: &mut R) -> QasmParseResult
 where
     S: AsRef<str>,
@@ -226,18 +237,27 @@ impl QasmSource {
 /// Parse a QASM file and return the parse result using the provided resolver.
 /// Returns `Err` if the resolver cannot resolve the file.
 /// Returns `Ok` otherwise. Any parse errors will be included in the result.
+/// Returns `None` if the file was already included, so that each file is
+/// only parsed once.
 ///
 /// This function is the start of a recursive process that will resolve all
 /// includes in the QASM file. Any includes are parsed as if their contents
 /// were defined where the include statement is.
-fn parse_qasm_file<P, R>(path: P, resolver: &mut R) -> QasmSource
+fn parse_qasm_file<P, R>(path: P, resolver: &mut R) -> Option<QasmSource>
 where
     P: AsRef<Path>,
     R: SourceResolver,
 {
     match resolver.resolve(&path) {
         Ok((path, source)) => {
-            let parse_result = parse_qasm_source(source, path, resolver);
+            // The same file may be reachable through different paths,
+            // so we key the included files by their canonical path.
+            let canonical_path = resolver.canonicalize_path(&path);
+            let parse_result = if resolver.ctx().mark_included(canonical_path) {
+                Some(parse_qasm_source(source, path, resolver))
+            } else {
+                None
+            };
 
             // Once we finish parsing the source, we pop the file from the
             // resolver. This is needed to keep track of multiple includes
@@ -246,10 +266,11 @@ where
 
             parse_result
         }
+        Err(crate::io::Error(crate::io::ErrorKind::MultipleInclude(..))) => None,
         Err(e) => {
             let error = crate::parser::error::ErrorKind::IO(e);
             let error = crate::parser::Error(error, None);
-            QasmSource {
+            Some(QasmSource {
                 path: path.as_ref().to_owned(),
                 source: Default::default(),
                 program: Program {
@@ -259,7 +280,7 @@ where
                 },
                 errors: vec![error],
                 included: vec![],
-            }
+            })
         }
     }
 }
@@ -281,28 +302,35 @@ fn parse_source_and_includes<P: AsRef<str>, R>(
 where
     R: SourceResolver,
 {
-    let (program, errors) = parse(source.as_ref());
-    let included = parse_includes(&program, resolver);
+    let (mut program, errors) = parse(source.as_ref());
+    let included = parse_includes(&mut program, resolver);
     (program, errors, included)
 }
 
-fn parse_includes<R>(program: &Program, resolver: &mut R) -> Vec<QasmSource>
+/// Parses the files included by `program`. Include statements for files
+/// that were already included are removed from the program, making them
+/// no-ops, so that each remaining include statement has a matching source.
+fn parse_includes<R>(program: &mut Program, resolver: &mut R) -> Vec<QasmSource>
 where
     R: SourceResolver,
 {
     let mut includes = vec![];
-    for stmt in &program.statements {
+    let mut statements = Vec::with_capacity(program.statements.len());
+    for stmt in std::mem::take(&mut program.statements).into_vec() {
         if let StmtKind::Include(include) = stmt.kind.as_ref() {
             let file_path = &include.filename;
             // Skip the standard gates include file.
             // Handling of this file is done by the compiler.
-            if file_path.to_lowercase() == "stdgates.inc" {
-                continue;
+            if file_path.to_lowercase() != "stdgates.inc" {
+                let Some(source) = parse_qasm_file(file_path, resolver) else {
+                    continue;
+                };
+                includes.push(source);
             }
-            let source = parse_qasm_file(file_path, resolver);
-            includes.push(source);
         }
+        statements.push(stmt);
     }
+    program.statements = statements.into_boxed_slice();
 
     includes
 }
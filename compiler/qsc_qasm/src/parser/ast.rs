@@ -1021,6 +1021,8 @@ pub struct QubitDeclaration {
     pub ty_span: Span,
     pub qubit: Ident,
     pub size: Option<Expr>,
+    /// Whether this declaration used the old-style `qreg` syntax.
+    pub is_old_style: bool,
 }
 
 impl Display for QubitDeclaration {
@@ -1161,6 +1163,8 @@ pub struct ClassicalDeclarationStmt {
     pub ty: Box<TypeDef>,
     pub identifier: Ident,
     pub init_expr: Option<Box<ValueExpr>>,
+    /// Whether this declaration used the old-style `creg` syntax.
+    pub is_old_style: bool,
 }
 
 impl Display for ClassicalDeclarationStmt {
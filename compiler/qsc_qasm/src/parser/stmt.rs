@@ -660,6 +660,7 @@ fn parse_quantum_decl(s: &mut ParserContext) -> Result<StmtKind> {
         ty_span,
         qubit: ident,
         size,
+        is_old_style: false,
     }))
 }
 
@@ -734,6 +735,7 @@ fn parse_non_constant_classical_decl(
         ty: Box::new(ty),
         identifier,
         init_expr,
+        is_old_style: false,
     };
 
     Ok(StmtKind::ClassicalDecl(decl))
@@ -879,6 +881,7 @@ fn creg_decl(s: &mut ParserContext) -> Result<StmtKind> {
         })),
         identifier,
         init_expr: None,
+        is_old_style: true,
     }))
 }
 
@@ -891,6 +894,7 @@ fn qreg_decl(s: &mut ParserContext) -> Result<StmtKind> {
         ty_span: s.span(lo),
         qubit: identifier,
         size,
+        is_old_style: true,
     }))
 }
 
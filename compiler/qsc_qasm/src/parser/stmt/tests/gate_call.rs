@@ -206,6 +206,61 @@ fn gate_call_ctrl_inv_modifiers() {
     );
 }
 
+#[test]
+fn gate_call_pow_modifier() {
+    check(
+        parse,
+        "pow(2) @ h q;",
+        &expect![[r#"
+            Stmt [0-13]:
+                annotations: <empty>
+                kind: GateCall [0-13]:
+                    modifiers:
+                        QuantumGateModifier [0-8]:
+                            modifier_keyword_span: [0-3]
+                            kind: Pow Expr [4-5]: Lit: Int(2)
+                    name: Ident [9-10] "h"
+                    args: <empty>
+                    duration: <none>
+                    qubits:
+                        GateOperand [11-12]:
+                            kind: IndexedIdent [11-12]:
+                                name: Ident [11-12] "q"
+                                index_span: [0-0]
+                                indices: <empty>"#]],
+    );
+}
+
+#[test]
+fn gate_call_pow_modifier_with_const_expr() {
+    check(
+        parse,
+        "pow(-k / 2) @ h q;",
+        &expect![[r#"
+            Stmt [0-18]:
+                annotations: <empty>
+                kind: GateCall [0-18]:
+                    modifiers:
+                        QuantumGateModifier [0-13]:
+                            modifier_keyword_span: [0-3]
+                            kind: Pow Expr [4-10]: BinaryOpExpr:
+                                op: Div
+                                lhs: Expr [4-6]: UnaryOpExpr:
+                                    op: Neg
+                                    expr: Expr [5-6]: Ident [5-6] "k"
+                                rhs: Expr [9-10]: Lit: Int(2)
+                    name: Ident [14-15] "h"
+                    args: <empty>
+                    duration: <none>
+                    qubits:
+                        GateOperand [16-17]:
+                            kind: IndexedIdent [16-17]:
+                                name: Ident [16-17] "q"
+                                index_span: [0-0]
+                                indices: <empty>"#]],
+    );
+}
+
 #[test]
 fn binary_expr_qubit() {
     check(
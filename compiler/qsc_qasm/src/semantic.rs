@@ -29,6 +29,7 @@ pub struct QasmSemanticParseResult {
     pub symbols: self::symbols::SymbolTable,
     pub program: self::ast::Program,
     pub errors: Vec<WithSource<crate::Error>>,
+    pub warnings: Vec<WithSource<crate::Error>>,
 }
 
 impl QasmSemanticParseResult {
@@ -107,7 +108,6 @@ where
 /// Parse a QASM file and return the parse result.
 /// This function will resolve includes using the provided resolver.
 /// If an include file cannot be resolved, an error will be returned.
-/// If a file is included recursively, a stack overflow occurs.
 pub fn parse_source<S, P, R>(source: S, path: P, resolver: &mut R) -> QasmSemanticParseResult
 where
     S: AsRef<str>,
@@ -124,5 +124,6 @@ where
         symbols: sem_res.symbols,
         program: sem_res.program,
         errors,
+        warnings: sem_res.warnings,
     }
 }
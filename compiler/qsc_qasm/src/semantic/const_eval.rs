@@ -37,6 +37,9 @@ pub enum ConstEvalError {
     #[error("{0} is not supported between types {1} and {2}")]
     #[diagnostic(code("Qasm.Lowerer.UnsupportedBinaryOp"))]
     UnsupportedBinaryOp(String, String, String, #[label] Span),
+    #[error("division by zero")]
+    #[diagnostic(code("Qasm.Lowerer.DivisionByZero"))]
+    DivisionByZero(#[label] Span),
 }
 
 impl Expr {
@@ -155,6 +158,11 @@ impl BinaryOpExpr {
             return None;
         }
 
+        if matches!(self.op, BinOp::Div | BinOp::Mod) && is_zero(&rhs) {
+            ctx.push_const_eval_error(ConstEvalError::DivisionByZero(self.rhs.span));
+            return None;
+        }
+
         match &self.op {
             // Bit Shifts
             BinOp::Shl => {
@@ -281,6 +289,8 @@ impl BinaryOpExpr {
             },
 
             // Comparison
+            // Floats are compared with IEEE semantics, like Q# `Double`s, so NaN
+            // compares unequal to everything, including itself.
             BinOp::Eq => match lhs_ty {
                 Type::Int(..) | Type::UInt(..) => {
                     rewrap_lit!((lhs, rhs), (Int(lhs), Int(rhs)), Bool(lhs == rhs))
@@ -504,6 +514,15 @@ impl BinaryOpExpr {
     }
 }
 
+fn is_zero(lit: &LiteralKind) -> bool {
+    match lit {
+        LiteralKind::Int(value) => *value == 0,
+        LiteralKind::Float(value) => *value == 0.0,
+        LiteralKind::Angle(angle) => angle.value == 0,
+        _ => false,
+    }
+}
+
 impl FunctionCall {
     #[allow(clippy::unused_self)]
     fn const_eval(&self, _ctx: &mut Lowerer, _ty: &Type) -> Option<LiteralKind> {
@@ -511,6 +530,51 @@ impl FunctionCall {
     }
 }
 
+/// The builtin math functions that can be called in const expressions,
+/// with the number of float arguments they take.
+const BUILTIN_MATH_FUNCTIONS: [(&str, usize); 11] = [
+    ("arccos", 1),
+    ("arcsin", 1),
+    ("arctan", 1),
+    ("ceiling", 1),
+    ("cos", 1),
+    ("exp", 1),
+    ("floor", 1),
+    ("log", 1),
+    ("sin", 1),
+    ("sqrt", 1),
+    ("tan", 1),
+];
+
+/// Returns the number of arguments of the builtin math function `name`,
+/// or `None` if there is no such function.
+pub(crate) fn builtin_math_function_arity(name: &str) -> Option<usize> {
+    BUILTIN_MATH_FUNCTIONS
+        .iter()
+        .find(|(function, _)| *function == name)
+        .map(|(_, arity)| *arity)
+}
+
+/// Evaluates the builtin math function `name` on const float arguments.
+/// Returns `None` if there is no such function taking `args.len()` arguments.
+pub(crate) fn eval_builtin_math_function(name: &str, args: &[f64]) -> Option<f64> {
+    let value = match (name, args) {
+        ("arccos", [x]) => x.acos(),
+        ("arcsin", [x]) => x.asin(),
+        ("arctan", [x]) => x.atan(),
+        ("ceiling", [x]) => x.ceil(),
+        ("cos", [x]) => x.cos(),
+        ("exp", [x]) => x.exp(),
+        ("floor", [x]) => x.floor(),
+        ("log", [x]) => x.ln(),
+        ("sin", [x]) => x.sin(),
+        ("sqrt", [x]) => x.sqrt(),
+        ("tan", [x]) => x.tan(),
+        _ => return None,
+    };
+    Some(value)
+}
+
 impl IndexExpr {
     #[allow(clippy::unused_self)]
     fn const_eval(&self, _ctx: &mut Lowerer, _ty: &Type) -> Option<LiteralKind> {
@@ -618,10 +682,10 @@ fn cast_to_uint(cast: &Cast, ctx: &mut Lowerer) -> Option<LiteralKind> {
 /// +---------------+------+-----+------+-------+-------+-----+
 /// | Casting to    | bool | int | uint | float | angle | bit |
 /// +---------------+------+-----+------+-------+-------+-----+
-/// | float         | Yes  | Yes | Yes  | -     | No    | No  |
+/// | float         | Yes  | Yes | Yes  | -     | Yes   | No  |
 /// +---------------+------+-----+------+-------+-------+-----+
 fn cast_to_float(cast: &Cast, ctx: &mut Lowerer) -> Option<LiteralKind> {
-    use LiteralKind::{Bool, Float, Int};
+    use LiteralKind::{Angle, Bool, Float, Int};
     let lit = cast.expr.const_eval(ctx)?;
 
     match &cast.expr.ty {
@@ -631,6 +695,7 @@ fn cast_to_float(cast: &Cast, ctx: &mut Lowerer) -> Option<LiteralKind> {
             Float(safe_i64_to_f64(val)?)
         }),
         Type::Float(..) => Some(lit),
+        Type::Angle(..) => rewrap_lit!(lit, Angle(val), Float(val.try_into().ok()?)),
         _ => None,
     }
 }
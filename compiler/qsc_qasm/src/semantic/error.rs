@@ -25,6 +25,15 @@ pub enum SemanticErrorKind {
     #[error("array literals are only allowed in classical declarations")]
     #[diagnostic(code("Qasm.Lowerer.ArrayLiteralInNonClassicalDecl"))]
     ArrayLiteralInNonClassicalDecl(#[label] Span),
+    #[error("array literal has {1} elements but the array has size {0}")]
+    #[diagnostic(code("Qasm.Lowerer.ArrayLiteralSizeMismatch"))]
+    ArrayLiteralSizeMismatch(u32, usize, #[label] Span),
+    #[error("cannot update array parameter {0}, the caller would not see the change")]
+    #[diagnostic(code("Qasm.Lowerer.ArrayParameterUpdate"))]
+    #[diagnostic(help(
+        "arrays are passed to subroutines by value, return the updated array instead"
+    ))]
+    ArrayParameterUpdate(String, #[label] Span),
     #[error("array size must be a non-negative integer const expression")]
     #[diagnostic(code("Qasm.Lowerer.ArraySizeMustBeNonNegativeConstExpr"))]
     ArraySizeMustBeNonNegativeConstExpr(#[label] Span),
@@ -80,6 +89,9 @@ pub enum SemanticErrorKind {
     #[error("{0} must be a const expression")]
     #[diagnostic(code("Qasm.Lowerer.ExprMustBeConst"))]
     ExprMustBeConst(String, #[label] Span),
+    #[error("{0} must be an integer")]
+    #[diagnostic(code("Qasm.Lowerer.ExprMustBeInt"))]
+    ExprMustBeInt(String, #[label] Span),
     #[error("{0} must fit in a u32")]
     #[diagnostic(code("Qasm.Lowerer.ExprMustFitInU32"))]
     ExprMustFitInU32(String, #[label] Span),
@@ -95,6 +107,22 @@ pub enum SemanticErrorKind {
     #[error("for statements must have a body or statement")]
     #[diagnostic(code("Qasm.Lowerer.ForStatementsMustHaveABodyOrStatement"))]
     ForStatementsMustHaveABodyOrStatement(#[label] Span),
+    #[error("non-integer powers are only supported on rotation gates, found {0}")]
+    #[diagnostic(code("Qasm.Lowerer.FractionalPowerOfNonRotationGate"))]
+    #[diagnostic(help(
+        "rotation gates take a single angle, which is scaled by the exponent of the pow modifier"
+    ))]
+    FractionalPowerOfNonRotationGate(String, #[label] Span),
+    #[error("gate {0} must operate on at least one qubit")]
+    #[diagnostic(code("Qasm.Lowerer.GateWithNoQubits"))]
+    #[diagnostic(help("use a def subroutine for classical code"))]
+    GateWithNoQubits(String, #[label] Span),
+    #[error("cannot broadcast a gate over registers of different sizes {0} and {1}")]
+    #[diagnostic(code("Qasm.Lowerer.BroadcastRegisterSizeMismatch"))]
+    #[diagnostic(help(
+        "a gate applied to several registers is applied to their qubits element-wise"
+    ))]
+    BroadcastRegisterSizeMismatch(u32, u32, #[label] Span),
     #[error("if statement missing {0} expression")]
     #[diagnostic(code("Qasm.Lowerer.IfStmtMissingExpression"))]
     IfStmtMissingExpression(String, #[label] Span),
@@ -113,6 +141,9 @@ pub enum SemanticErrorKind {
     #[error("indexed must be a single expression")]
     #[diagnostic(code("Qasm.Lowerer.IndexMustBeSingleExpr"))]
     IndexMustBeSingleExpr(#[label] Span),
+    #[error("index {0} is out of bounds for a register of size {1}")]
+    #[diagnostic(code("Qasm.Lowerer.IndexOutOfBounds"))]
+    IndexOutOfBounds(i64, u32, #[label] Span),
     #[error("assigning {0} values to {1} must be in a range that be converted to {1}")]
     #[diagnostic(code("Qasm.Lowerer.InvalidCastValueRange"))]
     InvalidCastValueRange(String, String, #[label] Span),
@@ -131,9 +162,22 @@ pub enum SemanticErrorKind {
     #[error("gate expects {0} qubit arguments, but {1} were provided")]
     #[diagnostic(code("Qasm.Lowerer.InvalidNumberOfQubitArgs"))]
     InvalidNumberOfQubitArgs(usize, usize, #[label] Span),
+    #[error("{0} are not allowed in gate definitions")]
+    #[diagnostic(help("gate bodies can only contain gate calls and control flow over them"))]
+    #[diagnostic(code("Qasm.Lowerer.InvalidStatementInGate"))]
+    InvalidStatementInGate(String, #[label] Span),
     #[error("{0} can only appear in {1} scopes")]
     #[diagnostic(code("Qasm.Lowerer.InvalidScope"))]
     InvalidScope(String, String, #[label] Span),
+    #[error("measurements are not allowed in gate definitions")]
+    #[diagnostic(code("Qasm.Lowerer.MeasurementInGate"))]
+    #[diagnostic(help(
+        "gates must be unitary, move the measurement outside the gate and measure after calling it"
+    ))]
+    MeasurementInGate(#[label("measurement in gate body")] Span),
+    #[error("cannot assign the measurement of {1} qubits to a register of {0} bits")]
+    #[diagnostic(code("Qasm.Lowerer.MeasurementSizeMismatch"))]
+    MeasurementSizeMismatch(u32, u32, #[label] Span),
     #[error("measure statements must have a name")]
     #[diagnostic(code("Qasm.Lowerer.MeasureExpressionsMustHaveName"))]
     MeasureExpressionsMustHaveName(#[label] Span),
@@ -152,6 +196,9 @@ pub enum SemanticErrorKind {
     #[error("{0} were introduced in version {1}")]
     #[diagnostic(code("Qasm.Lowerer.NotSupportedInThisVersion"))]
     NotSupportedInThisVersion(String, String, #[label] Span),
+    #[error("{0} declarations are OpenQASM 2 syntax, use {1} declarations instead")]
+    #[diagnostic(code("Qasm.Lowerer.OldStyleDeclaration"))]
+    OldStyleDeclaration(String, String, #[label] Span),
     #[error("the operator {0} is not valid with lhs {1} and rhs {2}")]
     #[diagnostic(code("Qasm.Lowerer.OperatorNotSupportedForTypes"))]
     OperatorNotSupportedForTypes(String, String, String, #[label] Span),
@@ -173,6 +220,12 @@ pub enum SemanticErrorKind {
     #[error("redefined symbol: {0}")]
     #[diagnostic(code("Qasm.Lowerer.RedefinedSymbol"))]
     RedefinedSymbol(String, #[label] Span),
+    #[error("resets are not allowed in gate definitions")]
+    #[diagnostic(code("Qasm.Lowerer.ResetInGate"))]
+    #[diagnostic(help(
+        "gates must be unitary, move the reset outside the gate and reset before calling it"
+    ))]
+    ResetInGate(#[label("reset in gate body")] Span),
     #[error("reset expression must have a gate operand")]
     #[diagnostic(code("Qasm.Lowerer.ResetExpressionMustHaveGateOperand"))]
     ResetExpressionMustHaveGateOperand(#[label] Span),
@@ -185,6 +238,17 @@ pub enum SemanticErrorKind {
     #[error("return statements are only allowed within subroutines")]
     #[diagnostic(code("Qasm.Lowerer.ReturnNotInSubroutine"))]
     ReturnNotInSubroutine(#[label] Span),
+    #[error("cannot return a value of type {0} from a subroutine returning {1}")]
+    #[diagnostic(code("Qasm.Lowerer.ReturnTypeMismatch"))]
+    ReturnTypeMismatch(
+        String,
+        String,
+        #[label("returned value")] Span,
+        #[label("subroutine signature")] Span,
+    ),
+    #[error("cannot assign {1} bits to a slice of {0} bits")]
+    #[diagnostic(code("Qasm.Lowerer.SliceLengthMismatch"))]
+    SliceLengthMismatch(u32, u32, #[label] Span),
     #[error("switch statement must have at least one non-default case")]
     #[diagnostic(code("Qasm.Lowerer.SwitchStatementMustHaveAtLeastOneCase"))]
     SwitchStatementMustHaveAtLeastOneCase(#[label] Span),
@@ -4,7 +4,7 @@
 use std::ops::ShlAssign;
 use std::rc::Rc;
 
-use super::const_eval::ConstEvalError;
+use super::const_eval::{builtin_math_function_arity, eval_builtin_math_function, ConstEvalError};
 use super::symbols::ScopeKind;
 use super::types::binop_requires_asymmetric_angle_op;
 use super::types::binop_requires_int_conversion_for_type;
@@ -22,9 +22,9 @@ use num_traits::FromPrimitive;
 use num_traits::Num;
 use qsc_data_structures::span::Span;
 use qsc_frontend::{compile::SourceMap, error::WithSource};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
-use super::symbols::{IOKind, Symbol, SymbolTable};
+use super::symbols::{IOKind, Symbol, SymbolId, SymbolTable};
 
 use crate::convert::safe_i64_to_f64;
 use crate::parser::ast::list_from_iter;
@@ -69,6 +69,9 @@ pub(crate) struct Lowerer {
     /// The source map of QASM sources for error reporting.
     pub source_map: SourceMap,
     pub errors: Vec<WithSource<crate::Error>>,
+    /// Diagnostics that do not prevent compilation on their own, such as
+    /// old-style `qreg` and `creg` declarations.
+    pub warnings: Vec<WithSource<crate::Error>>,
     /// The file stack is used to track the current file for error reporting.
     /// When we include a file, we push the file path to the stack and pop it
     /// when we are done with the file.
@@ -76,6 +79,12 @@ pub(crate) struct Lowerer {
     pub symbols: SymbolTable,
     pub version: Option<Version>,
     pub stmts: Vec<Stmt>,
+    /// The quantum expressions bound by `let` aliases, keyed by the alias
+    /// symbol. Gate operands naming an alias are resolved to these.
+    alias_exprs: FxHashMap<SymbolId, semantic::Expr>,
+    /// The array parameters of the `def` being lowered. Q# passes arrays
+    /// by value, so updates to them would not be visible to the caller.
+    array_params: FxHashSet<SymbolId>,
 }
 
 impl Lowerer {
@@ -88,9 +97,12 @@ impl Lowerer {
             source,
             source_map,
             errors,
+            warnings: Vec::new(),
             symbols,
             version,
             stmts,
+            alias_exprs: FxHashMap::default(),
+            array_params: FxHashSet::default(),
         }
     }
 
@@ -117,6 +129,7 @@ impl Lowerer {
             symbols: self.symbols,
             program,
             errors: self.errors,
+            warnings: self.warnings,
         }
     }
 
@@ -435,6 +448,8 @@ impl Lowerer {
                 .join(", ");
             let kind = SemanticErrorKind::InconsistentTypesInAlias(tys, alias.span);
             self.push_semantic_error(kind);
+        } else if rhs.len() == 1 && first.ty.is_quantum() {
+            self.alias_exprs.insert(symbol_id, first.clone());
         }
 
         semantic::StmtKind::Alias(semantic::AliasDeclStmt {
@@ -469,7 +484,7 @@ impl Lowerer {
             }
             syntax::ValueExpr::Measurement(measure_expr) => {
                 let expr = self.lower_measure_expr(measure_expr);
-                self.cast_expr_to_type(&ty, &expr)
+                self.cast_measurement_to_type(&ty, &expr)
             }
         };
 
@@ -478,6 +493,7 @@ impl Lowerer {
                 SemanticErrorKind::CannotUpdateConstVariable(ident.name.to_string(), ident.span);
             self.push_semantic_error(kind);
         }
+        self.check_array_param_update(symbol_id, ident);
 
         semantic::StmtKind::Assign(semantic::AssignStmt {
             symbol_id,
@@ -497,9 +513,6 @@ impl Lowerer {
         let (symbol_id, symbol) =
             self.try_get_existing_or_insert_err_symbol(&ident.name, ident.span);
 
-        let indexed_ty =
-            &self.get_indexed_type(&symbol.ty, index_expr.name.span, index_expr.indices.len());
-
         let indices = list_from_iter(
             index_expr
                 .indices
@@ -508,14 +521,28 @@ impl Lowerer {
         );
 
         let rhs = match rhs {
-            syntax::ValueExpr::Expr(expr) => {
-                let expr = self.lower_expr(expr);
-                self.cast_expr_with_target_type_or_default(Some(expr), indexed_ty, span)
-            }
-            syntax::ValueExpr::Measurement(measure_expr) => {
-                let expr = self.lower_measure_expr(measure_expr);
-                self.cast_expr_to_type(indexed_ty, &expr)
+            syntax::ValueExpr::Expr(expr) => self.lower_expr(expr),
+            syntax::ValueExpr::Measurement(measure_expr) => self.lower_measure_expr(measure_expr),
+        };
+
+        // A single range index selects a slice of the register, which is assigned element-wise.
+        let rhs = if let [index] = &*indices {
+            if let Some(slice_ty) = self.get_sliced_type(&symbol.ty, index) {
+                if self.check_slice_length(&slice_ty, &rhs, span) {
+                    self.cast_expr_with_target_type_or_default(Some(rhs), &slice_ty, span)
+                } else {
+                    rhs
+                }
+            } else if is_range_index(&index_expr.indices[0]) {
+                rhs
+            } else {
+                let indexed_ty = self.get_indexed_type(&symbol.ty, index_expr.name.span, 1);
+                self.cast_expr_with_target_type_or_default(Some(rhs), &indexed_ty, span)
             }
+        } else {
+            let indexed_ty =
+                self.get_indexed_type(&symbol.ty, index_expr.name.span, index_expr.indices.len());
+            self.cast_expr_with_target_type_or_default(Some(rhs), &indexed_ty, span)
         };
 
         if symbol.ty.is_const() {
@@ -523,6 +550,7 @@ impl Lowerer {
                 SemanticErrorKind::CannotUpdateConstVariable(ident.name.to_string(), ident.span);
             self.push_semantic_error(kind);
         }
+        self.check_array_param_update(symbol_id, &ident);
 
         semantic::StmtKind::IndexedAssign(semantic::IndexedAssignStmt {
             span,
@@ -533,6 +561,15 @@ impl Lowerer {
         })
     }
 
+    /// Array parameters can't be updated, since Q# passes arrays by value
+    /// and the caller would keep seeing the original array.
+    fn check_array_param_update(&mut self, symbol_id: SymbolId, ident: &syntax::Ident) {
+        if self.array_params.contains(&symbol_id) {
+            let kind = SemanticErrorKind::ArrayParameterUpdate(ident.name.to_string(), ident.span);
+            self.push_semantic_error(kind);
+        }
+    }
+
     fn lower_assign_op(&mut self, stmt: &syntax::AssignOpStmt) -> semantic::StmtKind {
         let op = stmt.op.into();
         let lhs = &stmt.lhs;
@@ -558,6 +595,7 @@ impl Lowerer {
                 SemanticErrorKind::CannotUpdateConstVariable(ident.name.to_string(), ident.span);
             self.push_semantic_error(kind);
         }
+        self.check_array_param_update(symbol_id, &ident);
 
         let lhs = self.lower_indexed_ident_expr(lhs);
         let rhs = match rhs {
@@ -588,10 +626,7 @@ impl Lowerer {
                 let rhs = self.lower_expr(&bin_op_expr.rhs);
                 self.lower_binary_op_expr(bin_op_expr.op, lhs, rhs, expr.span)
             }
-            syntax::ExprKind::Cast(_) => {
-                self.push_unimplemented_error_message("cast expr", expr.span);
-                err_expr!(Type::Err, expr.span)
-            }
+            syntax::ExprKind::Cast(cast) => self.lower_cast_expr(cast),
             syntax::ExprKind::Err => err_expr!(Type::Err, expr.span),
             syntax::ExprKind::FunctionCall(expr) => self.lower_function_call_expr(expr),
             syntax::ExprKind::Ident(ident) => self.lower_ident_expr(ident),
@@ -604,6 +639,32 @@ impl Lowerer {
         }
     }
 
+    fn lower_cast_expr(&mut self, cast: &syntax::Cast) -> semantic::Expr {
+        let expr = self.lower_expr(&cast.arg);
+        let ty = self.get_semantic_type_from_tydef(&cast.ty, expr.ty.is_const());
+        if matches!(expr.ty, Type::Err) || matches!(ty, Type::Err) {
+            return err_expr!(Type::Err, cast.span);
+        }
+
+        // Angles can't be implicitly converted to floats, but an explicit
+        // cast yields the value of the angle in radians.
+        if matches!(ty, Type::Float(..)) && matches!(expr.ty, Type::Angle(..)) {
+            return semantic::Expr {
+                span: cast.span,
+                kind: Box::new(semantic::ExprKind::Cast(semantic::Cast {
+                    span: cast.span,
+                    expr,
+                    ty: ty.clone(),
+                })),
+                ty,
+            };
+        }
+
+        let mut expr = self.cast_expr_with_target_type_or_default(Some(expr), &ty, cast.span);
+        expr.span = cast.span;
+        expr
+    }
+
     fn lower_ident_expr(&mut self, ident: &syntax::Ident) -> semantic::Expr {
         let name = ident.name.clone();
 
@@ -775,6 +836,22 @@ impl Lowerer {
                     self.push_semantic_error(kind);
                 }
                 let span = expr.span;
+                // Like the binary bitwise operators, `~` on a register is
+                // applied to its integer value and cast back to the register.
+                if let Type::BitArray(ArrayDimensions::One(size), is_const) = ty {
+                    let uint_ty = Type::UInt(Some(size), is_const);
+                    let unary = semantic::UnaryOpExpr {
+                        span,
+                        op: semantic::UnaryOp::NotB,
+                        expr: self.cast_expr_to_type(&uint_ty, &expr),
+                    };
+                    let expr = semantic::Expr {
+                        span,
+                        kind: Box::new(semantic::ExprKind::UnaryOp(unary)),
+                        ty: uint_ty,
+                    };
+                    return self.cast_expr_to_type(&ty, &expr);
+                }
                 let unary = semantic::UnaryOpExpr {
                     span,
                     op: semantic::UnaryOp::NotB,
@@ -852,14 +929,10 @@ impl Lowerer {
                     crate::types::Type::Int(is_const)
                 }
             }
-            Type::Float(_, _) => crate::types::Type::Double(is_const),
+            Type::Float(_, _) | Type::Duration(_) => crate::types::Type::Double(is_const),
             Type::Angle(_, _) => crate::types::Type::Angle(is_const),
             Type::Complex(_, _) => crate::types::Type::Complex(is_const),
             Type::Bool(_) => crate::types::Type::Bool(is_const),
-            Type::Duration(_) => {
-                self.push_unsupported_error_message("duration type values", span);
-                crate::types::Type::Err
-            }
             Type::Stretch(_) => {
                 self.push_unsupported_error_message("stretch type values", span);
                 crate::types::Type::Err
@@ -878,6 +951,7 @@ impl Lowerer {
                 }
             }
             Type::FloatArray(_, dims) => crate::types::Type::DoubleArray(dims.into()),
+            Type::AngleArray(_, dims) => crate::types::Type::AngleArray(dims.into(), is_const),
             Type::BoolArray(dims) => crate::types::Type::BoolArray(dims.into(), is_const),
             Type::Gate(cargs, qargs) => {
                 crate::types::Type::Callable(crate::types::CallableKind::Operation, *cargs, *qargs)
@@ -983,10 +1057,29 @@ impl Lowerer {
         &mut self,
         stmt: &syntax::ClassicalDeclarationStmt,
     ) -> semantic::StmtKind {
+        if stmt.is_old_style {
+            self.push_semantic_warning(SemanticErrorKind::OldStyleDeclaration(
+                "creg".to_string(),
+                "bit".to_string(),
+                stmt.span,
+            ));
+        }
         let is_const = false; // const decls are handled separately
-        let ty = self.get_semantic_type_from_tydef(&stmt.ty, is_const);
+        let mut ty = self.get_semantic_type_from_tydef(&stmt.ty, is_const);
 
         let init_expr = stmt.init_expr.as_deref();
+        let mut measurement = match init_expr {
+            Some(syntax::ValueExpr::Measurement(measure_expr)) => {
+                Some(self.lower_measure_expr(measure_expr))
+            }
+            _ => None,
+        };
+        // An unsized `bit` initialized by measuring a register is sized to the register.
+        if let (Type::Bit(_), Some(Type::BitArray(dims, _))) =
+            (&ty, measurement.as_ref().map(|expr| &expr.ty))
+        {
+            ty = Type::BitArray(dims.clone(), false);
+        }
         let ty_span = stmt.ty.span();
         let stmt_span = stmt.span;
         let name = stmt.identifier.name.clone();
@@ -1002,13 +1095,21 @@ impl Lowerer {
         // process the symbol and init_expr gathering any errors
         let init_expr = match init_expr {
             Some(expr) => match expr {
-                syntax::ValueExpr::Expr(expr) => {
-                    let expr = self.lower_expr(expr);
-                    self.cast_expr_with_target_type_or_default(Some(expr), &ty, stmt_span)
-                }
-                syntax::ValueExpr::Measurement(measure_expr) => {
-                    let expr = self.lower_measure_expr(measure_expr);
-                    self.cast_expr_to_type(&ty, &expr)
+                syntax::ValueExpr::Expr(expr) => match &*expr.kind {
+                    syntax::ExprKind::Lit(syntax::Lit {
+                        kind: syntax::LiteralKind::Array(elements),
+                        ..
+                    }) if ty.is_array() => self.lower_array_literal(elements, &ty, expr.span),
+                    _ => {
+                        let expr = self.lower_expr(expr);
+                        self.cast_expr_with_target_type_or_default(Some(expr), &ty, stmt_span)
+                    }
+                },
+                syntax::ValueExpr::Measurement(_) => {
+                    let expr = measurement
+                        .take()
+                        .expect("measurement should have been lowered");
+                    self.cast_measurement_to_type(&ty, &expr)
                 }
             },
             None => self.cast_expr_with_target_type_or_default(None, &ty, stmt_span),
@@ -1024,17 +1125,63 @@ impl Lowerer {
         })
     }
 
+    /// Lowers an array literal initializing an array of type `ty`,
+    /// casting each element to the element type.
+    fn lower_array_literal(
+        &mut self,
+        elements: &syntax::List<syntax::Expr>,
+        ty: &Type,
+        span: Span,
+    ) -> semantic::Expr {
+        let element_ty = ty.get_indexed_type().unwrap_or(Type::Err);
+        if let Some(ArrayDimensions::One(size)) = ty.array_dims() {
+            if *size as usize != elements.len() {
+                self.push_semantic_error(SemanticErrorKind::ArrayLiteralSizeMismatch(
+                    *size,
+                    elements.len(),
+                    span,
+                ));
+            }
+        }
+        let elements = elements.iter().map(|element| {
+            let element = self.lower_expr(element);
+            let element_span = element.span;
+            // Keep const elements const so that const arrays can be checked.
+            let element_ty = if element.ty.is_const() {
+                element_ty.as_const()
+            } else {
+                element_ty.clone()
+            };
+            self.cast_expr_with_target_type_or_default(Some(element), &element_ty, element_span)
+        });
+        semantic::Expr {
+            span,
+            kind: Box::new(semantic::ExprKind::Lit(semantic::LiteralKind::Array(
+                list_from_iter(elements),
+            ))),
+            ty: ty.clone(),
+        }
+    }
+
     fn lower_const_decl(&mut self, stmt: &syntax::ConstantDeclStmt) -> semantic::StmtKind {
         let is_const = true;
         let ty = self.get_semantic_type_from_tydef(&stmt.ty, is_const);
         let ty_span = stmt.ty.span();
         let name = stmt.identifier.name.clone();
-        let qsharp_ty = self.convert_semantic_type_to_qsharp_type(&ty.clone(), stmt.ty.span());
+        let qsharp_ty = self
+            .convert_semantic_type_to_qsharp_type(&ty.clone(), stmt.ty.span())
+            .as_const_array();
         let init_expr = match &stmt.init_expr {
-            syntax::ValueExpr::Expr(expr) => {
-                let expr = self.lower_expr(expr);
-                self.cast_expr_with_target_type_or_default(Some(expr), &ty, stmt.span)
-            }
+            syntax::ValueExpr::Expr(expr) => match &*expr.kind {
+                syntax::ExprKind::Lit(syntax::Lit {
+                    kind: syntax::LiteralKind::Array(elements),
+                    ..
+                }) if ty.is_array() => self.lower_array_literal(elements, &ty, expr.span),
+                _ => {
+                    let expr = self.lower_expr(expr);
+                    self.cast_expr_with_target_type_or_default(Some(expr), &ty, stmt.span)
+                }
+            },
             syntax::ValueExpr::Measurement(measure_expr) => self.lower_measure_expr(measure_expr),
         };
 
@@ -1052,7 +1199,15 @@ impl Lowerer {
 
         let symbol_id = self.try_insert_or_get_existing_symbol_id(name, symbol);
 
-        if !init_expr.ty.is_const() {
+        // Array types don't track constness, so array literals are const
+        // when all of their elements are.
+        let is_const_init = match init_expr.kind.as_ref() {
+            semantic::ExprKind::Lit(semantic::LiteralKind::Array(elements)) => {
+                elements.iter().all(|element| element.ty.is_const())
+            }
+            _ => init_expr.ty.is_const(),
+        };
+        if !is_const_init {
             self.push_semantic_error(SemanticErrorKind::ExprMustBeConst(
                 "const decl init expr".to_string(),
                 init_expr.span,
@@ -1099,6 +1254,13 @@ impl Lowerer {
         }
 
         // 3. Build the return type.
+        let signature_span = Span {
+            lo: stmt.span.lo,
+            hi: stmt
+                .return_type
+                .as_ref()
+                .map_or(stmt.name.span.hi, |ty| ty.span.hi),
+        };
         let (return_ty, qsharp_return_ty) = if let Some(ty) = &stmt.return_type {
             let ty_span = ty.span;
             let tydef = syntax::TypeDef::Scalar(*ty.clone());
@@ -1136,20 +1298,29 @@ impl Lowerer {
         let symbol_id = self.try_insert_or_get_existing_symbol_id(name, symbol);
 
         // Push the scope where the def lives.
-        self.symbols.push_scope(ScopeKind::Function(return_ty));
+        self.symbols
+            .push_scope(ScopeKind::Function(return_ty, signature_span));
 
-        let params = param_symbols
+        let params: Box<[SymbolId]> = param_symbols
             .into_iter()
             .map(|symbol| {
                 let name = symbol.name.clone();
                 self.try_insert_or_get_existing_symbol_id(name, symbol)
             })
             .collect();
+        self.array_params = stmt
+            .params
+            .iter()
+            .zip(params.iter())
+            .filter(|(param, _)| matches!(&***param, syntax::TypedParameter::ArrayReference(..)))
+            .map(|(_, symbol_id)| *symbol_id)
+            .collect();
 
         let body = semantic::Block {
             span: stmt.body.span,
             stmts: list_from_iter(stmt.body.stmts.iter().map(|stmt| self.lower_stmt(stmt))),
         };
+        self.array_params.clear();
 
         // Pop the scope where the def lives.
         self.symbols.pop_scope();
@@ -1319,12 +1490,33 @@ impl Lowerer {
     }
 
     fn lower_for_stmt(&mut self, stmt: &syntax::ForStmt) -> semantic::StmtKind {
-        let set_declaration = self.lower_enumerable_set(&stmt.set_declaration);
+        let ty = self.get_semantic_type_from_scalar_ty(&stmt.ty, false);
+        let mut set_declaration = self.lower_enumerable_set(&stmt.set_declaration);
+        // The values of a discrete set are assigned to the explicitly typed loop variable.
+        // Const values stay const, so they can still be folded.
+        if let semantic::EnumerableSet::DiscreteSet(set) = &mut set_declaration {
+            let values = set
+                .values
+                .iter()
+                .map(|value| {
+                    let target_ty = if value.ty.is_const() {
+                        ty.as_const()
+                    } else {
+                        ty.clone()
+                    };
+                    self.cast_expr_with_target_type_or_default(
+                        Some((**value).clone()),
+                        &target_ty,
+                        value.span,
+                    )
+                })
+                .collect::<Vec<_>>();
+            set.values = list_from_iter(values);
+        }
 
         // Push scope where the loop variable lives.
         self.symbols.push_scope(ScopeKind::Loop);
 
-        let ty = self.get_semantic_type_from_scalar_ty(&stmt.ty, false);
         let qsharp_ty = self.convert_semantic_type_to_qsharp_type(&ty.clone(), stmt.ty.span);
         let symbol = Symbol::new(
             &stmt.ident.name,
@@ -1381,6 +1573,12 @@ impl Lowerer {
         //    in the symbol table and get its symbol_id & symbol.
         let name = expr.name.name.clone();
         let name_span = expr.name.span;
+        // Builtin math functions can be shadowed by user-defined functions.
+        if let Some(arity) = builtin_math_function_arity(&name) {
+            if self.symbols.get_symbol_by_name(&name).is_none() {
+                return self.lower_builtin_math_function_call(expr, arity);
+            }
+        }
         let (symbol_id, symbol) = self.try_get_existing_or_insert_err_symbol(name, name_span);
 
         let (params_ty, return_ty) = if let Type::Function(params_ty, return_ty) = &symbol.ty {
@@ -1432,6 +1630,48 @@ impl Lowerer {
         }
     }
 
+    /// Folds a call to a builtin math function to a const float literal.
+    /// The arguments must be const, since the functions aren't compiled to Q#.
+    fn lower_builtin_math_function_call(
+        &mut self,
+        expr: &syntax::FunctionCall,
+        arity: usize,
+    ) -> semantic::Expr {
+        if arity != expr.args.len() {
+            self.push_semantic_error(SemanticErrorKind::InvalidNumberOfClassicalArgs(
+                arity,
+                expr.args.len(),
+                expr.span,
+            ));
+            return err_expr!(Type::Err, expr.span);
+        }
+
+        let mut values = Vec::with_capacity(arity);
+        for arg in &expr.args {
+            let arg = self.lower_expr(arg);
+            if !arg.ty.is_const() {
+                self.push_unsupported_error_message(
+                    "builtin math function calls with non-const arguments",
+                    arg.span,
+                );
+                return err_expr!(Type::Err, expr.span);
+            }
+            let arg = self.cast_expr_to_type(&Type::Float(None, true), &arg);
+            let Some(semantic::LiteralKind::Float(value)) = arg.const_eval(self) else {
+                return err_expr!(Type::Err, expr.span);
+            };
+            values.push(value);
+        }
+
+        let value = eval_builtin_math_function(&expr.name.name, &values)
+            .expect("arity of builtin math function should have been checked");
+        semantic::Expr {
+            span: expr.span,
+            kind: Box::new(semantic::ExprKind::Lit(semantic::LiteralKind::Float(value))),
+            ty: Type::Float(None, true),
+        }
+    }
+
     fn lower_gate_call_stmt(&mut self, stmt: &syntax::GateCall) -> semantic::StmtKind {
         // 1. Lower all the fields:
         //   1.1. Lower the modifiers.
@@ -1520,11 +1760,43 @@ impl Lowerer {
             ));
         }
 
-        // 6. Return:
-        //   6.1. Gate symbol_id.
-        //   6.2. All controls made explicit.
-        //   6.3. Classical args.
-        //   6.4. Quantum args in the order expected by the compiler.
+        // 6. Check that non-integer powers are only applied to rotation gates,
+        //    the compiler applies them by scaling the rotation angle.
+        for modifier in &modifiers {
+            if let semantic::GateModifierKind::Pow(expr) = &modifier.kind {
+                if matches!(expr.ty, Type::Float(..)) && !is_rotation_gate(&symbol.name) {
+                    self.push_semantic_error(SemanticErrorKind::FractionalPowerOfNonRotationGate(
+                        symbol.name.clone(),
+                        modifier.span,
+                    ));
+                }
+            }
+        }
+
+        // 7. Check that the registers the gate is broadcast over have the same size,
+        //    the compiler applies the gate to their qubits element-wise.
+        let mut register_sizes = qubits.iter().filter_map(|q| match &q.kind {
+            semantic::GateOperandKind::Expr(expr) => match &expr.ty {
+                Type::QubitArray(ArrayDimensions::One(size)) => Some((*size, q.span)),
+                _ => None,
+            },
+            _ => None,
+        });
+        if let Some((size, _)) = register_sizes.next() {
+            for (other_size, span) in register_sizes {
+                if other_size != size {
+                    self.push_semantic_error(SemanticErrorKind::BroadcastRegisterSizeMismatch(
+                        size, other_size, span,
+                    ));
+                }
+            }
+        }
+
+        // 8. Return:
+        //   8.1. Gate symbol_id.
+        //   8.2. All controls made explicit.
+        //   8.3. Classical args.
+        //   8.4. Quantum args in the order expected by the compiler.
         modifiers.reverse();
         let modifiers = list_from_iter(modifiers);
         semantic::StmtKind::GateCall(semantic::GateCall {
@@ -1594,8 +1866,24 @@ impl Lowerer {
             return Some(1);
         };
 
+        let error_count = self.errors.len();
         let expr = self.lower_expr(expr);
 
+        // Errors in the count itself, such as an undefined symbol, are already
+        // reported, and its type says nothing about what the user meant.
+        if expr.ty == Type::Err || self.errors.len() > error_count {
+            return None;
+        }
+
+        // The count must be an integer; a float or bool would otherwise be silently cast.
+        if !matches!(expr.ty, Type::Int(..) | Type::UInt(..)) {
+            self.push_semantic_error(SemanticErrorKind::ExprMustBeInt(
+                "ctrl modifier argument".into(),
+                expr.span,
+            ));
+            return None;
+        }
+
         let target_ty = &Type::UInt(None, true);
         let Some(expr) = Self::try_cast_expr_to_type(target_ty, &expr) else {
             self.push_invalid_cast_error(target_ty, &expr.ty, expr.span);
@@ -1694,6 +1982,73 @@ impl Lowerer {
         }
     }
 
+    /// Gate bodies are unitary, so measurements, resets, and classical I/O
+    /// are reported, including those nested in control flow.
+    fn check_gate_body_stmt(&mut self, stmt: &semantic::Stmt) {
+        let invalid = match &*stmt.kind {
+            semantic::StmtKind::Block(block) => {
+                block
+                    .stmts
+                    .iter()
+                    .for_each(|s| self.check_gate_body_stmt(s));
+                None
+            }
+            semantic::StmtKind::For(stmt) => {
+                self.check_gate_body_stmt(&stmt.body);
+                None
+            }
+            semantic::StmtKind::If(stmt) => {
+                self.check_gate_body_stmt(&stmt.if_body);
+                if let Some(else_body) = &stmt.else_body {
+                    self.check_gate_body_stmt(else_body);
+                }
+                None
+            }
+            semantic::StmtKind::Switch(stmt) => {
+                let blocks = stmt.cases.iter().map(|case| &case.block);
+                for block in blocks.chain(&stmt.default) {
+                    block
+                        .stmts
+                        .iter()
+                        .for_each(|s| self.check_gate_body_stmt(s));
+                }
+                None
+            }
+            semantic::StmtKind::WhileLoop(stmt) => {
+                self.check_gate_body_stmt(&stmt.body);
+                None
+            }
+            semantic::StmtKind::MeasureArrow(_) => {
+                Some(SemanticErrorKind::MeasurementInGate(stmt.span))
+            }
+            semantic::StmtKind::Assign(semantic::AssignStmt { rhs: expr, .. })
+            | semantic::StmtKind::IndexedAssign(semantic::IndexedAssignStmt {
+                rhs: expr, ..
+            })
+            | semantic::StmtKind::ExprStmt(semantic::ExprStmt { expr, .. })
+                if matches!(*expr.kind, semantic::ExprKind::Measure(_)) =>
+            {
+                Some(SemanticErrorKind::MeasurementInGate(stmt.span))
+            }
+            semantic::StmtKind::ClassicalDecl(decl)
+                if matches!(*decl.init_expr.kind, semantic::ExprKind::Measure(_)) =>
+            {
+                Some(SemanticErrorKind::MeasurementInGate(stmt.span))
+            }
+            semantic::StmtKind::Reset(_) => Some(SemanticErrorKind::ResetInGate(stmt.span)),
+            semantic::StmtKind::InputDeclaration(_) | semantic::StmtKind::OutputDeclaration(_) => {
+                Some(SemanticErrorKind::InvalidStatementInGate(
+                    "input and output declarations".to_string(),
+                    stmt.span,
+                ))
+            }
+            _ => None,
+        };
+        if let Some(invalid) = invalid {
+            self.push_semantic_error(invalid);
+        }
+    }
+
     fn lower_pragma(&mut self, stmt: &syntax::Pragma) -> semantic::StmtKind {
         self.push_unimplemented_error_message("pragma stmt", stmt.span);
         semantic::StmtKind::Err
@@ -1707,6 +2062,13 @@ impl Lowerer {
             self.push_semantic_error(kind);
         }
 
+        // QASM3 gates must operate on at least one qubit.
+        if stmt.qubits.is_empty() {
+            let kind =
+                SemanticErrorKind::GateWithNoQubits(stmt.ident.name.to_string(), stmt.ident.span);
+            self.push_semantic_error(kind);
+        }
+
         // 2. Push the gate symbol to the symbol table.
         #[allow(clippy::cast_possible_truncation)]
         let classical_arity = stmt
@@ -1769,6 +2131,9 @@ impl Lowerer {
             span: stmt.body.span,
             stmts: list_from_iter(stmt.body.stmts.iter().map(|stmt| self.lower_stmt(stmt))),
         };
+        for stmt in &body.stmts {
+            self.check_gate_body_stmt(stmt);
+        }
 
         // Pop the scope where the gate definition lives.
         self.symbols.pop_scope();
@@ -1784,6 +2149,18 @@ impl Lowerer {
     }
 
     fn lower_quantum_decl(&mut self, stmt: &syntax::QubitDeclaration) -> semantic::StmtKind {
+        // QASM3 semantics only allow qubit declarations in the global scope.
+        if !self.symbols.is_current_scope_global() {
+            let kind = SemanticErrorKind::QuantumDeclarationInNonGlobalScope(stmt.span);
+            self.push_semantic_error(kind);
+        }
+        if stmt.is_old_style {
+            self.push_semantic_warning(SemanticErrorKind::OldStyleDeclaration(
+                "qreg".to_string(),
+                "qubit".to_string(),
+                stmt.span,
+            ));
+        }
         // If there wasn't an explicit size, infer the size to be 1.
         let (ty, size_and_span) = if let Some(size_expr) = &stmt.size {
             let size_expr = self.lower_expr(size_expr);
@@ -1886,7 +2263,22 @@ impl Lowerer {
                     );
                     return semantic::StmtKind::Err;
                 }
-                *expr = Box::new(self.cast_expr_to_type(&ty, expr));
+                if matches!(expr.ty, Type::Err) {
+                    // The error was already reported when lowering the expression.
+                } else if let Some(cast_expr) = Self::try_cast_expr_to_type(&ty, expr) {
+                    **expr = cast_expr;
+                } else {
+                    let signature_span = self
+                        .symbols
+                        .get_subroutine_signature_span()
+                        .expect("return type should come from a subroutine scope");
+                    self.push_semantic_error(SemanticErrorKind::ReturnTypeMismatch(
+                        format!("{:?}", expr.ty),
+                        format!("{ty:?}"),
+                        expr.span,
+                        signature_span,
+                    ));
+                }
             }
         }
 
@@ -2176,8 +2568,7 @@ impl Lowerer {
         array_ty: &syntax::ArrayType,
         _is_const: bool,
     ) -> crate::semantic::types::Type {
-        self.push_unimplemented_error_message("semantic type from array type", array_ty.span);
-        crate::semantic::types::Type::Err
+        self.get_semantic_array_type(&array_ty.base_type, &array_ty.dimensions, array_ty.span)
     }
 
     fn get_semantic_type_from_array_reference_ty(
@@ -2185,11 +2576,50 @@ impl Lowerer {
         array_ref_ty: &syntax::ArrayReferenceType,
         _is_const: bool,
     ) -> crate::semantic::types::Type {
-        self.push_unimplemented_error_message(
-            "semantic type from array refence type",
+        self.get_semantic_array_type(
+            &array_ref_ty.base_type,
+            &array_ref_ty.dimensions,
             array_ref_ty.span,
-        );
-        crate::semantic::types::Type::Err
+        )
+    }
+
+    /// Builds the type of an array with the given base type and dimensions.
+    /// Only one-dimensional arrays are supported for now.
+    fn get_semantic_array_type(
+        &mut self,
+        base_type: &syntax::ArrayBaseTypeKind,
+        dimensions: &syntax::List<syntax::Expr>,
+        span: Span,
+    ) -> crate::semantic::types::Type {
+        let [size] = &**dimensions else {
+            self.push_unimplemented_error_message("multidimensional arrays", span);
+            return crate::semantic::types::Type::Err;
+        };
+        let Some(size) = self.const_eval_array_size_designator_from_expr(size) else {
+            return crate::semantic::types::Type::Err;
+        };
+        let dims = ArrayDimensions::One(size);
+
+        let kind = match base_type {
+            syntax::ArrayBaseTypeKind::Int(ty) => syntax::ScalarTypeKind::Int(ty.clone()),
+            syntax::ArrayBaseTypeKind::UInt(ty) => syntax::ScalarTypeKind::UInt(ty.clone()),
+            syntax::ArrayBaseTypeKind::Float(ty) => syntax::ScalarTypeKind::Float(ty.clone()),
+            syntax::ArrayBaseTypeKind::Complex(ty) => syntax::ScalarTypeKind::Complex(ty.clone()),
+            syntax::ArrayBaseTypeKind::Angle(ty) => syntax::ScalarTypeKind::Angle(ty.clone()),
+            syntax::ArrayBaseTypeKind::BoolType => syntax::ScalarTypeKind::BoolType,
+            syntax::ArrayBaseTypeKind::Duration => syntax::ScalarTypeKind::Duration,
+        };
+        let scalar_ty = syntax::ScalarType { span, kind };
+        match self.get_semantic_type_from_scalar_ty(&scalar_ty, false) {
+            Type::Int(width, _) => Type::IntArray(width, dims),
+            Type::UInt(width, _) => Type::UIntArray(width, dims),
+            Type::Float(width, _) => Type::FloatArray(width, dims),
+            Type::Complex(width, _) => Type::ComplexArray(width, dims),
+            Type::Angle(width, _) => Type::AngleArray(width, dims),
+            Type::Bool(_) => Type::BoolArray(dims),
+            Type::Duration(_) => Type::DurationArray(dims),
+            _ => Type::Err,
+        }
     }
 
     fn cast_expr_with_target_type_or_default(
@@ -2252,10 +2682,18 @@ impl Lowerer {
             measure_token_span: expr.measure_token_span,
             operand: self.lower_gate_operand(&expr.operand),
         };
+        // Measuring a register produces one bit per qubit.
+        let ty = match &measurement.operand.kind {
+            semantic::GateOperandKind::Expr(expr) => match &expr.ty {
+                Type::QubitArray(dims) => Type::BitArray(dims.clone(), false),
+                _ => Type::Bit(false),
+            },
+            _ => Type::Bit(false),
+        };
         semantic::Expr {
             span: expr.span,
             kind: Box::new(semantic::ExprKind::Measure(measurement)),
-            ty: Type::Bit(false),
+            ty,
         }
     }
 
@@ -2314,34 +2752,31 @@ impl Lowerer {
                 0.0,
                 semantic::TimeUnit::Ns,
             ))),
-            Type::BoolArray(_) => {
-                self.push_unimplemented_error_message("bool array default value", span);
-                None
-            }
-            Type::DurationArray(_) => {
-                self.push_unimplemented_error_message("duration array default value", span);
-                None
-            }
-            Type::AngleArray(_, _) => {
-                self.push_unimplemented_error_message("angle array default value", span);
-                None
-            }
-            Type::ComplexArray(_, _) => {
-                self.push_unimplemented_error_message("complex array default value", span);
-                None
-            }
-            Type::FloatArray(_, _) => {
-                self.push_unimplemented_error_message("float array default value", span);
-                None
-            }
-            Type::IntArray(_, _) => {
-                self.push_unimplemented_error_message("int array default value", span);
-                None
-            }
-            Type::UIntArray(_, _) => {
-                self.push_unimplemented_error_message("uint array default value", span);
-                None
-            }
+            Type::BoolArray(dims)
+            | Type::DurationArray(dims)
+            | Type::AngleArray(_, dims)
+            | Type::ComplexArray(_, dims)
+            | Type::FloatArray(_, dims)
+            | Type::IntArray(_, dims)
+            | Type::UIntArray(_, dims) => match dims {
+                ArrayDimensions::One(size) => {
+                    let element_ty = ty.get_indexed_type().unwrap_or(Type::Err);
+                    let elements = (0..*size).map(|_| self.get_default_value(&element_ty, span));
+                    Some(Expr {
+                        span: Span::default(),
+                        kind: Box::new(ExprKind::Lit(LiteralKind::Array(list_from_iter(elements)))),
+                        ty: ty.clone(),
+                    })
+                }
+                ArrayDimensions::Err => None,
+                _ => {
+                    self.push_unimplemented_error_message(
+                        "multidimensional array default value",
+                        span,
+                    );
+                    None
+                }
+            },
             Type::Gate(_, _) | Type::Function(..) | Type::Range | Type::Set | Type::Void => {
                 let message = format!("default values for {ty:?}");
                 self.push_unsupported_error_message(message, span);
@@ -3148,7 +3583,8 @@ impl Lowerer {
     fn lower_index_expr(&mut self, expr: &syntax::IndexExpr) -> semantic::Expr {
         let collection = self.lower_expr(&expr.collection);
         let index = self.lower_index_element(&expr.index);
-        let indexed_ty = self.get_indexed_type(&collection.ty, expr.span, 1);
+        let num_indices = usize::from(!is_range_index(&expr.index));
+        let indexed_ty = self.get_indexed_type(&collection.ty, expr.span, num_indices);
 
         semantic::Expr {
             span: expr.span,
@@ -3193,6 +3629,109 @@ impl Lowerer {
         indexed_ty
     }
 
+    /// If `index` is a const range into a one-dimensional bit or qubit register,
+    /// returns the type of the selected slice, validating that the range is in bounds.
+    /// Returns `None` for any other index or type, or if the range isn't const.
+    fn get_sliced_type(&mut self, ty: &Type, index: &semantic::IndexElement) -> Option<Type> {
+        let semantic::IndexElement::IndexSet(set) = index else {
+            return None;
+        };
+        let [item] = &*set.values else {
+            return None;
+        };
+        let semantic::IndexSetItem::RangeDefinition(range) = &**item else {
+            return None;
+        };
+        let size = match ty {
+            Type::BitArray(ArrayDimensions::One(size), _)
+            | Type::QubitArray(ArrayDimensions::One(size)) => *size,
+            _ => return None,
+        };
+        let len = self.const_eval_slice_len(range, size)?;
+        Some(match ty {
+            Type::QubitArray(_) => Type::QubitArray(ArrayDimensions::One(len)),
+            _ => Type::BitArray(ArrayDimensions::One(len), false),
+        })
+    }
+
+    /// Computes the number of elements a const range selects from a register of the given size.
+    fn const_eval_slice_len(
+        &mut self,
+        range: &semantic::RangeDefinition,
+        size: u32,
+    ) -> Option<u32> {
+        let mut eval_bound = |expr: Option<&semantic::Expr>, default: i64| match expr {
+            None => Some(default),
+            Some(expr) if expr.ty.is_const() => match expr.const_eval(self)? {
+                semantic::LiteralKind::Int(val) => Some(val),
+                _ => None,
+            },
+            Some(_) => None,
+        };
+        let start = eval_bound(range.start.as_ref(), 0)?;
+        let step = eval_bound(range.step.as_ref(), 1)?;
+        let end = eval_bound(range.end.as_ref(), i64::from(size) - 1)?;
+
+        let len = match step.cmp(&0) {
+            std::cmp::Ordering::Greater if start <= end => end.checked_sub(start)? / step + 1,
+            std::cmp::Ordering::Less if start >= end => {
+                start.checked_sub(end)? / step.checked_neg()? + 1
+            }
+            _ => 0,
+        };
+        if len > 0 {
+            // The range end is only an upper bound, so the last selected element
+            // is checked instead, which falls short of the end for uneven steps.
+            let last = start + step * (len - 1);
+            for (bound, expr) in [(start, &range.start), (last, &range.end)] {
+                if bound < 0 || bound >= i64::from(size) {
+                    let span = expr.as_ref().map_or(range.span, |expr| expr.span);
+                    self.push_semantic_error(SemanticErrorKind::IndexOutOfBounds(
+                        bound, size, span,
+                    ));
+                    return None;
+                }
+            }
+        }
+        u32::try_from(len).ok()
+    }
+
+    /// Casts the result of a measurement to `ty`, reporting a size mismatch rather than
+    /// a failed cast when a register is measured into a bit register of another size.
+    fn cast_measurement_to_type(&mut self, ty: &Type, expr: &semantic::Expr) -> semantic::Expr {
+        if let (
+            Type::BitArray(ArrayDimensions::One(bits), _),
+            Type::BitArray(ArrayDimensions::One(qubits), _),
+        ) = (ty, &expr.ty)
+        {
+            if bits != qubits {
+                self.push_semantic_error(SemanticErrorKind::MeasurementSizeMismatch(
+                    *bits, *qubits, expr.span,
+                ));
+                return err_expr!(ty.clone(), expr.span);
+            }
+        }
+        self.cast_expr_to_type(ty, expr)
+    }
+
+    /// Reports an error if a bit array assigned to a register slice has a different length.
+    /// Returns whether the lengths match.
+    fn check_slice_length(&mut self, slice_ty: &Type, rhs: &semantic::Expr, span: Span) -> bool {
+        if let (
+            Type::BitArray(ArrayDimensions::One(lhs_len), _),
+            Type::BitArray(ArrayDimensions::One(rhs_len), _),
+        ) = (slice_ty, &rhs.ty)
+        {
+            if lhs_len != rhs_len {
+                self.push_semantic_error(SemanticErrorKind::SliceLengthMismatch(
+                    *lhs_len, *rhs_len, span,
+                ));
+                return false;
+            }
+        }
+        true
+    }
+
     /// Lower an indexed identifier expression
     /// This is an identifier with *zero* or more indices
     /// we tranform this into two different cases:
@@ -3220,8 +3759,19 @@ impl Lowerer {
         };
 
         let ty = lhs_symbol.ty.clone();
-        // use the supplied number of indicies rathar than the number of indicies we lowered
-        let ty = self.get_indexed_type(&ty, indexed_ident.span, indexed_ident.indices.len());
+        // use the supplied number of indicies rathar than the number of indicies we lowered.
+        // range indices select a slice, so they don't reduce the dimensionality.
+        let num_indices = indexed_ident
+            .indices
+            .iter()
+            .filter(|index| !is_range_index(index))
+            .count();
+        let ty = match &*indices {
+            [index] => self
+                .get_sliced_type(&ty, index)
+                .unwrap_or_else(|| self.get_indexed_type(&ty, indexed_ident.span, num_indices)),
+            _ => self.get_indexed_type(&ty, indexed_ident.span, num_indices),
+        };
 
         semantic::Expr {
             span: indexed_ident.span,
@@ -3238,13 +3788,16 @@ impl Lowerer {
         }
     }
 
-    #[allow(clippy::unused_self)]
     fn lower_gate_operand(&mut self, operand: &syntax::GateOperand) -> semantic::GateOperand {
         let kind = match &operand.kind {
             syntax::GateOperandKind::IndexedIdent(indexed_ident) => {
-                semantic::GateOperandKind::Expr(Box::new(
-                    self.lower_indexed_ident_expr(indexed_ident),
-                ))
+                if let Some(expr) = self.resolve_alias_operand(indexed_ident) {
+                    semantic::GateOperandKind::Expr(Box::new(expr))
+                } else {
+                    semantic::GateOperandKind::Expr(Box::new(
+                        self.lower_indexed_ident_expr(indexed_ident),
+                    ))
+                }
             }
             syntax::GateOperandKind::HardwareQubit(hw) => {
                 semantic::GateOperandKind::HardwareQubit(Self::lower_hardware_qubit(hw))
@@ -3257,6 +3810,22 @@ impl Lowerer {
         }
     }
 
+    /// If the operand is an unindexed reference to a quantum `let` alias,
+    /// returns the aliased expression so that the gate is applied to the
+    /// underlying qubits.
+    fn resolve_alias_operand(
+        &self,
+        indexed_ident: &syntax::IndexedIdent,
+    ) -> Option<semantic::Expr> {
+        if !indexed_ident.indices.is_empty() {
+            return None;
+        }
+        let (symbol_id, _) = self.symbols.get_symbol_by_name(&indexed_ident.name.name)?;
+        let mut expr = self.alias_exprs.get(&symbol_id)?.clone();
+        expr.span = indexed_ident.span;
+        Some(expr)
+    }
+
     fn lower_hardware_qubit(hw: &syntax::HardwareQubit) -> semantic::HardwareQubit {
         semantic::HardwareQubit {
             span: hw.span,
@@ -3323,6 +3892,13 @@ impl Lowerer {
         self.errors.push(error);
     }
 
+    /// Pushes a semantic warning with the given kind.
+    pub fn push_semantic_warning(&mut self, kind: SemanticErrorKind) {
+        let kind = crate::ErrorKind::Semantic(crate::semantic::Error(kind));
+        let warning = self.create_err(kind);
+        self.warnings.push(warning);
+    }
+
     /// Pushes a const eval error with the given kind.
     pub fn push_const_eval_error(&mut self, kind: ConstEvalError) {
         let kind = crate::ErrorKind::ConstEval(kind);
@@ -3412,3 +3988,33 @@ fn try_get_qsharp_name_and_implicit_modifiers<S: AsRef<str>>(
         _ => None,
     }
 }
+
+/// Returns true if `gate_name` is a standard gate taking a single rotation angle,
+/// so that raising the gate to any power is the same as scaling the angle.
+fn is_rotation_gate(gate_name: &str) -> bool {
+    matches!(
+        gate_name,
+        "rx" | "ry"
+            | "rz"
+            | "p"
+            | "phase"
+            | "u1"
+            | "cp"
+            | "cu1"
+            | "rxx"
+            | "ryy"
+            | "rzz"
+            | "rzx"
+            | "gphase"
+    )
+}
+
+fn is_range_index(index: &syntax::IndexElement) -> bool {
+    match index {
+        syntax::IndexElement::IndexSet(set) => matches!(
+            &*set.values,
+            [item] if matches!(**item, syntax::IndexSetItem::RangeDefinition(_))
+        ),
+        syntax::IndexElement::DiscreteSet(_) => false,
+    }
+}
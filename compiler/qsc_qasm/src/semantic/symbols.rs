@@ -275,8 +275,9 @@ pub enum ScopeKind {
     /// This is the only scope where gates, qubits, and arrays can be declared.
     Global,
     /// Function scopes need to remember their return type, so that `return` stmts
-    /// can do an implicit cast to the correct type, if any;
-    Function(Rc<Type>),
+    /// can do an implicit cast to the correct type, if any, and the span of their
+    /// signature, so that return type mismatches can point at it.
+    Function(Rc<Type>, Span),
     Gate,
     Block,
     Loop,
@@ -513,13 +514,25 @@ impl SymbolTable {
     #[must_use]
     pub fn get_subroutine_return_ty(&self) -> Option<Rc<Type>> {
         for scope in self.scopes.iter().rev() {
-            if let ScopeKind::Function(return_ty) = &scope.kind {
+            if let ScopeKind::Function(return_ty, _) = &scope.kind {
                 return Some(return_ty.clone());
             }
         }
         None
     }
 
+    /// Returns `None` if the current scope is not rooted in a subroutine.
+    /// Otherwise, returns the span of the subroutine signature.
+    #[must_use]
+    pub fn get_subroutine_signature_span(&self) -> Option<Span> {
+        for scope in self.scopes.iter().rev() {
+            if let ScopeKind::Function(_, signature_span) = &scope.kind {
+                return Some(*signature_span);
+            }
+        }
+        None
+    }
+
     #[must_use]
     pub fn is_scope_rooted_in_gate_or_subroutine(&self) -> bool {
         self.scopes
@@ -26,3 +26,40 @@ fn array_with_no_init_expr() {
                 size_span: [7-8]"#]],
     );
 }
+
+#[test]
+fn qubit_in_global_scope() {
+    check_stmt_kind(
+        "qubit q;",
+        &expect![[r#"
+            QubitDeclaration [0-8]:
+                symbol_id: 8"#]],
+    );
+}
+
+#[test]
+fn not_allowed_in_non_global_scope() {
+    check_stmt_kind(
+        "{ qubit q; }",
+        &expect![[r#"
+            Program:
+                version: <none>
+                statements:
+                    Stmt [0-12]:
+                        annotations: <empty>
+                        kind: Block [0-12]:
+                            Stmt [2-10]:
+                                annotations: <empty>
+                                kind: QubitDeclaration [2-10]:
+                                    symbol_id: 8
+
+            [Qasm.Lowerer.QuantumDeclarationInNonGlobalScope
+
+              x quantum declarations must be done in global scope
+               ,-[test:1:3]
+             1 | { qubit q; }
+               :   ^^^^^^^^
+               `----
+            ]"#]],
+    );
+}
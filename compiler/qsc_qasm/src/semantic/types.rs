@@ -171,6 +171,22 @@ impl Type {
         }
     }
 
+    #[must_use]
+    pub fn array_dims(&self) -> Option<&ArrayDimensions> {
+        match self {
+            Type::AngleArray(_, dims)
+            | Type::BitArray(dims, _)
+            | Type::BoolArray(dims)
+            | Type::DurationArray(dims)
+            | Type::ComplexArray(_, dims)
+            | Type::FloatArray(_, dims)
+            | Type::IntArray(_, dims)
+            | Type::QubitArray(dims)
+            | Type::UIntArray(_, dims) => Some(dims),
+            _ => None,
+        }
+    }
+
     /// Get the indexed type of a given type.
     /// For example, if the type is `Int[2][3]`, the indexed type is `Int[2]`.
     /// If the type is `Int[2]`, the indexed type is `Int`.
@@ -816,7 +832,7 @@ pub(crate) fn binary_op_is_supported_for_types(op: BinOp, lhs_ty: &Type, rhs_ty:
             base_types_equal(lhs_ty, rhs_ty)
                 && matches!(
                     lhs_ty,
-                    Int(..) | UInt(..) | Angle(..) | Bit(..) | BitArray(..)
+                    Int(..) | UInt(..) | Float(..) | Angle(..) | Bit(..) | BitArray(..)
                 )
         }
 
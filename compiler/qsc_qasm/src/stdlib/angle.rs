@@ -308,8 +308,8 @@ impl DivAssign<u64> for Angle {
 impl TryInto<f64> for Angle {
     type Error = &'static str;
 
-    /// Angle to float cast is not allowed in QASM3.
-    /// This function is only meant to be used in unit tests.
+    /// Angle to float cast is only allowed explicitly in QASM3,
+    /// yielding the value of the angle in radians.
     fn try_into(self) -> Result<f64, Self::Error> {
         if self.size > 64 {
             return Err("Size exceeds 64 bits");
@@ -49,6 +49,10 @@ pub const STD_LIB: &[(&str, &str)] = &[
         "openqasm-library-source:QasmStd/Angle.qs",
         include_str!("QasmStd/src/QasmStd/Angle.qs"),
     ),
+    (
+        "openqasm-library-source:QasmStd/Arithmetic.qs",
+        include_str!("QasmStd/src/QasmStd/Arithmetic.qs"),
+    ),
     (
         "openqasm-library-source:QasmStd/Convert.qs",
         include_str!("QasmStd/src/QasmStd/Convert.qs"),
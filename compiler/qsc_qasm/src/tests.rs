@@ -18,6 +18,7 @@ use qsc::{
 };
 use qsc_hir::hir::PackageId;
 use qsc_passes::PackageType;
+use rustc_hash::FxHashSet;
 use std::{path::Path, sync::Arc};
 
 pub(crate) mod assignment;
@@ -75,7 +76,29 @@ pub(crate) fn generate_qir_from_ast(
     )
 }
 
-fn compile<S>(source: S) -> miette::Result<QasmCompileUnit, Vec<Report>>
+/// Like [`generate_qir_from_ast`], but returns the RIR program that QIR is generated from.
+pub(crate) fn generate_program_from_ast(
+    ast_package: Package,
+    source_map: SourceMap,
+    profile: Profile,
+) -> Result<qsc::codegen::qir::Program, Vec<Error>> {
+    let capabilities = profile.into();
+    let (stdid, qasmid, mut store) = package_store_with_qasm(capabilities);
+    let dependencies = vec![
+        (PackageId::CORE, None),
+        (stdid, None),
+        (qasmid, Some("QasmStd".into())),
+    ];
+    qsc::codegen::qir::get_program_from_ast(
+        &mut store,
+        &dependencies,
+        ast_package,
+        source_map,
+        capabilities,
+    )
+}
+
+pub(crate) fn compile<S>(source: S) -> miette::Result<QasmCompileUnit, Vec<Report>>
 where
     S: AsRef<str>,
 {
@@ -109,8 +132,12 @@ where
         source_map: res.source_map,
         config,
         stmts: vec![],
+        num_hardware_qubits: 0,
+        in_callable_body: false,
         symbols: res.symbols,
         errors: res.errors,
+        warnings: res.warnings,
+        gates_without_functors: FxHashSet::default(),
     };
 
     let unit = compiler.compile(&program);
@@ -183,8 +210,12 @@ where
         source_map: res.source_map,
         config,
         stmts: vec![],
+        num_hardware_qubits: 0,
+        in_callable_body: false,
         symbols: res.symbols,
         errors: res.errors,
+        warnings: res.warnings,
+        gates_without_functors: FxHashSet::default(),
     };
 
     let unit = compiler.compile(&program);
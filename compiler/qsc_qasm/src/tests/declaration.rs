@@ -10,13 +10,18 @@ mod float;
 mod gate;
 mod integer;
 mod io;
+mod old_style;
 mod qubit;
 mod unsigned_integer;
 
 use crate::{
-    tests::{compile_fragments, compile_with_config, fail_on_compilation_errors},
-    CompilerConfig, OutputSemantics, ProgramType, QubitSemantics,
+    tests::{
+        compile_fragments, compile_with_config, fail_on_compilation_errors,
+        qsharp_from_qasm_compilation,
+    },
+    CompilerConfig, OutputSemantics, ProgramType, QubitSemantics, Seconds,
 };
+use expect_test::expect;
 use miette::Report;
 use qsc::target::Profile;
 
@@ -59,29 +64,64 @@ fn duration_literal() -> miette::Result<(), Vec<Report>> {
         duration dur4 = 1s;
     ";
 
-    let config = CompilerConfig::new(
+    let mut config = CompilerConfig::new(
         QubitSemantics::Qiskit,
         OutputSemantics::OpenQasm,
         ProgramType::Fragments,
         None,
         None,
     );
+    config.dt_seconds = Some(Seconds(1e-9));
     let unit = compile_with_config(source, config).expect("parse failed");
-    for error in &unit.errors {
-        println!("{error}");
-    }
-    assert_eq!(unit.errors.len(), 10);
-    for error in &unit.errors {
-        assert!([
-            "duration type values are not supported",
-            "timing literals are not supported",
-        ]
-        .contains(&error.to_string().as_str()));
-    }
+    fail_on_compilation_errors(&unit);
+    Ok(())
+}
 
+#[test]
+fn dt_duration_is_normalized_with_configured_cycle_time() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        duration d = 100dt;
+    ";
+
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.dt_seconds = Some(Seconds(0.5));
+    let unit = compile_with_config(source, config).expect("parse failed");
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable d = 50.;
+    "#]]
+    .assert_eq(&qsharp);
     Ok(())
 }
 
+#[test]
+fn dt_duration_without_configured_cycle_time_fails() {
+    let source = "
+        duration d = 100dt;
+    ";
+
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    let unit = compile_with_config(source, config).expect("parse failed");
+    let errors: Vec<_> = unit.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        errors,
+        vec!["durations in dt units require the cycle time dt to be configured"]
+    );
+}
+
 #[test]
 fn stretch() {
     let source = "
@@ -4,7 +4,8 @@
 mod bit;
 mod qubit;
 
-use crate::tests::{compile_fragments, fail_on_compilation_errors};
+use crate::tests::{compile_fragments, compile_qasm_stmt_to_qsharp, fail_on_compilation_errors};
+use expect_test::expect;
 use miette::Report;
 
 #[test]
@@ -32,3 +33,17 @@ fn arrays() -> miette::Result<(), Vec<Report>> {
     fail_on_compilation_errors(&unit);
     Ok(())
 }
+
+#[test]
+fn const_angle_array_is_declared_with_let() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        const array[angle[32], 2] angles = {pi, pi/2};
+    ";
+
+    let qsharp = compile_qasm_stmt_to_qsharp(source)?;
+    expect![[r#"
+        let angles = [QasmStd.Angle.DoubleAsAngle(Std.Math.PI(), 32), QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 2., 32)];
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
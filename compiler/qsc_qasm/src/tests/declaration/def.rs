@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::compile_qasm_stmt_to_qsharp;
+use crate::tests::{compile_qasm_stmt_to_qsharp, compile_qasm_to_qsharp, compile_with_config};
+use crate::{CompilerConfig, OutputSemantics, ProgramType, QubitSemantics};
 use expect_test::expect;
 use miette::Report;
 
@@ -184,6 +185,37 @@ fn capturing_external_variables_const_evaluate_them() -> miette::Result<(), Vec<
     Ok(())
 }
 
+#[test]
+fn return_expr_with_mismatched_type_fails() {
+    let source = r#"
+        def f(complex c) -> int {
+            return c;
+        }
+    "#;
+
+    let Err(errors) = compile_qasm_stmt_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.ReturnTypeMismatch
+
+          x cannot return a value of type Complex(None, false) from a subroutine
+          | returning Int(None, false)
+           ,-[Test.qasm:2:9]
+         1 | 
+         2 |         def f(complex c) -> int {
+           :         ^^^^^^^^^^^|^^^^^^^^^^^
+           :                    `-- subroutine signature
+         3 |             return c;
+           :                    |
+           :                    `-- returned value
+         4 |         }
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
 #[test]
 fn capturing_non_const_external_variable_fails() {
     let source = r#"
@@ -207,15 +239,6 @@ fn capturing_non_const_external_variable_fails() {
            :                    ^
          5 |         }
            `----
-        , Qasm.Lowerer.CannotCast
-
-          x cannot cast expression of type Err to type Int(None, false)
-           ,-[Test.qasm:4:20]
-         3 |         def f() -> int {
-         4 |             return a;
-           :                    ^
-         5 |         }
-           `----
         ]"#]]
     .assert_eq(&format!("{errors:?}"));
 }
@@ -255,3 +278,165 @@ fn capturing_non_const_evaluatable_external_variable_fails() {
         ]"#]]
     .assert_eq(&format!("{errors:?}"));
 }
+
+#[test]
+fn nested_def_calls() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        def inner(int x) -> int {
+            return x + 1;
+        }
+        def outer(int x) -> int {
+            return inner(x) * 2;
+        }
+        int a = outer(2);
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        function inner(x : Int) : Int {
+            return x + 1;
+        }
+        function outer(x : Int) : Int {
+            return inner(x) * 2;
+        }
+        mutable a = outer(2);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn conditionally_recursive_def_is_allowed() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        def fact(int n) -> int {
+            if (n <= 1) {
+                return 1;
+            }
+            return n * fact(n - 1);
+        }
+        int a = fact(3);
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        function fact(n : Int) : Int {
+            if n <= 1 {
+                return 1;
+            };
+            return n * fact(n - 1);
+        }
+        mutable a = fact(3);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn unconditionally_recursive_def_fails() {
+    let source = r#"
+        def f(int n) -> int {
+            return f(n + 1);
+        }
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Compiler.DefCallDepthExceeded
+
+          x calls made by f exceed the maximum def call depth of 100
+           ,-[Test.qasm:3:20]
+         2 |         def f(int n) -> int {
+         3 |             return f(n + 1);
+           :                    ^^^^^^^^
+         4 |         }
+           `----
+          help: unconditional recursive calls never terminate
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn def_call_chain_deeper_than_configured_limit_fails() {
+    let source = r#"
+        def a() {}
+        def b() { a(); }
+        def c() { b(); }
+    "#;
+
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.max_def_call_depth = 2;
+    let unit = compile_with_config(source, config).expect("parse failed");
+    let errors = unit
+        .errors()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    expect![[r#"
+        [
+            "calls made by c exceed the maximum def call depth of 2",
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
+
+#[test]
+fn array_parameter_can_be_read() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        def first(readonly array[int, 3] a) -> int {
+            return a[0];
+        }
+        array[int, 3] values = {1, 2, 3};
+        int x = first(values);
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        function first(a : Int[]) : Int {
+            return a[0];
+        }
+        mutable values = [1, 2, 3];
+        mutable x = first(values);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn updating_array_parameter_fails() {
+    let source = r#"
+        def clear(mutable array[int, 3] a) {
+            a[0] = 0;
+        }
+    "#;
+
+    let Err(errors) = compile_qasm_stmt_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.ArrayParameterUpdate
+
+          x cannot update array parameter a, the caller would not see the change
+           ,-[Test.qasm:3:13]
+         2 |         def clear(mutable array[int, 3] a) {
+         3 |             a[0] = 0;
+           :             ^
+         4 |         }
+           `----
+          help: arrays are passed to subroutines by value, return the updated array
+                instead
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
@@ -1,9 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::compile_qasm_stmt_to_qsharp;
+use crate::tests::{compile_qasm_stmt_to_qsharp, compile_qasm_to_qir, compile_qasm_to_qsharp};
 use expect_test::expect;
 use miette::Report;
+use qsc::target::Profile;
 
 #[test]
 fn single_qubit() -> miette::Result<(), Vec<Report>> {
@@ -177,3 +178,199 @@ fn capturing_non_const_evaluatable_external_variable_fails() {
         ]"#]]
     .assert_eq(&format!("{errors:?}"));
 }
+
+#[test]
+fn gate_calls_in_control_flow_are_allowed_in_gate_body() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        gate my_gate(a) q {
+            for int i in [0:1] {
+                rx(a) q;
+            }
+        }
+    "#;
+
+    let qsharp = compile_qasm_stmt_to_qsharp(source)?;
+    expect![[r#"
+        operation my_gate(a : QasmStd.Angle.Angle, q : Qubit) : Unit is Adj + Ctl {
+            for i : Int in 0..1 {
+                rx(a, q);
+            }
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn measurement_in_gate_body_fails() {
+    let source = r#"
+        include "stdgates.inc";
+        gate my_gate q {
+            h q;
+            if (true) {
+                bit b = measure q;
+            }
+        }
+    "#;
+
+    let Err(errors) = compile_qasm_stmt_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.MeasurementInGate
+
+          x measurements are not allowed in gate definitions
+           ,-[Test.qasm:6:17]
+         5 |             if (true) {
+         6 |                 bit b = measure q;
+           :                 ^^^^^^^^^|^^^^^^^^
+           :                          `-- measurement in gate body
+         7 |             }
+           `----
+          help: gates must be unitary, move the measurement outside the gate and
+                measure after calling it
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn gate_with_no_qubits_fails() {
+    let source = r#"
+        gate my_gate(a) { }
+    "#;
+
+    let Err(errors) = compile_qasm_stmt_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.GateWithNoQubits
+
+          x gate my_gate must operate on at least one qubit
+           ,-[Test.qasm:2:14]
+         1 | 
+         2 |         gate my_gate(a) { }
+           :              ^^^^^^^
+         3 |     
+           `----
+          help: use a def subroutine for classical code
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn reset_in_gate_body_fails() {
+    let source = r#"
+        include "stdgates.inc";
+        gate my_gate q {
+            reset q;
+            h q;
+        }
+    "#;
+
+    let Err(errors) = compile_qasm_stmt_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.ResetInGate
+
+          x resets are not allowed in gate definitions
+           ,-[Test.qasm:4:13]
+         3 |         gate my_gate q {
+         4 |             reset q;
+           :             ^^^^|^^^
+           :                 `-- reset in gate body
+         5 |             h q;
+           `----
+          help: gates must be unitary, move the reset outside the gate and reset
+                before calling it
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn unitary_gate_supports_adjoint_and_controlled_calls() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        gate my_gate q {
+            h q;
+            t q;
+        }
+        qubit[2] q;
+        inv @ my_gate q[0];
+        ctrl @ my_gate q[0], q[1];
+        bit[2] c = measure q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        operation my_gate(q : Qubit) : Unit is Adj + Ctl {
+            h(q);
+            t(q);
+        }
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        Adjoint my_gate(q[0]);
+        Controlled my_gate([q[0]], q[1]);
+        mutable c = Std.Measurement.MeasureEachZ(q);
+    "#]]
+    .assert_eq(&qsharp);
+    compile_qasm_to_qir(source, Profile::AdaptiveRI)?;
+    Ok(())
+}
+
+#[test]
+fn gate_with_non_invertible_body_is_compiled_without_functors() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        def flip(qubit q) {
+            x q;
+        }
+        gate calls_def q {
+            flip(q);
+        }
+        gate updates_variable q {
+            int i = 0;
+            i = 1;
+            x q;
+        }
+        gate calls_gate_without_functors q {
+            calls_def q;
+        }
+        qubit q;
+        calls_def q;
+        updates_variable q;
+        calls_gate_without_functors q;
+        bit c = measure q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        operation flip(q : Qubit) : Unit {
+            x(q);
+        }
+        operation calls_def(q : Qubit) : Unit {
+            flip(q);
+        }
+        operation updates_variable(q : Qubit) : Unit {
+            mutable i = 0;
+            set i = 1;
+            x(q);
+        }
+        operation calls_gate_without_functors(q : Qubit) : Unit {
+            calls_def(q);
+        }
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        calls_def(q);
+        updates_variable(q);
+        calls_gate_without_functors(q);
+        mutable c = QIR.Intrinsic.__quantum__qis__m__body(q);
+    "#]]
+    .assert_eq(&qsharp);
+    compile_qasm_to_qir(source, Profile::AdaptiveRIF)?;
+    Ok(())
+}
@@ -237,3 +237,21 @@ input bit[2] b2;
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn signature_input_params_follow_declaration_order() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+input float zeta;
+int x = 1;
+input int alpha;
+qubit q;
+input bool mid;
+"#;
+
+    let unit = crate::tests::compile(source)?;
+    let signature = unit
+        .signature
+        .expect("file programs should have a signature");
+    expect!["zeta: Double, alpha: Int, mid: bool"].assert_eq(&signature.input_params());
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::expect;
+use miette::Report;
+
+use crate::{
+    tests::{compile_with_config, fail_on_compilation_errors, gen_qsharp},
+    CompilerConfig, OutputSemantics, ProgramType, QubitSemantics,
+};
+
+fn config(strict_declarations: bool) -> CompilerConfig {
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.strict_declarations = strict_declarations;
+    config
+}
+
+#[test]
+fn qreg_and_creg_lower_like_qubit_and_bit_arrays() -> miette::Result<(), Vec<Report>> {
+    let old_style = "
+        qreg q[2];
+        creg c[2];
+        c[0] = measure q[0];
+    ";
+    let new_style = "
+        qubit[2] q;
+        bit[2] c;
+        c[0] = measure q[0];
+    ";
+
+    let old_unit = compile_with_config(old_style, config(false))?;
+    fail_on_compilation_errors(&old_unit);
+    let new_unit = compile_with_config(new_style, config(false))?;
+    fail_on_compilation_errors(&new_unit);
+
+    assert_eq!(gen_qsharp(&old_unit.package), gen_qsharp(&new_unit.package));
+    assert!(new_unit.warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn qreg_and_creg_report_deprecation_warnings_by_default() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        qreg q[2];
+        creg c[2];
+    ";
+
+    let unit = compile_with_config(source, config(false))?;
+    fail_on_compilation_errors(&unit);
+    let warnings = unit
+        .warnings()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    expect![[r#"
+        [
+            "qreg declarations are OpenQASM 2 syntax, use qubit declarations instead",
+            "creg declarations are OpenQASM 2 syntax, use bit declarations instead",
+        ]
+    "#]]
+    .assert_debug_eq(&warnings);
+    Ok(())
+}
+
+#[test]
+fn qreg_and_creg_are_errors_with_strict_declarations() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        qreg q[2];
+        creg c[2];
+    ";
+
+    let unit = compile_with_config(source, config(true))?;
+    assert_eq!(unit.errors().len(), 2);
+    assert!(unit.warnings().is_empty());
+    Ok(())
+}
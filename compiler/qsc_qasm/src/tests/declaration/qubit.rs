@@ -6,7 +6,10 @@ use miette::Report;
 
 use crate::tests::{compile_fragments, fail_on_compilation_errors};
 use crate::{
-    tests::{compile_qasm_stmt_to_qsharp, compile_qasm_stmt_to_qsharp_with_semantics},
+    tests::{
+        compile_qasm_stmt_to_qsharp, compile_qasm_stmt_to_qsharp_with_semantics,
+        compile_qasm_to_qsharp,
+    },
     QubitSemantics,
 };
 
@@ -53,3 +56,55 @@ fn single_qubit_decl_with_qsharp_semantics() -> miette::Result<(), Vec<Report>>
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn qubit_decl_in_for_loop_generates_error() {
+    let source = "
+        for int i in {0, 1} {
+            qubit q;
+        }
+    ";
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.QuantumDeclarationInNonGlobalScope
+
+          x quantum declarations must be done in global scope
+           ,-[Test.qasm:3:13]
+         2 |         for int i in {0, 1} {
+         3 |             qubit q;
+           :             ^^^^^^^^
+         4 |         }
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn qubit_decl_in_def_generates_error() {
+    let source = "
+        def f() {
+            qubit q;
+        }
+    ";
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.QuantumDeclarationInNonGlobalScope
+
+          x quantum declarations must be done in global scope
+           ,-[Test.qasm:3:13]
+         2 |         def f() {
+         3 |             qubit q;
+           :             ^^^^^^^^
+         4 |         }
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
@@ -3,6 +3,7 @@
 
 mod binary;
 mod bits;
+mod explicit_cast;
 mod function_call;
 mod ident;
 mod implicit_cast_from_bit;
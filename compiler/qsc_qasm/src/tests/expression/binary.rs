@@ -9,6 +9,7 @@ mod angle;
 mod arithmetic_conversions;
 mod comparison;
 mod complex;
+mod division;
 mod ident;
 mod literal;
 
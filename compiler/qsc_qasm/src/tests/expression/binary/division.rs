@@ -0,0 +1,131 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::expect;
+use miette::Report;
+
+use crate::tests::compile_qasm_to_qsharp;
+
+#[test]
+fn float_division_is_checked_at_runtime() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        float x = 1.0;
+        float y = 0.0;
+        float z = x / y;
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable x = 1.;
+        mutable y = 0.;
+        mutable z = QasmStd.Arithmetic.DivideDoubles(x, y);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn float_division_assign_op_is_checked_at_runtime() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        float x = 1.0;
+        float y = 0.0;
+        x /= y;
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable x = 1.;
+        mutable y = 0.;
+        set x = QasmStd.Arithmetic.DivideDoubles(x, y);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn float_division_by_nonzero_literal_is_not_checked() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        float x = 1.0;
+        float z = x / 2.0;
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable x = 1.;
+        mutable z = x / 2.;
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn int_modulo_is_left_to_the_runtime_check() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        int x = 5;
+        int y = 0;
+        int z = x % y;
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable x = 5;
+        mutable y = 0;
+        mutable z = x % y;
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn const_int_modulo_by_zero_fails() {
+    let source = "
+        const int x = 5 % 0;
+        bit[x] b;
+    ";
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        Qasm.Lowerer.DivisionByZero
+
+          x division by zero
+           ,-[Test.qasm:2:27]
+         1 | 
+         2 |         const int x = 5 % 0;
+           :                           ^
+         3 |         bit[x] b;
+           `----
+    "#]]
+    .assert_eq(&format!("{:?}", errors[0]));
+}
+
+#[test]
+fn const_float_division_by_zero_fails() {
+    let source = "
+        const float x = 1.0 / 0.0;
+        def f() { x; }
+    ";
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        Qasm.Lowerer.DivisionByZero
+
+          x division by zero
+           ,-[Test.qasm:2:31]
+         1 | 
+         2 |         const float x = 1.0 / 0.0;
+           :                               ^^^
+         3 |         def f() { x; }
+           `----
+    "#]]
+    .assert_eq(&format!("{:?}", errors[0]));
+}
@@ -19,8 +19,8 @@ fn bit_array_bits_and_register_ops() -> miette::Result<(), Vec<Report>> {
         a_and_b = (a & b); // Bitwise AND produces "00000000"
         output bit[8] a_xor_b;
         a_xor_b = (a ^ b); // Bitwise XOR produces "11111111"
-        //output bit[8] not_a;
-        //not_a = ~a; // Bitwise NOT produces "01110000"
+        output bit[8] not_a;
+        not_a = ~a; // Bitwise NOT produces "01110000"
         output bit[8] rs_a_1;
         rs_a_1 = (a >> 1); // Bit shift right produces "01000111"
     "#;
@@ -29,7 +29,7 @@ fn bit_array_bits_and_register_ops() -> miette::Result<(), Vec<Report>> {
         namespace qasm_import {
             import QasmStd.Intrinsic.*;
             @EntryPoint()
-            operation Test() : (Result[], Result[], Result[], Result[], Result[]) {
+            operation Test() : (Result[], Result[], Result[], Result[], Result[], Result[]) {
                 mutable a = [One, Zero, Zero, Zero, One, One, One, One];
                 mutable b = [Zero, One, One, One, Zero, Zero, Zero, Zero];
                 mutable ls_a_1 = [Zero, Zero, Zero, Zero, Zero, Zero, Zero, Zero];
@@ -40,9 +40,11 @@ fn bit_array_bits_and_register_ops() -> miette::Result<(), Vec<Report>> {
                 set a_and_b = (QasmStd.Convert.IntAsResultArrayBE(QasmStd.Convert.ResultArrayAsIntBE(a) &&& QasmStd.Convert.ResultArrayAsIntBE(b), 8));
                 mutable a_xor_b = [Zero, Zero, Zero, Zero, Zero, Zero, Zero, Zero];
                 set a_xor_b = (QasmStd.Convert.IntAsResultArrayBE(QasmStd.Convert.ResultArrayAsIntBE(a) ^^^ QasmStd.Convert.ResultArrayAsIntBE(b), 8));
+                mutable not_a = [Zero, Zero, Zero, Zero, Zero, Zero, Zero, Zero];
+                set not_a = QasmStd.Convert.IntAsResultArrayBE(~~~QasmStd.Convert.ResultArrayAsIntBE(a), 8);
                 mutable rs_a_1 = [Zero, Zero, Zero, Zero, Zero, Zero, Zero, Zero];
                 set rs_a_1 = (QasmStd.Convert.IntAsResultArrayBE(QasmStd.Convert.ResultArrayAsIntBE(a) >>> 1, 8));
-                (ls_a_1, a_or_b, a_and_b, a_xor_b, rs_a_1)
+                (ls_a_1, a_or_b, a_and_b, a_xor_b, not_a, rs_a_1)
             }
         }"#]]
     .assert_eq(&qsharp);
@@ -67,3 +69,41 @@ fn bit_array_left_shift() -> miette::Result<(), Vec<Report>> {
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn bit_array_and_in_declaration() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        bit[4] a = "1100";
+        bit[4] b = "1010";
+        bit[4] c = a & b;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable a = [One, One, Zero, Zero];
+        mutable b = [One, Zero, One, Zero];
+        mutable c = QasmStd.Convert.IntAsResultArrayBE(QasmStd.Convert.ResultArrayAsIntBE(a) &&& QasmStd.Convert.ResultArrayAsIntBE(b), 4);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn bit_array_not() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        bit[4] a = "1100";
+        bit[4] c;
+        c = ~a;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable a = [One, One, Zero, Zero];
+        mutable c = [Zero, Zero, Zero, Zero];
+        set c = QasmStd.Convert.IntAsResultArrayBE(~~~QasmStd.Convert.ResultArrayAsIntBE(a), 4);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use expect_test::expect;
+use miette::Report;
+
+use crate::tests::compile_qasm_to_qsharp;
+
+#[test]
+fn angle_to_float_explicitly() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        angle a = 1.0;
+        float f = float(a);
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable a = new QasmStd.Angle.Angle {
+            Value = 1433540284805665,
+            Size = 53
+        };
+        mutable f = QasmStd.Angle.AngleAsDouble(a);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn sized_angle_to_float_explicitly() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        angle[8] a = 1.0;
+        float[64] f = float[64](a);
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable a = new QasmStd.Angle.Angle {
+            Value = 41,
+            Size = 8
+        };
+        mutable f = QasmStd.Angle.AngleAsDouble(a);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn float_to_sized_angle_explicitly() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        float f = 7.0;
+        angle[8] a = angle[8](f);
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable f = 7.;
+        mutable a = QasmStd.Angle.DoubleAsAngle(f, 8);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn float_to_angle_explicitly() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        float f = -1.0;
+        angle a = angle(f);
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable f = -1.;
+        mutable a = QasmStd.Angle.DoubleAsAngle(f, 53);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn const_angle_to_float_explicitly() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        const angle[4] a = pi;
+        const float f = float(a);
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let a = QasmStd.Angle.DoubleAsAngle(Std.Math.PI(), 4);
+        let f = QasmStd.Angle.AngleAsDouble(a);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn angle_to_float_implicitly_is_an_error() {
+    let source = "
+        angle a = 1.0;
+        float f = a;
+    ";
+
+    let Err(error) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error")
+    };
+
+    expect!["cannot cast expression of type Angle(None, false) to type Float(None, false)"]
+        .assert_eq(&error[0].to_string());
+}
@@ -157,3 +157,58 @@ fn tau_unicode_is_resolved() -> miette::Result<(), Vec<Report>> {
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn pi_unicode_in_gate_args_matches_latin() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        include \"stdgates.inc\";
+        qubit q;
+        rz(π/2) q;
+    ";
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        rz(QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 2., 53), q);
+    "#]]
+    .assert_eq(&qsharp);
+    assert_eq!(qsharp, compile_qasm_to_qsharp(&source.replace('π', "pi"))?);
+    Ok(())
+}
+
+#[test]
+fn tau_unicode_in_const_decl_matches_latin() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        const float t = τ;
+    ";
+
+    let qsharp = compile_qasm_stmt_to_qsharp(source)?;
+    expect![[r#"
+        let t = 2. * Std.Math.PI();
+    "#]]
+    .assert_eq(&qsharp);
+    assert_eq!(
+        qsharp,
+        compile_qasm_stmt_to_qsharp(&source.replace('τ', "tau"))?
+    );
+    Ok(())
+}
+
+#[test]
+fn euler_unicode_in_array_size_matches_latin() -> miette::Result<(), Vec<Report>> {
+    let source = "
+        array[int, int(ℇ)] a;
+    ";
+
+    let qsharp = compile_qasm_stmt_to_qsharp(source)?;
+    expect![[r#"
+        mutable a = [0, 0];
+    "#]]
+    .assert_eq(&qsharp);
+    assert_eq!(
+        qsharp,
+        compile_qasm_stmt_to_qsharp(&source.replace('ℇ', "euler"))?
+    );
+    Ok(())
+}
@@ -2,12 +2,12 @@
 // Licensed under the MIT License.
 
 use crate::{
-    tests::{fail_on_compilation_errors, gen_qsharp},
+    tests::{compile, fail_on_compilation_errors, gen_qsharp, generate_program_from_ast},
     CompilerConfig, OutputSemantics, ProgramType, QubitSemantics,
 };
 use expect_test::expect;
 use miette::Report;
-use qsc::target::Profile;
+use qsc::{codegen::qir::ToQir, target::Profile};
 
 use super::{compile_qasm_to_qir, compile_with_config};
 
@@ -312,3 +312,193 @@ c2[2] = measure q[4];
 
     Ok(())
 }
+
+#[test]
+fn qir_generation_labels_output_registers_by_name() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+OPENQASM 3.0;
+include "stdgates.inc";
+output bit[2] c;
+output bit[3] c2;
+qubit[5] q;
+x q[2];
+c[0] = measure q[0];
+c[1] = measure q[1];
+c2[0] = measure q[2];
+c2[1] = measure q[3];
+c2[2] = measure q[4];
+    "#;
+
+    let unit = compile(source)?;
+    fail_on_compilation_errors(&unit);
+    let labels = unit
+        .signature
+        .as_ref()
+        .expect("file programs should have a signature")
+        .output_labels
+        .clone();
+    let mut program = generate_program_from_ast(unit.package, unit.source_map, Profile::AdaptiveRI)
+        .map_err(|errors| errors.into_iter().map(Report::new).collect::<Vec<_>>())?;
+    qsc::codegen::qir::set_output_labels(&mut program, &labels).expect("labels should apply");
+    let qir = program.to_qir(&program);
+    let output_records = qir
+        .lines()
+        .filter(|line| {
+            line.contains("call void @__quantum__rt__") || line.contains("internal constant")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    expect![[r#"
+        @0 = internal constant [3 x i8] c"c2\00"
+        @1 = internal constant [2 x i8] c"c\00"
+          call void @__quantum__rt__tuple_record_output(i64 2, i8* null)
+          call void @__quantum__rt__array_record_output(i64 3, i8* getelementptr inbounds ([3 x i8], [3 x i8]* @0, i64 0, i64 0))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 4 to %Result*), i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 3 to %Result*), i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 2 to %Result*), i8* null)
+          call void @__quantum__rt__array_record_output(i64 2, i8* getelementptr inbounds ([2 x i8], [2 x i8]* @1, i64 0, i64 0))
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)"#]]
+    .assert_eq(&output_records);
+
+    Ok(())
+}
+
+#[test]
+fn using_re_semantics_accounts_for_gate_duration_annotations() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        @duration 1.5us
+        gate slow q {
+            h q;
+        }
+        qubit q;
+        slow q;
+    "#;
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::ResourceEstimation,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config).expect("parse failed");
+    fail_on_compilation_errors(&unit);
+    let qsharp = gen_qsharp(&unit.package);
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Unit {
+                operation slow(q : Qubit) : Unit is Adj + Ctl {
+                    QasmStd.Intrinsic.AccountForRuntime(1500);
+                    h(q);
+                }
+                let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+                slow(q);
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_gate_duration_annotation_fails() {
+    let source = r#"
+        include "stdgates.inc";
+        @duration fast
+        gate slow q {
+            h q;
+        }
+    "#;
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::ResourceEstimation,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config).expect("parse failed");
+    let errors: Vec<_> = unit.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(errors, vec!["invalid gate duration: fast"]);
+}
+
+#[test]
+fn using_qiskit_semantics_single_bit_output_is_a_scalar_result() -> miette::Result<(), Vec<Report>>
+{
+    let source = r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bit c;
+        qubit q;
+        c = measure q;
+    "#;
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::Qiskit,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config).expect("parse failed");
+    fail_on_compilation_errors(&unit);
+    let qsharp = gen_qsharp(&unit.package);
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Result {
+                mutable c = Zero;
+                let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+                set c = QIR.Intrinsic.__quantum__qis__m__body(q);
+                c
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+
+    Ok(())
+}
+
+#[test]
+fn using_qiskit_semantics_single_bits_and_bit_arrays_are_output_in_reverse_order(
+) -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bit a;
+        output bit[2] b;
+        qubit[3] q;
+        a = measure q[0];
+        b[0] = measure q[1];
+        b[1] = measure q[2];
+    "#;
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::Qiskit,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config).expect("parse failed");
+    fail_on_compilation_errors(&unit);
+    let qsharp = gen_qsharp(&unit.package);
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : (Result[], Result) {
+                mutable a = Zero;
+                mutable b = [Zero, Zero];
+                let q = QIR.Runtime.AllocateQubitArray(3);
+                set a = QIR.Intrinsic.__quantum__qis__m__body(q[0]);
+                set b w/= 0 <- QIR.Intrinsic.__quantum__qis__m__body(q[1]);
+                set b w/= 1 <- QIR.Intrinsic.__quantum__qis__m__body(q[2]);
+                (Std.Arrays.Reversed(b), a)
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+
+    Ok(())
+}
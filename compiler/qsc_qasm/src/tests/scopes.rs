@@ -138,7 +138,7 @@ fn gate_can_call_previously_declared_def() -> miette::Result<(), Vec<Report>> {
         operation apply_h(q : Qubit) : Unit {
             h(q);
         }
-        operation my_hx(q : Qubit) : Unit is Adj + Ctl {
+        operation my_hx(q : Qubit) : Unit {
             apply_h(q);
             x(q);
         }
@@ -301,7 +301,7 @@ fn unary_op_negb_bitarray() -> miette::Result<(), Vec<Report>> {
     expect![[r#"
         import QasmStd.Intrinsic.*;
         let a = [One, Zero, One];
-        let b = QasmStd.Convert.ResultArrayAsIntBE(~~~a);
+        let b = QasmStd.Convert.ResultArrayAsIntBE(QasmStd.Convert.IntAsResultArrayBE(~~~QasmStd.Convert.ResultArrayAsIntBE(a), 3));
         mutable r = [Zero, Zero];
     "#]]
     .assert_eq(&qsharp);
@@ -1054,6 +1054,35 @@ fn binary_op_comparison_uint() -> miette::Result<(), Vec<Report>> {
     Ok(())
 }
 
+#[test]
+fn binary_op_comparison_float() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        const float a = 2.0;
+        const float b = 2.5;
+        bit[a == a] r1;
+        bit[a != a] r2;
+        bit[a > b] r3;
+        bit[a >= b] r4;
+        bit[a < b] r5;
+        bit[a <= b] r6;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let a = 2.;
+        let b = 2.5;
+        mutable r1 = [Zero];
+        mutable r2 = [];
+        mutable r3 = [];
+        mutable r4 = [];
+        mutable r5 = [Zero];
+        mutable r6 = [Zero];
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
 #[test]
 
 fn binary_op_comparison_angle() -> miette::Result<(), Vec<Report>> {
@@ -1975,26 +2004,6 @@ fn fuzzer_issue_2294() {
            :                ^
          3 |     
            `----
-
-        Qasm.Lowerer.ExprMustBeConst
-
-          x expression must be const
-           ,-[Test.qasm:2:16]
-         1 | 
-         2 |         ctrl(5/_)@l
-           :                ^
-         3 |     
-           `----
-
-        Qasm.Lowerer.ExprMustBeConst
-
-          x ctrl modifier argument must be a const expression
-           ,-[Test.qasm:2:14]
-         1 | 
-         2 |         ctrl(5/_)@l
-           :              ^^^
-         3 |     
-           `----
     "#]]
     .assert_eq(&errs_string);
 }
@@ -2041,16 +2050,63 @@ fn binary_op_with_non_supported_types_fails() {
            :                   ^
          4 |     
            `----
+    "#]]
+    .assert_eq(&errs_string);
+}
 
-        Qasm.Compiler.NotSupported
+#[test]
+fn builtin_math_functions_are_folded_in_const_exprs() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        const float a = sqrt(2.0);
+        const float b = sin(pi / 2);
+        const float c = exp(0.0);
+        const float d = floor(2.7) + cos(0.0);
+    "#;
 
-          x timing literals are not supported
-           ,-[Test.qasm:2:27]
-         1 | 
-         2 |         const int a = 2 / 0s;
-           :                           ^^
-         3 |         def f() { a; }
-           `----
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let a = 1.4142135623730951;
+        let b = 1.;
+        let c = 1.;
+        let d = 2. + 1.;
     "#]]
-    .assert_eq(&errs_string);
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn builtin_math_function_with_non_const_arg_is_not_supported() {
+    let source = r#"
+        float x = 2.0;
+        float y = sqrt(x);
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect!["builtin math function calls with non-const arguments are not supported"]
+        .assert_eq(&errors[0].to_string());
+}
+
+#[test]
+fn builtin_math_functions_can_be_shadowed_by_defs() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        def sqrt(float x) -> float {
+            return x;
+        }
+        float y = sqrt(2.0);
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        function sqrt(x : Double) : Double {
+            return x;
+        }
+        mutable y = sqrt(2.);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
 }
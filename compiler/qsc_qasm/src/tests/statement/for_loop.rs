@@ -1,9 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::compile_qasm_to_qsharp;
+use crate::tests::{compile_qasm_to_qir, compile_qasm_to_qsharp};
 use expect_test::expect;
 use miette::Report;
+use qsc::target::Profile;
 
 #[test]
 fn for_loops_can_iterate_over_discrete_set() -> miette::Result<(), Vec<Report>> {
@@ -153,3 +154,120 @@ fn loop_variables_should_be_scoped_to_for_loop() {
 
     expect!["undefined symbol: i"].assert_eq(&errors[0].to_string());
 }
+
+#[test]
+fn loop_variables_shadow_outer_variables_until_the_loop_ends() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        output int sum;
+        int i = 5;
+        for int i in [0:2] {
+            sum += i;
+        }
+        sum += i;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable sum = 0;
+        mutable i = 5;
+        for i : Int in 0..2 {
+            set sum += i;
+        }
+        set sum += i;
+    "#]]
+    .assert_eq(&qsharp);
+    compile_qasm_to_qir(source, Profile::AdaptiveRIF)?;
+    Ok(())
+}
+
+#[test]
+fn discrete_set_values_are_cast_to_loop_variable_type() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        output float sum;
+        for float f in {1, 2} {
+            sum += f;
+        }
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable sum = 0.;
+        for f : Double in [1., 2.] {
+            set sum += f;
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    compile_qasm_to_qir(source, Profile::AdaptiveRIF)?;
+    Ok(())
+}
+
+#[test]
+fn for_loops_can_index_const_angle_array() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        const array[angle[32], 3] angles = {pi/4, pi/2, pi};
+        qubit q;
+        for int i in {0, 1, 2} {
+            rz(angles[i]) q;
+        }
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let angles = [QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 4., 32), QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 2., 32), QasmStd.Angle.DoubleAsAngle(Std.Math.PI(), 32)];
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        for i : Int in [0, 1, 2] {
+            rz(angles[i], q);
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn const_angle_array_elements_are_folded_in_gate_loops() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        const array[angle[32], 3] angles = {pi/4, pi/2, pi};
+        qubit q;
+        for int i in {0, 1, 2} {
+            rz(angles[i]) q;
+        }
+    "#;
+
+    let qir = compile_qasm_to_qir(source, Profile::Base)?;
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__rz__body(double 0.7853981633974483, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__rz__body(double 1.5707963267948966, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__rz__body(double 3.141592653589793, %Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__rt__tuple_record_output(i64 0, i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__rz__body(double, %Qubit*)
+
+        declare void @__quantum__rt__tuple_record_output(i64, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="1" "required_num_results"="0" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+    "#]]
+    .assert_eq(&qir);
+    Ok(())
+}
@@ -1,7 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::{compile_qasm_to_qir, compile_qasm_to_qsharp};
+use crate::{
+    tests::{
+        compile_qasm_to_qir, compile_qasm_to_qsharp, compile_with_config,
+        qsharp_from_qasm_compilation,
+    },
+    CompilerConfig, OutputSemantics, ProgramType, QubitSemantics,
+};
 use expect_test::expect;
 use miette::Report;
 use qsc::target::Profile;
@@ -23,6 +29,68 @@ fn u_gate_can_be_called() -> miette::Result<(), Vec<Report>> {
     Ok(())
 }
 
+fn legacy_u_config(warn_on_legacy_u: bool) -> CompilerConfig {
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.warn_on_legacy_u = warn_on_legacy_u;
+    config
+}
+
+#[test]
+fn u_gate_call_reports_deprecation_warning() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        qubit q;
+        U(1.0, 2.0, 3.0) q;
+    "#;
+
+    let unit = compile_with_config(source, legacy_u_config(true))?;
+    assert!(unit.errors().is_empty());
+    let warnings = unit
+        .warnings()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    expect![[r#"
+        [
+            "the builtin U gate is the legacy spelling of the u3 gate",
+        ]
+    "#]]
+    .assert_debug_eq(&warnings);
+    Ok(())
+}
+
+#[test]
+fn u_gate_call_deprecation_warning_can_be_disabled() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        qubit q;
+        U(1.0, 2.0, 3.0) q;
+    "#;
+
+    let unit = compile_with_config(source, legacy_u_config(false))?;
+    assert!(unit.errors().is_empty());
+    assert!(unit.warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn u3_gate_call_reports_no_warnings() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit q;
+        u3(1.0, 2.0, 3.0) q;
+    "#;
+
+    let unit = compile_with_config(source, legacy_u_config(true))?;
+    assert!(unit.errors().is_empty());
+    assert!(unit.warnings().is_empty());
+    Ok(())
+}
+
 #[test]
 fn gphase_gate_can_be_called() -> miette::Result<(), Vec<Report>> {
     let source = r#"
@@ -119,7 +187,7 @@ fn barrier_can_be_called_on_single_qubit() -> miette::Result<(), Vec<Report>> {
     expect![[r#"
         import QasmStd.Intrinsic.*;
         let q = QIR.Runtime.__quantum__rt__qubit_allocate();
-        __quantum__qis__barrier__body();
+        barrier([q]);
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -137,7 +205,29 @@ fn barrier_can_be_called_without_qubits() -> miette::Result<(), Vec<Report>> {
     expect![[r#"
         import QasmStd.Intrinsic.*;
         let q = QIR.Runtime.__quantum__rt__qubit_allocate();
-        __quantum__qis__barrier__body();
+        barrier([]);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn barrier_can_be_called_on_registers_and_qubits() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        qubit a;
+        qubit b;
+        barrier a, q, b;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        let a = QIR.Runtime.__quantum__rt__qubit_allocate();
+        let b = QIR.Runtime.__quantum__rt__qubit_allocate();
+        barrier([a] + q + [b]);
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -212,7 +302,7 @@ fn barrier_can_be_called_on_two_qubit() -> miette::Result<(), Vec<Report>> {
     expect![[r#"
         import QasmStd.Intrinsic.*;
         let q = QIR.Runtime.AllocateQubitArray(2);
-        __quantum__qis__barrier__body();
+        barrier([q[0], q[1]]);
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -466,7 +556,7 @@ fn custom_gate_can_be_called_with_negctrl_modifier() -> miette::Result<(), Vec<R
         }
         let ctl = QIR.Runtime.AllocateQubitArray(2);
         let q = QIR.Runtime.AllocateQubitArray(2);
-        ApplyControlledOnInt(0, my_gate, [ctl[0], ctl[1]], (q[0], q[1]));
+        ApplyNegCtrl(my_gate, [ctl[0], ctl[1]], (q[0], q[1]));
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -654,3 +744,300 @@ fn all_qiskit_stdgates_can_be_called_included() -> miette::Result<(), Vec<Report
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn gate_can_be_applied_to_register() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        h q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        for __qubit__ : Qubit in q {
+            h(__qubit__);
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn gate_can_be_applied_to_alias_of_register_slice() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[3] q;
+        let a = q[0:1];
+        h a;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(3);
+        let a = q[0..1];
+        for __qubit__ : Qubit in q[0..1] {
+            h(__qubit__);
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn gate_can_be_applied_to_alias_of_single_qubit() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        let a = q[1];
+        cx q[0], a;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        let a = q[1];
+        cx(q[0], q[1]);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn two_qubit_gate_is_broadcast_over_two_registers() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        gate mygate a, b {
+            cx a, b;
+        }
+        qubit[3] q;
+        qubit[3] r;
+        mygate q, r;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        operation mygate(a : Qubit, b : Qubit) : Unit is Adj + Ctl {
+            cx(a, b);
+        }
+        let q = QIR.Runtime.AllocateQubitArray(3);
+        let r = QIR.Runtime.AllocateQubitArray(3);
+        for __index__ : Int in 0..2 {
+            mygate(q[__index__], r[__index__]);
+        }
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn broadcasting_over_registers_of_different_sizes_fails() {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[3] q;
+        qubit[2] r;
+        cx q, r;
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.BroadcastRegisterSizeMismatch
+
+          x cannot broadcast a gate over registers of different sizes 3 and 2
+           ,-[Test.qasm:5:15]
+         4 |         qubit[2] r;
+         5 |         cx q, r;
+           :               ^
+         6 |     
+           `----
+          help: a gate applied to several registers is applied to their qubits
+                element-wise
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn gate_call_with_runtime_angle_expression_is_computed_at_runtime(
+) -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        input float theta;
+        qubit q;
+        rx(theta * 2) q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        rx(QasmStd.Angle.DoubleAsAngle(theta * 2., 53), q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn gate_call_with_runtime_int_expression_generates_error() {
+    let source = r#"
+        include "stdgates.inc";
+        input int n;
+        qubit q;
+        rx(n * 2) q;
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.CannotCast
+
+          x cannot cast expression of type Int(None, false) to type Angle(None, false)
+           ,-[Test.qasm:5:12]
+         4 |         qubit q;
+         5 |         rx(n * 2) q;
+           :            ^^^^^
+         6 |     
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+#[test]
+fn ctrl_modifier_with_count_controls_on_that_many_qubits() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[3] q;
+        ctrl(2) @ x q[0], q[1], q[2];
+        negctrl(2) @ x q[0], q[1], q[2];
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(3);
+        Controlled x([q[0], q[1]], q[2]);
+        ApplyNegCtrl(x, [q[0], q[1]], q[2]);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn ctrl_modifier_with_float_count_generates_error() {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        ctrl(1.5) @ x q[0], q[1];
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.ExprMustBeInt
+
+          x ctrl modifier argument must be an integer
+           ,-[Test.qasm:4:14]
+         3 |         qubit[2] q;
+         4 |         ctrl(1.5) @ x q[0], q[1];
+           :              ^^^
+         5 |     
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
+
+fn symbolic_pi_config(symbolic_pi: bool) -> CompilerConfig {
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.symbolic_pi = symbolic_pi;
+    config
+}
+
+#[test]
+fn rotation_angles_that_are_fractions_of_pi_are_emitted_symbolically(
+) -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit q;
+        rz(pi/4) q;
+        rz(0.7853981633974483) q;
+        rx(-1.5707963267948966) q;
+        ry(9.42477796076938) q;
+        ry(0.5) q;
+    "#;
+
+    let unit = compile_with_config(source, symbolic_pi_config(true))?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        rz(QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 4., 53), q);
+        rz(QasmStd.Angle.DoubleAsAngle(Std.Math.PI() / 4., 53), q);
+        rx(QasmStd.Angle.DoubleAsAngle(-Std.Math.PI() / 2., 53), q);
+        ry(QasmStd.Angle.DoubleAsAngle(3. * Std.Math.PI(), 53), q);
+        ry(QasmStd.Angle.DoubleAsAngle(0.5, 53), q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn rotation_angles_are_emitted_as_floats_without_symbolic_pi() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit q;
+        rz(0.7853981633974483) q;
+    "#;
+
+    let unit = compile_with_config(source, symbolic_pi_config(false))?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        rz(QasmStd.Angle.DoubleAsAngle(0.7853981633974483, 53), q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn ctrl_modifier_with_undefined_count_only_reports_undefined_symbol() {
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        ctrl(n) @ x q[0], q[1];
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected error");
+    };
+
+    expect![[r#"
+        [Qasm.Lowerer.UndefinedSymbol
+
+          x undefined symbol: n
+           ,-[Test.qasm:4:14]
+         3 |         qubit[2] q;
+         4 |         ctrl(n) @ x q[0], q[1];
+           :              ^
+         5 |     
+           `----
+        ]"#]]
+    .assert_eq(&format!("{errors:?}"));
+}
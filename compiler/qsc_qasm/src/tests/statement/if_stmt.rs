@@ -1,9 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::compile_qasm_to_qsharp;
+use crate::tests::{compile_qasm_to_qir, compile_qasm_to_qsharp};
 use expect_test::expect;
 use miette::Report;
+use qsc::target::Profile;
 
 #[test]
 fn can_use_cond_with_implicit_cast_to_bool() -> miette::Result<(), Vec<Report>> {
@@ -146,3 +147,103 @@ fn using_cond_that_cannot_implicit_cast_to_bool_fail() {
     expect!["cannot cast expression of type Qubit to type Bool(false)"]
         .assert_eq(&errors[0].to_string());
 }
+
+const MEASUREMENT_CONDITIONED_IF: &str = r#"
+    include "stdgates.inc";
+    qubit[2] q;
+    bit[2] c;
+    h q[0];
+    c[0] = measure q[0];
+    c[1] = measure q[1];
+    if (c == 1) {
+        x q[1];
+    } else {
+        z q[1];
+    }
+"#;
+
+#[test]
+fn measurement_conditioned_if_on_register_compiles_for_adaptive_ri(
+) -> miette::Result<(), Vec<Report>> {
+    let qir = compile_qasm_to_qir(MEASUREMENT_CONDITIONED_IF, Profile::AdaptiveRI)?;
+    expect![[r#"
+        %Result = type opaque
+        %Qubit = type opaque
+
+        define void @ENTRYPOINT__main() #0 {
+        block_0:
+          call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*))
+          call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*))
+          call void @__quantum__qis__m__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Result* inttoptr (i64 1 to %Result*))
+          %var_3 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 1 to %Result*))
+          br i1 %var_3, label %block_1, label %block_2
+        block_1:
+          br label %block_2
+        block_2:
+          %var_9 = phi i64 [0, %block_0], [1, %block_1]
+          %var_5 = call i1 @__quantum__qis__read_result__body(%Result* inttoptr (i64 0 to %Result*))
+          br i1 %var_5, label %block_3, label %block_4
+        block_3:
+          %var_7 = or i64 %var_9, 2
+          br label %block_4
+        block_4:
+          %var_10 = phi i64 [%var_9, %block_2], [%var_7, %block_3]
+          %var_8 = icmp eq i64 %var_10, 1
+          br i1 %var_8, label %block_5, label %block_6
+        block_5:
+          call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+          br label %block_7
+        block_6:
+          call void @__quantum__qis__z__body(%Qubit* inttoptr (i64 1 to %Qubit*))
+          br label %block_7
+        block_7:
+          call void @__quantum__rt__array_record_output(i64 2, i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 1 to %Result*), i8* null)
+          call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+          ret void
+        }
+
+        declare void @__quantum__qis__h__body(%Qubit*)
+
+        declare void @__quantum__qis__m__body(%Qubit*, %Result*) #1
+
+        declare i1 @__quantum__qis__read_result__body(%Result*)
+
+        declare void @__quantum__qis__x__body(%Qubit*)
+
+        declare void @__quantum__qis__z__body(%Qubit*)
+
+        declare void @__quantum__rt__array_record_output(i64, i8*)
+
+        declare void @__quantum__rt__result_record_output(%Result*, i8*)
+
+        attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="adaptive_profile" "required_num_qubits"="2" "required_num_results"="2" }
+        attributes #1 = { "irreversible" }
+
+        ; module flags
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"int_computations", !"i64"}
+    "#]].assert_eq(&qir);
+    Ok(())
+}
+
+#[test]
+fn measurement_conditioned_if_on_register_fails_for_base() {
+    let Err(errors) = compile_qasm_to_qir(MEASUREMENT_CONDITIONED_IF, Profile::Base) else {
+        panic!("Expected capability errors");
+    };
+    let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    expect![[r#"
+        [
+            "cannot use a dynamic bool value",
+            "cannot use a dynamic integer value",
+        ]
+    "#]]
+    .assert_debug_eq(&errors);
+}
@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 use crate::{
+    io::InMemorySourceResolver,
     tests::{compile_all_with_config, qsharp_from_qasm_compilation},
     CompilerConfig, OutputSemantics, ProgramType, QubitSemantics,
 };
@@ -126,7 +127,7 @@ fn including_stdgates_multiple_times_causes_symbol_redifintion_errors() {
 }
 
 #[test]
-fn multiple_include_in_same_file_errors() {
+fn multiple_include_in_same_file_is_ignored() -> miette::Result<(), Vec<Report>> {
     let main = r#"
         include "source1.inc";
         include "source1.inc";
@@ -146,17 +147,23 @@ fn multiple_include_in_same_file_errors() {
         None,
     );
 
-    let Err(errors) = compile_all_with_config("main.qasm", all_sources, config) else {
-        panic!("expected errors")
-    };
-
-    let errors: Vec<_> = errors.iter().map(|e| format!("{e}")).collect();
-    let errors_string = errors.join("\n");
-    expect!["source1.inc was already included in: main.qasm"].assert_eq(&errors_string);
+    let unit = compile_all_with_config("main.qasm", all_sources, config)?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Result[] {
+                mutable c = [Zero];
+                Std.Arrays.Reversed(c)
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+    Ok(())
 }
 
 #[test]
-fn multiple_include_in_different_files_errors() {
+fn multiple_include_in_different_files_is_ignored() -> miette::Result<(), Vec<Report>> {
     let main = r#"
         include "source1.inc";
         include "source2.inc";
@@ -184,13 +191,74 @@ fn multiple_include_in_different_files_errors() {
         None,
     );
 
-    let Err(errors) = compile_all_with_config("main.qasm", all_sources, config) else {
-        panic!("expected errors")
-    };
+    let unit = compile_all_with_config("main.qasm", all_sources, config)?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Result[] {
+                mutable c = [Zero];
+                Std.Arrays.Reversed(c)
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
 
-    let errors: Vec<_> = errors.iter().map(|e| format!("{e}")).collect();
-    let errors_string = errors.join("\n");
-    expect!["source3.inc was already included in: source1.inc"].assert_eq(&errors_string);
+#[test]
+fn include_of_same_file_through_different_paths_is_ignored() -> miette::Result<(), Vec<Report>> {
+    let main = r#"
+        include "stdgates.inc";
+        include "source1.inc";
+        include "source2.inc";
+    "#;
+    let source1 = r#"
+        gate my_gate q {
+            x q;
+        }
+    "#;
+    let source2 = r#"
+        include "./source1.inc";
+        qubit q;
+        bit c;
+        my_gate q;
+        c = measure q;
+    "#;
+    // Both paths name the same file.
+    let all_sources = [
+        ("main.qasm".into(), main.into()),
+        ("source1.inc".into(), source1.into()),
+        ("./source1.inc".into(), source1.into()),
+        ("source2.inc".into(), source2.into()),
+    ];
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::Qiskit,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+
+    let unit = compile_all_with_config("main.qasm", all_sources, config)?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Unit {
+                operation my_gate(q : Qubit) : Unit is Adj + Ctl {
+                    x(q);
+                }
+                let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+                mutable c = Zero;
+                my_gate(q);
+                set c = QIR.Intrinsic.__quantum__qis__m__body(q);
+                ()
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+    Ok(())
 }
 
 #[test]
@@ -262,6 +330,36 @@ fn mutual_include_errors() {
     .assert_eq(&errors_string);
 }
 
+#[test]
+fn include_cycle_through_main_file_errors() {
+    let a = r#"
+        include "b.qasm";
+    "#;
+    let b = r#"
+        include "a.qasm";
+    "#;
+    let all_sources = [("a.qasm".into(), a.into()), ("b.qasm".into(), b.into())];
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::Qiskit,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+
+    let Err(errors) = compile_all_with_config("a.qasm", all_sources, config) else {
+        panic!("expected errors")
+    };
+
+    let errors: Vec<_> = errors.iter().map(|e| format!("{e}")).collect();
+    let errors_string = errors.join("\n");
+    expect![[r#"
+        Cyclic include:
+          a.qasm includes b.qasm
+          b.qasm includes a.qasm"#]]
+    .assert_eq(&errors_string);
+}
+
 #[test]
 fn cyclic_include_errors() {
     let main = r#"
@@ -304,3 +402,29 @@ fn cyclic_include_errors() {
           source3.inc includes source1.inc"#]]
     .assert_eq(&errors_string);
 }
+
+#[test]
+fn resolved_includes_lists_all_included_files() {
+    let main = r#"
+        include "stdgates.inc";
+        include "source1.inc";
+        include "source2.inc";
+    "#;
+    let source1 = "bit[1] c;";
+    let source2 = "qubit q;";
+    let mut resolver = InMemorySourceResolver::from_iter([
+        ("main.qasm".into(), main.into()),
+        ("source1.inc".into(), source1.into()),
+        ("source2.inc".into(), source2.into()),
+    ]);
+
+    let res = crate::parser::parse_source(main, "main.qasm", &mut resolver);
+    assert!(!res.has_errors());
+    expect![[r#"
+        [
+            "source1.inc",
+            "source2.inc",
+        ]
+    "#]]
+    .assert_debug_eq(&res.resolved_includes());
+}
@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::tests::compile_qasm_to_qsharp;
+use crate::tests::{compile_qasm_to_qsharp, compile_with_config, qsharp_from_qasm_compilation};
+use crate::{CompilerConfig, OutputSemantics, ProgramType, QubitSemantics};
 use expect_test::expect;
 use miette::Report;
 use std::fmt::Write;
@@ -116,6 +117,98 @@ fn measuring_hardware_qubits_generates_an_error() {
     .assert_eq(&errs_string);
 }
 
+#[test]
+fn hardware_qubits_map_to_physical_qubits() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        bit c;
+        h $0;
+        cx $0, $2;
+        c = measure $2;
+        reset $0;
+    "#;
+
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.physical_qubits = true;
+    let unit = compile_with_config(source, config)?;
+    let qsharp = qsharp_from_qasm_compilation(unit)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let __hardware_qubits__ = QIR.Runtime.AllocateQubitArray(3);
+        mutable c = Zero;
+        h(__hardware_qubits__[0]);
+        cx(__hardware_qubits__[0], __hardware_qubits__[2]);
+        set c = QIR.Intrinsic.__quantum__qis__m__body(__hardware_qubits__[2]);
+        Reset(__hardware_qubits__[0]);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+fn physical_qubits_config(max_hardware_qubits: u32) -> CompilerConfig {
+    let mut config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::OpenQasm,
+        ProgramType::Fragments,
+        None,
+        None,
+    );
+    config.physical_qubits = true;
+    config.max_hardware_qubits = max_hardware_qubits;
+    config
+}
+
+#[test]
+fn hardware_qubits_beyond_the_maximum_generate_errors() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        h $1;
+        h $2;
+        h $4294967295;
+    "#;
+
+    let unit = compile_with_config(source, physical_qubits_config(2))?;
+    let errors: Vec<_> = unit.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "hardware qubit $2 is out of range for 2 hardware qubits",
+            "hardware qubit $4294967295 is out of range for 2 hardware qubits",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn hardware_qubits_in_def_and_gate_bodies_generate_errors() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        def f() {
+            h $0;
+        }
+        gate g q {
+            cx q, $1;
+        }
+    "#;
+
+    let unit = compile_with_config(source, physical_qubits_config(2))?;
+    let errors: Vec<_> = unit.errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "hardware qubits in def and gate bodies are not supported",
+            "hardware qubits in def and gate bodies are not supported",
+        ]
+    );
+    Ok(())
+}
+
 #[test]
 fn value_from_measurement_can_be_dropped() -> miette::Result<(), Vec<Report>> {
     let source = r#"
@@ -132,3 +225,181 @@ fn value_from_measurement_can_be_dropped() -> miette::Result<(), Vec<Report>> {
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn register_can_be_measured_into_bit_register() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        bit[2] c;
+        qubit[2] q;
+        c = measure q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable c = [Zero, Zero];
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        set c = Std.Measurement.MeasureEachZ(q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn unsized_bit_initialized_by_measuring_register_is_sized_to_register(
+) -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        qubit[3] q;
+        bit c = measure q;
+        bit last = c[2];
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(3);
+        mutable c = Std.Measurement.MeasureEachZ(q);
+        mutable last = c[2];
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn bit_register_initialized_by_measuring_register_of_other_size_generates_an_error() {
+    let source = r#"
+        qubit[3] q;
+        bit[2] c = measure q;
+    "#;
+
+    let Err(errs) = compile_qasm_to_qsharp(source) else {
+        panic!("Mismatched register sizes should have generated an error");
+    };
+
+    let mut errs_string = String::new();
+
+    for err in errs {
+        writeln!(&mut errs_string, "{err:?}").expect("");
+    }
+
+    expect![[r#"
+        Qasm.Lowerer.MeasurementSizeMismatch
+
+          x cannot assign the measurement of 3 qubits to a register of 2 bits
+           ,-[Test.qasm:3:20]
+         2 |         qubit[3] q;
+         3 |         bit[2] c = measure q;
+           :                    ^^^^^^^^^
+         4 |     
+           `----
+
+    "#]]
+    .assert_eq(&errs_string);
+}
+
+#[test]
+fn register_slice_can_be_measured_into_bit_register_slice() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        bit[4] c;
+        qubit[4] q;
+        c[0:2] = measure q[1:3];
+        measure q[0:1] -> c[2:3];
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable c = [Zero, Zero, Zero, Zero];
+        let q = QIR.Runtime.AllocateQubitArray(4);
+        set c w/= 0..2 <- Std.Measurement.MeasureEachZ(q[1..3]);
+        set c w/= 2..3 <- Std.Measurement.MeasureEachZ(q[0..1]);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn register_slice_with_step_larger_than_register_can_be_measured_into(
+) -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        bit[2] c;
+        qubit[1] q;
+        c[0:4:3] = measure q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        mutable c = [Zero, Zero];
+        let q = QIR.Runtime.AllocateQubitArray(1);
+        set c w/= 0..4..3 <- Std.Measurement.MeasureEachZ(q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn bit_register_slice_length_mismatch_generates_an_error() {
+    let source = r#"
+        bit[4] c;
+        qubit[4] q;
+        c[0:1] = measure q[0:2];
+    "#;
+
+    let Err(errs) = compile_qasm_to_qsharp(source) else {
+        panic!("Mismatched slice lengths should have generated an error");
+    };
+
+    let mut errs_string = String::new();
+
+    for err in errs {
+        writeln!(&mut errs_string, "{err:?}").expect("");
+    }
+
+    expect![[r#"
+        Qasm.Lowerer.SliceLengthMismatch
+
+          x cannot assign 3 bits to a slice of 2 bits
+           ,-[Test.qasm:4:9]
+         3 |         qubit[4] q;
+         4 |         c[0:1] = measure q[0:2];
+           :         ^^^^^^^^^^^^^^^^^^^^^^^^
+         5 |     
+           `----
+
+    "#]]
+    .assert_eq(&errs_string);
+}
+
+#[test]
+fn bit_register_slice_out_of_bounds_generates_an_error() {
+    let source = r#"
+        bit[4] c;
+        qubit[4] q;
+        c[2:4] = measure q[0:2];
+    "#;
+
+    let Err(errs) = compile_qasm_to_qsharp(source) else {
+        panic!("Out of bounds slice should have generated an error");
+    };
+
+    let mut errs_string = String::new();
+
+    for err in errs {
+        writeln!(&mut errs_string, "{err:?}").expect("");
+    }
+
+    expect![[r#"
+        Qasm.Lowerer.IndexOutOfBounds
+
+          x index 4 is out of bounds for a register of size 4
+           ,-[Test.qasm:4:13]
+         3 |         qubit[4] q;
+         4 |         c[2:4] = measure q[0:2];
+           :             ^
+         5 |     
+           `----
+
+    "#]]
+    .assert_eq(&errs_string);
+}
@@ -159,7 +159,7 @@ fn neg_ctrl_can_be_applied_and_wrapped_in_another_modifier() -> miette::Result<(
         import QasmStd.Intrinsic.*;
         let q = QIR.Runtime.AllocateQubitArray(4);
         let f = QIR.Runtime.__quantum__rt__qubit_allocate();
-        Adjoint ApplyControlledOnInt(0, Adjoint Controlled rx, [q[1], q[0], q[2]], ([f], (QasmStd.Angle.DoubleAsAngle(0.5, 53), q[3])));
+        Adjoint ApplyNegCtrl(Adjoint Controlled rx, [q[1], q[0], q[2]], ([f], (QasmStd.Angle.DoubleAsAngle(0.5, 53), q[3])));
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -179,7 +179,7 @@ fn neg_ctrl_can_wrap_another_neg_crtl_modifier() -> miette::Result<(), Vec<Repor
         import QasmStd.Intrinsic.*;
         let q = QIR.Runtime.AllocateQubitArray(6);
         let f = QIR.Runtime.__quantum__rt__qubit_allocate();
-        ApplyControlledOnInt(0, ApplyControlledOnInt, [q[1], q[0], q[2]], (0, Controlled rx, [q[3], q[4]], ([f], (QasmStd.Angle.DoubleAsAngle(0.5, 53), q[5]))));
+        ApplyNegCtrl(ApplyNegCtrl, [q[1], q[0], q[2]], (Controlled rx, [q[3], q[4]], ([f], (QasmStd.Angle.DoubleAsAngle(0.5, 53), q[5]))));
     "#]]
     .assert_eq(&qsharp);
     Ok(())
@@ -222,3 +222,73 @@ fn pow_can_be_applied_on_a_simple_gate() -> miette::Result<(), Vec<Report>> {
     .assert_eq(&qsharp);
     Ok(())
 }
+
+#[test]
+fn pow_with_negative_exponent_can_be_applied_on_a_simple_gate() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit f;
+        pow(-2) @ s f;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let f = QIR.Runtime.__quantum__rt__qubit_allocate();
+        ApplyOperationPowerA(-2, s, (f));
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn fractional_pow_scales_the_angle_of_a_rotation_gate() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        include "stdgates.inc";
+        qubit q;
+        pow(0.5) @ rx(pi) q;
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.__quantum__rt__qubit_allocate();
+        rx(QasmStd.Angle.DoubleAsAngle(QasmStd.Angle.AngleAsDouble(QasmStd.Angle.DoubleAsAngle(Std.Math.PI(), 53)) * 0.5, 53), q);
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn fractional_pow_scales_the_angle_of_a_controlled_rotation_gate() -> miette::Result<(), Vec<Report>>
+{
+    let source = r#"
+        include "stdgates.inc";
+        qubit[2] q;
+        pow(-0.25) @ crz(pi) q[0], q[1];
+    "#;
+
+    let qsharp = compile_qasm_to_qsharp(source)?;
+    expect![[r#"
+        import QasmStd.Intrinsic.*;
+        let q = QIR.Runtime.AllocateQubitArray(2);
+        Controlled rz([q[0]], (QasmStd.Angle.DoubleAsAngle(QasmStd.Angle.AngleAsDouble(QasmStd.Angle.DoubleAsAngle(Std.Math.PI(), 53)) * -0.25, 53), q[1]));
+    "#]]
+    .assert_eq(&qsharp);
+    Ok(())
+}
+
+#[test]
+fn fractional_pow_of_non_rotation_gate_is_an_error() {
+    let source = r#"
+        include "stdgates.inc";
+        qubit q;
+        pow(0.5) @ x q;
+    "#;
+
+    let Err(errors) = compile_qasm_to_qsharp(source) else {
+        panic!("Expected an error");
+    };
+    expect!["non-integer powers are only supported on rotation gates, found x"]
+        .assert_eq(&errors[0].to_string());
+}
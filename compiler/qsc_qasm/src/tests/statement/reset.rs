@@ -160,3 +160,85 @@ fn reset_with_adaptive_ri_profile_generates_reset_qir() -> miette::Result<(), Ve
 
     Ok(())
 }
+
+#[test]
+fn reset_of_register_resets_all_qubits() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        bit[2] meas;
+        qubit[2] q;
+        h q[0];
+        reset q;
+        meas = measure q;
+    "#;
+
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::Qiskit,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config)?;
+    fail_on_compilation_errors(&unit);
+    let qsharp = gen_qsharp(&unit.package);
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Result[] {
+                mutable meas = [Zero, Zero];
+                let q = QIR.Runtime.AllocateQubitArray(2);
+                h(q[0]);
+                ResetAll(q);
+                set meas = Std.Measurement.MeasureEachZ(q);
+                Std.Arrays.Reversed(meas)
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+    compile_qasm_to_qir(source, Profile::AdaptiveRI)?;
+
+    Ok(())
+}
+
+#[test]
+fn reset_with_re_semantics_is_estimated_as_a_measurement() -> miette::Result<(), Vec<Report>> {
+    let source = r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        output bit[2] meas;
+        qubit[2] q;
+        h q[0];
+        reset q[0];
+        reset q;
+        meas = measure q;
+    "#;
+
+    let config = CompilerConfig::new(
+        QubitSemantics::Qiskit,
+        OutputSemantics::ResourceEstimation,
+        ProgramType::File,
+        Some("Test".into()),
+        None,
+    );
+    let unit = compile_with_config(source, config)?;
+    fail_on_compilation_errors(&unit);
+    let qsharp = gen_qsharp(&unit.package);
+    expect![[r#"
+        namespace qasm_import {
+            import QasmStd.Intrinsic.*;
+            @EntryPoint()
+            operation Test() : Unit {
+                mutable meas = [Zero, Zero];
+                let q = QIR.Runtime.AllocateQubitArray(2);
+                h(q[0]);
+                Std.Measurement.MResetZ(q[0]);
+                Std.Measurement.MResetEachZ(q);
+                set meas = Std.Measurement.MeasureEachZ(q);
+            }
+        }"#]]
+    .assert_eq(&qsharp);
+
+    Ok(())
+}
@@ -296,7 +296,9 @@ fn spec_case_5() -> miette::Result<(), Vec<Report>> {
         mutable i = 0;
         if i == 1 or i == 2 or i == 5 or i == 12 {} elif i == 3 {
             if j == 10 or j == 15 or j == 20 {
-                h(q);
+                for __qubit__ : Qubit in q {
+                    h(__qubit__);
+                }
             };
         };
     "#]]
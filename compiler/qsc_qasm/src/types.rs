@@ -10,6 +10,7 @@ pub struct Complex {
 }
 
 impl Complex {
+    #[must_use]
     pub fn new(real: f64, imaginary: f64) -> Self {
         Self { real, imaginary }
     }
@@ -27,6 +28,7 @@ pub enum Type {
     Result(bool),
     Tuple(Vec<Type>),
     Range,
+    AngleArray(ArrayDimensions, bool),
     BoolArray(ArrayDimensions, bool),
     BigIntArray(ArrayDimensions, bool),
     IntArray(ArrayDimensions, bool),
@@ -40,6 +42,42 @@ pub enum Type {
     Err,
 }
 
+impl Type {
+    #[must_use]
+    pub fn is_const(&self) -> bool {
+        match self {
+            Type::Angle(is_const)
+            | Type::Bool(is_const)
+            | Type::BigInt(is_const)
+            | Type::Complex(is_const)
+            | Type::Int(is_const)
+            | Type::Double(is_const)
+            | Type::Result(is_const)
+            | Type::AngleArray(_, is_const)
+            | Type::BoolArray(_, is_const)
+            | Type::BigIntArray(_, is_const)
+            | Type::IntArray(_, is_const)
+            | Type::ResultArray(_, is_const) => *is_const,
+            _ => false,
+        }
+    }
+
+    /// Returns the const version of an array type. Semantic array types
+    /// don't track constness, so const array declarations use this to
+    /// mark their Q# type instead.
+    #[must_use]
+    pub fn as_const_array(&self) -> Self {
+        match self {
+            Type::AngleArray(dims, _) => Type::AngleArray(dims.clone(), true),
+            Type::BoolArray(dims, _) => Type::BoolArray(dims.clone(), true),
+            Type::BigIntArray(dims, _) => Type::BigIntArray(dims.clone(), true),
+            Type::IntArray(dims, _) => Type::IntArray(dims.clone(), true),
+            Type::ResultArray(dims, _) => Type::ResultArray(dims.clone(), true),
+            _ => self.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CallableKind {
     /// A function.
@@ -104,6 +142,7 @@ impl Display for Type {
                 }
                 write!(f, ")")
             }
+            Type::AngleArray(dim, _) => write!(f, "Angle{dim}"),
             Type::BoolArray(dim, _) => write!(f, "bool{dim}"),
             Type::BigIntArray(dim, _) => write!(f, "BigInt{dim}"),
             Type::IntArray(dim, _) => write!(f, "Int{dim}"),
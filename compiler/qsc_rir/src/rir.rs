@@ -14,6 +14,10 @@ pub struct Program {
     pub config: Config,
     pub num_qubits: u32,
     pub num_results: u32,
+    /// The string constants referenced by `Literal::Tag` operands, such as output labels.
+    pub tags: Vec<String>,
+    /// The number of shots the program should be run for, if known.
+    pub shots: Option<usize>,
 }
 
 impl Display for Program {
@@ -607,7 +611,7 @@ impl Operand {
                 Literal::Bool(_) => Ty::Boolean,
                 Literal::Integer(_) => Ty::Integer,
                 Literal::Double(_) => Ty::Double,
-                Literal::Pointer => Ty::Pointer,
+                Literal::Pointer | Literal::Tag(..) => Ty::Pointer,
             },
             Operand::Variable(var) => var.ty,
         }
@@ -622,6 +626,9 @@ pub enum Literal {
     Integer(i64),
     Double(f64),
     Pointer,
+    /// A pointer to the tag at the given index in `Program::tags`, along with the
+    /// length of the tag in bytes including its null terminator.
+    Tag(usize, usize),
 }
 
 impl Display for Literal {
@@ -633,6 +640,7 @@ impl Display for Literal {
             Self::Integer(i) => write!(f, "Integer({i})")?,
             Self::Double(d) => write!(f, "Double({d})")?,
             Self::Pointer => write!(f, "Pointer")?,
+            Self::Tag(index, len) => write!(f, "Tag({index}, {len})")?,
         }
         Ok(())
     }
@@ -665,6 +673,13 @@ impl PartialEq for Literal {
                 }
             }
             Self::Pointer => matches!(other, Self::Pointer),
+            Self::Tag(self_index, self_len) => {
+                if let Self::Tag(other_index, other_len) = other {
+                    self_index == other_index && self_len == other_len
+                } else {
+                    false
+                }
+            }
             Self::Qubit(self_qubit) => {
                 if let Self::Qubit(other_qubit) = other {
                     self_qubit == other_qubit
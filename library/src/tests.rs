@@ -16,7 +16,7 @@ mod table_lookup;
 
 use indoc::indoc;
 use qsc::{
-    interpret::{self, GenericReceiver, Interpreter, Result, Value},
+    interpret::{self, GenericReceiver, Interpreter, IntoResultValue, Value},
     target::Profile,
     Backend, LanguageFeatures, PackageType, SourceMap, SparseSim,
 };
@@ -57,7 +57,7 @@ pub fn test_expression_with_lib_and_profile_and_sim(
     expr: &str,
     lib: &str,
     profile: Profile,
-    sim: &mut impl Backend<ResultType = impl Into<Result>>,
+    sim: &mut impl Backend<ResultType = impl IntoResultValue>,
     expected: &Value,
 ) -> String {
     let mut stdout = vec![];
@@ -106,7 +106,7 @@ pub fn test_expression_fails_with_lib_and_profile_and_sim(
     expr: &str,
     lib: &str,
     profile: Profile,
-    sim: &mut impl Backend<ResultType = impl Into<Result>>,
+    sim: &mut impl Backend<ResultType = impl IntoResultValue>,
 ) -> String {
     let mut stdout = vec![];
     let mut out = GenericReceiver::new(&mut stdout);
@@ -89,6 +89,8 @@ export interface Unitary extends BaseOperation {
   targets: Register[];
   /** Control registers the gate acts on. */
   controls?: Register[];
+  /** Control registers that are active in the |0⟩ state, a subset of `controls`. */
+  negativeControls?: Register[];
   /** Whether gate is an adjoint operation. */
   isAdjoint?: boolean;
 }
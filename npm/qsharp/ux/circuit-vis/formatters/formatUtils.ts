@@ -105,6 +105,21 @@ export const circle = (
 export const controlDot = (x: number, y: number, radius = 5): SVGElement =>
   circle(x, y, radius, "control-dot");
 
+/**
+ * Generate the SVG representation of an open control dot used for negative controls.
+ *
+ * @param x      x coord of circle.
+ * @param y      y coord of circle.
+ * @param radius Radius of circle.
+ *
+ * @returns SVG element for open control dot.
+ */
+export const openControlDot = (
+  x: number,
+  y: number,
+  radius = 5,
+): SVGElement => circle(x, y, radius, "control-dot open-control-dot");
+
 /**
  * Generate the SVG representation of a unitary box that represents an arbitrary unitary operation.
  *
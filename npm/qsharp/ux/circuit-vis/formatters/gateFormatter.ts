@@ -19,6 +19,7 @@ import {
   line,
   circle,
   controlDot,
+  openControlDot,
   box,
   text,
   arc,
@@ -448,7 +449,8 @@ const _controlledGate = (
   nestedDepth: number,
 ): SVGElement => {
   const targetGateSvgs: SVGElement[] = [];
-  const { type, x, controlsY, label, displayArgs, width } = renderData;
+  const { type, x, controlsY, negativeControlsY, label, displayArgs, width } =
+    renderData;
   let { targetsY } = renderData;
 
   // Get SVG for target gates
@@ -473,7 +475,7 @@ const _controlledGate = (
   }
   // Get SVGs for control dots
   const controlledDotsSvg: SVGElement[] = controlsY.map((y) =>
-    controlDot(x, y),
+    negativeControlsY?.includes(y) ? openControlDot(x, y) : controlDot(x, y),
   );
   // Create control lines
   const maxY: number = Math.max(...controlsY, ...(targetsY as number[]));
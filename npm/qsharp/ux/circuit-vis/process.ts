@@ -155,6 +155,7 @@ const _opToRenderData = (
 
   let isAdjoint: boolean;
   let controls: Register[] | undefined;
+  let negativeControls: Register[] | undefined;
   let targets: Register[];
   switch (op.kind) {
     case "measurement":
@@ -165,6 +166,7 @@ const _opToRenderData = (
     case "unitary":
       isAdjoint = op.isAdjoint ?? false;
       controls = op.controls;
+      negativeControls = op.negativeControls;
       targets = op.targets;
       break;
     case "ket":
@@ -186,6 +188,11 @@ const _opToRenderData = (
   // Set y coords
   renderData.controlsY = controls?.map((reg) => _getRegY(reg, registers)) || [];
   renderData.targetsY = targets.map((reg) => _getRegY(reg, registers));
+  if (negativeControls != null && negativeControls.length > 0) {
+    renderData.negativeControlsY = negativeControls.map((reg) =>
+      _getRegY(reg, registers),
+    );
+  }
 
   if (isConditional) {
     // Classically-controlled operations
@@ -11,6 +11,8 @@ from ._qsharp import (
     estimate,
     set_quantum_seed,
     set_classical_seed,
+    set_custom_intrinsic_handler,
+    set_display_precision,
     dump_machine,
     dump_circuit,
     StateDump,
@@ -42,6 +44,8 @@ __all__ = [
     "run",
     "set_quantum_seed",
     "set_classical_seed",
+    "set_custom_intrinsic_handler",
+    "set_display_precision",
     "dump_machine",
     "dump_circuit",
     "compile",
@@ -144,12 +144,25 @@ class Interpreter:
         """
         ...
 
-    def interpret(self, input: str, output_fn: Callable[[Output], None]) -> Any:
+    def target_profile(self) -> TargetProfile:
+        """
+        Returns the target profile the interpreter was constructed with.
+        """
+        ...
+
+    def interpret(
+        self,
+        input: str,
+        output_fn: Callable[[Output], None],
+        all_values: bool = False,
+    ) -> Any:
         """
         Interprets Q# source code.
 
         :param input: The Q# source code to interpret.
         :param output_fn: A callback function that will be called with each output.
+        :param all_values: If true, each top-level statement is interpreted in turn and
+            the list of their values is returned, with `None` for those that produce no value.
 
         :returns value: The value returned by the last statement in the input.
 
@@ -164,6 +177,13 @@ class Interpreter:
         noise: Optional[Tuple[float, float, float]],
         callable: Optional[GlobalCallable],
         args: Optional[Any],
+        report_timing: bool = False,
+        replay: Optional[List[bool]] = None,
+        pack: bool = False,
+        msb_first: bool = False,
+        shots: int = 1,
+        progress: Optional[Callable[[int], Optional[bool]]] = None,
+        capture_intrinsics: bool = False,
     ) -> Any:
         """
         Runs the given Q# expression with an independent instance of the simulator.
@@ -174,10 +194,78 @@ class Interpreter:
             to use in simulation as a parametric Pauli noise.
         :param callable: The callable to run, if no entry expression is provided.
         :param args: The arguments to pass to the callable, if any.
+        :param report_timing: If true, the wall-clock duration of the run, or of each
+            shot, is measured.
+        :param replay: Measurement outcomes to force, in order, typically recorded with
+            `last_measurements` so that a previous run is reproduced exactly.
+        :param pack: If true and the result is a non-empty `Result[]`, it is returned as
+            an int with the first result as the least significant bit.
+        :param msb_first: If true, packed results have the first result as the most
+            significant bit instead, as in a bit string read from left to right.
+        :param shots: The number of times to run the entry expression, each with a fresh
+            simulator. If a quantum seed is set, it is incremented for each shot.
+            Not supported with `callable` or `replay`.
+        :param progress: A callback function that is called periodically during evaluation
+            with the number of loop iterations evaluated so far. Returning `False` aborts the run.
+        :param capture_intrinsics: If true, the custom intrinsic calls answered by the handler
+            set with `set_custom_intrinsic_handler` are recorded for each shot.
+
+        :returns values: A result or runtime errors, or a list with the result or a
+            `QSharpError` for each shot if `shots` is greater than 1. If
+            `capture_intrinsics` is true, a tuple of that and a list with, for each shot,
+            the `(name, args)` of each custom intrinsic call in order. If `report_timing`
+            is true, a tuple of all that and the duration of the run in milliseconds, or
+            a list of the duration of each shot if `shots` is greater than 1.
 
-        :returns values: A result or runtime errors.
+        :raises QSharpError: If there is an error interpreting the input.
+        :raises ValueError: If `shots` is 0.
+        """
+        ...
+
+    def run_histogram(
+        self,
+        entry_expr: str,
+        shots: int,
+        output_fn: Optional[Callable[[Output], None]] = None,
+        noise: Optional[Tuple[float, float, float]] = None,
+    ) -> Dict[str, int]:
+        """
+        Runs the given Q# expression for the given number of shots, each with an
+        independent instance of the simulator, and counts the distinct results.
+
+        :param entry_expr: The entry expression.
+        :param shots: The number of shots to run.
+        :param output_fn: A callback function that will be called with each output.
+        :param noise: A tuple with probabilities of Pauli-X, Pauli-Y, and Pauli-Z errors
+            to use in simulation as a parametric Pauli noise.
+
+        :returns histogram: A dictionary mapping the Q# display form of each distinct
+            result, such as `"[One, Zero]"`, to the number of shots that returned it.
 
         :raises QSharpError: If there is an error interpreting the input.
+        :raises ValueError: If `shots` is 0.
+        """
+        ...
+
+    def get_entry_expr(self) -> Optional[str]:
+        """
+        Returns the entry expression that was set or last run, or `None` if there
+        is none, such as after evaluating other code.
+        """
+        ...
+
+    def clear_entry_expr(self) -> None:
+        """
+        Clears the entry expression, so that `run` without an entry expression
+        fails until a new one is set.
+        """
+        ...
+
+    def last_measurements(self) -> List[bool]:
+        """
+        Returns the measurement outcomes of the most recent run, invocation or
+        interpreted input, in order, with `True` for `One`. Pass them to `run` as
+        `replay` to reproduce that run.
         """
         ...
 
@@ -186,22 +274,39 @@ class Interpreter:
         callable: GlobalCallable,
         args: Any,
         output_fn: Callable[[Output], None],
+        progress: Optional[Callable[[int], Optional[bool]]] = None,
     ) -> Any:
         """
         Invokes the callable with the given arguments, converted into the appropriate Q# values.
         :param callable: The callable to invoke.
         :param args: The arguments to pass to the callable.
         :param output_fn: A callback function that will be called with each output.
+        :param progress: A callback function that is called periodically during evaluation
+            with the number of loop iterations evaluated so far. Returning `False` aborts the invocation.
         :returns values: A result or runtime errors.
         :raises QSharpError: If there is an error interpreting the input.
         """
         ...
 
+    def can_invoke(self, callable: GlobalCallable, args: Optional[Any] = None) -> bool:
+        """
+        Checks whether the given arguments can be converted to the input type of the
+        callable, without invoking it.
+        :param callable: The callable to check the arguments against.
+        :param args: The arguments that would be passed to the callable.
+        :returns: True if invoking the callable with the arguments would not fail
+            because of their types.
+        :raises QSharpError: If the callable is not found.
+        """
+        ...
+
     def qir(
         self,
         entry_expr: Optional[str],
         callable: Optional[GlobalCallable],
         args: Optional[Any],
+        strict: bool = True,
+        shots: Optional[int] = None,
     ) -> str:
         """
         Generates QIR from Q# source code. Either an entry expression or a callable with arguments must be provided.
@@ -209,8 +314,45 @@ class Interpreter:
         :param entry_expr: The entry expression.
         :param callable: The callable to generate QIR for, if no entry expression is provided.
         :param args: The arguments to pass to the callable, if any.
+        :param strict: If False, violations of the target profile are emitted as warnings
+            instead of errors, and best-effort QIR is still generated.
+        :param shots: If set, the number of shots to run the program for is recorded
+            in the module flags of the QIR.
+
+        :returns qir: The QIR string.
+        """
+        ...
+
+    def qir_to_writer(self, entry_expr: Optional[str], writer: Any) -> None:
+        """
+        Generates QIR from Q# source code and writes it to `writer` in chunks,
+        instead of returning it as one string.
+
+        :param entry_expr: The entry expression.
+        :param writer: An object with a `write` method that accepts a string,
+            such as an open text file or `io.StringIO`.
+
+        :raises QSharpError: If there is an error generating QIR.
+        """
+        ...
+
+    def qir_for_operation(
+        self,
+        operation_name: str,
+        args: Optional[Any] = None,
+    ) -> str:
+        """
+        Generates QIR for a global operation, looked up by name.
+
+        :param operation_name: The name of the operation, optionally qualified
+            with its namespace, such as `Program` or `Sample.Program`. An unqualified
+            name must match a single operation.
+        :param args: The arguments to pass to the operation, if any.
 
         :returns qir: The QIR string.
+
+        :raises QSharpError: If the operation is not found or is ambiguous, or QIR
+            can't be generated for it.
         """
         ...
 
@@ -220,6 +362,7 @@ class Interpreter:
         operation: Optional[str],
         callable: Optional[GlobalCallable],
         args: Optional[Any],
+        basis_gates: Optional[List[str]] = None,
     ) -> Circuit:
         """
         Synthesizes a circuit for a Q# program. Either an entry
@@ -235,6 +378,8 @@ class Interpreter:
 
         :param args: The arguments to pass to the callable, if any.
 
+        :param basis_gates: The gates to decompose the circuit into, e.g. `["rz", "sx", "cx"]`.
+
         :raises QSharpError: If there is an error synthesizing the circuit.
         """
         ...
@@ -276,6 +421,42 @@ class Interpreter:
         """
         ...
 
+    def set_noise(self, px: float, py: float, pz: float) -> None:
+        """
+        Sets the Pauli noise used by all subsequent `interpret`, `invoke` and `run` calls.
+        A `noise` passed to `run` overrides it for that call.
+
+        :param px: The probability of a Pauli-X error.
+        :param py: The probability of a Pauli-Y error.
+        :param pz: The probability of a Pauli-Z error.
+        """
+        ...
+
+    def clear_noise(self) -> None:
+        """
+        Removes the noise set with `set_noise`, so subsequent calls are noiseless.
+        """
+        ...
+
+    def set_custom_intrinsic_handler(
+        self,
+        handler: Optional[Callable[[str, Any], Any]],
+        names: List[str] = [],
+    ) -> None:
+        """
+        Sets a handler for custom intrinsics that the simulator does not recognize.
+
+        :param handler: A callable invoked with the intrinsic name and a tuple of its
+            arguments, one item per parameter, whose return value is converted to the
+            intrinsic's declared output type and used as the result of the intrinsic.
+            If None, any existing handler is removed.
+        :param names: The names of the intrinsics the handler should be invoked for.
+            Must not be empty when a handler is given.
+
+        :raises ValueError: If a handler is given without any names.
+        """
+        ...
+
     def dump_machine(self) -> StateDumpData:
         """
         Returns the sparse state vector of the simulator as a StateDump object.
@@ -284,6 +465,110 @@ class Interpreter:
         """
         ...
 
+    def dump_machine_npy(self) -> bytes:
+        """
+        Returns the state vector of the simulator as the contents of a `.npy` file,
+        which can be loaded with `numpy.load`. The array is a dense complex128 array
+        indexed as in `dump_machine`, so its size doubles with each qubit.
+
+        :returns: The bytes of the `.npy` file.
+        :raises ValueError: If the state has more than 30 qubits.
+        """
+        ...
+
+    def dump_probabilities(self, threshold: float = 1e-12) -> Dict[int, float]:
+        """
+        Returns the probability of measuring each computational basis state in the
+        current simulator state, without collapsing it.
+
+        :param threshold: Probabilities below this value are omitted.
+
+        :returns: A dictionary from basis state indices, as shown by `dump_machine`,
+            to their probabilities.
+        """
+        ...
+
+    def fidelity(self, reference_dump: StateDumpData) -> float:
+        """
+        Returns the fidelity between the current simulator state and a state
+        previously captured with `dump_machine`.
+
+        :param reference_dump: The reference state dump.
+
+        :returns: The fidelity, between 0.0 and 1.0.
+        """
+        ...
+
+    def density_matrix(self, qubits: List[int]) -> List[List[complex]]:
+        """
+        Returns the reduced density matrix of the given qubits in the current
+        simulator state, tracing out all other qubits.
+
+        :param qubits: The indices of the qubits to keep, as shown by `dump_machine`.
+            The first qubit is the most significant bit of the matrix indices.
+
+        :returns: The reduced density matrix.
+        """
+        ...
+
+    def outcome_probability(self, bits: List[Tuple[int, bool]]) -> float:
+        """
+        Returns the probability that the given qubits would measure to the given
+        outcomes in the current simulator state, summing over the outcomes of all
+        other qubits. The state is not collapsed.
+
+        :param bits: Pairs of a qubit index, as shown by `dump_machine`, and its
+            outcome, where True is `One`.
+
+        :returns: The probability, between 0.0 and 1.0.
+        """
+        ...
+
+    def are_entangled(self, q0: int, q1: int, tol: float = 1e-9) -> bool:
+        """
+        Returns whether the two given qubits are entangled in the current
+        simulator state, meaning their joint state is not a product state.
+
+        :param q0: The index of the first qubit, as shown by `dump_machine`.
+        :param q1: The index of the second qubit, as shown by `dump_machine`.
+        :param tol: The tolerance used when comparing the joint state to a product state.
+
+        :returns: True if the qubits are entangled.
+        """
+        ...
+
+    def reset_all_qubits(self) -> None:
+        """
+        Resets and releases all qubits in the simulator, so that the next allocation
+        starts from a fresh `|0...0⟩` register. Qubits allocated by earlier input
+        must not be used afterwards.
+        """
+        ...
+
+    def global_phase(self) -> float:
+        """
+        Returns the accumulated angle, in radians, of the global phases
+        applied to the simulator state.
+        """
+        ...
+
+    def set_state_size_tracking(self, enabled: bool) -> None:
+        """
+        Enables or disables recording the number of nonzero amplitudes in the
+        simulator state after each gate, which slows down simulation.
+
+        :param enabled: Whether to record the state size.
+        """
+        ...
+
+    def state_size_history(self) -> List[int]:
+        """
+        Returns the sizes the simulator state went through while state size
+        tracking was enabled, since the last run on a fresh simulator. Consecutive
+        gates that leave the size unchanged are summarized as a single entry.
+        """
+        ...
+
     def dump_circuit(self) -> Circuit:
         """
         Dumps the current circuit state of the interpreter.
@@ -316,8 +601,11 @@ class Interpreter:
             **kwargs: Additional keyword arguments to pass to the execution.
               - name (str): The name of the program. This is used as the entry point for the program.
               - search_path (Optional[str]): The optional search path for resolving file references.
+              - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+              - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
               - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
               - program_type (ProgramType, optional): The type of program compilation to perform.
+              - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 
         Returns:
             value: The value returned by the last statement in the source code.
@@ -376,6 +664,22 @@ class StateDumpData:
     complex amplitudes.
     """
     def get_dict(self) -> dict: ...
+    def phases(self) -> List[Tuple[int, float]]:
+        """
+        Returns the phase in radians of each nonzero amplitude, as pairs of state
+        integer and phase.
+        """
+        ...
+
+    def get_array(self) -> Any:
+        """
+        Returns the state as a dense numpy `complex128` array of length
+        `2**qubit_count`, including zero amplitudes. Requires numpy.
+
+        :raises ValueError: If the state has more than 30 qubits.
+        """
+        ...
+
     def __repr__(self) -> str: ...
     def __str__(self) -> str: ...
     def _repr_markdown_(self) -> str: ...
@@ -383,6 +687,28 @@ class StateDumpData:
 
 class Circuit:
     def json(self) -> str: ...
+    def depth(self) -> int:
+        """
+        Returns the depth of the circuit, the number of layers of operations when
+        each operation is applied as soon as the qubits it acts on are free.
+        """
+        ...
+
+    def gate_counts(self) -> Dict[str, int]:
+        """
+        Returns the number of each kind of gate in the circuit, named as in basis
+        gate lists, such as `"cx"` or `"sdg"`. Measurements are counted under
+        `"measure"` and resets under `"reset"`.
+        """
+        ...
+
+    def latex(self) -> str:
+        """
+        Returns the circuit as a `quantikz` diagram, which can be included in LaTeX
+        documents that load the `quantikz` package.
+        """
+        ...
+
     def __repr__(self) -> str: ...
     def __str__(self) -> str: ...
 
@@ -400,6 +726,15 @@ class QasmError(BaseException):
 
     ...
 
+def set_display_precision(digits: int) -> None:
+    """
+    Sets the number of decimal digits used when rendering state amplitudes.
+
+    :param digits: The number of digits after the decimal point.
+    :raises ValueError: If the number of digits is negative or greater than 17.
+    """
+    ...
+
 def physical_estimates(logical_resources: str, params: str) -> str:
     """
     Estimates physical resources from pre-calculated logical resources.
@@ -437,6 +772,9 @@ def circuit_qasm_program(
         **kwargs: Additional keyword arguments to pass to the execution.
           - name (str): The name of the program. This is used as the entry point for the program.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
     Returns:
         Circuit: The synthesized circuit.
 
@@ -474,6 +812,9 @@ def compile_qasm_program_to_qir(
           - name (str): The name of the circuit. This is used as the entry point for the program.
           - target_profile (TargetProfile): The target profile to use for code generation.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
           - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
 
     Returns:
@@ -485,6 +826,76 @@ def compile_qasm_program_to_qir(
     """
     ...
 
+def compile_qasm_program_diagnostics(
+    source: str,
+    read_file: Callable[[str], Tuple[str, str]],
+    list_directory: Callable[[str], List[Dict[str, str]]],
+    resolve_path: Callable[[str, str], str],
+    fetch_github: Callable[[str, str, str, str], str],
+    **kwargs
+) -> List[Dict[str, Any]]:
+    """
+    Parses and analyzes the OpenQASM source code, returning every syntax and
+    semantic diagnostic found rather than failing on the first one.
+
+    Note:
+        This call while exported is not intended to be used directly by the user.
+        It is intended to be used by the Python wrapper which will handle the
+        callbacks and other Python specific details.
+
+    Args:
+        source (str): The OpenQASM source code to check.
+        read_file (Callable[[str], Tuple[str, str]]): A callable that reads a file and returns its content and path.
+        list_directory (Callable[[str], List[Dict[str, str]]]): A callable that lists the contents of a directory.
+        resolve_path (Callable[[str, str], str]): A callable that resolves a file path given a base path and a relative path.
+        fetch_github (Callable[[str, str, str, str], str]): A callable that fetches a file from GitHub.
+        **kwargs: Additional keyword arguments to pass to the compilation.
+          - name (str): The name of the circuit. This is used as the entry point for the program.
+          - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
+          - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
+
+    Returns:
+        List[Dict[str, Any]]: One entry per diagnostic with the keys `message`, `code`,
+        `file`, `start`, and `end`. `start` and `end` are byte offsets into `file`.
+    """
+    ...
+
+def qasm3_resolved_includes(
+    source: str,
+    search_path: str,
+    read_file: Callable[[str], Tuple[str, str]],
+    list_directory: Callable[[str], List[Dict[str, str]]],
+    resolve_path: Callable[[str, str], str],
+    fetch_github: Callable[[str, str, str, str], str],
+) -> List[str]:
+    """
+    Parses the OpenQASM source code and returns the paths of every file it
+    includes, directly or transitively, in the order they were resolved.
+
+    Note:
+        This call while exported is not intended to be used directly by the user.
+        It is intended to be used by the Python wrapper which will handle the
+        callbacks and other Python specific details.
+
+    Args:
+        source (str): The OpenQASM source code to parse.
+        search_path (str): The path used to resolve relative include paths.
+        read_file (Callable[[str], Tuple[str, str]]): A callable that reads a file and returns its content and path.
+        list_directory (Callable[[str], List[Dict[str, str]]]): A callable that lists the contents of a directory.
+        resolve_path (Callable[[str, str], str]): A callable that resolves a file path given a base path and a relative path.
+        fetch_github (Callable[[str, str, str, str], str]): A callable that fetches a file from GitHub.
+
+    Returns:
+        List[str]: The resolved include paths. The root source is not included.
+
+    Raises:
+        QasmError: If the source or any of its includes fail to parse or resolve.
+    """
+    ...
+
 def compile_qasm_to_qsharp(
     source: str,
     read_file: Callable[[str], Tuple[str, str]],
@@ -510,6 +921,9 @@ def compile_qasm_to_qsharp(
         **kwargs: Additional keyword arguments to pass to the execution.
           - name (str): The name of the circuit. This is used as the entry point for the program.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 
     Returns:
         str: The converted Q# code as a string.
@@ -543,6 +957,9 @@ def resource_estimate_qasm_program(
         **kwargs: Additional keyword arguments to pass to the execution.
           - name (str): The name of the circuit. This is used as the entry point for the program. Defaults to 'program'.
           - search_path (str): The optional search path for resolving imports.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
     Returns:
         str: The estimated resource requirements for executing the OpenQASM source code.
     """
@@ -579,9 +996,13 @@ def run_qasm_program(
           - target_profile (TargetProfile): The target profile to use for execution.
           - name (str): The name of the circuit. This is used as the entry point for the program. Defaults to 'program'.
           - search_path (str): The optional search path for resolving imports.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
           - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
           - shots (int): The number of shots to run the program for. Defaults to 1.
           - seed (int): The seed to use for the random number generator.
+          - inputs (dict): The values of the program's `input` declarations, keyed by name.
 
     Returns:
         Any: The result of the execution.
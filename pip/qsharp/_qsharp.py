@@ -10,6 +10,7 @@ from ._native import (  # type: ignore
     Output,
     Circuit,
     GlobalCallable,
+    set_display_precision as _set_display_precision,
 )
 from typing import (
     Any,
@@ -315,12 +316,25 @@ class StateDump:
                 return False
         return True
 
+    def phases(self) -> List[Tuple[int, float]]:
+        """
+        Returns the phase in radians of each nonzero amplitude, as pairs of state index and phase.
+        """
+        return self.__data.phases()
+
     def as_dense_state(self) -> List[complex]:
         """
         Returns the state dump as a dense list of complex amplitudes. This will include zero amplitudes.
         """
         return [self.__inner.get(i, complex(0)) for i in range(2**self.qubit_count)]
 
+    def get_array(self) -> Any:
+        """
+        Returns the state dump as a dense numpy `complex128` array, including zero amplitudes.
+        Requires numpy, and raises a `ValueError` if the state has more than 30 qubits.
+        """
+        return self.__data.get_array()
+
 
 class ShotResult(TypedDict):
     """
@@ -464,7 +478,10 @@ def run(
             DepolarizingNoise,
         ]
     ] = None,
-) -> List[Any]:
+    report_timing: bool = False,
+    pack: bool = False,
+    msb_first: bool = False,
+) -> Union[List[Any], Tuple[List[Any], List[float]]]:
     """
     Runs the given Q# expression for the given number of shots.
     Each shot uses an independent instance of the simulator.
@@ -476,9 +493,17 @@ def run(
     :param on_result: A callback function that will be called with each result.
     :param save_events: If true, the output of each shot will be saved. If false, they will be printed.
     :param noise: The noise to use in simulation.
+    :param report_timing: If true, the wall-clock duration of each shot is measured.
+    :param pack: If true, a `Result[]` returned by a shot is packed into an int, with the
+        first result as the least significant bit. This is much faster for large registers.
+        Empty arrays are returned as lists.
+    :param msb_first: If true, packed results have the first result as the most significant
+        bit instead, which matches the qubit order of `dump_machine` indices when each result
+        measures the qubit at the same position. Defaults to false.
 
     :returns values: A list of results or runtime errors. If `save_events` is true,
-    a List of ShotResults is returned.
+    a List of ShotResults is returned. If `report_timing` is true, a tuple of those
+    values and a parallel list of shot durations in milliseconds is returned.
 
     :raises QSharpError: If there is an error interpreting the input.
     :raises ValueError: If the number of shots is less than 1.
@@ -492,6 +517,7 @@ def run(
     start_time = monotonic()
 
     results: List[ShotResult] = []
+    timings: List[float] = []
 
     def print_output(output: Output) -> None:
         if _in_jupyter:
@@ -532,7 +558,13 @@ def run(
             noise,
             callable,
             args,
+            report_timing,
+            pack=pack,
+            msb_first=msb_first,
         )
+        if report_timing:
+            run_results, duration = run_results
+            timings.append(duration)
         results[-1]["result"] = run_results
         if on_result:
             on_result(results[-1])
@@ -544,10 +576,10 @@ def run(
     durationMs = (monotonic() - start_time) * 1000
     telemetry_events.on_run_end(durationMs, shots)
 
-    if save_events:
-        return results
-    else:
-        return [shot["result"] for shot in results]
+    values = results if save_events else [shot["result"] for shot in results]
+    if report_timing:
+        return (values, timings)
+    return values
 
 
 # Class that wraps generated QIR, which can be used by
@@ -619,6 +651,7 @@ def circuit(
     entry_expr: Optional[Union[str, Callable]] = None,
     *args,
     operation: Optional[str] = None,
+    basis_gates: Optional[List[str]] = None,
 ) -> Circuit:
     """
     Synthesizes a circuit for a Q# program. Either an entry
@@ -633,6 +666,9 @@ def circuit(
     an operation of a lambda expression. The operation must take only
     qubits or arrays of qubits as parameters.
 
+    :param basis_gates: The gates to decompose the circuit into, e.g. `["rz", "sx", "cx"]`.
+        Gates that can't be decomposed into this set raise an error.
+
     :raises QSharpError: If there is an error synthesizing the circuit.
     """
     ipython_helper()
@@ -644,10 +680,10 @@ def circuit(
         elif len(args) == 0:
             args = None
         res = get_interpreter().circuit(
-            callable=entry_expr.__global_callable, args=args
+            callable=entry_expr.__global_callable, args=args, basis_gates=basis_gates
         )
     else:
-        res = get_interpreter().circuit(entry_expr, operation)
+        res = get_interpreter().circuit(entry_expr, operation, basis_gates=basis_gates)
 
     durationMs = (monotonic() - start) * 1000
     telemetry_events.on_circuit_end(durationMs)
@@ -736,6 +772,38 @@ def set_classical_seed(seed: Optional[int]) -> None:
     get_interpreter().set_classical_seed(seed)
 
 
+def set_custom_intrinsic_handler(
+    handler: Optional[Callable[[str, Any], Any]], names: List[str]
+) -> None:
+    """
+    Sets a handler for custom intrinsics that the simulator does not recognize,
+    for example to log or mock calls that would be handled by hardware.
+    The handler is called with the intrinsic name and a tuple of its arguments,
+    one item per parameter, and its return value is converted to the intrinsic's
+    declared output type and used as the result of the intrinsic. Handlers for
+    intrinsics that return `Unit` should return None.
+
+    :param handler: The callable to invoke. If None, any existing handler is removed.
+    :param names: The names of the intrinsics the handler should be invoked for.
+        Must not be empty when a handler is given.
+
+    :raises ValueError: If a handler is given without any names.
+    """
+    get_interpreter().set_custom_intrinsic_handler(handler, names)
+
+
+def set_display_precision(digits: int) -> None:
+    """
+    Sets the number of digits after the decimal point used when displaying
+    state amplitudes, e.g. in the output of `dump_machine`.
+
+    :param digits: The number of digits to display, from 0 to 17. The default is 4.
+
+    :raises ValueError: If the number of digits is out of range.
+    """
+    _set_display_precision(digits)
+
+
 def dump_machine() -> StateDump:
     """
     Returns the sparse state vector of the simulator as a StateDump object.
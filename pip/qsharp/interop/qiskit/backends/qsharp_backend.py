@@ -36,6 +36,9 @@ def _to_qiskit_bitstring(obj):
         return " ".join([_to_qiskit_bitstring(term) for term in obj])
     elif isinstance(obj, list):
         return "".join([_map_qsharp_value_to_bit(bit) for bit in obj])
+    elif isinstance(obj, Result):
+        # A single declared `output bit` is returned as a scalar result.
+        return _map_qsharp_value_to_bit(obj)
     else:
         return obj
 
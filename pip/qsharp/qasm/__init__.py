@@ -2,7 +2,7 @@
 # Licensed under the MIT License.
 
 from ._circuit import circuit
-from ._compile import compile
+from ._compile import compile, diagnostics, resolved_includes
 from ._estimate import estimate
 from ._import import import_qasm
 from ._run import run
@@ -11,8 +11,10 @@ from .._native import ProgramType, OutputSemantics, QasmError  # type: ignore
 __all__ = [
     "circuit",
     "compile",
+    "diagnostics",
     "estimate",
     "import_qasm",
+    "resolved_includes",
     "run",
     "ProgramType",
     "OutputSemantics",
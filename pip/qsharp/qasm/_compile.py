@@ -3,12 +3,14 @@
 
 import warnings
 from time import monotonic
-from typing import Any, Callable, Dict, Optional, Union
+from typing import Any, Callable, Dict, List, Optional, Union
 from .._fs import read_file, list_directory, resolve
 from .._http import fetch_github
 
 from .._native import (  # type: ignore
+    compile_qasm_program_diagnostics,
     compile_qasm_program_to_qir,
+    qasm3_resolved_includes,
 )
 from .._qsharp import QirInputData, get_interpreter, ipython_helper, TargetProfile
 from .. import telemetry_events
@@ -32,6 +34,9 @@ def compile(
           - name (str): The name of the circuit. This is used as the entry point for the program.
           - target_profile (TargetProfile): The target profile to use for code generation.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
           - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
 
     Returns:
@@ -99,3 +104,76 @@ def compile(
     telemetry_events.on_compile_qasm_end(durationMs, target_profile)
 
     return res
+
+
+def diagnostics(
+    source: str,
+    **kwargs: Optional[Dict[str, Any]],
+) -> List[Dict[str, Any]]:
+    """
+    Parses and analyzes the OpenQASM source code without generating QIR,
+    returning every syntax and semantic diagnostic found rather than raising
+    on the first one. This is useful for editor integrations that want to
+    show every problem at once.
+
+    Args:
+        source (str): An OpenQASM program.
+        **kwargs: Additional keyword arguments to pass to the compilation.
+          - name (str): The name of the circuit. This is used as the entry point for the program.
+          - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
+          - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
+
+    Returns:
+        List[Dict[str, Any]]: One entry per diagnostic with the keys `message`, `code`,
+        `file`, `start`, and `end`. `start` and `end` are byte offsets into `file`.
+        The list is empty if the program has no errors.
+    """
+    # remove any entries from kwargs with a None key or None value
+    kwargs = {k: v for k, v in kwargs.items() if k is not None and v is not None}
+
+    if "search_path" not in kwargs:
+        kwargs["search_path"] = "."
+
+    return compile_qasm_program_diagnostics(
+        source,
+        read_file,
+        list_directory,
+        resolve,
+        fetch_github,
+        **kwargs,
+    )
+
+
+def resolved_includes(
+    source: str,
+    **kwargs: Optional[Dict[str, Any]],
+) -> List[str]:
+    """
+    Parses the OpenQASM source code and returns the paths of every file it
+    includes, directly or transitively. This is useful for auditing the
+    dependencies of a program.
+
+    Args:
+        source (str): An OpenQASM program.
+        **kwargs: Additional keyword arguments.
+          - search_path (Optional[str]): The optional search path for resolving file references.
+
+    Returns:
+        List[str]: The resolved include paths, in the order they were resolved.
+
+    Raises:
+        QasmError: If the source or any of its includes fail to parse or resolve.
+    """
+    search_path = kwargs.get("search_path") or "."
+
+    return qasm3_resolved_includes(
+        source,
+        search_path,
+        read_file,
+        list_directory,
+        resolve,
+        fetch_github,
+    )
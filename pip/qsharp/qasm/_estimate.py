@@ -38,6 +38,9 @@ def estimate(
         **kwargs: Additional keyword arguments to pass to the execution.
           - name (str): The name of the circuit. This is used as the entry point for the program. Defaults to 'program'.
           - search_path (str): The optional search path for resolving imports.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 
     Returns:
         EstimatorResult: The estimated resources.
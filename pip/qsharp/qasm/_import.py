@@ -27,6 +27,9 @@ def import_qasm(
         **kwargs: Additional keyword arguments to pass to the execution.
           - name (str): The name of the program. This is used as the entry point for the program.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
           - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
           - program_type (ProgramType, optional): The type of program compilation to perform. Defaults to `ProgramType.Operation`.
 
@@ -55,8 +55,12 @@ def run(
           - name (str): The name of the circuit. This is used as the entry point for the program.
           - target_profile (TargetProfile): The target profile to use for code generation.
           - search_path (Optional[str]): The optional search path for resolving file references.
+          - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+          - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+          - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
           - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
           - seed (int): The seed to use for the random number generator.
+          - inputs (Dict[str, Any]): The values of the program's `input` declarations, keyed by name.
 
     Returns:
         values: A list of results or runtime errors. If `save_events` is true,
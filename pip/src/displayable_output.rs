@@ -7,10 +7,30 @@ mod tests;
 use num_bigint::BigUint;
 use num_complex::{Complex64, ComplexFloat};
 use qsc::{
-    fmt_basis_state_label, fmt_complex, format_state_id, get_matrix_latex, get_phase,
-    get_state_latex,
+    fmt_basis_state_label, fmt_complex, fmt_complex_with_precision, format_state_id,
+    get_matrix_latex, get_phase, get_state_latex_with_precision, DEFAULT_DISPLAY_PRECISION,
 };
-use std::fmt::Write;
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Largest number of decimal digits that can be used when rendering state amplitudes,
+/// which is the most a `f64` can carry.
+pub const MAX_DISPLAY_PRECISION: usize = 17;
+
+/// Number of decimal digits used when rendering state amplitudes.
+static DISPLAY_PRECISION: AtomicUsize = AtomicUsize::new(DEFAULT_DISPLAY_PRECISION);
+
+/// Sets the number of decimal digits used when rendering state amplitudes.
+pub fn set_display_precision(digits: usize) {
+    DISPLAY_PRECISION.store(digits, Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn display_precision() -> usize {
+    DISPLAY_PRECISION.load(Ordering::Relaxed)
+}
 
 #[derive(Clone)]
 pub struct DisplayableState(pub Vec<(BigUint, Complex64)>, pub usize);
@@ -18,6 +38,7 @@ pub struct DisplayableMatrix(pub Vec<Vec<Complex64>>);
 
 impl DisplayableState {
     pub fn to_plain(&self) -> String {
+        let precision = display_precision();
         if self.1 > 0 {
             format!(
                 "STATE:{}",
@@ -28,7 +49,7 @@ impl DisplayableState {
                             output,
                             "\n{}: {}",
                             format_state_id(id, self.1),
-                            fmt_complex(state)
+                            fmt_complex_with_precision(state, precision)
                         );
                         output
                     })
@@ -39,6 +60,7 @@ impl DisplayableState {
     }
 
     pub fn to_html(&self) -> String {
+        let precision = display_precision();
         if self.1 > 0 {
             format!(
                 include_str!("state_header_template.html"),
@@ -50,7 +72,7 @@ impl DisplayableState {
                             output,
                             include_str!("state_row_template.html"),
                             fmt_basis_state_label(id, self.1),
-                            fmt_complex(state),
+                            fmt_complex_with_precision(state, precision),
                             amplitude,
                             amplitude,
                             get_phase(state),
@@ -68,7 +90,7 @@ impl DisplayableState {
     }
 
     pub fn to_latex(&self) -> Option<String> {
-        get_state_latex(&self.0, self.1)
+        get_state_latex_with_precision(&self.0, self.1, display_precision())
     }
 }
 
@@ -1,17 +1,23 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use std::ffi::CString;
 use std::path::{Path, PathBuf};
 
-use pyo3::exceptions::PyException;
+use miette::Diagnostic;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use pyo3::exceptions::{PyException, PyUserWarning, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use pyo3::IntoPyObjectExt;
+use qsc::codegen::qir::{set_output_labels, Program, ToQir};
 use qsc::hir::PackageId;
 use qsc::interpret::output::Receiver;
 use qsc::interpret::{into_errors, CircuitEntryPoint, Interpreter};
 use qsc::qasm::io::{SourceResolver, SourceResolverContext};
-use qsc::qasm::{OperationSignature, QubitSemantics};
+use qsc::qasm::types::{ArrayDimensions, Type};
+use qsc::qasm::{OperationSignature, QubitSemantics, Seconds};
 use qsc::target::Profile;
 use qsc::{
     ast::Package, error::WithSource, interpret, project::FileSystem, LanguageFeatures, SourceMap,
@@ -100,9 +106,13 @@ where
 ///       - target_profile (TargetProfile): The target profile to use for execution.
 ///       - name (str): The name of the circuit. This is used as the entry point for the program. Defaults to 'program'.
 ///       - search_path (str): The optional search path for resolving imports.
+///       - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+///       - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+///       - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 ///       - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
 ///       - shots (int): The number of shots to run the program for. Defaults to 1.
 ///       - seed (int): The seed to use for the random number generator.
+///       - inputs (dict): The values of the program's `input` declarations, keyed by name.
 ///
 /// Returns:
 ///     Any: The result of the execution.
@@ -126,7 +136,12 @@ pub(crate) fn run_qasm_program(
     fetch_github: Option<PyObject>,
     kwargs: Option<Bound<'_, PyDict>>,
 ) -> PyResult<PyObject> {
-    let mut receiver = OptionalCallbackReceiver { callback, py };
+    let mut receiver = OptionalCallbackReceiver {
+        callback,
+        progress: None,
+        progress_error: None,
+        py,
+    };
 
     let kwargs = kwargs.unwrap_or_else(|| PyDict::new(py));
 
@@ -136,6 +151,7 @@ pub(crate) fn run_qasm_program(
     let seed = get_seed(&kwargs);
     let shots = get_shots(&kwargs)?;
     let search_path = get_search_path(&kwargs)?;
+    let inputs = get_inputs(&kwargs)?;
 
     let fs = create_filesystem_from_py(py, read_file, list_directory, resolve_path, fetch_github);
     let mut resolver = ImportResolver::new(fs, PathBuf::from(search_path));
@@ -146,16 +162,27 @@ pub(crate) fn run_qasm_program(
         &mut resolver,
         ProgramType::File,
         output_semantics,
-        false,
+        inputs.is_some(),
+        &kwargs,
     )?;
 
-    let package_type = PackageType::Exe;
+    // An operation with input parameters can't be an entry point, so when the inputs are
+    // bound, the package is compiled as a library and run through the entry expression.
+    let package_type = if inputs.is_some() {
+        PackageType::Lib
+    } else {
+        PackageType::Exe
+    };
     let language_features = LanguageFeatures::default();
     let mut interpreter =
         create_interpreter_from_ast(package, source_map, target, language_features, package_type)
             .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
 
-    let entry_expr = signature.create_entry_expr_from_params(String::new());
+    let params = match inputs {
+        Some(inputs) => create_entry_params_from_inputs(&inputs, &signature)?,
+        None => String::new(),
+    };
+    let entry_expr = signature.create_entry_expr_from_params(params);
     interpreter
         .set_entry_expr(&entry_expr)
         .map_err(|errors| map_entry_compilation_errors(errors, &signature))?;
@@ -215,6 +242,9 @@ pub(crate) fn run_ast(
 ///     **kwargs: Additional keyword arguments to pass to the execution.
 ///       - name (str): The name of the circuit. This is used as the entry point for the program. Defaults to 'program'.
 ///       - search_path (str): The optional search path for resolving imports.
+///       - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+///       - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+///       - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 /// Returns:
 ///     str: The estimated resource requirements for executing the OpenQASM source code.
 #[pyfunction]
@@ -249,6 +279,7 @@ pub(crate) fn resource_estimate_qasm_program(
         program_type,
         output_semantics,
         false,
+        &kwargs,
     )?;
 
     match crate::interop::estimate_qasm(package, source_map, job_params) {
@@ -295,6 +326,9 @@ pub(crate) fn resource_estimate_qasm_program(
 ///       - name (str): The name of the circuit. This is used as the entry point for the program.
 ///       - target_profile (TargetProfile): The target profile to use for code generation.
 ///       - search_path (Optional[str]): The optional search path for resolving file references.
+///       - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+///       - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+///       - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 ///       - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
 ///
 /// Returns:
@@ -335,6 +369,7 @@ pub(crate) fn compile_qasm_program_to_qir(
         program_ty,
         output_semantics,
         false,
+        &kwargs,
     )?;
 
     let package_type = PackageType::Lib;
@@ -344,7 +379,152 @@ pub(crate) fn compile_qasm_program_to_qir(
             .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
     let entry_expr = signature.create_entry_expr_from_params(String::new());
 
-    generate_qir_from_ast(entry_expr, &mut interpreter)
+    let mut program = generate_program_from_ast(entry_expr, &mut interpreter)?;
+    set_output_labels(&mut program, &signature.output_labels)
+        .map_err(|err| QSharpError::new_err(err.to_string()))?;
+    Ok(program.to_qir(&program))
+}
+
+/// Parses and analyzes the OpenQASM source code, returning every syntax and
+/// semantic diagnostic found rather than failing on the first one.
+///
+/// Note:
+///     This call while exported is not intended to be used directly by the user.
+///     It is intended to be used by the Python wrapper which will handle the
+///     callbacks and other Python specific details.
+///
+/// Args:
+///     source (str): The OpenQASM source code to check.
+///     read_file (Callable[[str], Tuple[str, str]]): A callable that reads a file and returns its content and path.
+///     list_directory (Callable[[str], List[Dict[str, str]]]): A callable that lists the contents of a directory.
+///     resolve_path (Callable[[str, str], str]): A callable that resolves a file path given a base path and a relative path.
+///     fetch_github (Callable[[str, str, str, str], str]): A callable that fetches a file from GitHub.
+///     **kwargs: Additional keyword arguments to pass to the compilation.
+///       - name (str): The name of the circuit. This is used as the entry point for the program.
+///       - search_path (Optional[str]): The optional search path for resolving file references.
+///       - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+///       - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+///       - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
+///       - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
+///
+/// Returns:
+///     List[Dict[str, Any]]: One entry per diagnostic with the keys `message`, `code`,
+///     `file`, `start`, and `end`. `start` and `end` are byte offsets into `file`.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(
+    signature = (source, read_file, list_directory, resolve_path, fetch_github, **kwargs)
+)]
+pub(crate) fn compile_qasm_program_diagnostics(
+    py: Python,
+    source: &str,
+    read_file: Option<PyObject>,
+    list_directory: Option<PyObject>,
+    resolve_path: Option<PyObject>,
+    fetch_github: Option<PyObject>,
+    kwargs: Option<Bound<'_, PyDict>>,
+) -> PyResult<PyObject> {
+    let kwargs = kwargs.unwrap_or_else(|| PyDict::new(py));
+
+    let operation_name = get_operation_name(&kwargs)?;
+    let search_path = get_search_path(&kwargs)?;
+    let output_semantics = get_output_semantics(&kwargs, || OutputSemantics::Qiskit)?;
+
+    let fs = create_filesystem_from_py(py, read_file, list_directory, resolve_path, fetch_github);
+    let mut resolver = ImportResolver::new(fs, PathBuf::from(search_path));
+
+    let path = format!("{operation_name}.qasm");
+    let mut errors = qsc::qasm::parser::parse_source(source, &path, &mut resolver).all_errors();
+    if errors.is_empty() {
+        let mut config = qsc::qasm::CompilerConfig::new(
+            QubitSemantics::Qiskit,
+            output_semantics.into(),
+            ProgramType::File.into(),
+            Some(operation_name.as_str().into()),
+            None,
+        );
+        config.strict_declarations = get_strict_declarations(&kwargs)?;
+        config.warn_on_legacy_u = get_warn_on_legacy_u(&kwargs)?;
+        config.dt_seconds = get_dt_seconds(&kwargs)?.map(Seconds);
+        // Forget the includes seen while parsing, so they are resolved again.
+        resolver.ctx = Default::default();
+        let unit =
+            qsc::qasm::compile_to_qsharp_ast_with_config(source, path, Some(&mut resolver), config);
+        errors = unit.errors();
+    }
+
+    let diagnostics = errors
+        .iter()
+        .map(|error| qasm_diagnostic_to_dict(py, error))
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, diagnostics)?.into_py_any(py)
+}
+
+/// Converts a QASM error into a dictionary describing the diagnostic.
+/// The location is taken from the first label of the error, if any.
+fn qasm_diagnostic_to_dict<'py>(
+    py: Python<'py>,
+    error: &WithSource<qsc::qasm::error::Error>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("message", error.to_string())?;
+    dict.set_item("code", error.code().map(|code| code.to_string()))?;
+    match error.labels().and_then(|mut labels| labels.next()) {
+        Some(label) => {
+            let (source, span) = error.resolve_span(label.inner());
+            dict.set_item("file", source.name.to_string())?;
+            dict.set_item("start", span.offset())?;
+            dict.set_item("end", span.offset() + span.len())?;
+        }
+        None => {
+            dict.set_item("file", py.None())?;
+            dict.set_item("start", py.None())?;
+            dict.set_item("end", py.None())?;
+        }
+    }
+    Ok(dict)
+}
+
+/// Parses the given OpenQASM program and returns the paths of every file it
+/// includes, directly or transitively, in the order they were resolved.
+///
+/// Args:
+///     source (str): The OpenQASM source code to parse.
+///     search_path (str): The path used to resolve relative include paths.
+///     read_file (Callable[[str], Tuple[str, str]]): A callable that reads a file and returns its content and path.
+///     list_directory (Callable[[str], List[Dict[str, str]]]): A callable that lists the contents of a directory.
+///     resolve_path (Callable[[str, str], str]): A callable that resolves a file path given a base path and a relative path.
+///     fetch_github (Callable[[str, str, str, str], str]): A callable that fetches a file from GitHub.
+///
+/// Returns:
+///     List[str]: The resolved include paths. The root source is not included.
+///
+/// Raises:
+///     QasmError: If the source or any of its includes fail to parse or resolve.
+#[pyfunction]
+#[pyo3(signature = (source, search_path, read_file, list_directory, resolve_path, fetch_github))]
+pub(crate) fn qasm3_resolved_includes(
+    py: Python,
+    source: &str,
+    search_path: &str,
+    read_file: Option<PyObject>,
+    list_directory: Option<PyObject>,
+    resolve_path: Option<PyObject>,
+    fetch_github: Option<PyObject>,
+) -> PyResult<Vec<String>> {
+    let fs = create_filesystem_from_py(py, read_file, list_directory, resolve_path, fetch_github);
+    let mut resolver = ImportResolver::new(fs, PathBuf::from(search_path));
+
+    let res = qsc::qasm::parser::parse_source(source, "main.qasm", &mut resolver);
+    if res.has_errors() {
+        return Err(QasmError::new_err(format_qasm_errors(res.all_errors())));
+    }
+
+    Ok(res
+        .resolved_includes()
+        .iter()
+        .map(ToString::to_string)
+        .collect())
 }
 
 pub(crate) fn compile_qasm_enriching_errors<S: AsRef<str>, R: SourceResolver>(
@@ -354,21 +534,27 @@ pub(crate) fn compile_qasm_enriching_errors<S: AsRef<str>, R: SourceResolver>(
     program_ty: ProgramType,
     output_semantics: OutputSemantics,
     allow_input_params: bool,
+    kwargs: &Bound<'_, PyDict>,
 ) -> PyResult<(Package, SourceMap, OperationSignature)> {
     let path = format!("{}.qasm", operation_name.as_ref());
-    let config = qsc::qasm::CompilerConfig::new(
+    let mut config = qsc::qasm::CompilerConfig::new(
         QubitSemantics::Qiskit,
         output_semantics.into(),
         program_ty.into(),
         Some(operation_name.as_ref().into()),
         None,
     );
+    config.strict_declarations = get_strict_declarations(kwargs)?;
+    config.warn_on_legacy_u = get_warn_on_legacy_u(kwargs)?;
+    config.dt_seconds = get_dt_seconds(kwargs)?.map(Seconds);
     let unit = qsc::qasm::compile_to_qsharp_ast_with_config(source, path, Some(resolver), config);
 
+    let warnings = unit.warnings();
     let (source_map, errors, package, sig) = unit.into_tuple();
     if !errors.is_empty() {
         return Err(QasmError::new_err(format_qasm_errors(errors)));
     }
+    warn_qasm_warnings(kwargs.py(), warnings)?;
 
     let Some(signature) = sig else {
         return Err(QasmError::new_err(
@@ -389,12 +575,12 @@ pub(crate) fn compile_qasm_enriching_errors<S: AsRef<str>, R: SourceResolver>(
     Ok((package, source_map, signature))
 }
 
-fn generate_qir_from_ast<S: AsRef<str>>(
+fn generate_program_from_ast<S: AsRef<str>>(
     entry_expr: S,
     interpreter: &mut Interpreter,
-) -> PyResult<String> {
+) -> PyResult<Program> {
     interpreter
-        .qirgen(entry_expr.as_ref())
+        .rirgen(entry_expr.as_ref())
         .map_err(map_qirgen_errors)
 }
 
@@ -432,6 +618,7 @@ pub(crate) fn compile_qasm_to_qsharp(
         program_ty,
         output_semantics,
         true,
+        &kwargs,
     )?;
 
     let qsharp = qsc::codegen::qsharp::write_package_string(&package);
@@ -555,6 +742,9 @@ fn estimate_qasm(
 ///     **kwargs: Additional keyword arguments to pass to the execution.
 ///       - name (str): The name of the program. This is used as the entry point for the program.
 ///       - search_path (Optional[str]): The optional search path for resolving file references.
+///       - strict_declarations (bool): Whether old-style `qreg` and `creg` declarations are errors instead of warnings. Defaults to False.
+///       - warn_on_legacy_u (bool): Whether calls to the builtin `U` gate are reported as warnings suggesting `u3`. Defaults to True.
+///       - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
 /// Returns:
 ///     Circuit: The synthesized circuit.
 ///
@@ -589,8 +779,11 @@ pub(crate) fn circuit_qasm_program(
         &operation_name,
         &mut resolver,
         ProgramType::File,
-        OutputSemantics::ResourceEstimation,
+        // Resource estimation semantics would replace resets with measurements and
+        // add runtime accounting calls, neither of which belongs in a circuit.
+        OutputSemantics::OpenQasm,
         false,
+        &kwargs,
     )?;
 
     let package_type = PackageType::Exe;
@@ -609,7 +802,7 @@ pub(crate) fn circuit_qasm_program(
         .set_entry_expr(&entry_expr)
         .map_err(|errors| map_entry_compilation_errors(errors, &signature))?;
 
-    match interpreter.circuit(CircuitEntryPoint::EntryExpr(entry_expr), false) {
+    match interpreter.circuit(CircuitEntryPoint::EntryExpr(entry_expr), false, None) {
         Ok(circuit) => crate::interpreter::Circuit(circuit).into_py_any(py),
         Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
     }
@@ -636,6 +829,18 @@ pub(crate) fn format_qasm_errors(errors: Vec<WithSource<qsc::qasm::error::Error>
         .collect::<String>()
 }
 
+/// Reports the warnings of a QASM compilation through Python's warnings machinery.
+pub(crate) fn warn_qasm_warnings(
+    py: Python,
+    warnings: Vec<WithSource<qsc::qasm::error::Error>>,
+) -> PyResult<()> {
+    for warning in warnings {
+        let message = CString::new(format_qasm_errors(vec![warning]))?;
+        PyErr::warn(py, &py.get_type::<PyUserWarning>(), &message, 1)?;
+    }
+    Ok(())
+}
+
 /// Creates a `FileSystem` from the provided Python callbacks.
 /// If any of the callbacks are missing, this will panic.
 pub(crate) fn create_filesystem_from_py(
@@ -803,6 +1008,163 @@ pub(crate) fn get_shots(kwargs: &Bound<'_, PyDict>) -> PyResult<usize> {
     )
 }
 
+/// Extracts the cycle time `dt`, in seconds, from the kwargs dictionary.
+/// If the cycle time is not present, returns None.
+pub(crate) fn get_dt_seconds(kwargs: &Bound<'_, PyDict>) -> PyResult<Option<f64>> {
+    kwargs
+        .get_item("dt_seconds")?
+        .map_or_else(|| Ok(None), |x| x.extract::<Option<f64>>())
+}
+
+/// Extracts whether old-style `qreg` and `creg` declarations are errors from the kwargs dictionary.
+/// If the option is not present, returns false, so they are only reported as warnings.
+pub(crate) fn get_strict_declarations(kwargs: &Bound<'_, PyDict>) -> PyResult<bool> {
+    kwargs
+        .get_item("strict_declarations")?
+        .map_or_else(|| Ok(false), |x| x.extract::<bool>())
+}
+
+/// Extracts whether calls to the builtin `U` gate are reported as warnings from the kwargs
+/// dictionary. If the option is not present, returns true.
+pub(crate) fn get_warn_on_legacy_u(kwargs: &Bound<'_, PyDict>) -> PyResult<bool> {
+    kwargs
+        .get_item("warn_on_legacy_u")?
+        .map_or_else(|| Ok(true), |x| x.extract::<bool>())
+}
+
+/// Extracts the values of the program inputs from the kwargs dictionary.
+/// If the inputs are not present, returns None.
+pub(crate) fn get_inputs<'py>(kwargs: &Bound<'py, PyDict>) -> PyResult<Option<Bound<'py, PyDict>>> {
+    kwargs
+        .get_item("inputs")?
+        .map_or_else(|| Ok(None), |x| Ok(Some(x.downcast_into::<PyDict>()?)))
+}
+
+/// Renders the values in `inputs` as Q# literals, in the order of the signature's
+/// input parameters, so they can be passed as the arguments of the entry expression.
+fn create_entry_params_from_inputs(
+    inputs: &Bound<'_, PyDict>,
+    sig: &OperationSignature,
+) -> PyResult<String> {
+    let mut names = vec![];
+    for key in inputs.keys() {
+        names.push(key.extract::<String>()?);
+    }
+    let missing = sig
+        .input
+        .iter()
+        .filter(|(name, _)| !names.contains(name))
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    let unexpected = names
+        .iter()
+        .filter(|name| !sig.input.iter().any(|(input, _)| input == *name))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    if !missing.is_empty() || !unexpected.is_empty() {
+        let mut message = "the supplied inputs do not match the program inputs".to_string();
+        if !missing.is_empty() {
+            write!(message, "\n  missing: {}", missing.join(", "))
+                .expect("writing to string should succeed");
+        }
+        if !unexpected.is_empty() {
+            write!(message, "\n  unexpected: {}", unexpected.join(", "))
+                .expect("writing to string should succeed");
+        }
+        let expected = sig.input.iter().map(|(name, _)| name.as_str());
+        write!(
+            message,
+            "\n  expected: {}",
+            expected.collect::<Vec<_>>().join(", ")
+        )
+        .expect("writing to string should succeed");
+        return Err(PyValueError::new_err(message));
+    }
+
+    let mut params = vec![];
+    for (name, ty) in &sig.input {
+        let value = inputs
+            .get_item(name)?
+            .expect("input should be present after checking the names");
+        let literal = input_to_qsharp_literal(&value, ty).map_err(|err| {
+            let err = err.value(inputs.py()).to_string();
+            PyValueError::new_err(format!("invalid value for input `{name}`: {err}"))
+        })?;
+        params.push(literal);
+    }
+    Ok(params.join(", "))
+}
+
+/// Renders a Python value as a Q# literal of the given input type.
+fn input_to_qsharp_literal(value: &Bound<'_, PyAny>, ty: &Type) -> PyResult<String> {
+    match ty {
+        Type::Bool(_) => Ok(value.extract::<bool>()?.to_string()),
+        Type::Int(_) => Ok(value.extract::<i64>()?.to_string()),
+        Type::BigInt(_) => Ok(format!("{}L", value.extract::<BigInt>()?)),
+        Type::Double(_) => double_to_qsharp_literal(value.extract::<f64>()?),
+        Type::Complex(_) => {
+            let value = value.extract::<Complex64>()?;
+            Ok(format!(
+                "Std.Math.Complex({}, {})",
+                double_to_qsharp_literal(value.re)?,
+                double_to_qsharp_literal(value.im)?
+            ))
+        }
+        Type::Result(_) => match value.extract::<crate::interpreter::Result>() {
+            Ok(crate::interpreter::Result::Zero) => Ok("Zero".to_string()),
+            Ok(crate::interpreter::Result::One) => Ok("One".to_string()),
+            Err(_) => Ok(if value.extract::<bool>()? {
+                "One"
+            } else {
+                "Zero"
+            }
+            .to_string()),
+        },
+        Type::BoolArray(dims, _) => array_to_qsharp_literal(value, dims, &Type::Bool(false)),
+        Type::IntArray(dims, _) => array_to_qsharp_literal(value, dims, &Type::Int(false)),
+        Type::BigIntArray(dims, _) => array_to_qsharp_literal(value, dims, &Type::BigInt(false)),
+        Type::DoubleArray(dims) => array_to_qsharp_literal(value, dims, &Type::Double(false)),
+        Type::ResultArray(dims, _) => array_to_qsharp_literal(value, dims, &Type::Result(false)),
+        _ => Err(PyValueError::new_err(format!(
+            "unsupported input type: `{ty}`"
+        ))),
+    }
+}
+
+/// Renders a Python sequence, nested once per dimension, as a Q# array literal
+/// of items of the given type.
+fn array_to_qsharp_literal(
+    value: &Bound<'_, PyAny>,
+    dims: &ArrayDimensions,
+    item_ty: &Type,
+) -> PyResult<String> {
+    let inner_dims = match dims {
+        ArrayDimensions::One(..) => None,
+        ArrayDimensions::Two(_, dim) => Some(ArrayDimensions::One(*dim)),
+        ArrayDimensions::Three(_, dim1, dim2) => Some(ArrayDimensions::Two(*dim1, *dim2)),
+    };
+    let mut items = vec![];
+    for item in value.try_iter()? {
+        let item = item?;
+        items.push(match &inner_dims {
+            Some(inner_dims) => array_to_qsharp_literal(&item, inner_dims, item_ty)?,
+            None => input_to_qsharp_literal(&item, item_ty)?,
+        });
+    }
+    Ok(format!("[{}]", items.join(", ")))
+}
+
+/// Renders a finite float as a Q# `Double` literal.
+fn double_to_qsharp_literal(value: f64) -> PyResult<String> {
+    if value.is_finite() {
+        Ok(format!("{value:?}"))
+    } else {
+        Err(PyValueError::new_err(format!(
+            "{value} cannot be written as a Q# literal"
+        )))
+    }
+}
+
 /// Extracts the seed from the kwargs dictionary.
 /// If the seed is not present, or is not a valid u64, returns None.
 pub(crate) fn get_seed(kwargs: &Bound<'_, PyDict>) -> Option<u64> {
@@ -2,12 +2,16 @@
 // Licensed under the MIT License.
 
 use crate::{
-    displayable_output::{DisplayableMatrix, DisplayableOutput, DisplayableState},
+    displayable_output::{
+        DisplayableMatrix, DisplayableOutput, DisplayableState, MAX_DISPLAY_PRECISION,
+    },
     fs::file_system,
     interop::{
-        circuit_qasm_program, compile_qasm_program_to_qir, compile_qasm_to_qsharp,
-        create_filesystem_from_py, get_operation_name, get_output_semantics, get_program_type,
-        get_search_path, resource_estimate_qasm_program, run_qasm_program, ImportResolver,
+        circuit_qasm_program, compile_qasm_program_diagnostics, compile_qasm_program_to_qir,
+        compile_qasm_to_qsharp, create_filesystem_from_py, get_dt_seconds, get_operation_name,
+        get_output_semantics, get_program_type, get_search_path, get_strict_declarations,
+        get_warn_on_legacy_u, qasm3_resolved_includes, resource_estimate_qasm_program,
+        run_qasm_program, warn_qasm_warnings, ImportResolver,
     },
     noisy_simulator::register_noisy_simulator_submodule,
 };
@@ -16,12 +20,17 @@ use num_bigint::{BigInt, BigUint};
 use num_complex::Complex64;
 use pyo3::{
     create_exception,
-    exceptions::{PyException, PyValueError},
+    exceptions::{PyException, PyUserWarning, PyValueError},
     prelude::*,
-    types::{PyDict, PyList, PyString, PyTuple, PyType},
+    types::{
+        PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyInt, PyList, PyMemoryView, PyString,
+        PyTuple, PyType,
+    },
     IntoPyObjectExt,
 };
 use qsc::{
+    codegen::qir::{set_output_labels, write_qir, Program, ToQir},
+    dense_state_len,
     error::WithSource,
     fir::{self},
     hir::ty::{Prim, Ty},
@@ -32,13 +41,16 @@ use qsc::{
     },
     packages::BuildableProgram,
     project::{FileSystem, PackageCache, PackageGraphSources},
-    qasm::{compile_to_qsharp_ast_with_config, CompilerConfig, QubitSemantics},
+    qasm::{compile_to_qsharp_ast_with_config, CompilerConfig, QubitSemantics, Seconds},
     target::Profile,
-    LanguageFeatures, PackageType, SourceMap,
+    write_dense_state_bytes, CustomIntrinsicHandler, LanguageFeatures, PackageType, SourceMap,
 };
 
 use resource_estimator::{self as re, estimate_call, estimate_expr};
-use std::{cell::RefCell, fmt::Write, path::PathBuf, rc::Rc, str::FromStr};
+use rustc_hash::FxHashMap;
+use std::{
+    cell::RefCell, ffi::CString, fmt::Write, path::PathBuf, rc::Rc, str::FromStr, time::Instant,
+};
 
 /// If the classes are not Send, the Python interpreter
 /// will not be able to use them in a separate thread.
@@ -80,6 +92,7 @@ fn _native<'a>(py: Python<'a>, m: &Bound<'a, PyModule>) -> PyResult<()> {
     m.add_class::<Circuit>()?;
     m.add_class::<GlobalCallable>()?;
     m.add_function(wrap_pyfunction!(physical_estimates, m)?)?;
+    m.add_function(wrap_pyfunction!(set_display_precision, m)?)?;
     m.add("QSharpError", py.get_type::<QSharpError>())?;
     register_noisy_simulator_submodule(py, m)?;
     // QASM interop
@@ -88,6 +101,8 @@ fn _native<'a>(py: Python<'a>, m: &Bound<'a, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(run_qasm_program, m)?)?;
     m.add_function(wrap_pyfunction!(circuit_qasm_program, m)?)?;
     m.add_function(wrap_pyfunction!(compile_qasm_program_to_qir, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_qasm_program_diagnostics, m)?)?;
+    m.add_function(wrap_pyfunction!(qasm3_resolved_includes, m)?)?;
     m.add_function(wrap_pyfunction!(compile_qasm_to_qsharp, m)?)?;
     Ok(())
 }
@@ -314,12 +329,23 @@ impl From<ProgramType> for qsc::qasm::ProgramType {
     }
 }
 
+/// The approximate size, in bytes, of each chunk of text passed to the writer
+/// in `Interpreter::qir_to_writer`.
+const QIR_WRITE_CHUNK_SIZE: usize = 64 * 1024;
+
 #[allow(clippy::struct_field_names)]
 #[pyclass(unsendable)]
 pub(crate) struct Interpreter {
     pub(crate) interpreter: interpret::Interpreter,
     /// The Python function to call to create a new function wrapping a callable invocation.
     pub(crate) make_callable: Option<PyObject>,
+    /// The target profile the interpreter was constructed with.
+    pub(crate) target_profile: TargetProfile,
+    /// The noise set with `set_noise`, used when a call doesn't pass its own.
+    pub(crate) noise: Option<PauliNoise>,
+    /// The output labels of operations imported with `import_qasm`, applied to the QIR
+    /// generated for them.
+    pub(crate) output_labels: FxHashMap<fir::StoreItemId, Vec<String>>,
 }
 
 thread_local! { static PACKAGE_CACHE: Rc<RefCell<PackageCache>> = Rc::default(); }
@@ -394,29 +420,52 @@ impl Interpreter {
                 Ok(Self {
                     interpreter,
                     make_callable,
+                    target_profile,
+                    noise: None,
+                    output_labels: FxHashMap::default(),
                 })
             }
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
     }
 
+    /// Returns the target profile the interpreter was constructed with.
+    fn target_profile(&self) -> TargetProfile {
+        self.target_profile
+    }
+
     /// Interprets Q# source code.
     ///
     /// :param input: The Q# source code to interpret.
     /// :param output_fn: A callback function that will be called with each output.
+    /// :param all_values: If true, each top-level statement is interpreted in turn and
+    ///     the list of their values is returned, with `None` for those that produce no value.
     ///
     /// :returns value: The value returned by the last statement in the input.
     ///
     /// :raises QSharpError: If there is an error interpreting the input.
-    #[pyo3(signature=(input, callback=None))]
+    #[pyo3(signature=(input, callback=None, all_values=false))]
     fn interpret(
         &mut self,
         py: Python,
         input: &str,
         callback: Option<PyObject>,
+        all_values: bool,
     ) -> PyResult<PyObject> {
-        let mut receiver = OptionalCallbackReceiver { callback, py };
-        match self.interpreter.eval_fragments(&mut receiver, input) {
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            progress: None,
+            progress_error: None,
+            py,
+        };
+        let result = if all_values {
+            self.interpreter
+                .eval_fragments_each(&mut receiver, input)
+                .map(|values| Value::Array(values.into()))
+        } else {
+            self.interpreter.eval_fragments(&mut receiver, input)
+        };
+        match result {
             Ok(value) => {
                 if let Some(make_callable) = &self.make_callable {
                     // Get any global callables from the evaluated input and add them to the environment. This will grab
@@ -448,6 +497,11 @@ impl Interpreter {
     ///         - search_path (Optional[str]): The optional search path for resolving file references.
     ///         - output_semantics (OutputSemantics, optional): The output semantics for the compilation.
     ///         - program_type (ProgramType, optional): The type of program compilation to perform.
+    ///         - dt_seconds (float, optional): The cycle time used to convert durations in `dt` units to seconds.
+    ///         - strict_declarations (bool, optional): Whether old-style `qreg` and `creg` declarations
+    ///           are errors instead of warnings. Defaults to False.
+    ///         - warn_on_legacy_u (bool, optional): Whether calls to the builtin `U` gate are reported
+    ///           as warnings suggesting `u3`. Defaults to True.
     ///
     /// Returns:
     ///     value: The value returned by the last statement in the source code.
@@ -476,21 +530,26 @@ impl Interpreter {
         let search_path = get_search_path(&kwargs)?;
         let program_ty = get_program_type(&kwargs, || ProgramType::Operation)?;
         let output_semantics = get_output_semantics(&kwargs, || OutputSemantics::OpenQasm)?;
+        let dt_seconds = get_dt_seconds(&kwargs)?;
 
         let fs =
             create_filesystem_from_py(py, read_file, list_directory, resolve_path, fetch_github);
         let mut resolver = ImportResolver::new(fs, PathBuf::from(search_path));
 
-        let config = CompilerConfig::new(
+        let mut config = CompilerConfig::new(
             QubitSemantics::Qiskit,
             output_semantics.into(),
             program_ty.into(),
             Some(operation_name.into()),
             None,
         );
+        config.dt_seconds = dt_seconds.map(Seconds);
+        config.strict_declarations = get_strict_declarations(&kwargs)?;
+        config.warn_on_legacy_u = get_warn_on_legacy_u(&kwargs)?;
 
         let unit = compile_to_qsharp_ast_with_config(input, "<none>", Some(&mut resolver), config);
-        let (sources, errors, package, _) = unit.into_tuple();
+        let warnings = unit.warnings();
+        let (sources, errors, package, signature) = unit.into_tuple();
 
         if !errors.is_empty() {
             let errors = errors
@@ -506,8 +565,11 @@ impl Interpreter {
                 .collect();
             return Err(QSharpError::new_err(format_errors(errors)));
         }
+        warn_qasm_warnings(py, warnings)?;
         let mut receiver = OptionalCallbackReceiver {
             callback: output_fn,
+            progress: None,
+            progress_error: None,
             py,
         };
 
@@ -516,12 +578,23 @@ impl Interpreter {
             .eval_ast_fragments(&mut receiver, input, package)
         {
             Ok(value) => {
+                let new_items = self.interpreter.source_globals();
+                if let Some(signature) = signature {
+                    // Keep the output labels of the imported operation so that QIR generated
+                    // for it is labeled the same way as QIR compiled from the source directly.
+                    let imported = new_items.iter().find_map(|(_, name, val)| match val {
+                        Value::Global(id, _) if name.as_ref() == signature.name => Some(*id),
+                        _ => None,
+                    });
+                    if let Some(id) = imported {
+                        self.output_labels.insert(id, signature.output_labels);
+                    }
+                }
                 if let Some(make_callable) = &self.make_callable {
                     // Get any global callables from the evaluated input and add them to the environment. This will grab
                     // every callable that was defined in the input and by previous calls that added to the open package.
                     // This is safe because either the callable will be replaced with itself or a new callable with the
                     // same name will shadow the previous one, which is the expected behavior.
-                    let new_items = self.interpreter.source_globals();
                     for (namespace, name, val) in new_items {
                         create_py_callable(py, make_callable, &namespace, &name, val)?;
                     }
@@ -544,6 +617,57 @@ impl Interpreter {
         self.interpreter.set_classical_seed(seed);
     }
 
+    /// Sets the Pauli noise used by all subsequent calls that don't pass their own.
+    fn set_noise(&mut self, px: f64, py: f64, pz: f64) -> PyResult<()> {
+        let noise = PauliNoise::from_probabilities(px, py, pz).map_err(PyException::new_err)?;
+        self.interpreter.set_noise(Some(noise));
+        self.noise = Some(noise);
+        Ok(())
+    }
+
+    /// Removes the noise set with `set_noise`.
+    fn clear_noise(&mut self) {
+        self.interpreter.set_noise(None);
+        self.noise = None;
+    }
+
+    /// Sets a handler for custom intrinsics that the simulator does not recognize.
+    /// The handler is called with the intrinsic name and a tuple of its converted arguments,
+    /// one item per parameter, for each name in `names`, and its return value is converted
+    /// back into a Q# value of the intrinsic's declared output type, failing if it doesn't
+    /// match. Passing `None` as the handler removes any existing handler.
+    #[pyo3(signature=(handler=None, names=Vec::new()))]
+    fn set_custom_intrinsic_handler(
+        &mut self,
+        handler: Option<PyObject>,
+        names: Vec<String>,
+    ) -> PyResult<()> {
+        if handler.is_some() && names.is_empty() {
+            return Err(PyValueError::new_err(
+                "names must list at least one intrinsic for the handler",
+            ));
+        }
+        let output_tys = self.interpreter.intrinsic_output_tys();
+        let handler = handler.map(|handler| -> CustomIntrinsicHandler {
+            Rc::new(move |name: &str, arg: &Value| {
+                if !names.iter().any(|n| n == name) {
+                    return None;
+                }
+                Some(Python::with_gil(|py| {
+                    let args = intrinsic_args_to_py(py, arg).map_err(|e| e.to_string())?;
+                    let result = handler.call1(py, (name, args)).map_err(|e| e.to_string())?;
+                    match output_tys.borrow().get(name) {
+                        Some(ty) => convert_intrinsic_result(py, &result, ty),
+                        None => convert_obj_to_value(py, &result),
+                    }
+                    .map_err(|e| e.to_string())
+                }))
+            })
+        });
+        self.interpreter.set_custom_intrinsic_handler(handler);
+        Ok(())
+    }
+
     /// Dumps the quantum state of the interpreter.
     /// Returns a tuple of (amplitudes, num_qubits), where amplitudes is a dictionary from integer indices to
     /// pairs of real and imaginary amplitudes.
@@ -552,6 +676,87 @@ impl Interpreter {
         StateDumpData(DisplayableState(state, qubit_count))
     }
 
+    /// Dumps the quantum state of the interpreter as the bytes of a `.npy` file
+    /// containing a dense complex128 array of the amplitudes.
+    fn dump_machine_npy<'a>(&mut self, py: Python<'a>) -> PyResult<Bound<'a, PyBytes>> {
+        let bytes = self
+            .interpreter
+            .get_quantum_state_npy()
+            .map_err(PyValueError::new_err)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Returns the probability of measuring each computational basis state, keyed by
+    /// its index as shown by `dump_machine`, without collapsing the simulator state.
+    /// Probabilities below `threshold` are omitted.
+    #[pyo3(signature=(threshold=1e-12))]
+    fn dump_probabilities(&mut self, threshold: f64) -> FxHashMap<BigUint, f64> {
+        let (state, _) = self.interpreter.get_quantum_state();
+        state
+            .into_iter()
+            .map(|(idx, amp)| (idx, amp.norm_sqr()))
+            .filter(|(_, prob)| *prob >= threshold)
+            .collect()
+    }
+
+    /// Returns the fidelity between the current simulator state and a state
+    /// previously captured with `dump_machine`.
+    fn fidelity(&mut self, reference_dump: &StateDumpData) -> f64 {
+        self.interpreter.fidelity(&reference_dump.0 .0)
+    }
+
+    /// Returns the reduced density matrix of the given qubits in the current
+    /// simulator state, tracing out all other qubits.
+    #[allow(clippy::needless_pass_by_value)]
+    fn density_matrix(&mut self, qubits: Vec<usize>) -> PyResult<Vec<Vec<Complex64>>> {
+        self.interpreter
+            .density_matrix(&qubits)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Returns the probability that the given qubits would measure to the paired
+    /// outcomes, where `True` is `One`, without collapsing the simulator state.
+    #[allow(clippy::needless_pass_by_value)]
+    fn outcome_probability(&mut self, bits: Vec<(usize, bool)>) -> PyResult<f64> {
+        self.interpreter
+            .outcome_probability(&bits)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Returns whether the two given qubits are entangled in the current simulator
+    /// state, meaning their joint state is not a product state within `tol`.
+    #[pyo3(signature=(q0, q1, tol=1e-9))]
+    fn are_entangled(&mut self, q0: usize, q1: usize, tol: f64) -> PyResult<bool> {
+        self.interpreter
+            .are_entangled(q0, q1, tol)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Resets and releases all qubits in the simulator, so that the next allocation
+    /// starts from a fresh `|0...0⟩` register.
+    fn reset_all_qubits(&mut self) {
+        self.interpreter.reset_all_qubits();
+    }
+
+    /// Returns the accumulated angle, in radians, of the global phases
+    /// applied to the simulator state.
+    fn global_phase(&self) -> f64 {
+        self.interpreter.global_phase()
+    }
+
+    /// Enables or disables recording the number of nonzero amplitudes in the simulator
+    /// state after each gate, which slows down simulation.
+    fn set_state_size_tracking(&mut self, enabled: bool) {
+        self.interpreter.set_state_size_tracking(enabled);
+    }
+
+    /// Returns the sizes the simulator state went through while state size tracking
+    /// was enabled, with consecutive gates that leave the size unchanged summarized
+    /// as a single entry.
+    fn state_size_history(&self) -> Vec<usize> {
+        self.interpreter.state_size_history()
+    }
+
     /// Dumps the current circuit state of the interpreter.
     ///
     /// This circuit will contain the gates that have been applied
@@ -560,7 +765,8 @@ impl Interpreter {
         Circuit(self.interpreter.get_circuit()).into_py_any(py)
     }
 
-    #[pyo3(signature=(entry_expr=None, callback=None, noise=None, callable=None, args=None))]
+    #[pyo3(signature=(entry_expr=None, callback=None, noise=None, callable=None, args=None, report_timing=false, replay=None, pack=false, msb_first=false, shots=1, progress=None, capture_intrinsics=false))]
+    #[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
     fn run(
         &mut self,
         py: Python,
@@ -569,17 +775,75 @@ impl Interpreter {
         noise: Option<(f64, f64, f64)>,
         callable: Option<GlobalCallable>,
         args: Option<PyObject>,
+        report_timing: bool,
+        replay: Option<Vec<bool>>,
+        pack: bool,
+        msb_first: bool,
+        shots: usize,
+        progress: Option<PyObject>,
+        capture_intrinsics: bool,
     ) -> PyResult<PyObject> {
-        let mut receiver = OptionalCallbackReceiver { callback, py };
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            progress,
+            progress_error: None,
+            py,
+        };
 
         let noise = match noise {
-            None => None,
+            None => self.noise,
             Some((px, py, pz)) => match PauliNoise::from_probabilities(px, py, pz) {
                 Ok(noise_struct) => Some(noise_struct),
                 Err(error_message) => return Err(PyException::new_err(error_message)),
             },
         };
 
+        let to_py = |py, value: Value| -> PyResult<PyObject> {
+            Ok(
+                match pack.then(|| pack_results(&value, msb_first)).flatten() {
+                    Some(packed) => packed.into_pyobject(py)?.into_any().unbind(),
+                    None => ValueWrapper(value).into_pyobject(py)?.unbind(),
+                },
+            )
+        };
+
+        if shots == 0 {
+            return Err(PyValueError::new_err("The number of shots must be greater than 0."));
+        }
+
+        self.interpreter.set_capture_intrinsics(capture_intrinsics);
+        if shots > 1 {
+            if callable.is_some() || replay.is_some() {
+                return Err(PyException::new_err(
+                    "multiple shots are only supported for an entry expression without replay",
+                ));
+            }
+            let results = self
+                .interpreter
+                .run_shots(&mut receiver, entry_expr, noise, shots)
+                .map_err(|errors| run_error(py, errors, &mut receiver))?;
+            let mut values = Vec::with_capacity(results.len());
+            let mut durations_ms = Vec::with_capacity(results.len());
+            for (result, duration) in results {
+                values.push(match result {
+                    Ok(value) => to_py(py, value)?,
+                    Err(errors) => QSharpError::new_err(format_errors(errors))
+                        .into_value(py)
+                        .into_any(),
+                });
+                durations_ms.push(duration.as_secs_f64() * 1000.0);
+            }
+            let intrinsic_calls = capture_intrinsics
+                .then(|| intrinsic_calls_to_py(py, self.interpreter.intrinsic_logs()))
+                .transpose()?;
+            let durations_ms = report_timing
+                .then(|| durations_ms.into_py_any(py))
+                .transpose()?;
+            return run_output(py, values.into_py_any(py)?, intrinsic_calls, durations_ms);
+        }
+
+        let start = report_timing.then(Instant::now);
+
         let result = match callable {
             Some(callable) => {
                 let (input_ty, output_ty) = self
@@ -588,26 +852,99 @@ impl Interpreter {
                     .ok_or(QSharpError::new_err("callable not found"))?;
                 let args = args_to_values(py, args, &input_ty, &output_ty)?;
                 self.interpreter
-                    .invoke_with_noise(&mut receiver, callable.0, args, noise)
+                    .invoke_with_noise(&mut receiver, callable.0, args, noise, replay)
             }
-            _ => self.interpreter.run(&mut receiver, entry_expr, noise),
+            _ => self
+                .interpreter
+                .run(&mut receiver, entry_expr, noise, replay),
         };
+        let elapsed_ms = start
+            .map(|start| (start.elapsed().as_secs_f64() * 1000.0).into_py_any(py))
+            .transpose()?;
 
         match result {
-            Ok(value) => Ok(ValueWrapper(value).into_pyobject(py)?.unbind()),
-            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+            Ok(value) => {
+                let value = to_py(py, value)?;
+                let intrinsic_calls = capture_intrinsics
+                    .then(|| intrinsic_calls_to_py(py, self.interpreter.intrinsic_logs()))
+                    .transpose()?;
+                run_output(py, value, intrinsic_calls, elapsed_ms)
+            }
+            Err(errors) => Err(run_error(py, errors, &mut receiver)),
+        }
+    }
+
+    /// Runs the given entry expression `shots` times, each with a fresh simulator, and
+    /// counts the distinct results by their Q# display form, such as `[One, Zero]`,
+    /// so that only the counts cross into Python.
+    #[pyo3(signature=(entry_expr, shots, callback=None, noise=None))]
+    fn run_histogram(
+        &mut self,
+        py: Python,
+        entry_expr: &str,
+        shots: usize,
+        callback: Option<PyObject>,
+        noise: Option<(f64, f64, f64)>,
+    ) -> PyResult<FxHashMap<String, usize>> {
+        if shots == 0 {
+            return Err(PyValueError::new_err("The number of shots must be greater than 0."));
+        }
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            progress: None,
+            progress_error: None,
+            py,
+        };
+        let noise = match noise {
+            None => self.noise,
+            Some((px, py, pz)) => {
+                Some(PauliNoise::from_probabilities(px, py, pz).map_err(PyException::new_err)?)
+            }
+        };
+
+        let results = self
+            .interpreter
+            .run_shots(&mut receiver, Some(entry_expr), noise, shots)
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
+        let mut histogram = FxHashMap::default();
+        for (result, _) in results {
+            let value = result.map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
+            *histogram.entry(value.to_string()).or_default() += 1;
         }
+        Ok(histogram)
+    }
+
+    /// Returns the entry expression that was set or last run, if any.
+    fn get_entry_expr(&self) -> Option<String> {
+        self.interpreter.get_entry_expr().map(ToString::to_string)
+    }
+
+    /// Clears the entry expression, so that running without one fails until a new one is set.
+    fn clear_entry_expr(&mut self) {
+        self.interpreter.clear_entry_expr();
+    }
+
+    /// Returns the measurement outcomes of the most recent run, invocation or
+    /// interpreted input, in order, which can be passed to `run` as `replay`.
+    fn last_measurements(&self) -> Vec<bool> {
+        self.interpreter.measurement_log().to_vec()
     }
 
-    #[pyo3(signature=(callable, args=None, callback=None))]
+    #[pyo3(signature=(callable, args=None, callback=None, progress=None))]
     fn invoke(
         &mut self,
         py: Python,
         callable: GlobalCallable,
         args: Option<PyObject>,
         callback: Option<PyObject>,
+        progress: Option<PyObject>,
     ) -> PyResult<PyObject> {
-        let mut receiver = OptionalCallbackReceiver { callback, py };
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            progress,
+            progress_error: None,
+            py,
+        };
         let (input_ty, output_ty) = self
             .interpreter
             .global_tys(&callable.0)
@@ -617,38 +954,123 @@ impl Interpreter {
 
         match self.interpreter.invoke(&mut receiver, callable.0, args) {
             Ok(value) => Ok(ValueWrapper(value).into_pyobject(py)?.unbind()),
-            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+            Err(errors) => Err(run_error(py, errors, &mut receiver)),
         }
     }
 
-    #[pyo3(signature=(entry_expr=None, callable=None, args=None))]
+    /// Returns whether `args` can be converted to the input type of the callable,
+    /// so that invoking it wouldn't fail on its arguments. Nothing is evaluated.
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature=(callable, args=None))]
+    fn can_invoke(
+        &mut self,
+        py: Python,
+        callable: GlobalCallable,
+        args: Option<PyObject>,
+    ) -> PyResult<bool> {
+        let (input_ty, output_ty) = self
+            .interpreter
+            .global_tys(&callable.0)
+            .ok_or(QSharpError::new_err("callable not found"))?;
+
+        Ok(args_to_values(py, args, &input_ty, &output_ty).is_ok())
+    }
+
+    #[pyo3(signature=(entry_expr=None, callable=None, args=None, strict=true, shots=None))]
     fn qir(
         &mut self,
         py: Python,
         entry_expr: Option<&str>,
         callable: Option<GlobalCallable>,
         args: Option<PyObject>,
+        strict: bool,
+        shots: Option<usize>,
     ) -> PyResult<String> {
-        if let Some(entry_expr) = entry_expr {
-            match self.interpreter.qirgen(entry_expr) {
-                Ok(qir) => Ok(qir),
-                Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
-            }
-        } else {
-            let callable = callable.ok_or_else(|| {
-                QSharpError::new_err("either entry_expr or callable must be specified")
-            })?;
-            let (input_ty, output_ty) = self
-                .interpreter
-                .global_tys(&callable.0)
-                .ok_or(QSharpError::new_err("callable not found"))?;
+        let program = self.rirgen(py, entry_expr, callable, args, strict, shots)?;
+        Ok(program.to_qir(&program))
+    }
 
-            let args = args_to_values(py, args, &input_ty, &output_ty)?;
-            match self.interpreter.qirgen_from_callable(&callable.0, args) {
-                Ok(qir) => Ok(qir),
-                Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+    /// Generates QIR for the global operation with the given name, such as
+    /// `Program` or `Sample.Program`, passing `args` to it. An unqualified name
+    /// must match a single operation.
+    #[pyo3(signature=(operation_name, args=None))]
+    fn qir_for_operation(
+        &mut self,
+        py: Python,
+        operation_name: &str,
+        args: Option<PyObject>,
+    ) -> PyResult<String> {
+        let operations = self
+            .interpreter
+            .user_globals()
+            .into_iter()
+            .chain(self.interpreter.source_globals())
+            .filter(|(_, _, value)| self.interpreter.is_global_operation(value))
+            .map(|(namespace, name, value)| {
+                let qualified = namespace
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .chain([name.as_ref()])
+                    .collect::<Vec<_>>()
+                    .join(".");
+                (qualified, name, value)
+            })
+            .collect::<Vec<_>>();
+        let mut candidates = operations
+            .iter()
+            .filter(|(qualified, _, _)| qualified == operation_name)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            candidates = operations
+                .iter()
+                .filter(|(_, name, _)| name.as_ref() == operation_name)
+                .collect();
+        }
+        let callable = match candidates.as_slice() {
+            [] => {
+                return Err(QSharpError::new_err(format!(
+                    "operation `{operation_name}` not found"
+                )));
+            }
+            [(_, _, value)] => GlobalCallable::from(value.clone()),
+            _ => {
+                let names = candidates
+                    .iter()
+                    .map(|(qualified, _, _)| qualified.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(QSharpError::new_err(format!(
+                    "operation `{operation_name}` is ambiguous, it could be any of: {names}"
+                )));
             }
+        };
+        self.qir(py, None, Some(callable), args, true, None)
+    }
+
+    /// Generates QIR for `entry_expr` and writes it to `writer`, an object with a
+    /// `write` method such as an open file, in chunks as it is generated so that the
+    /// whole program is never held in one string.
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature=(entry_expr, writer))]
+    fn qir_to_writer(
+        &mut self,
+        py: Python,
+        entry_expr: Option<&str>,
+        writer: PyObject,
+    ) -> PyResult<()> {
+        let program = self.rirgen(py, entry_expr, None, None, true, None)?;
+        let mut writer = PyWriter {
+            py,
+            writer,
+            chunk: String::new(),
+            error: None,
+        };
+        if write_qir(&program, &mut writer).is_err() || writer.flush().is_err() {
+            return Err(writer
+                .error
+                .unwrap_or_else(|| QSharpError::new_err("failed to write QIR")));
         }
+        Ok(())
     }
 
     /// Synthesizes a circuit for a Q# program. Either an entry
@@ -665,7 +1087,7 @@ impl Interpreter {
     /// :param args: The arguments to pass to the callable.
     ///
     /// :raises QSharpError: If there is an error synthesizing the circuit.
-    #[pyo3(signature=(entry_expr=None, operation=None, callable=None, args=None))]
+    #[pyo3(signature=(entry_expr=None, operation=None, callable=None, args=None, basis_gates=None))]
     fn circuit(
         &mut self,
         py: Python,
@@ -673,6 +1095,7 @@ impl Interpreter {
         operation: Option<String>,
         callable: Option<GlobalCallable>,
         args: Option<PyObject>,
+        basis_gates: Option<Vec<String>>,
     ) -> PyResult<PyObject> {
         let entrypoint = match (entry_expr, operation, callable) {
             (Some(entry_expr), None, None) => CircuitEntryPoint::EntryExpr(entry_expr),
@@ -692,7 +1115,7 @@ impl Interpreter {
             }
         };
 
-        match self.interpreter.circuit(entrypoint, false) {
+        match self.interpreter.circuit(entrypoint, false, basis_gates) {
             Ok(circuit) => Circuit(circuit).into_py_any(py),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
@@ -747,6 +1170,106 @@ impl Interpreter {
     }
 }
 
+impl Interpreter {
+    /// Generates the RIR program for either `entry_expr` or `callable` with `args`, applying
+    /// any output labels of the callable and the number of `shots` to run it for.
+    fn rirgen(
+        &mut self,
+        py: Python,
+        entry_expr: Option<&str>,
+        callable: Option<GlobalCallable>,
+        args: Option<PyObject>,
+        strict: bool,
+        shots: Option<usize>,
+    ) -> PyResult<Program> {
+        let mut labels = None;
+        let result = if let Some(entry_expr) = entry_expr {
+            if strict {
+                self.interpreter
+                    .rirgen(entry_expr)
+                    .map(|program| (program, Vec::new()))
+            } else {
+                self.interpreter.rirgen_lenient(entry_expr)
+            }
+        } else {
+            let callable = callable.ok_or_else(|| {
+                QSharpError::new_err("either entry_expr or callable must be specified")
+            })?;
+            let (input_ty, output_ty) = self
+                .interpreter
+                .global_tys(&callable.0)
+                .ok_or(QSharpError::new_err("callable not found"))?;
+            if let Value::Global(id, _) = &callable.0 {
+                labels = self.output_labels.get(id).cloned();
+            }
+
+            let args = args_to_values(py, args, &input_ty, &output_ty)?;
+            if strict {
+                self.interpreter
+                    .rirgen_from_callable(&callable.0, args)
+                    .map(|program| (program, Vec::new()))
+            } else {
+                self.interpreter
+                    .rirgen_from_callable_lenient(&callable.0, &args)
+            }
+        };
+
+        match result {
+            Ok((mut program, warnings)) => {
+                // Profile violations that were downgraded in non-strict mode
+                // are surfaced through Python's warnings machinery.
+                for warning in warnings {
+                    let message = CString::new(format_error(&warning))?;
+                    PyErr::warn(py, &py.get_type::<PyUserWarning>(), &message, 1)?;
+                }
+                if let Some(labels) = labels {
+                    set_output_labels(&mut program, &labels)
+                        .map_err(|err| QSharpError::new_err(err.to_string()))?;
+                }
+                program.shots = shots;
+                Ok(program)
+            }
+            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+        }
+    }
+}
+
+/// Adapts a Python object with a `write` method to `std::fmt::Write`, passing text
+/// to it in chunks of about `QIR_WRITE_CHUNK_SIZE` bytes. A Python exception raised by
+/// `write` is kept in `error`.
+struct PyWriter<'py> {
+    py: Python<'py>,
+    writer: PyObject,
+    chunk: String,
+    error: Option<PyErr>,
+}
+
+impl PyWriter<'_> {
+    fn flush(&mut self) -> std::fmt::Result {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::take(&mut self.chunk);
+        match self.writer.call_method1(self.py, "write", (chunk,)) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.error = Some(err);
+                Err(std::fmt::Error)
+            }
+        }
+    }
+}
+
+impl Write for PyWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.chunk.push_str(s);
+        if self.chunk.len() >= QIR_WRITE_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
+
 fn args_to_values(
     py: Python,
     args: Option<PyObject>,
@@ -852,6 +1375,24 @@ fn convert_obj_with_ty(py: Python, obj: &PyObject, ty: &Ty) -> PyResult<Value> {
             }
         }
         Ty::Array(ty) => {
+            let numeric: Option<Vec<Value>> = match &**ty {
+                Ty::Prim(Prim::Double) => buffer_items(py, obj, &["d"]).map(|items| {
+                    items
+                        .into_iter()
+                        .map(|item| Value::Double(f64::from_ne_bytes(item)))
+                        .collect()
+                }),
+                Ty::Prim(Prim::Int) => buffer_items(py, obj, &["q", "l"]).map(|items| {
+                    items
+                        .into_iter()
+                        .map(|item| Value::Int(i64::from_ne_bytes(item)))
+                        .collect()
+                }),
+                _ => None,
+            };
+            if let Some(values) = numeric {
+                return Ok(Value::Array(values.into()));
+            }
             let obj = obj.extract::<Vec<PyObject>>(py)?;
             let mut values = Vec::with_capacity(obj.len());
             for item in &obj {
@@ -863,6 +1404,103 @@ fn convert_obj_with_ty(py: Python, obj: &PyObject, ty: &Ty) -> PyResult<Value> {
     }
 }
 
+/// Converts the value returned by a custom intrinsic handler into a Q# value of the
+/// intrinsic's declared output type, where `None` is the only `Unit` value. Generic
+/// output types are inferred from the Python type instead.
+fn convert_intrinsic_result(py: Python, obj: &PyObject, ty: &Ty) -> PyResult<Value> {
+    if let Some(unsupported) = first_unsupported_interop_ty(ty) {
+        if matches!(unsupported, Ty::Param { .. }) {
+            return convert_obj_to_value(py, obj);
+        }
+        return Err(QSharpError::new_err(format!(
+            "unsupported output type: `{unsupported}`"
+        )));
+    }
+    if matches!(ty, Ty::Tuple(tup) if tup.is_empty()) {
+        return if obj.is_none(py) {
+            Ok(Value::unit())
+        } else {
+            Err(QSharpError::new_err("expected the handler to return `None`"))
+        };
+    }
+    convert_obj_with_ty(py, obj, ty).map_err(|e| {
+        QSharpError::new_err(format!(
+            "expected the handler to return a value of type `{ty}`: {e}"
+        ))
+    })
+}
+
+/// Returns the raw items of a one-dimensional buffer of 8-byte items, such as a numpy
+/// array, if the buffer's struct format is one of `formats`. Returns `None` for objects
+/// without such a buffer, so that they are converted item by item instead.
+/// The buffer is read through a `memoryview`, since the limited API only has the
+/// buffer protocol from Python 3.11.
+fn buffer_items(py: Python, obj: &PyObject, formats: &[&str]) -> Option<Vec<[u8; 8]>> {
+    let view = PyMemoryView::from(obj.bind(py)).ok()?;
+    let format = view.getattr("format").ok()?.extract::<String>().ok()?;
+    let ndim = view.getattr("ndim").ok()?.extract::<usize>().ok()?;
+    let itemsize = view.getattr("itemsize").ok()?.extract::<usize>().ok()?;
+    if ndim != 1 || itemsize != 8 || !formats.contains(&format.trim_start_matches(['@', '='])) {
+        return None;
+    }
+    let bytes = view.call_method0("tobytes").ok()?;
+    let bytes = bytes.downcast::<PyBytes>().ok()?.as_bytes();
+    Some(
+        bytes
+            .chunks_exact(8)
+            .map(|item| item.try_into().expect("item should be 8 bytes"))
+            .collect(),
+    )
+}
+
+/// Converts a Python object into a Q# value without a known target type, inferring the
+/// Q# type from the Python type. `None` maps to `Unit`, tuples to tuples and lists to arrays.
+fn convert_obj_to_value(py: Python, obj: &PyObject) -> PyResult<Value> {
+    let obj = obj.bind(py);
+    if obj.is_none() {
+        Ok(Value::unit())
+    } else if let Ok(result) = obj.extract::<Result>() {
+        Ok(Value::Result(qsc::interpret::Result::Val(
+            result == Result::One,
+        )))
+    } else if let Ok(pauli) = obj.extract::<Pauli>() {
+        Ok(Value::Pauli(match pauli {
+            Pauli::I => fir::Pauli::I,
+            Pauli::X => fir::Pauli::X,
+            Pauli::Y => fir::Pauli::Y,
+            Pauli::Z => fir::Pauli::Z,
+        }))
+    } else if obj.is_instance_of::<PyBool>() {
+        Ok(Value::Bool(obj.extract::<bool>()?))
+    } else if obj.is_instance_of::<PyInt>() {
+        match obj.extract::<i64>() {
+            Ok(val) => Ok(Value::Int(val)),
+            Err(_) => Ok(Value::BigInt(obj.extract::<BigInt>()?)),
+        }
+    } else if obj.is_instance_of::<PyFloat>() {
+        Ok(Value::Double(obj.extract::<f64>()?))
+    } else if obj.is_instance_of::<PyString>() {
+        Ok(Value::String(obj.extract::<String>()?.into()))
+    } else if let Ok(tup) = obj.downcast::<PyTuple>() {
+        let values = tup
+            .iter()
+            .map(|item| convert_obj_to_value(py, &item.unbind()))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Value::Tuple(values.into()))
+    } else if let Ok(list) = obj.downcast::<PyList>() {
+        let values = list
+            .iter()
+            .map(|item| convert_obj_to_value(py, &item.unbind()))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Value::Array(values.into()))
+    } else {
+        Err(QSharpError::new_err(format!(
+            "unsupported value type: `{}`",
+            obj.get_type().name()?
+        )))
+    }
+}
+
 #[pyfunction]
 pub fn physical_estimates(logical_resources: &str, job_params: &str) -> PyResult<String> {
     match re::estimate_physical_resources_from_json(logical_resources, job_params) {
@@ -871,6 +1509,20 @@ pub fn physical_estimates(logical_resources: &str, job_params: &str) -> PyResult
     }
 }
 
+/// Sets the number of decimal digits used when rendering state amplitudes.
+#[pyfunction]
+pub fn set_display_precision(digits: i64) -> PyResult<()> {
+    match usize::try_from(digits) {
+        Ok(digits) if digits <= MAX_DISPLAY_PRECISION => {
+            crate::displayable_output::set_display_precision(digits);
+            Ok(())
+        }
+        _ => Err(PyValueError::new_err(format!(
+            "display precision must be between 0 and {MAX_DISPLAY_PRECISION}, got {digits}"
+        ))),
+    }
+}
+
 create_exception!(
     module,
     QSharpError,
@@ -981,6 +1633,27 @@ impl StateDumpData {
         dict.into_pyobject(py)
     }
 
+    /// Returns the phase in radians of each nonzero amplitude, keyed by its index.
+    fn phases(&self) -> Vec<(BigUint, f64)> {
+        self.0
+             .0
+            .iter()
+            .map(|(idx, amp)| (idx.clone(), amp.arg()))
+            .collect()
+    }
+
+    /// Returns the state as a dense numpy `complex128` array of length `2**qubit_count`,
+    /// with zeros for the amplitudes missing from the sparse state. The amplitudes are
+    /// written once into a `bytearray` that backs the returned array.
+    fn get_array<'a>(&self, py: Python<'a>) -> PyResult<Bound<'a, PyAny>> {
+        let len = dense_state_len(self.0 .1).map_err(PyValueError::new_err)?;
+        let buffer = PyByteArray::new_with(py, len * 16, |bytes| {
+            write_dense_state_bytes(&self.0 .0, bytes);
+            Ok(())
+        })?;
+        py.import("numpy")?.call_method1("frombuffer", (buffer, "<c16"))
+    }
+
     #[getter]
     fn get_qubit_count(&self) -> usize {
         self.0 .1
@@ -1100,8 +1773,98 @@ impl<'py> IntoPyObject<'py> for ValueWrapper {
     }
 }
 
+/// Packs a `Result[]` into an integer without creating a Python object per result.
+/// The first result is the least significant bit, or the most significant bit if
+/// `msb_first` is set. Returns `None` for other values, including empty arrays, whose
+/// element type is unknown at runtime.
+fn pack_results(value: &Value, msb_first: bool) -> Option<BigUint> {
+    let Value::Array(values) = value else {
+        return None;
+    };
+    if values.is_empty() {
+        return None;
+    }
+    let mut packed = BigUint::default();
+    let len = values.len() as u64;
+    for (index, value) in (0_u64..).zip(values.iter()) {
+        let bit = if msb_first { len - 1 - index } else { index };
+        let Value::Result(result) = value else {
+            return None;
+        };
+        if result.unwrap_bool() {
+            packed.set_bit(bit, true);
+        }
+    }
+    Some(packed)
+}
+
+/// Pairs the result of `run` with the captured custom intrinsic calls, if any, and then
+/// with the duration of the run in milliseconds, or the list of shot durations, if timing
+/// was enabled.
+fn run_output(
+    py: Python,
+    value: PyObject,
+    intrinsic_calls: Option<PyObject>,
+    elapsed_ms: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let value = match intrinsic_calls {
+        Some(intrinsic_calls) => (value, intrinsic_calls).into_py_any(py)?,
+        None => value,
+    };
+    match elapsed_ms {
+        Some(elapsed_ms) => (value, elapsed_ms).into_py_any(py),
+        None => Ok(value),
+    }
+}
+
+/// Converts the custom intrinsic calls of each shot into a list of lists of
+/// `(name, args)` tuples.
+fn intrinsic_calls_to_py(py: Python, logs: &[Vec<(String, Value)>]) -> PyResult<PyObject> {
+    logs.iter()
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|(name, args)| {
+                    (name.as_str(), intrinsic_args_to_py(py, args)?).into_py_any(py)
+                })
+                .collect::<PyResult<Vec<_>>>()
+        })
+        .collect::<PyResult<Vec<_>>>()?
+        .into_py_any(py)
+}
+
+/// Converts the argument of a custom intrinsic call into a tuple with one item per
+/// parameter, so that handlers see the same shape whatever the number of parameters.
+fn intrinsic_args_to_py<'py>(py: Python<'py>, arg: &Value) -> PyResult<Bound<'py, PyTuple>> {
+    match arg {
+        Value::Tuple(items) => {
+            PyTuple::new(py, items.iter().map(|item| ValueWrapper(item.clone())))
+        }
+        _ => PyTuple::new(py, [ValueWrapper(arg.clone())]),
+    }
+}
+
+/// Converts the errors of a run into a `QSharpError`, with the exception raised by the
+/// progress callback of `receiver`, which aborts the run, attached as its cause.
+fn run_error(
+    py: Python,
+    errors: Vec<interpret::Error>,
+    receiver: &mut OptionalCallbackReceiver,
+) -> PyErr {
+    let error = QSharpError::new_err(format_errors(errors));
+    if let Some(cause) = receiver.progress_error.take() {
+        error.set_cause(py, Some(cause));
+    }
+    error
+}
+
 pub(crate) struct OptionalCallbackReceiver<'a> {
     pub(crate) callback: Option<PyObject>,
+    /// Called periodically with the number of loop iterations evaluated, which
+    /// aborts evaluation by returning `False` or raising.
+    pub(crate) progress: Option<PyObject>,
+    /// The exception raised by the progress callback, if it aborted evaluation.
+    pub(crate) progress_error: Option<PyErr>,
     pub(crate) py: Python<'a>,
 }
 
@@ -1160,6 +1923,21 @@ impl Receiver for OptionalCallbackReceiver<'_> {
         }
         Ok(())
     }
+
+    fn progress(&mut self, iterations: u64) -> core::result::Result<(), Error> {
+        if let Some(progress) = &self.progress {
+            // Keep the exception raised by the callback, so that it can be attached
+            // to the error that aborts the run.
+            let keep_going = progress.call1(self.py, (iterations,)).map_err(|err| {
+                self.progress_error = Some(err);
+                Error
+            })?;
+            if matches!(keep_going.extract::<bool>(self.py), Ok(false)) {
+                return Err(Error);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[pyclass]
@@ -1178,6 +1956,22 @@ impl Circuit {
     fn json(&self, _py: Python) -> PyResult<String> {
         serde_json::to_string(&self.0).map_err(|e| PyException::new_err(e.to_string()))
     }
+
+    /// Returns the number of layers of operations in the circuit.
+    fn depth(&self) -> usize {
+        self.0.depth()
+    }
+
+    /// Returns the number of each kind of gate in the circuit, with measurements
+    /// counted under `"measure"`.
+    fn gate_counts(&self) -> FxHashMap<String, usize> {
+        self.0.gate_counts()
+    }
+
+    /// Returns the circuit as a `quantikz` diagram for LaTeX documents.
+    fn latex(&self) -> String {
+        self.0.to_latex()
+    }
 }
 
 trait IntoPyErr {
@@ -3,7 +3,7 @@
 
 from concurrent.futures import ThreadPoolExecutor
 import pytest
-from qsharp import QSharpError, TargetProfile
+from qsharp import QSharpError, Result, TargetProfile
 
 from interop_qiskit import QISKIT_AVAILABLE, SKIP_REASON
 
@@ -17,6 +17,7 @@ if QISKIT_AVAILABLE:
     from qiskit.providers import JobStatus
     from qiskit import ClassicalRegister
     from qsharp.interop.qiskit import QSharpBackend
+    from qsharp.interop.qiskit.backends.qsharp_backend import _to_qiskit_bitstring
     from .test_circuits import (
         generate_repro_information,
     )
@@ -168,3 +169,9 @@ def test_simulting_with_unbound_param_raises():
     except Exception as ex:
         additional_info = generate_repro_information(circuit, backend)
         raise RuntimeError(additional_info) from ex
+
+
+@pytest.mark.skipif(not QISKIT_AVAILABLE, reason=SKIP_REASON)
+def test_scalar_result_outputs_are_converted_to_single_bits() -> None:
+    assert _to_qiskit_bitstring(Result.One) == "1"
+    assert _to_qiskit_bitstring(([Result.One, Result.Zero], Result.Zero)) == "10 0"
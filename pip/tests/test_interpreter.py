@@ -1,7 +1,11 @@
 # Copyright (c) Microsoft Corporation.
 # Licensed under the MIT License.
 
+import io
+import math
+import struct
 from textwrap import dedent
+import warnings
 from qsharp._native import (
     Interpreter,
     Result,
@@ -101,6 +105,369 @@ def test_dump_machine() -> None:
     assert state_dump[2].imag == 0.0
 
 
+def test_run_with_capture_intrinsics_records_calls_per_shot() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.set_custom_intrinsic_handler(lambda name, args: None, ["Pulse"])
+    e.interpret(
+        """
+    operation Pulse(q : Qubit, duration : Int) : Unit { body intrinsic; }
+    operation Main() : Result {
+        use q = Qubit();
+        Pulse(q, 10);
+        X(q);
+        Pulse(q, 20);
+        MResetZ(q)
+    }
+    """
+    )
+    result, calls = e.run(
+        "Main()", None, None, None, None, shots=2, capture_intrinsics=True
+    )
+    assert result == [Result.One, Result.One]
+    assert len(calls) == 2
+    for shot in calls:
+        assert [(name, args[1]) for (name, args) in shot] == [
+            ("Pulse", 10),
+            ("Pulse", 20),
+        ]
+
+    result, calls = e.run("Main()", None, None, None, None, capture_intrinsics=True)
+    assert result == Result.One
+    assert len(calls) == 1 and len(calls[0]) == 2
+
+
+def test_set_noise_applies_to_subsequent_calls_until_cleared() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("operation Flip() : Result { use q = Qubit(); MResetZ(q) }")
+    e.set_noise(1.0, 0.0, 0.0)
+    assert e.interpret("{ use q = Qubit(); MResetZ(q) }") == Result.One
+    assert e.run("Flip()", None, None, None, None) == Result.One
+    assert e.run("Flip()", None, (0.0, 0.0, 0.0), None, None) == Result.Zero
+    e.clear_noise()
+    assert e.interpret("{ use q = Qubit(); MResetZ(q) }") == Result.Zero
+    assert e.run("Flip()", None, None, None, None) == Result.Zero
+
+
+def test_dump_machine_phases() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use q = Qubit();
+    H(q);
+    S(q);
+    """
+    )
+    phases = dict(e.dump_machine().phases())
+    assert set(phases) == {0, 1}
+    assert abs(phases[0]) < 1e-9
+    assert abs(phases[1] - math.pi / 2) < 1e-9
+
+
+def test_dump_machine_get_array_is_dense() -> None:
+    np = pytest.importorskip("numpy")
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    X(qs[0]);
+    """
+    )
+    array = e.dump_machine().get_array()
+    assert array.dtype == np.complex128
+    assert list(array) == [0, 0, 1, 0]
+
+
+def test_dump_machine_get_array_fails_for_too_many_qubits() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("use qs = Qubit[31];")
+    with pytest.raises(ValueError) as excinfo:
+        e.dump_machine().get_array()
+    assert "31 qubits" in str(excinfo.value)
+
+
+def test_target_profile_returns_construction_profile() -> None:
+    e = Interpreter(TargetProfile.Adaptive_RI)
+    assert e.target_profile() == TargetProfile.Adaptive_RI
+
+
+def test_interpret_all_values_returns_value_of_each_fragment() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    assert e.interpret("1+1; 2*3;", all_values=True) == [2, 6]
+    assert e.interpret("let x = 1; x", all_values=True) == [None, 1]
+
+
+def test_global_phase_accumulates() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    assert e.global_phase() == 0.0
+    e.interpret(
+        """
+    use q = Qubit();
+    R(PauliI, -Std.Math.PI(), q);
+    R(PauliI, -Std.Math.PI(), q);
+    """
+    )
+    assert abs(e.global_phase() - 3.141592653589793) < 1e-12
+
+
+def test_reset_all_qubits_starts_from_fresh_register() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("use qs = Qubit[2]; H(qs[0]); CNOT(qs[0], qs[1]);")
+    e.reset_all_qubits()
+    assert e.dump_machine().qubit_count == 0
+    e.interpret("use q = Qubit();")
+    state_dump = e.dump_machine()
+    assert state_dump.qubit_count == 1
+    assert state_dump.get_dict() == {0: 1}
+
+
+def test_state_size_history_records_growth_of_state() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    assert e.state_size_history() == []
+    e.set_state_size_tracking(True)
+    e.interpret("use qs = Qubit[3]; for q in qs { H(q); }")
+    assert e.state_size_history()[:3] == [2, 4, 8]
+
+
+def test_fidelity_with_reference_state() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    H(qs[0]);
+    CNOT(qs[0], qs[1]);
+    """
+    )
+    bell = e.dump_machine()
+    assert abs(e.fidelity(bell) - 1.0) < 1e-12
+    e.interpret("ResetAll(qs);")
+    zero = e.dump_machine()
+    e.interpret("X(qs[0]);")
+    assert abs(e.fidelity(zero)) < 1e-12
+
+
+def test_density_matrix_of_bell_pair_qubit_is_maximally_mixed() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    H(qs[0]);
+    CNOT(qs[0], qs[1]);
+    """
+    )
+    rho = e.density_matrix([1])
+    expected = [[0.5, 0.0], [0.0, 0.5]]
+    for row, expected_row in zip(rho, expected):
+        for val, expected_val in zip(row, expected_row):
+            assert abs(val - expected_val) < 1e-12
+    full = e.density_matrix([0, 1])
+    assert abs(full[0][3] - 0.5) < 1e-12
+    with pytest.raises(ValueError):
+        e.density_matrix([2])
+
+
+def test_are_entangled_distinguishes_bell_pair_from_product_state() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[4];
+    H(qs[0]);
+    CNOT(qs[0], qs[1]);
+    H(qs[2]);
+    X(qs[3]);
+    """
+    )
+    assert e.are_entangled(0, 1)
+    assert not e.are_entangled(2, 3)
+    assert not e.are_entangled(1, 2)
+    with pytest.raises(ValueError):
+        e.are_entangled(0, 4)
+
+
+def test_outcome_probability_of_bell_pair() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    H(qs[0]);
+    CNOT(qs[0], qs[1]);
+    """
+    )
+    assert abs(e.outcome_probability([(0, False), (1, False)]) - 0.5) < 1e-9
+    assert abs(e.outcome_probability([(0, False), (1, True)])) < 1e-9
+    assert e.are_entangled(0, 1)
+    with pytest.raises(ValueError):
+        e.outcome_probability([(2, True)])
+
+
+def test_dump_probabilities_omits_states_below_threshold() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    H(qs[0]);
+    CNOT(qs[0], qs[1]);
+    Ry(0.01, qs[1]);
+    """
+    )
+    probabilities = e.dump_probabilities()
+    assert set(probabilities) == {0, 1, 2, 3}
+    assert abs(sum(probabilities.values()) - 1.0) < 1e-9
+    assert set(e.dump_probabilities(threshold=1e-3)) == {0, 3}
+    assert e.are_entangled(0, 1), "dumping probabilities should not collapse the state"
+
+
+def test_dump_machine_npy_contains_dense_state() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use qs = Qubit[2];
+    X(qs[1]);
+    """
+    )
+    data = e.dump_machine_npy()
+    assert data[:8] == b"\x93NUMPY\x01\x00"
+    header_len = int.from_bytes(data[8:10], "little")
+    assert b"'shape': (4,)" in data[10 : 10 + header_len]
+    amplitudes = data[10 + header_len :]
+    zero = struct.pack("<dd", 0.0, 0.0)
+    one = struct.pack("<dd", 1.0, 0.0)
+    assert amplitudes == zero + one + zero + zero
+
+
+def test_dump_machine_npy_fails_for_too_many_qubits() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("use qs = Qubit[31];")
+    with pytest.raises(ValueError) as excinfo:
+        e.dump_machine_npy()
+    assert "31 qubits" in str(excinfo.value)
+
+
+def test_run_with_replay_reproduces_measurements() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Flips() : Result[] {
+        use qs = Qubit[8];
+        ApplyToEach(H, qs);
+        MResetEachZ(qs)
+    }
+    """
+    )
+    first = e.run("Flips()")
+    recorded = e.last_measurements()
+    assert len(recorded) == 8
+    assert [r == Result.One for r in first] == recorded
+    for _ in range(5):
+        assert e.run("Flips()", replay=recorded) == first
+        assert e.last_measurements() == recorded
+
+
+def test_last_measurements_covers_only_the_most_recent_interpret() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("use q = Qubit(); X(q); MResetZ(q)")
+    assert e.last_measurements() == [True]
+    e.interpret("use q = Qubit(); MResetZ(q)")
+    assert e.last_measurements() == [False]
+    e.interpret("1 + 1")
+    assert e.last_measurements() == []
+
+
+def test_run_with_shots_param_returns_deterministic_list() -> None:
+    source = """
+    operation Flips() : Result[] {
+        use qs = Qubit[8];
+        ApplyToEach(H, qs);
+        MResetEachZ(qs)
+    }
+    """
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(source)
+    e.set_quantum_seed(7)
+    results = e.run("Flips()", shots=4)
+    assert len(results) == 4
+    assert len(set(tuple(shot) for shot in results)) > 1
+    assert results[0] == e.run("Flips()")
+
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(source)
+    e.set_quantum_seed(7)
+    assert e.run("Flips()", shots=4) == results
+
+
+def test_run_with_shots_records_errors_of_failing_shots() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Flaky() : Int {
+        use q = Qubit();
+        H(q);
+        if MResetZ(q) == One {
+            fail "measured One";
+        }
+        7
+    }
+    """
+    )
+    e.set_quantum_seed(42)
+    results = e.run("Flaky()", shots=20)
+    assert len(results) == 20
+    assert 7 in results
+    errors = [result for result in results if isinstance(result, QSharpError)]
+    assert len(errors) > 0
+    assert all("measured One" in str(error) for error in errors)
+
+
+def test_run_with_shots_and_report_timing_returns_duration_of_each_shot() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("operation Foo() : Result { use q = Qubit(); MResetZ(q) }")
+    results, durations = e.run("Foo()", shots=3, report_timing=True)
+    assert results == [Result.Zero] * 3
+    assert len(durations) == 3
+    assert all(isinstance(duration, float) for duration in durations)
+
+
+def test_run_with_zero_shots_raises_value_error() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("operation Foo() : Result { Zero }")
+    with pytest.raises(ValueError, match="greater than 0"):
+        e.run("Foo()", shots=0)
+    with pytest.raises(ValueError, match="greater than 0"):
+        e.run_histogram("Foo()", 0)
+
+
+def test_run_histogram_counts_distinct_results() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Bell() : (Result, Result) {
+        use (q0, q1) = (Qubit(), Qubit());
+        H(q0);
+        CNOT(q0, q1);
+        (MResetZ(q0), MResetZ(q1))
+    }
+    """
+    )
+    e.set_quantum_seed(3)
+    histogram = e.run_histogram("Bell()", 100)
+    assert set(histogram.keys()) == {"(Zero, Zero)", "(One, One)"}
+    assert sum(histogram.values()) == 100
+
+
+def test_entry_expr_can_be_inspected_and_cleared() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("function Two() : Int { 2 }")
+    assert e.get_entry_expr() is None
+    assert e.run("Two()") == 2
+    assert e.get_entry_expr() == "Two()"
+    assert e.run() == 2
+
+    e.clear_entry_expr()
+    assert e.get_entry_expr() is None
+    with pytest.raises(QSharpError) as excinfo:
+        e.run()
+    assert "entry point not found" in str(excinfo.value)
+
+
 def test_error() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
 
@@ -215,6 +582,104 @@ def test_qirgen() -> None:
     assert isinstance(qir, str)
 
 
+def test_qirgen_with_shots_adds_module_flag() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.interpret("operation Program() : Result { use q = Qubit(); return M(q) }")
+    qir = e.qir("Program()", shots=1000)
+    assert '!{i32 1, !"shots", i64 1000}' in qir
+    assert '!"shots"' not in e.qir("Program()")
+
+
+def test_qir_to_writer_writes_same_qir_as_qir() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.interpret(
+        """
+        operation Program() : Result {
+            use q = Qubit();
+            H(q);
+            M(q)
+        }
+        """
+    )
+    writer = io.StringIO()
+    e.qir_to_writer("Program()", writer)
+    assert writer.getvalue() == e.qir("Program()")
+
+
+def test_qir_to_writer_raises_writer_errors() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.interpret("operation Program() : Result { use q = Qubit(); return M(q) }")
+
+    class FailingWriter:
+        def write(self, text: str) -> None:
+            raise OSError("disk full")
+
+    with pytest.raises(OSError) as excinfo:
+        e.qir_to_writer("Program()", FailingWriter())
+    assert str(excinfo.value) == "disk full"
+
+
+def test_qir_for_operation_resolves_operation_by_name() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.interpret(
+        dedent(
+            """
+            namespace Sample {
+                operation Program(nQubits : Int) : Result[] {
+                    use qs = Qubit[nQubits];
+                    MResetEachZ(qs)
+                }
+            }
+            """
+        )
+    )
+    qir = e.qir_for_operation("Sample.Program", 3)
+    assert "define void @ENTRYPOINT__main()" in qir
+    assert '"required_num_qubits"="3" "required_num_results"="3"' in qir
+    assert e.qir_for_operation("Program", 3) == qir
+    with pytest.raises(QSharpError) as excinfo:
+        e.qir_for_operation("Missing")
+    assert "operation `Missing` not found" in str(excinfo.value)
+
+
+def test_qir_for_operation_rejects_ambiguous_names_and_functions() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.interpret(
+        dedent(
+            """
+            namespace First {
+                operation Program() : Result { use q = Qubit(); MResetZ(q) }
+            }
+            namespace Second {
+                operation Program() : Result { use q = Qubit(); MResetZ(q) }
+                function Helper() : Int { 1 }
+            }
+            """
+        )
+    )
+    with pytest.raises(QSharpError) as excinfo:
+        e.qir_for_operation("Program")
+    assert "could be any of: First.Program, Second.Program" in str(excinfo.value)
+    assert "define void @ENTRYPOINT__main()" in e.qir_for_operation("First.Program")
+    with pytest.raises(QSharpError) as excinfo:
+        e.qir_for_operation("Helper")
+    assert "operation `Helper` not found" in str(excinfo.value)
+
+
+def test_qirgen_non_strict_emits_profile_errors_as_warnings() -> None:
+    e = Interpreter(TargetProfile.Base)
+    entry_expr = "{ use q = Qubit(); if M(q) == One { X(q); } MResetZ(q) }"
+    with pytest.raises(QSharpError) as excinfo:
+        e.qir(entry_expr)
+    assert str(excinfo.value).startswith("Qsc.CapabilitiesCk.UseOfDynamicBool")
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        qir = e.qir(entry_expr, strict=False)
+    assert "__quantum__qis__read_result__body" in qir
+    assert len(caught) == 1
+    assert str(caught[0].message).startswith("Qsc.CapabilitiesCk.UseOfDynamicBool")
+
+
 def test_run_with_shots() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
 
@@ -274,6 +739,44 @@ def test_entry_expr_circuit() -> None:
     )
 
 
+def test_circuit_depth_and_gate_counts() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Foo() : Result[] {
+        use qs = Qubit[3];
+        H(qs[0]);
+        CNOT(qs[0], qs[1]);
+        H(qs[2]);
+        [M(qs[0]), M(qs[1])]
+    }
+    """
+    )
+    circuit = e.circuit("Foo()")
+    assert circuit.depth() == 3
+    assert circuit.gate_counts() == {"h": 2, "cx": 1, "measure": 2}
+
+
+def test_circuit_latex() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Foo() : Result {
+        use qs = Qubit[2];
+        H(qs[0]);
+        CNOT(qs[0], qs[1]);
+        M(qs[1])
+    }
+    """
+    )
+    latex = e.circuit("Foo()").latex()
+    assert latex.startswith("\\begin{quantikz}")
+    assert "\\gate{H}" in latex
+    assert "\\ctrl{1}" in latex
+    assert "\\targ{}" in latex
+    assert "\\meter{}" in latex
+
+
 def test_swap_label_circuit() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.interpret(
@@ -288,6 +791,65 @@ def test_swap_label_circuit() -> None:
     )
 
 
+def test_can_invoke_checks_argument_types_without_invoking() -> None:
+    callables = {}
+    e = Interpreter(
+        TargetProfile.Unrestricted,
+        make_callable=lambda callable, namespace, name: callables.update(
+            {name: callable}
+        ),
+    )
+    e.interpret(
+        """
+    operation Foo(a : Int, b : Double[]) : Unit { fail "should not be invoked"; }
+    operation Bar() : Unit {}
+    """
+    )
+    assert e.can_invoke(callables["Foo"], (1, [2.0, 3.0]))
+    assert not e.can_invoke(callables["Foo"], (1, 2.0))
+    assert not e.can_invoke(callables["Foo"], ("1", [2.0]))
+    assert not e.can_invoke(callables["Foo"])
+    assert e.can_invoke(callables["Bar"])
+    assert not e.can_invoke(callables["Bar"], 1)
+
+
+def test_invoke_with_progress_callback_fires_during_classical_loop() -> None:
+    callables = {}
+    e = Interpreter(
+        TargetProfile.Unrestricted,
+        make_callable=lambda callable, namespace, name: callables.update(
+            {name: callable}
+        ),
+    )
+    e.interpret(
+        """
+    operation Count(n : Int) : Int {
+        mutable total = 0;
+        for i in 1..n {
+            set total += i;
+        }
+        total
+    }
+    """
+    )
+    counts = []
+    assert e.invoke(callables["Count"], 50000, None, counts.append) == 1250025000
+    assert len(counts) > 1
+    assert counts == sorted(counts)
+
+    with pytest.raises(QSharpError) as excinfo:
+        e.invoke(callables["Count"], 50000, None, lambda _: False)
+    assert "evaluation aborted" in str(excinfo.value)
+
+    def fail(_):
+        raise RuntimeError("stop counting")
+
+    with pytest.raises(QSharpError) as excinfo:
+        e.invoke(callables["Count"], 50000, None, fail)
+    assert isinstance(excinfo.value.__cause__, RuntimeError)
+    assert str(excinfo.value.__cause__) == "stop counting"
+
+
 def test_callables_failing_profile_validation_are_not_registered() -> None:
     e = Interpreter(TargetProfile.Adaptive_RI)
     with pytest.raises(Exception) as excinfo:
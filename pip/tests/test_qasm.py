@@ -1,7 +1,11 @@
 # Copyright (c) Microsoft Corporation.
 # Licensed under the MIT License.
 
+import json
+import os
+import warnings
 from math import pi
+from tempfile import TemporaryDirectory
 from textwrap import dedent
 import pytest
 from qsharp import (
@@ -17,9 +21,13 @@ from qsharp.qasm import (
     import_qasm,
     run,
     compile,
+    diagnostics,
+    resolved_includes,
     circuit,
     estimate,
     ProgramType,
+    OutputSemantics,
+    QasmError,
 )
 import qsharp.code as code
 
@@ -71,6 +79,107 @@ def test_run_with_result(capsys) -> None:
     assert results == [Result.Zero, Result.Zero, Result.Zero]
 
 
+def test_run_single_bit_output_with_qiskit_semantics_is_scalar_result() -> None:
+    results = run(
+        """
+        include "stdgates.inc";
+        output bit c;
+        qubit q;
+        x q;
+        c = measure q;
+        """,
+        2,
+        output_semantics=OutputSemantics.Qiskit,
+    )
+    assert results == [Result.One, Result.One]
+
+
+def test_run_bit_array_output_with_qiskit_semantics_is_list() -> None:
+    results = run(
+        """
+        include "stdgates.inc";
+        output bit[2] c;
+        qubit[2] q;
+        x q[0];
+        c[0] = measure q[0];
+        c[1] = measure q[1];
+        """,
+        1,
+        output_semantics=OutputSemantics.Qiskit,
+    )
+    assert results == [[Result.Zero, Result.One]]
+
+
+def test_run_float_division_by_zero_raises_error() -> None:
+    with pytest.raises(QSharpError) as excinfo:
+        run("float x = 1.0; float y = 0.0; output float z; z = x / y;", 1)
+    assert "division by zero" in str(excinfo.value)
+
+
+def test_run_int_modulo_by_zero_raises_error() -> None:
+    with pytest.raises(QSharpError) as excinfo:
+        run("int x = 5; int y = 0; output int z; z = x % y;", 1)
+    assert "Qsc.Eval.DivZero" in str(excinfo.value)
+
+
+def test_run_with_inputs_binds_input_values() -> None:
+    results = run(
+        """
+        input int a;
+        input float f;
+        input bool b;
+        output int n;
+        output float g;
+        output bool c;
+        n = a + 1;
+        g = f * 2.0;
+        c = !b;
+        """,
+        1,
+        inputs={"a": 2, "f": 0.5, "b": True},
+    )
+    assert results == [(3, 1.0, False)]
+
+
+def test_run_with_array_input_binds_input_values() -> None:
+    results = run(
+        """
+        input array[int, 3] xs;
+        output int total;
+        total = xs[0] + xs[1] + xs[2];
+        """,
+        1,
+        inputs={"xs": [1, 2, 3]},
+    )
+    assert results == [6]
+
+
+def test_run_with_complex_input_binds_input_values() -> None:
+    results = run(
+        """
+        input complex z;
+        output complex w;
+        w = z;
+        """,
+        1,
+        inputs={"z": 1 + 2j},
+    )
+    assert results == [(1.0, 2.0)]
+
+
+def test_run_with_mismatched_inputs_raises_error() -> None:
+    with pytest.raises(ValueError) as excinfo:
+        run(
+            "input int a; input int b; output int c; c = a + b;",
+            1,
+            inputs={"a": 1, "d": 2},
+        )
+    message = str(excinfo.value)
+    assert "missing: b" in message
+    assert "unexpected: d" in message
+    assert "expected: a, b" in message
+
+
 # Import
 
 
@@ -102,6 +211,16 @@ def test_import_can_declare_callables_from_fragments() -> None:
     assert qsharp_eval("Foo()") == 42
 
 
+def test_import_normalizes_dt_durations_with_configured_cycle_time() -> None:
+    init(target_profile=TargetProfile.Base)
+    import_qasm(
+        "duration d = 100dt;", program_type=ProgramType.Fragments, dt_seconds=0.5
+    )
+    from qsharp import eval as qsharp_eval
+
+    assert qsharp_eval("d") == 50.0
+
+
 def test_import_can_declare_files_with_namespaces() -> None:
     init(target_profile=TargetProfile.Adaptive_RI)
     import_qasm("output int x; x = 42;", program_type=ProgramType.File)
@@ -232,6 +351,36 @@ def test_compile_qir_str() -> None:
     assert '"required_num_qubits"="1" "required_num_results"="1"' in qir
 
 
+def test_compile_qir_str_labels_output_registers() -> None:
+    init(target_profile=TargetProfile.Base)
+    qir = str(
+        compile(
+            "qubit[3] q; output bit[1] a; output bit[2] b; a[0] = measure q[0]; b = measure q[1:2];"
+        )
+    )
+    assert '@0 = internal constant [2 x i8] c"b\\00"' in qir
+    assert '@1 = internal constant [2 x i8] c"a\\00"' in qir
+    assert (
+        "@__quantum__rt__array_record_output(i64 2, i8* getelementptr inbounds ([2 x i8], [2 x i8]* @0, i64 0, i64 0))"
+        in qir
+    )
+
+
+def test_compile_qir_str_from_imported_callable_labels_output_registers() -> None:
+    init(target_profile=TargetProfile.Base)
+    import_qasm(
+        "qubit[3] q; output bit[1] a; output bit[2] b; a[0] = measure q[0]; b = measure q[1:2];",
+        name="Program",
+    )
+    qir = str(compile(code.Program))
+    assert '@0 = internal constant [2 x i8] c"a\\00"' in qir
+    assert '@1 = internal constant [2 x i8] c"b\\00"' in qir
+    assert (
+        "@__quantum__rt__array_record_output(i64 2, i8* getelementptr inbounds ([2 x i8], [2 x i8]* @1, i64 0, i64 0))"
+        in qir
+    )
+
+
 def test_compile_qir_str_with_single_arg_raises_error() -> None:
     init(target_profile=TargetProfile.Base)
     with pytest.raises(QSharpError) as excinfo:
@@ -252,6 +401,77 @@ def test_compile_qir_str_with_single_arg_raises_error() -> None:
     )
 
 
+def test_compile_diagnostics_reports_all_errors() -> None:
+    source = """
+        int a = b;
+        int c = d;
+        """
+    results = [
+        d
+        for d in diagnostics(source)
+        if d["code"] == "Qasm.Lowerer.UndefinedSymbol"
+    ]
+    assert len(results) == 2
+    assert results[0]["message"] == "undefined symbol: b"
+    assert results[1]["message"] == "undefined symbol: d"
+    assert results[0]["start"] == source.index("b;")
+    assert results[1]["start"] == source.index("d;")
+
+
+def test_compile_diagnostics_empty_for_valid_program() -> None:
+    assert diagnostics("qubit q; output bit c; c = measure q;") == []
+
+
+def test_compile_old_style_declarations_warn_unless_strict() -> None:
+    source = "qreg q[1]; creg c[1]; c[0] = measure q[0];"
+    with pytest.warns(UserWarning, match="qreg declarations are OpenQASM 2 syntax"):
+        compile(source)
+    with pytest.raises(QasmError) as excinfo:
+        compile(source, strict_declarations=True)
+    assert "Qasm.Lowerer.OldStyleDeclaration" in str(excinfo.value)
+
+
+def test_compile_and_diagnostics_resolve_dt_durations_with_dt_seconds() -> None:
+    source = "duration d = 100dt;"
+    with pytest.raises(QasmError) as excinfo:
+        compile(source)
+    assert "Qasm.Compiler.DtWithoutCycleTime" in str(excinfo.value)
+    assert any(
+        d["code"] == "Qasm.Compiler.DtWithoutCycleTime" for d in diagnostics(source)
+    )
+
+    compile(source, dt_seconds=1e-9)
+    assert diagnostics(source, dt_seconds=1e-9) == []
+
+
+def test_compile_legacy_u_warning_can_be_disabled() -> None:
+    source = "qubit q; U(0.1, 0.2, 0.3) q; output bit c; c = measure q;"
+    with pytest.warns(UserWarning, match="Qasm.Compiler.DeprecatedUGate"):
+        compile(source)
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        compile(source, warn_on_legacy_u=False)
+    assert not any("DeprecatedUGate" in str(w.message) for w in caught)
+
+
+def test_resolved_includes_returns_all_included_files() -> None:
+    with TemporaryDirectory() as dir:
+        with open(os.path.join(dir, "first.inc"), "w") as f:
+            f.write("bit[1] c;")
+        with open(os.path.join(dir, "second.inc"), "w") as f:
+            f.write("qubit q;")
+        source = """
+            include "stdgates.inc";
+            include "first.inc";
+            include "second.inc";
+            """
+        includes = resolved_includes(source, search_path=dir)
+        assert [os.path.basename(path) for path in includes] == [
+            "first.inc",
+            "second.inc",
+        ]
+
+
 # Import + Compile
 
 
@@ -285,7 +505,7 @@ def test_compile_qir_str_from_python_callable_with_single_arg() -> None:
         in qir
     )
     assert (
-        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)"
+        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* getelementptr inbounds ([2 x i8], [2 x i8]* @0, i64 0, i64 0))"
         in qir
     )
     assert '"required_num_qubits"="1" "required_num_results"="1"' in qir
@@ -313,7 +533,7 @@ def test_compile_qir_str_from_python_callable_with_multiple_args() -> None:
         in qir
     )
     assert (
-        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)"
+        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* getelementptr inbounds ([2 x i8], [2 x i8]* @0, i64 0, i64 0))"
         in qir
     )
     assert '"required_num_qubits"="1" "required_num_results"="1"' in qir
@@ -344,7 +564,7 @@ def test_compile_qir_str_from_python_callable_with_multiple_args_passed_as_tuple
         in qir
     )
     assert (
-        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)"
+        "call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* getelementptr inbounds ([2 x i8], [2 x i8]* @0, i64 0, i64 0))"
         in qir
     )
     assert '"required_num_qubits"="1" "required_num_results"="1"' in qir
@@ -396,6 +616,30 @@ def test_callable_with_double_exposed_into_env_fails_incorrect_types() -> None:
         code.Identity([4])
 
 
+def test_float_comparisons_follow_ieee_nan_semantics() -> None:
+    init()
+    import_qasm(
+        """
+        def Lt(float a, float b) -> bool { return a < b; }
+        def Gt(float a, float b) -> bool { return a > b; }
+        def Lte(float a, float b) -> bool { return a <= b; }
+        def Gte(float a, float b) -> bool { return a >= b; }
+        def Eq(float a, float b) -> bool { return a == b; }
+        def Neq(float a, float b) -> bool { return a != b; }
+        """,
+        program_type=ProgramType.Fragments,
+    )
+    nan = float("nan")
+    assert code.Lt(nan, 1.0) is False
+    assert code.Gt(nan, 1.0) is False
+    assert code.Lte(nan, 1.0) is False
+    assert code.Gte(1.0, nan) is False
+    assert code.Eq(nan, nan) is False
+    assert code.Neq(nan, nan) is True
+    assert code.Lt(0.5, 1.0) is True
+    assert code.Eq(1.0, 1.0) is True
+
+
 def test_callable_with_bigint_exposed_into_env_fails_incorrect_types() -> None:
     init()
     import_qasm(
@@ -493,6 +737,27 @@ def test_circuit_from_program() -> None:
     )
 
 
+def test_circuit_with_negctrl_has_negative_controls() -> None:
+    init()
+    c = circuit(
+        """
+        include "stdgates.inc";
+        qubit q1;
+        qubit q2;
+        negctrl @ x q1, q2;
+        """,
+    )
+    assert str(c) == dedent(
+        """\
+        q_0    ── ○ ──
+        q_1    ── X ──
+        """
+    )
+    op = json.loads(c.json())["componentGrid"][0]["components"][0]
+    assert op["controls"] == [{"qubit": 0}]
+    assert op["negativeControls"] == [{"qubit": 0}]
+
+
 def test_circuit_from_callable() -> None:
     init()
     import_qasm(
@@ -551,6 +816,55 @@ def test_circuit_with_measure_from_callable() -> None:
     )
 
 
+def test_circuit_with_barrier_from_program() -> None:
+    init()
+    c = circuit(
+        """
+        include "stdgates.inc";
+        qubit[3] q;
+        h q[0];
+        barrier q[0], q[1];
+        x q[1];
+        barrier;
+        """,
+    )
+    barriers = [
+        op
+        for column in json.loads(c.json())["componentGrid"]
+        for op in column["components"]
+        if op["gate"] == "barrier"
+    ]
+    assert [op["targets"] for op in barriers] == [
+        [{"qubit": 0}, {"qubit": 1}],
+        [{"qubit": 0}, {"qubit": 1}, {"qubit": 2}],
+    ]
+
+
+def test_circuit_with_reset_from_program() -> None:
+    init()
+    c = circuit(
+        """
+        include "stdgates.inc";
+        qubit q;
+        h q;
+        reset q;
+        """,
+    )
+    assert str(c) == dedent(
+        """\
+        q_0    ── H ──── |0〉 ──
+        """
+    )
+    components = [
+        op
+        for column in json.loads(c.json())["componentGrid"]
+        for op in column["components"]
+    ]
+    assert components[-1]["kind"] == "ket"
+    assert components[-1]["gate"] == "0"
+    assert all(op["kind"] != "measurement" for op in components)
+
+
 # Estimate
 
 
@@ -580,6 +894,40 @@ def test_qasm_estimation() -> None:
     )
 
 
+def test_qasm_estimation_accounts_for_gate_durations() -> None:
+    source = """
+        include "stdgates.inc";
+        {annotation}
+        gate slow q {{
+            t q;
+        }}
+        qubit[2] q;
+        slow q[0];
+        slow q[1];
+        """
+    res = estimate(source.format(annotation=""))
+    annotated_res = estimate(source.format(annotation="@duration 2ms"))
+    assert annotated_res.logical_counts["tCount"] == res.logical_counts["tCount"]
+    runtime = res["physicalCounts"]["runtime"]
+    assert annotated_res["physicalCounts"]["runtime"] >= runtime + 4_000_000
+
+
+def test_qasm_estimation_counts_resets_as_measurements() -> None:
+    source = """
+        include "stdgates.inc";
+        qubit[2] q;
+        output bit[2] c;
+        t q[0];
+        {resets}
+        c = measure q;
+        """
+    res = estimate(source.format(resets=""))
+    reset_res = estimate(source.format(resets="reset q[0]; reset q;"))
+    assert res.logical_counts["measurementCount"] == 2
+    assert reset_res.logical_counts["measurementCount"] == 5
+    assert reset_res.logical_counts["tCount"] == res.logical_counts["tCount"]
+
+
 def test_qasm_estimation_with_single_params() -> None:
     params = EstimatorParams()
     params.error_budget = 0.333
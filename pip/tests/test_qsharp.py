@@ -105,6 +105,66 @@ def test_classical_seed() -> None:
     assert value1 != value3
 
 
+def test_custom_intrinsic_handler_is_invoked_with_args() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    calls = []
+
+    def handler(name, args):
+        calls.append((name, args))
+        return args[0] * 2
+
+    qsharp.set_custom_intrinsic_handler(handler, ["ReadSensor"])
+    qsharp.eval("function ReadSensor(x : Int, label : String) : Int { body intrinsic; }")
+    assert qsharp.eval('ReadSensor(21, "temp")') == 42
+    assert calls == [("ReadSensor", (21, "temp"))]
+
+
+def test_custom_intrinsic_handler_errors_for_unregistered_names() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_custom_intrinsic_handler(lambda name, args: 0, ["ReadSensor"])
+    qsharp.eval("function Other(x : Int) : Int { body intrinsic; }")
+    with pytest.raises(qsharp.QSharpError):
+        qsharp.eval("Other(1)")
+
+
+def test_custom_intrinsic_handler_gets_single_argument_as_tuple() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    calls = []
+
+    def handler(name, args):
+        calls.append(args)
+        return args[0] + 1
+
+    qsharp.set_custom_intrinsic_handler(handler, ["ReadSensor"])
+    qsharp.eval("function ReadSensor(x : Int) : Int { body intrinsic; }")
+    assert qsharp.eval("ReadSensor(1)") == 2
+    assert calls == [(1,)]
+
+
+def test_custom_intrinsic_handler_result_uses_declared_output_type() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_custom_intrinsic_handler(lambda name, args: 1, ["ReadSensor"])
+    qsharp.eval("function ReadSensor() : Double { body intrinsic; }")
+    value = qsharp.eval("ReadSensor() / 2.0")
+    assert value == 0.5
+    assert isinstance(value, float)
+
+
+def test_custom_intrinsic_handler_result_of_wrong_type_raises_error() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_custom_intrinsic_handler(lambda name, args: "high", ["ReadSensor"])
+    qsharp.eval("function ReadSensor() : Int { body intrinsic; }")
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("ReadSensor()")
+    assert "expected the handler to return a value of type `Int`" in str(excinfo.value)
+
+
+def test_custom_intrinsic_handler_without_names_raises_value_error() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    with pytest.raises(ValueError):
+        qsharp.set_custom_intrinsic_handler(lambda name, args: 0, [])
+
+
 def test_dump_machine() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
     qsharp.eval(
@@ -168,6 +228,29 @@ def test_dump_machine() -> None:
     assert state_dump.check_eq([1.0], tolerance=1e-4)
 
 
+def test_dump_machine_display_precision() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval("use q = Qubit(); Ry(1.0, q);")
+    try:
+        qsharp.set_display_precision(2)
+        short = str(qsharp.dump_machine())
+        qsharp.set_display_precision(8)
+        long = str(qsharp.dump_machine())
+    finally:
+        qsharp.set_display_precision(4)
+    assert "|0⟩: 0.88+0.00𝑖" in short
+    assert "|0⟩: 0.87758256+0.00000000𝑖" in long
+    assert len(long) > len(short)
+    assert "|0⟩: 0.8776+0.0000𝑖" in str(qsharp.dump_machine())
+
+
+def test_set_display_precision_out_of_range_raises_value_error() -> None:
+    with pytest.raises(ValueError):
+        qsharp.set_display_precision(-1)
+    with pytest.raises(ValueError):
+        qsharp.set_display_precision(18)
+
+
 def test_dump_operation() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
     res = qsharp.utils.dump_operation("qs => ()", 1)
@@ -429,6 +512,54 @@ def test_run_with_result(capsys) -> None:
     assert stdout == "Hello, world!\nHello, world!\nHello, world!\n"
 
 
+def test_run_with_timing_reports_duration_per_shot() -> None:
+    qsharp.init()
+    qsharp.eval("operation Foo() : Result { use q = Qubit(); M(q) }")
+    results, timings = qsharp.run("Foo()", 3, report_timing=True)
+    assert results == [qsharp.Result.Zero, qsharp.Result.Zero, qsharp.Result.Zero]
+    assert len(timings) == 3
+    assert all(duration >= 0 for duration in timings)
+
+
+def test_run_with_pack_returns_register_as_int() -> None:
+    qsharp.init()
+    qsharp.eval(
+        """
+        operation Foo() : Result[] {
+            use qs = Qubit[20];
+            X(qs[0]);
+            X(qs[5]);
+            X(qs[19]);
+            MResetEachZ(qs)
+        }
+        """
+    )
+    results = qsharp.run("Foo()", 3, pack=True)
+    assert results == [1 | 1 << 5 | 1 << 19] * 3
+    assert all(type(result) is int for result in results)
+
+
+def test_run_with_pack_leaves_empty_array_unpacked() -> None:
+    qsharp.init()
+    qsharp.eval("operation Foo() : Result[] { [] }")
+    assert qsharp.run("Foo()", 1, pack=True) == [[]]
+
+
+def test_run_with_pack_msb_first_reverses_bit_order() -> None:
+    qsharp.init()
+    qsharp.eval(
+        """
+        operation Foo() : Result[] {
+            use qs = Qubit[3];
+            X(qs[0]);
+            MResetEachZ(qs)
+        }
+        """
+    )
+    assert qsharp.run("Foo()", 1, pack=True) == [1]
+    assert qsharp.run("Foo()", 1, pack=True, msb_first=True) == [4]
+
+
 def test_run_with_result_from_callable(capsys) -> None:
     qsharp.init()
     qsharp.eval(
@@ -543,6 +674,26 @@ def test_callable_exposed_into_env_with_array() -> None:
     ), "callable that takes array should work"
 
 
+def test_callable_exposed_into_env_with_numeric_buffer() -> None:
+    from array import array
+
+    qsharp.init()
+    qsharp.eval(
+        "function Sum(a : Double[]) : Double { Std.Arrays.Fold((x, y) -> x + y, 0.0, a) }"
+    )
+    qsharp.eval("function Smallest(a : Int[]) : Int { Std.Math.Min(a)}")
+    # Numpy arrays are passed through the same buffer protocol as `array.array`.
+    assert (
+        qsharp.code.Sum(array("d", [1.5, 2.0, 0.5])) == 4.0
+    ), "callable that takes a Double[] should accept a buffer of doubles"
+    assert (
+        qsharp.code.Smallest(array("q", [3, -1, 2])) == -1
+    ), "callable that takes an Int[] should accept a buffer of ints"
+    assert (
+        qsharp.code.Smallest(array("i", [3, -1, 2])) == -1
+    ), "buffers of other item types should be converted item by item"
+
+
 def test_callable_with_int_exposed_into_env_fails_incorrect_types() -> None:
     qsharp.init()
     qsharp.eval("function Identity(a : Int) : Int { a }")
@@ -767,6 +918,18 @@ def test_circuit_with_measure_from_callable() -> None:
     )
 
 
+def test_circuit_with_basis_gates_from_callable() -> None:
+    qsharp.init()
+    qsharp.eval("operation Foo() : Result { use q = Qubit(); H(q); return M(q) }")
+    circuit = qsharp.circuit(qsharp.code.Foo, basis_gates=["rz", "sx", "cx"])
+    assert str(circuit) == dedent(
+        """\
+        q_0    ─ Rz(1.5708) ─── SX ─── Rz(1.5708) ──── M ──
+                                                       ╘═══
+        """
+    )
+
+
 def test_swap_label_circuit_from_callable() -> None:
     qsharp.init()
     qsharp.eval(
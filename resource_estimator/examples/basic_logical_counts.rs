@@ -43,6 +43,7 @@ fn main() {
         ccz_count: 100,
         ccix_count: 0,
         measurement_count: 10,
+        additional_runtime: 0,
     });
 
     // 5) An error budget; in this example we are using a uniform error budget
@@ -34,6 +34,8 @@ pub struct LogicalCounter {
     ccz_count: usize,
     /// Number of single-qubit and multiple-qubit measurements
     m_count: usize,
+    /// Runtime in nanoseconds of operations whose duration is given explicitly
+    additional_runtime: u64,
     /// Global allocation barrier (when calling global barrier this is advanced
     /// to allocate new qubits after the barrier)
     allocation_barrier: usize,
@@ -58,6 +60,7 @@ impl Default for LogicalCounter {
             r_count: 0,
             ccz_count: 0,
             m_count: 0,
+            additional_runtime: 0,
             allocation_barrier: 0,
             caching_stack: vec![],
             caching_layers: FxHashMap::default(),
@@ -78,6 +81,7 @@ impl LogicalCounter {
             ccz_count: self.ccz_count as _,
             ccix_count: 0,
             measurement_count: self.m_count as _,
+            additional_runtime: self.additional_runtime,
         }
     }
 
@@ -229,6 +233,7 @@ impl LogicalCounter {
                 .map_err(|_| format!("Estimate count {count} is too large to fit in a usize.",))?,
             start_depth,
             m_count: self.m_count,
+            additional_runtime: self.additional_runtime,
         });
 
         Ok(())
@@ -240,6 +245,7 @@ impl LogicalCounter {
             count,
             start_depth,
             m_count,
+            additional_runtime,
         }) = self.repeats.pop()
         {
             if count == 0 {
@@ -259,6 +265,8 @@ impl LogicalCounter {
             let combined_r_count = sum.r * (count - 1);
             let combined_ccz_count = sum.ccz * (count - 1);
             let combined_m_count = (self.m_count - m_count) * (count - 1);
+            let combined_additional_runtime =
+                (self.additional_runtime - additional_runtime) * (count as u64 - 1);
 
             if r_depth > 0 {
                 let first_layer_r_count = combined_r_count - (combined_r_depth - 1);
@@ -283,11 +291,19 @@ impl LogicalCounter {
             self.r_count += combined_r_count;
             self.ccz_count += combined_ccz_count;
             self.m_count += combined_m_count;
+            self.additional_runtime += combined_additional_runtime;
 
             self.global_barrier();
         }
     }
 
+    fn add_runtime(&mut self, nanoseconds: i64) -> Result<(), String> {
+        let nanoseconds =
+            u64::try_from(nanoseconds).map_err(|_| format!("Negative runtime: {nanoseconds}"))?;
+        self.additional_runtime += nanoseconds;
+        Ok(())
+    }
+
     fn add_estimate(
         &mut self,
         estimates: &[(i64, i64)],
@@ -564,6 +580,10 @@ impl Backend for LogicalCounter {
                         .map(|()| Value::unit()),
                 )
             }
+            "AccountForRuntimeInternal" => {
+                let nanoseconds = arg.unwrap_int();
+                Some(self.add_runtime(nanoseconds).map(|()| Value::unit()))
+            }
             "GlobalPhase" | "ConfigurePauliNoise" | "ApplyIdleNoise" => Some(Ok(Value::unit())),
             _ => None,
         }
@@ -625,4 +645,5 @@ struct RepeatEntry {
     count: usize,
     start_depth: usize,
     m_count: usize,
+    additional_runtime: u64,
 }
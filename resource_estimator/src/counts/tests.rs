@@ -71,6 +71,7 @@ fn gates_are_counted() {
                 ccz_count: 2,
                 ccix_count: 0,
                 measurement_count: 10,
+                additional_runtime: 0,
             }
         "]],
     );
@@ -118,6 +119,7 @@ fn estimate_caching_works() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                additional_runtime: 0,
             }
         "]],
     );
@@ -165,6 +167,7 @@ fn estimate_repeat_works() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                additional_runtime: 0,
             }
         "#]],
     );
@@ -204,11 +207,50 @@ fn account_for_estimates_works() {
                 ccz_count: 5,
                 ccix_count: 0,
                 measurement_count: 6,
+                additional_runtime: 0,
             }
         "]],
     );
 }
 
+#[test]
+fn account_for_runtime_is_repeated_with_estimates() {
+    verify_logical_counts(
+        indoc! {"
+            namespace Test {
+                import Std.ResourceEstimation.*;
+
+                operation AccountForRuntimeInternal(nanoseconds : Int) : Unit {
+                    body intrinsic;
+                }
+
+                @EntryPoint()
+                operation Main() : Unit {
+                    AccountForRuntimeInternal(100);
+                    within {
+                        RepeatEstimates(3);
+                    } apply {
+                        AccountForRuntimeInternal(50);
+                    }
+                }
+            }
+        "},
+        None,
+        &expect![[r#"
+            LogicalResourceCounts {
+                num_qubits: 0,
+                t_count: 0,
+                rotation_count: 0,
+                rotation_depth: 0,
+                ccz_count: 0,
+                ccix_count: 0,
+                measurement_count: 0,
+                additional_runtime: 250,
+            }
+        "#]],
+    );
+}
+
 #[test]
 fn pauli_i_rotation_for_global_phase_is_noop() {
     verify_logical_counts(
@@ -232,6 +274,7 @@ fn pauli_i_rotation_for_global_phase_is_noop() {
                 ccz_count: 0,
                 ccix_count: 0,
                 measurement_count: 0,
+                additional_runtime: 0,
             }
         "#]],
     );
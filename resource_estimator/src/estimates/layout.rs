@@ -24,6 +24,14 @@ pub trait Overhead {
     /// supported by available factory builders in the physical estimation.
     fn num_magic_states(&self, budget: &ErrorBudget, index: usize) -> u64;
 
+    /// The runtime in nanoseconds of operations whose duration is given
+    /// explicitly instead of in logical cycles
+    ///
+    /// This time is added to the runtime of the logical cycles.
+    fn additional_runtime(&self) -> u64 {
+        0
+    }
+
     /// When implemented, prunes the error budget with respect to the provided
     /// strategy
     #[allow(unused_variables)]
@@ -42,6 +50,8 @@ pub struct RealizedOverhead {
     logical_qubits: u64,
     logical_depth: u64,
     num_magic_states: Vec<u64>,
+    #[serde(skip)]
+    additional_runtime: u64,
 }
 
 impl RealizedOverhead {
@@ -60,6 +70,7 @@ impl RealizedOverhead {
             logical_qubits,
             logical_depth,
             num_magic_states,
+            additional_runtime: overhead.additional_runtime(),
         }
     }
 
@@ -91,4 +102,8 @@ impl Overhead for RealizedOverhead {
     fn num_magic_states(&self, _budget: &ErrorBudget, index: usize) -> u64 {
         self.num_magic_states[index]
     }
+
+    fn additional_runtime(&self) -> u64 {
+        self.additional_runtime
+    }
 }
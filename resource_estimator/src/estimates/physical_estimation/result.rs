@@ -54,7 +54,8 @@ impl<E: ErrorCorrection<Parameter = impl Clone>, F: Factory<Parameter = E::Param
 
         let physical_qubits = physical_qubits_for_algorithm + physical_qubits_for_factories;
 
-        let runtime = (logical_patch.logical_cycle_time()) * num_cycles;
+        let runtime = (logical_patch.logical_cycle_time()) * num_cycles
+            + estimation.layout_overhead.additional_runtime();
 
         let rqops = (estimation.layout_overhead().logical_qubits() as f64
             * logical_patch.logical_cycles_per_second())
@@ -43,6 +43,15 @@ pub struct LogicalResourceCounts {
     pub ccix_count: u64,
     #[serde(default)]
     pub measurement_count: u64,
+    /// Runtime in nanoseconds of operations whose duration is given explicitly,
+    /// which is added to the runtime of the logical cycles.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub additional_runtime: u64,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 /// Models the logical resources after layout
@@ -78,6 +87,10 @@ impl Overhead for LogicalResourceCounts {
                 * self.rotation_count
     }
 
+    fn additional_runtime(&self) -> u64 {
+        self.additional_runtime
+    }
+
     fn prune_error_budget(&self, budget: &mut ErrorBudget, strategy: ErrorBudgetStrategy) {
         if matches![strategy, ErrorBudgetStrategy::PruneLogicalAndRotations] {
             if let Some(num_ts_per_rotation) = self.num_ts_per_rotation(budget.rotations()) {
@@ -37,6 +37,7 @@ fn estimate_single() {
         ccz_count: 0,
         ccix_count: 0,
         measurement_count: 0,
+        additional_runtime: 0,
     };
 
     let params: &str = "[{}]";
@@ -63,6 +64,7 @@ fn estimate_frontier() {
         ccz_count: 0,
         ccix_count: 0,
         measurement_count: 0,
+        additional_runtime: 0,
     };
 
     let params: &str = r#"[{
@@ -93,6 +95,7 @@ fn physical_estimates_crash() {
             ccz_count: 8,
             ccix_count: 0,
             measurement_count: 5,
+            additional_runtime: 0,
         },
         r#"[{"qubitParams": {"name": "qubit_maj_ns_e6"},
             "qecScheme": {"name": "floquet_code"},
@@ -1006,6 +1009,7 @@ fn test_report() {
         ccz_count: 0,
         ccix_count: 0,
         measurement_count: 0,
+        additional_runtime: 0,
     };
 
     let params: &str = "[{}]";
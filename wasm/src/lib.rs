@@ -138,7 +138,7 @@ pub fn get_circuit(
     .map_err(interpret_errors_into_qsharp_errors_json)?;
 
     let circuit = interpreter
-        .circuit(entry_point, simulate)
+        .circuit(entry_point, simulate, None)
         .map_err(interpret_errors_into_qsharp_errors_json)?;
 
     serde_wasm_bindgen::to_value(&circuit).map_err(|e| e.to_string())